@@ -211,6 +211,7 @@ macro_rules! gate_doc { ($($s:literal { $($name:ident => $feature:ident)* })*) =
                     "experimental" {
                         cfg => doc_cfg
                         cfg_hide => doc_cfg_hide
+                        example_exclude => doc_example_exclude
                         masked => doc_masked
                         notable_trait => doc_notable_trait
                     }
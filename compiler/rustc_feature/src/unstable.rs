@@ -447,6 +447,12 @@ pub fn internal(&self, feature: Symbol) -> bool {
     (unstable, doc_cfg, "1.21.0", Some(43781)),
     /// Allows `#[doc(cfg_hide(...))]`.
     (unstable, doc_cfg_hide, "1.57.0", Some(43781)),
+    // no-tracking-issue-start
+    /// Allows `#[doc(example_exclude)]`.
+    // FIXME: point to the tracking issue of `#[doc(example_exclude)]` once it is opened. It is
+    // separate from the one of scraped examples (#88791).
+    (unstable, doc_example_exclude, "CURRENT_RUSTC_VERSION", None),
+    // no-tracking-issue-end
     /// Allows `#[doc(masked)]`.
     (unstable, doc_masked, "1.21.0", Some(44027)),
     /// Allows `dyn* Trait` objects.
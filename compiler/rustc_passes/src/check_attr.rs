@@ -1200,6 +1200,7 @@ fn check_doc_attrs(
                         // passes: deprecated
                         // plugins: removed, but rustdoc warns about it itself
                         sym::cfg
+                        | sym::example_exclude
                         | sym::hidden
                         | sym::no_default_passes
                         | sym::notable_trait
@@ -741,6 +741,7 @@
         doc_auto_cfg,
         doc_cfg,
         doc_cfg_hide,
+        doc_example_exclude,
         doc_keyword,
         doc_masked,
         doc_notable_trait,
@@ -787,6 +788,7 @@
         eq,
         ermsb_target_feature,
         exact_div,
+        example_exclude,
         except,
         exchange_malloc,
         exclusive_range_pattern,
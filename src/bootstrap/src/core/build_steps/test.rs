@@ -1767,7 +1767,9 @@ fn run(self, builder: &Builder<'_>) {
         cmd.arg("--host").arg(&*compiler.host.triple);
         cmd.arg("--llvm-filecheck").arg(builder.llvm_filecheck(builder.config.build));
 
-        if builder.config.cmd.bless() {
+        if builder.config.cmd.bless_dry_run() {
+            cmd.arg("--bless-dry-run");
+        } else if builder.config.cmd.bless() {
             cmd.arg("--bless");
         }
 
@@ -2075,6 +2077,23 @@ fn run(self, builder: &Builder<'_>) {
             cmd.arg("--rustfix-coverage");
         }
 
+        if builder.config.cmd.minimize() {
+            cmd.arg("--minimize");
+        }
+
+        if let Some(dir) = builder.config.cmd.json_report() {
+            // Some suites run in several modes (e.g. `coverage`) or compare modes, which must
+            // not overwrite each other's report.
+            let mut name = format!("{target}-{suite}");
+            if mode != suite {
+                name.push_str(&format!("-{mode}"));
+            }
+            if let Some(compare_mode) = compare_mode {
+                name.push_str(&format!("-{compare_mode}"));
+            }
+            cmd.arg("--json-report").arg(dir.join(format!("{name}.json")));
+        }
+
         cmd.env("BOOTSTRAP_CARGO", &builder.initial_cargo);
 
         cmd.arg("--channel").arg(&builder.config.channel);
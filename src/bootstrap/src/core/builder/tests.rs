@@ -637,9 +637,12 @@ fn test_with_no_doc_stage0() {
             no_doc: true,
             doc: false,
             bless: false,
+            bless_dry_run: false,
             force_rerun: false,
             compare_mode: None,
             rustfix_coverage: false,
+            minimize: false,
+            json_report: None,
             pass: None,
             run: None,
             only_modified: false,
@@ -708,9 +711,12 @@ fn test_docs() {
             doc: true,
             no_doc: false,
             bless: false,
+            bless_dry_run: false,
             force_rerun: false,
             compare_mode: None,
             rustfix_coverage: false,
+            minimize: false,
+            json_report: None,
             pass: None,
             run: None,
             only_modified: false,
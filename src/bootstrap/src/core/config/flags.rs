@@ -360,6 +360,10 @@ pub enum Subcommand {
         /// whether to automatically update stderr/stdout files
         bless: bool,
         #[arg(long)]
+        /// show the changes `--bless` would make to stderr/stdout files, without writing them
+        /// (except in rustdoc and run-make tests)
+        bless_dry_run: bool,
+        #[arg(long)]
         /// comma-separated list of other files types to check (accepts py, py:lint,
         /// py:fmt, shell)
         extra_checks: Option<String>,
@@ -382,6 +386,14 @@ pub enum Subcommand {
         /// enable this to generate a Rustfix coverage file, which is saved in
         /// `/<build_base>/rustfix_missing_coverage.txt`
         rustfix_coverage: bool,
+        #[arg(long)]
+        /// reduce failing and crash tests while they fail the same way, writing the reduced
+        /// files to the build directory
+        minimize: bool,
+        #[arg(long, value_hint = clap::ValueHint::DirPath, value_name = "DIR")]
+        /// write a JSON report of the outcome and timings of each test to a file per suite and
+        /// mode in DIR
+        json_report: Option<PathBuf>,
     },
     /// Build and run some test suites *in Miri*
     Miri {
@@ -539,6 +551,13 @@ pub fn bless(&self) -> bool {
         }
     }
 
+    pub fn bless_dry_run(&self) -> bool {
+        match *self {
+            Subcommand::Test { bless_dry_run, .. } => bless_dry_run,
+            _ => false,
+        }
+    }
+
     pub fn extra_checks(&self) -> Option<&str> {
         match *self {
             Subcommand::Test { ref extra_checks, .. } => extra_checks.as_ref().map(String::as_str),
@@ -567,6 +586,20 @@ pub fn rustfix_coverage(&self) -> bool {
         }
     }
 
+    pub fn minimize(&self) -> bool {
+        match *self {
+            Subcommand::Test { minimize, .. } => minimize,
+            _ => false,
+        }
+    }
+
+    pub fn json_report(&self) -> Option<&Path> {
+        match *self {
+            Subcommand::Test { ref json_report, .. } => json_report.as_deref(),
+            _ => None,
+        }
+    }
+
     pub fn compare_mode(&self) -> Option<&str> {
         match *self {
             Subcommand::Test { ref compare_mode, .. } => compare_mode.as_ref().map(|s| &s[..]),
@@ -0,0 +1,886 @@
+//! Comparing the public API of two versions of a crate, for `--api-diff`.
+//!
+//! Both versions are read from the JSON output of rustdoc (`--output-format json`). Each of them is
+//! flattened into an [`ApiSurface`]: a map from every publicly reachable path to the signature of
+//! the item at that path. Trait implementations, including the auto trait implementations that
+//! rustdoc synthesizes, are part of the surface too. Comparing the two maps then gives the list of
+//! added, removed and changed items, which we classify following the [semver guidelines] of the
+//! Cargo book. Without type information this classification is necessarily approximate: it errs
+//! on the side of reporting a change as breaking.
+//!
+//! [semver guidelines]: https://doc.rust-lang.org/cargo/reference/semver.html
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{fmt, fs};
+
+use rinja::Template;
+use rustc_data_structures::fx::FxHashMap;
+use rustdoc_json_types::{
+    Crate, Id, Import, Item, ItemEnum, MacroKind, Module, StructKind, VariantKind, Visibility,
+    FORMAT_VERSION,
+};
+use serde::Deserialize;
+
+use self::signature::Printer;
+use crate::docfs::PathError;
+use crate::error::Error;
+
+pub(crate) mod signature;
+#[cfg(test)]
+mod tests;
+
+/// A public item, as far as it matters for the API of its crate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ApiItem {
+    /// What kind of item this is, e.g. "function" or "trait impl".
+    pub(crate) kind: &'static str,
+    /// The path of the item that contains this item, e.g. the module of a function or the trait
+    /// of an associated type.
+    pub(crate) parent: String,
+    pub(crate) signature: String,
+    /// The documentation of the item, as written in the source.
+    pub(crate) docs: Option<String>,
+    pub(crate) deprecated: bool,
+    /// For trait items: whether implementors have to provide this item.
+    pub(crate) required: bool,
+    /// Why adding this item breaks existing users of the crate, if it does.
+    pub(crate) breaking_if_added: Option<&'static str>,
+}
+
+/// The public API of one version of a crate.
+pub(crate) struct ApiSurface {
+    pub(crate) crate_name: String,
+    pub(crate) version: Option<String>,
+    /// All public items, by path. Trait implementations are listed under
+    /// `impl Trait for Type` (with fully qualified paths) instead.
+    pub(crate) items: BTreeMap<String, ApiItem>,
+}
+
+impl ApiSurface {
+    pub(crate) fn new(krate: &Crate) -> Self {
+        let root = &krate.index[&krate.root];
+        let crate_name = root.name.clone().unwrap_or_default();
+        let mut builder =
+            SurfaceBuilder { krate, items: BTreeMap::new(), module_stack: Vec::new() };
+        if let ItemEnum::Module(module) = &root.inner {
+            builder.module_stack.push(&root.id);
+            builder.visit_module(&crate_name, module);
+        }
+        ApiSurface { crate_name, version: krate.crate_version.clone(), items: builder.items }
+    }
+}
+
+struct SurfaceBuilder<'a> {
+    krate: &'a Crate,
+    items: BTreeMap<String, ApiItem>,
+    /// The modules we are currently inside of, to not follow cyclic re-exports forever.
+    module_stack: Vec<&'a Id>,
+}
+
+impl<'a> SurfaceBuilder<'a> {
+    fn get(&self, id: &Id) -> Option<&'a Item> {
+        self.krate.index.get(id)
+    }
+
+    fn printer(&self) -> Printer<'a> {
+        Printer::new(self.krate, false)
+    }
+
+    /// Records an item. If the same path is reached twice (e.g. through a glob re-export that is
+    /// shadowed by an item), the first one wins.
+    fn insert(
+        &mut self,
+        path: String,
+        parent: &str,
+        kind: &'static str,
+        signature: String,
+        item: &Item,
+    ) -> &mut ApiItem {
+        self.items.entry(path).or_insert_with(|| ApiItem {
+            kind,
+            parent: parent.to_owned(),
+            signature,
+            docs: item.docs.clone(),
+            deprecated: item.deprecation.is_some(),
+            required: false,
+            breaking_if_added: None,
+        })
+    }
+
+    fn visit_module(&mut self, path: &str, module: &'a Module) {
+        for id in &module.items {
+            // Items from other crates are only in the index if they are re-exported, which we
+            // handle in `visit_import`.
+            let Some(item) = self.get(id) else { continue };
+            if item.visibility != Visibility::Public {
+                continue;
+            }
+            match &item.inner {
+                ItemEnum::Import(import) => self.visit_import(path, import, item),
+                _ => {
+                    if let Some(name) = &item.name {
+                        self.visit_item(path, name, item);
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit_import(&mut self, parent: &str, import: &Import, item: &Item) {
+        let target = import.id.as_ref().and_then(|id| self.get(id));
+        if import.glob {
+            if let Some(Item { id, inner: ItemEnum::Module(module), .. }) = target {
+                if !self.module_stack.contains(&id) {
+                    self.module_stack.push(id);
+                    self.visit_module(parent, module);
+                    self.module_stack.pop();
+                }
+            } else {
+                let path = format!("{parent}::{}::*", import.source);
+                let signature = format!("pub use {}::*", import.source);
+                self.insert(path, parent, "re-export", signature, item);
+            }
+            return;
+        }
+        match target {
+            Some(target) => self.visit_item(parent, &import.name, target),
+            None => {
+                // A re-export of an item from another crate. We only know the path it came from.
+                let path = format!("{parent}::{}", import.name);
+                let mut signature = format!("pub use {}", import.source);
+                if !import.source.ends_with(&format!("::{}", import.name))
+                    && import.source != import.name
+                {
+                    signature.push_str(&format!(" as {}", import.name));
+                }
+                self.insert(path, parent, "re-export", signature, item);
+            }
+        }
+    }
+
+    /// Records `item`, which is reachable as `parent::name`, and all public items inside it.
+    fn visit_item(&mut self, parent: &str, name: &str, item: &'a Item) {
+        let path = format!("{parent}::{name}");
+        let mut p = self.printer();
+        match &item.inner {
+            ItemEnum::Module(module) => {
+                self.insert(path.clone(), parent, "module", format!("mod {name}"), item);
+                if !self.module_stack.contains(&&item.id) {
+                    self.module_stack.push(&item.id);
+                    self.visit_module(&path, module);
+                    self.module_stack.pop();
+                }
+            }
+            ItemEnum::ExternCrate { name: krate, rename } => {
+                let signature = match rename {
+                    Some(rename) => format!("extern crate {krate} as {rename}"),
+                    None => format!("extern crate {krate}"),
+                };
+                self.insert(path, parent, "extern crate", signature, item);
+            }
+            ItemEnum::Import(import) => self.visit_import(parent, import, item),
+            ItemEnum::Struct(struct_) => {
+                let non_exhaustive = is_non_exhaustive(item);
+                if non_exhaustive {
+                    p.str("#[non_exhaustive] ");
+                }
+                p.str("struct ").str(name).generic_params(&struct_.generics);
+                match &struct_.kind {
+                    StructKind::Unit => {
+                        p.where_clause(&struct_.generics).str(";");
+                    }
+                    StructKind::Tuple(fields) => {
+                        p.str("(");
+                        for (i, field) in fields.iter().enumerate() {
+                            if i != 0 {
+                                p.str(", ");
+                            }
+                            match field.as_ref().and_then(|id| self.get(id)) {
+                                Some(Item { inner: ItemEnum::StructField(ty), .. }) => {
+                                    p.str("pub ").ty(ty);
+                                }
+                                _ => {
+                                    p.str("_");
+                                }
+                            }
+                        }
+                        p.str(")").where_clause(&struct_.generics).str(";");
+                    }
+                    StructKind::Plain { fields, fields_stripped } => {
+                        p.where_clause(&struct_.generics);
+                        p.str(if *fields_stripped {
+                            " { /* private fields */ }"
+                        } else {
+                            " { .. }"
+                        });
+                        // Adding a field to a struct that has no private fields breaks struct
+                        // literals and patterns without `..`.
+                        let breaking_if_added = (!fields_stripped && !non_exhaustive).then_some(
+                            "the struct has no private fields and is not `#[non_exhaustive]`",
+                        );
+                        self.visit_fields(&path, fields, breaking_if_added);
+                    }
+                }
+                self.insert(path.clone(), parent, "struct", p.finish(), item);
+                self.visit_impls(&path, &struct_.impls);
+            }
+            ItemEnum::Union(union) => {
+                p.str("union ")
+                    .str(name)
+                    .generic_params(&union.generics)
+                    .where_clause(&union.generics)
+                    .str(if union.fields_stripped {
+                        " { /* private fields */ }"
+                    } else {
+                        " { .. }"
+                    });
+                self.insert(path.clone(), parent, "union", p.finish(), item);
+                self.visit_fields(&path, &union.fields, None);
+                self.visit_impls(&path, &union.impls);
+            }
+            ItemEnum::Enum(enum_) => {
+                let non_exhaustive = is_non_exhaustive(item);
+                if non_exhaustive {
+                    p.str("#[non_exhaustive] ");
+                }
+                p.str("enum ")
+                    .str(name)
+                    .generic_params(&enum_.generics)
+                    .where_clause(&enum_.generics)
+                    .str(" { .. }");
+                self.insert(path.clone(), parent, "enum", p.finish(), item);
+                let breaking_if_added =
+                    (!non_exhaustive).then_some("the enum is not `#[non_exhaustive]`");
+                for variant in enum_.variants.iter().filter_map(|id| self.get(id)) {
+                    self.visit_variant(&path, variant, breaking_if_added);
+                }
+                self.visit_impls(&path, &enum_.impls);
+            }
+            ItemEnum::Function(function) => {
+                p.header(&function.header)
+                    .str("fn ")
+                    .str(name)
+                    .generic_params(&function.generics)
+                    .fn_decl(&function.decl)
+                    .where_clause(&function.generics);
+                self.insert(path, parent, "function", p.finish(), item);
+            }
+            ItemEnum::Trait(trait_) => {
+                if trait_.is_unsafe {
+                    p.str("unsafe ");
+                }
+                if trait_.is_auto {
+                    p.str("auto ");
+                }
+                p.str("trait ").str(name).generic_params(&trait_.generics);
+                if !trait_.bounds.is_empty() {
+                    p.str(": ").bounds(&trait_.bounds);
+                }
+                p.where_clause(&trait_.generics);
+                self.insert(path.clone(), parent, "trait", p.finish(), item);
+                for trait_item in trait_.items.iter().filter_map(|id| self.get(id)) {
+                    self.visit_assoc_item(&path, trait_item, true);
+                }
+            }
+            ItemEnum::TraitAlias(alias) => {
+                p.str("trait ")
+                    .str(name)
+                    .generic_params(&alias.generics)
+                    .str(" = ")
+                    .bounds(&alias.params)
+                    .where_clause(&alias.generics);
+                self.insert(path, parent, "trait alias", p.finish(), item);
+            }
+            ItemEnum::TypeAlias(alias) => {
+                p.str("type ")
+                    .str(name)
+                    .generic_params(&alias.generics)
+                    .where_clause(&alias.generics)
+                    .str(" = ")
+                    .ty(&alias.type_);
+                self.insert(path, parent, "type alias", p.finish(), item);
+            }
+            ItemEnum::Constant { type_, const_: _ } => {
+                p.str("const ").str(name).str(": ").ty(type_);
+                self.insert(path, parent, "constant", p.finish(), item);
+            }
+            ItemEnum::Static(static_) => {
+                p.str(if static_.mutable { "static mut " } else { "static " })
+                    .str(name)
+                    .str(": ")
+                    .ty(&static_.type_);
+                self.insert(path, parent, "static", p.finish(), item);
+            }
+            ItemEnum::ForeignType => {
+                self.insert(path, parent, "foreign type", format!("type {name}"), item);
+            }
+            ItemEnum::Macro(macro_) => {
+                self.insert(path, parent, "macro", macro_.clone(), item);
+            }
+            ItemEnum::ProcMacro(proc_macro) => {
+                let signature = match proc_macro.kind {
+                    MacroKind::Bang => format!("{name}!()"),
+                    MacroKind::Attr => format!("#[{name}]"),
+                    MacroKind::Derive if proc_macro.helpers.is_empty() => {
+                        format!("#[derive({name})]")
+                    }
+                    MacroKind::Derive => {
+                        format!("#[derive({name})], attributes({})", proc_macro.helpers.join(", "))
+                    }
+                };
+                self.insert(path, parent, "proc macro", signature, item);
+            }
+            ItemEnum::Primitive(primitive) => {
+                self.insert(path.clone(), parent, "primitive", primitive.name.clone(), item);
+                self.visit_impls(&path, &primitive.impls);
+            }
+            // These are only reachable through their parent item.
+            ItemEnum::StructField(_)
+            | ItemEnum::Variant(_)
+            | ItemEnum::Impl(_)
+            | ItemEnum::AssocConst { .. }
+            | ItemEnum::AssocType { .. } => {}
+        }
+    }
+
+    fn visit_fields(
+        &mut self,
+        parent: &str,
+        fields: &[Id],
+        breaking_if_added: Option<&'static str>,
+    ) {
+        for field in fields.iter().filter_map(|id| self.get(id)) {
+            let (Some(name), ItemEnum::StructField(ty)) = (&field.name, &field.inner) else {
+                continue;
+            };
+            let signature = self.printer().str(name).str(": ").ty(ty).finish();
+            let path = format!("{parent}::{name}");
+            self.insert(path, parent, "field", signature, field).breaking_if_added =
+                breaking_if_added;
+        }
+    }
+
+    fn visit_variant(
+        &mut self,
+        parent: &str,
+        variant: &Item,
+        breaking_if_added: Option<&'static str>,
+    ) {
+        let (Some(name), ItemEnum::Variant(inner)) = (&variant.name, &variant.inner) else {
+            return;
+        };
+        let mut p = self.printer();
+        if is_non_exhaustive(variant) {
+            p.str("#[non_exhaustive] ");
+        }
+        p.str(name);
+        match &inner.kind {
+            VariantKind::Plain => {}
+            VariantKind::Tuple(fields) => {
+                p.str("(");
+                for (i, field) in fields.iter().enumerate() {
+                    if i != 0 {
+                        p.str(", ");
+                    }
+                    match field.as_ref().and_then(|id| self.get(id)) {
+                        Some(Item { inner: ItemEnum::StructField(ty), .. }) => {
+                            p.ty(ty);
+                        }
+                        _ => {
+                            p.str("_");
+                        }
+                    }
+                }
+                p.str(")");
+            }
+            VariantKind::Struct { fields, fields_stripped } => {
+                p.str(" { ");
+                for field in fields.iter().filter_map(|id| self.get(id)) {
+                    if let (Some(name), ItemEnum::StructField(ty)) = (&field.name, &field.inner) {
+                        p.str(name).str(": ").ty(ty).str(", ");
+                    }
+                }
+                p.str(if *fields_stripped { ".. }" } else { "}" });
+            }
+        }
+        if let Some(discriminant) = &inner.discriminant {
+            p.str(" = ").str(&discriminant.value);
+        }
+        let path = format!("{parent}::{name}");
+        self.insert(path, parent, "variant", p.finish(), variant).breaking_if_added =
+            breaking_if_added;
+    }
+
+    /// Records an associated item of a trait or an inherent impl.
+    fn visit_assoc_item(&mut self, parent: &str, item: &Item, in_trait: bool) {
+        let Some(name) = &item.name else { return };
+        let mut p = self.printer();
+        let (kind, required) = match &item.inner {
+            ItemEnum::Function(function) => {
+                p.header(&function.header)
+                    .str("fn ")
+                    .str(name)
+                    .generic_params(&function.generics)
+                    .fn_decl(&function.decl)
+                    .where_clause(&function.generics);
+                let is_method =
+                    function.decl.inputs.first().is_some_and(|(name, _)| name == "self");
+                (if is_method { "method" } else { "associated function" }, !function.has_body)
+            }
+            ItemEnum::AssocConst { type_, default } => {
+                p.str("const ").str(name).str(": ").ty(type_);
+                ("associated constant", default.is_none())
+            }
+            ItemEnum::AssocType { generics, bounds, default } => {
+                p.str("type ").str(name).generic_params(generics);
+                if !bounds.is_empty() {
+                    p.str(": ").bounds(bounds);
+                }
+                p.where_clause(generics);
+                if let Some(default) = default {
+                    p.str(" = ").ty(default);
+                }
+                ("associated type", default.is_none())
+            }
+            // Inherent impls can contain constants that are not associated constants.
+            ItemEnum::Constant { type_, const_: _ } => {
+                p.str("const ").str(name).str(": ").ty(type_);
+                ("associated constant", false)
+            }
+            _ => return,
+        };
+        let path = format!("{parent}::{name}");
+        let api_item = self.insert(path, parent, kind, p.finish(), item);
+        if in_trait && required {
+            api_item.required = true;
+            api_item.breaking_if_added =
+                Some("the trait gained a required item, which existing implementations lack");
+        }
+    }
+
+    /// Records the public items of the inherent impls, and the trait impls, of the type at
+    /// `owner`.
+    fn visit_impls(&mut self, owner: &str, impls: &[Id]) {
+        for item in impls.iter().filter_map(|id| self.get(id)) {
+            let ItemEnum::Impl(impl_) = &item.inner else { continue };
+            // Blanket impls follow from the other impls of the type, so they aren't interesting
+            // on their own.
+            if impl_.blanket_impl.is_some() {
+                continue;
+            }
+            let Some(trait_) = &impl_.trait_ else {
+                for assoc_item in impl_.items.iter().filter_map(|id| self.get(id)) {
+                    if assoc_item.visibility == Visibility::Public {
+                        self.visit_assoc_item(owner, assoc_item, false);
+                    }
+                }
+                continue;
+            };
+            let negative = if impl_.negative { "!" } else { "" };
+            // Trait impls don't have a path, so we identify them by the (fully qualified) trait
+            // and type.
+            let mut key = Printer::new(self.krate, true);
+            key.str("impl ").str(negative).path(trait_).str(" for ").ty(&impl_.for_);
+            let mut p = self.printer();
+            if impl_.is_unsafe {
+                p.str("unsafe ");
+            }
+            p.str("impl")
+                .generic_params(&impl_.generics)
+                .str(" ")
+                .str(negative)
+                .path(trait_)
+                .str(" for ")
+                .ty(&impl_.for_)
+                .where_clause(&impl_.generics);
+            let kind = if impl_.synthetic { "auto trait impl" } else { "trait impl" };
+            self.insert(key.finish(), owner, kind, p.finish(), item);
+        }
+    }
+}
+
+fn is_non_exhaustive(item: &Item) -> bool {
+    item.attrs.iter().any(|attr| attr == "#[non_exhaustive]")
+}
+
+/// How the version number of a crate has to change for a change to its API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum SemverLevel {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for SemverLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SemverLevel::Patch => "patch",
+            SemverLevel::Minor => "minor",
+            SemverLevel::Major => "major",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Changed => "changed",
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ApiChange {
+    pub(crate) path: String,
+    pub(crate) kind: &'static str,
+    pub(crate) change: ChangeKind,
+    /// The signature in the old version, unless the item was added.
+    pub(crate) old: Option<String>,
+    /// The signature in the new version, unless the item was removed.
+    pub(crate) new: Option<String>,
+    pub(crate) level: SemverLevel,
+    pub(crate) reason: &'static str,
+}
+
+/// All differences between the APIs of two versions of a crate.
+pub(crate) struct ApiDiff {
+    pub(crate) crate_name: String,
+    pub(crate) old_version: Option<String>,
+    pub(crate) new_version: Option<String>,
+    /// Sorted by path.
+    pub(crate) changes: Vec<ApiChange>,
+}
+
+impl ApiDiff {
+    pub(crate) fn new(old: &ApiSurface, new: &ApiSurface) -> Self {
+        let mut changes = Vec::new();
+        for (path, old_item) in &old.items {
+            let Some(new_item) = new.items.get(path) else {
+                // Don't list everything inside of a removed item separately.
+                if old.items.contains_key(&old_item.parent)
+                    && !new.items.contains_key(&old_item.parent)
+                {
+                    continue;
+                }
+                changes.push(ApiChange {
+                    path: path.clone(),
+                    kind: old_item.kind,
+                    change: ChangeKind::Removed,
+                    old: Some(old_item.signature.clone()),
+                    new: None,
+                    level: SemverLevel::Major,
+                    reason: "removed from the public API",
+                });
+                continue;
+            };
+            let (level, reason) = if old_item.signature != new_item.signature {
+                (SemverLevel::Major, "signature changed")
+            } else if !old_item.required && new_item.required {
+                (SemverLevel::Major, "no longer has a default, so implementations must provide it")
+            } else if old_item.required && !new_item.required {
+                (SemverLevel::Minor, "now has a default")
+            } else if !old_item.deprecated && new_item.deprecated {
+                (SemverLevel::Minor, "deprecated")
+            } else if old_item.deprecated && !new_item.deprecated {
+                (SemverLevel::Minor, "no longer deprecated")
+            } else {
+                continue;
+            };
+            changes.push(ApiChange {
+                path: path.clone(),
+                kind: new_item.kind,
+                change: ChangeKind::Changed,
+                old: Some(old_item.signature.clone()),
+                new: Some(new_item.signature.clone()),
+                level,
+                reason,
+            });
+        }
+        for (path, new_item) in &new.items {
+            if old.items.contains_key(path)
+                || (new.items.contains_key(&new_item.parent)
+                    && !old.items.contains_key(&new_item.parent))
+            {
+                continue;
+            }
+            let (level, reason) = match new_item.breaking_if_added {
+                Some(reason) => (SemverLevel::Major, reason),
+                None => (SemverLevel::Minor, "added to the public API"),
+            };
+            changes.push(ApiChange {
+                path: path.clone(),
+                kind: new_item.kind,
+                change: ChangeKind::Added,
+                old: None,
+                new: Some(new_item.signature.clone()),
+                level,
+                reason,
+            });
+        }
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        ApiDiff {
+            crate_name: new.crate_name.clone(),
+            old_version: old.version.clone(),
+            new_version: new.version.clone(),
+            changes,
+        }
+    }
+
+    /// The smallest version bump that is allowed for these changes.
+    pub(crate) fn required_bump(&self) -> SemverLevel {
+        self.changes.iter().map(|change| change.level).max().unwrap_or(SemverLevel::Patch)
+    }
+
+    fn changes_at(&self, level: SemverLevel) -> Vec<&ApiChange> {
+        self.changes.iter().filter(|change| change.level == level).collect()
+    }
+
+    fn versions(&self) -> (&str, &str) {
+        (
+            self.old_version.as_deref().unwrap_or("the old version"),
+            self.new_version.as_deref().unwrap_or("the new version"),
+        )
+    }
+
+    pub(crate) fn to_markdown(&self) -> String {
+        let (old_version, new_version) = self.versions();
+        let mut out = format!(
+            "# API changes in `{}` from {old_version} to {new_version}\n\n\
+            Required version bump: **{}**\n",
+            self.crate_name,
+            self.required_bump(),
+        );
+        for (level, title) in
+            [(SemverLevel::Major, "Breaking changes"), (SemverLevel::Minor, "Compatible changes")]
+        {
+            let changes = self.changes_at(level);
+            if changes.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n## {title} ({level})\n\n"));
+            for change in changes {
+                out.push_str(&format!(
+                    "- **{}** {} `{}`: {}\n",
+                    change.change, change.kind, change.path, change.reason
+                ));
+                match (&change.old, &change.new) {
+                    (Some(old), Some(new)) if old != new => {
+                        out.push_str(&format!("  - before: `{old}`\n  - after: `{new}`\n"));
+                    }
+                    (Some(signature), _) | (_, Some(signature)) => {
+                        out.push_str(&format!("  - `{signature}`\n"));
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+        out
+    }
+
+    pub(crate) fn to_html(&self) -> String {
+        let (old_version, new_version) = self.versions();
+        ApiDiffPage {
+            diff: self,
+            old_version,
+            new_version,
+            sections: vec![
+                ("Breaking changes", self.changes_at(SemverLevel::Major)),
+                ("Compatible changes", self.changes_at(SemverLevel::Minor)),
+            ],
+        }
+        .render()
+        .unwrap()
+    }
+}
+
+#[derive(Template)]
+#[template(path = "api_diff.html")]
+struct ApiDiffPage<'a> {
+    diff: &'a ApiDiff,
+    old_version: &'a str,
+    new_version: &'a str,
+    sections: Vec<(&'static str, Vec<&'a ApiChange>)>,
+}
+
+/// In which version each item of the public API of a crate was added, for the "since" badges of
+/// `--version-history`.
+#[derive(Debug)]
+pub(crate) struct VersionHistory {
+    /// For every path in the newest of the earlier versions: the oldest version since which it has
+    /// been part of the API without interruption.
+    since: FxHashMap<String, String>,
+    oldest_version: String,
+    /// The version being documented. Paths that are not in `since` were added in this version.
+    current_version: Option<String>,
+}
+
+impl VersionHistory {
+    /// `versions` are the APIs of earlier versions of the crate, oldest first.
+    pub(crate) fn new(versions: &[(String, ApiSurface)], current_version: Option<String>) -> Self {
+        let mut since = FxHashMap::default();
+        if let Some(((_, newest), older)) = versions.split_last() {
+            for path in newest.items.keys() {
+                let first = older
+                    .iter()
+                    .rposition(|(_, api)| !api.items.contains_key(path))
+                    .map_or(0, |missing| missing + 1);
+                since.insert(path.clone(), versions[first].0.clone());
+            }
+        }
+        let oldest_version =
+            versions.first().map(|(version, _)| version.clone()).unwrap_or_default();
+        VersionHistory { since, oldest_version, current_version }
+    }
+
+    /// Returns the version in which the item at `path` was added. Items that already exist in the
+    /// oldest known version may be even older, so we don't return anything for them.
+    pub(crate) fn since(&self, path: &str) -> Option<&str> {
+        match self.since.get(path) {
+            Some(version) if *version == self.oldest_version => None,
+            Some(version) => Some(version),
+            None => self.current_version.as_deref(),
+        }
+    }
+}
+
+/// Reads the JSON documentation of the earlier versions given with `--version-history`.
+pub(crate) fn load_version_history(
+    history: &[(String, PathBuf)],
+    current_version: Option<String>,
+) -> Result<VersionHistory, Error> {
+    let versions = history
+        .iter()
+        .map(|(version, path)| Ok((version.clone(), ApiSurface::new(&load_crate(path)?))))
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(VersionHistory::new(&versions, current_version))
+}
+
+/// The items of a crate when it is built with different `cfg`s, e.g. for different targets or
+/// features, for `--cfg-variant`.
+#[derive(Debug)]
+pub(crate) struct CfgVariants {
+    labels: Vec<String>,
+    /// For every path in any of the configurations: the item in each of them, in the order of
+    /// `labels`, or `None` if it doesn't exist in that configuration.
+    items: FxHashMap<String, Vec<Option<ApiItem>>>,
+}
+
+impl CfgVariants {
+    pub(crate) fn new(variants: Vec<(String, ApiSurface)>) -> Self {
+        let mut items: FxHashMap<String, Vec<Option<ApiItem>>> = FxHashMap::default();
+        let count = variants.len();
+        let labels = variants
+            .into_iter()
+            .enumerate()
+            .map(|(i, (label, api))| {
+                for (path, item) in api.items {
+                    items.entry(path).or_insert_with(|| vec![None; count])[i] = Some(item);
+                }
+                label
+            })
+            .collect();
+        CfgVariants { labels, items }
+    }
+
+    /// Returns the item at `path` in each configuration, if its signature or documentation is not
+    /// the same in all of them.
+    pub(crate) fn differing(&self, path: &str) -> Option<Vec<(&str, Option<&ApiItem>)>> {
+        let items = self.items.get(path)?;
+        let key = |item: &Option<ApiItem>| {
+            item.as_ref().map(|item| (item.signature.as_str(), item.docs.as_deref()))
+        };
+        if items.iter().all(|item| key(item) == key(&items[0])) {
+            return None;
+        }
+        Some(self.with_labels(items))
+    }
+
+    /// Returns the items directly inside of the item at `parent` in any of the configurations, by
+    /// name, with the item in each configuration. Sorted by name.
+    pub(crate) fn children(&self, parent: &str) -> Vec<(&str, Vec<(&str, Option<&ApiItem>)>)> {
+        let mut children: Vec<_> = self
+            .items
+            .iter()
+            .filter_map(|(path, items)| {
+                let name = path.strip_prefix(parent)?.strip_prefix("::")?;
+                // Glob re-exports of other crates are recorded as `parent::path::*`.
+                if name.contains("::") {
+                    return None;
+                }
+                let item = items.iter().flatten().next()?;
+                (item.parent == parent).then(|| (name, self.with_labels(items)))
+            })
+            .collect();
+        children.sort_by(|(a, _), (b, _)| a.cmp(b));
+        children
+    }
+
+    fn with_labels<'a>(
+        &'a self,
+        items: &'a [Option<ApiItem>],
+    ) -> Vec<(&'a str, Option<&'a ApiItem>)> {
+        self.labels.iter().map(String::as_str).zip(items.iter().map(Option::as_ref)).collect()
+    }
+}
+
+/// Reads the JSON documentation of the configurations given with `--cfg-variant`.
+pub(crate) fn load_cfg_variants(variants: &[(String, PathBuf)]) -> Result<CfgVariants, Error> {
+    let variants = variants
+        .iter()
+        .map(|(label, path)| Ok((label.clone(), ApiSurface::new(&load_crate(path)?))))
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(CfgVariants::new(variants))
+}
+
+/// The part of the JSON output we need to read before knowing whether we can read the rest.
+#[derive(Deserialize)]
+struct FormatVersion {
+    format_version: u32,
+}
+
+/// Reads the JSON output of rustdoc from `path`.
+pub(crate) fn load_crate(path: &Path) -> Result<Crate, Error> {
+    let data = fs::read(path).map_err(|err| Error::new(err, path))?;
+    let not_json =
+        |err: serde_json::Error| Error::new(format!("not rustdoc JSON output: {err}"), path);
+    let FormatVersion { format_version } = serde_json::from_slice(&data).map_err(not_json)?;
+    if format_version != FORMAT_VERSION {
+        return Err(Error::new(
+            format!(
+                "has format version {format_version}, but this rustdoc can only read format \
+                version {FORMAT_VERSION}; regenerate it with this rustdoc"
+            ),
+            path,
+        ));
+    }
+    serde_json::from_slice(&data).map_err(not_json)
+}
+
+/// Compares the JSON documentation at `old_path` with the one at `new_path`, and writes the report
+/// to `api-diff.md` and `api-diff.html` in `out_dir`.
+pub(crate) fn run(old_path: &Path, new_path: &Path, out_dir: &Path) -> Result<(), String> {
+    let old = ApiSurface::new(&load_crate(old_path).map_err(|err| err.to_string())?);
+    let new = ApiSurface::new(&load_crate(new_path).map_err(|err| err.to_string())?);
+    let diff = ApiDiff::new(&old, &new);
+
+    fs::create_dir_all(out_dir)
+        .map_err(|err| format!("failed to create `{}`: {err}", out_dir.display()))?;
+    for (file_name, contents) in
+        [("api-diff.md", diff.to_markdown()), ("api-diff.html", diff.to_html())]
+    {
+        let file = out_dir.join(file_name);
+        fs::write(&file, contents)
+            .map_err(|err| format!("failed to write `{}`: {err}", file.display()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,347 @@
+//! Printing the signatures of items from the JSON output as (approximately) Rust source code.
+//!
+//! The printed signatures are compared with each other and shown in the report, and in the
+//! declarations of the Markdown and man page output, so they don't need to be exactly what the
+//! user wrote, only stable between two runs of rustdoc.
+
+use std::fmt::Write;
+
+use rustdoc_json_types::{
+    Abi, Crate, DynTrait, FnDecl, FunctionPointer, GenericArg, GenericArgs, GenericBound,
+    GenericParamDef, GenericParamDefKind, Generics, Header, Path, PolyTrait, Term,
+    TraitBoundModifier, Type, TypeBindingKind, WherePredicate,
+};
+
+pub(crate) struct Printer<'a> {
+    krate: &'a Crate,
+    /// Whether to print paths to items as their fully qualified path (if rustdoc knows it) instead
+    /// of the path that was written in the source code.
+    full_paths: bool,
+    out: String,
+}
+
+impl<'a> Printer<'a> {
+    pub(crate) fn new(krate: &'a Crate, full_paths: bool) -> Self {
+        Printer { krate, full_paths, out: String::new() }
+    }
+
+    pub(crate) fn finish(self) -> String {
+        self.out
+    }
+
+    pub(crate) fn str(&mut self, s: &str) -> &mut Self {
+        self.out.push_str(s);
+        self
+    }
+
+    fn sep<T>(&mut self, items: &[T], sep: &str, mut f: impl FnMut(&mut Self, &T)) -> &mut Self {
+        for (i, item) in items.iter().enumerate() {
+            if i != 0 {
+                self.out.push_str(sep);
+            }
+            f(self, item);
+        }
+        self
+    }
+
+    pub(crate) fn path(&mut self, path: &Path) -> &mut Self {
+        match self.krate.paths.get(&path.id) {
+            Some(summary) if self.full_paths => {
+                let full_path = summary.path.join("::");
+                self.str(&full_path);
+            }
+            _ => {
+                self.str(&path.name);
+            }
+        }
+        if let Some(args) = &path.args {
+            self.generic_args(args);
+        }
+        self
+    }
+
+    pub(crate) fn ty(&mut self, ty: &Type) -> &mut Self {
+        match ty {
+            Type::ResolvedPath(path) => self.path(path),
+            Type::DynTrait(dyn_trait) => self.dyn_trait(dyn_trait),
+            Type::Generic(name) | Type::Primitive(name) => self.str(name),
+            Type::FunctionPointer(fn_ptr) => self.fn_ptr(fn_ptr),
+            Type::Tuple(types) => {
+                self.str("(").sep(types, ", ", |p, ty| {
+                    p.ty(ty);
+                });
+                if types.len() == 1 {
+                    self.str(",");
+                }
+                self.str(")")
+            }
+            Type::Slice(ty) => self.str("[").ty(ty).str("]"),
+            Type::Array { type_, len } => self.str("[").ty(type_).str("; ").str(len).str("]"),
+            Type::Pat { type_, __pat_unstable_do_not_use: pat } => {
+                self.ty(type_).str(" is ").str(pat)
+            }
+            Type::ImplTrait(bounds) => self.str("impl ").bounds(bounds),
+            Type::Infer => self.str("_"),
+            Type::RawPointer { mutable, type_ } => {
+                self.str(if *mutable { "*mut " } else { "*const " }).ty(type_)
+            }
+            Type::BorrowedRef { lifetime, mutable, type_ } => {
+                self.str("&");
+                if let Some(lifetime) = lifetime {
+                    self.str(lifetime).str(" ");
+                }
+                if *mutable {
+                    self.str("mut ");
+                }
+                self.ty(type_)
+            }
+            Type::QualifiedPath { name, args, self_type, trait_ } => {
+                match trait_ {
+                    Some(trait_) => {
+                        self.str("<").ty(self_type).str(" as ").path(trait_).str(">");
+                    }
+                    None => {
+                        self.ty(self_type);
+                    }
+                }
+                self.str("::").str(name).generic_args(args)
+            }
+        }
+    }
+
+    fn dyn_trait(&mut self, dyn_trait: &DynTrait) -> &mut Self {
+        self.str("dyn ").sep(&dyn_trait.traits, " + ", |p, poly_trait| {
+            p.poly_trait(poly_trait);
+        });
+        if let Some(lifetime) = &dyn_trait.lifetime {
+            self.str(" + ").str(lifetime);
+        }
+        self
+    }
+
+    fn poly_trait(&mut self, poly_trait: &PolyTrait) -> &mut Self {
+        self.for_binder(&poly_trait.generic_params).path(&poly_trait.trait_)
+    }
+
+    fn fn_ptr(&mut self, fn_ptr: &FunctionPointer) -> &mut Self {
+        self.for_binder(&fn_ptr.generic_params)
+            .header(&fn_ptr.header)
+            .str("fn")
+            .fn_decl(&fn_ptr.decl)
+    }
+
+    /// Prints a `for<'a>` binder, if there are any parameters.
+    fn for_binder(&mut self, params: &[GenericParamDef]) -> &mut Self {
+        if !params.is_empty() {
+            self.str("for<")
+                .sep(params, ", ", |p, param| {
+                    p.generic_param(param);
+                })
+                .str("> ");
+        }
+        self
+    }
+
+    pub(crate) fn generic_args(&mut self, args: &GenericArgs) -> &mut Self {
+        match args {
+            GenericArgs::AngleBracketed { args, bindings } => {
+                if args.is_empty() && bindings.is_empty() {
+                    return self;
+                }
+                self.str("<").sep(args, ", ", |p, arg| {
+                    p.generic_arg(arg);
+                });
+                if !args.is_empty() && !bindings.is_empty() {
+                    self.str(", ");
+                }
+                self.sep(bindings, ", ", |p, binding| {
+                    p.str(&binding.name).generic_args(&binding.args);
+                    match &binding.binding {
+                        TypeBindingKind::Equality(term) => {
+                            p.str(" = ").term(term);
+                        }
+                        TypeBindingKind::Constraint(bounds) => {
+                            p.str(": ").bounds(bounds);
+                        }
+                    }
+                })
+                .str(">")
+            }
+            GenericArgs::Parenthesized { inputs, output } => {
+                self.str("(")
+                    .sep(inputs, ", ", |p, ty| {
+                        p.ty(ty);
+                    })
+                    .str(")");
+                if let Some(output) = output {
+                    self.str(" -> ").ty(output);
+                }
+                self
+            }
+        }
+    }
+
+    fn generic_arg(&mut self, arg: &GenericArg) -> &mut Self {
+        match arg {
+            GenericArg::Lifetime(lifetime) => self.str(lifetime),
+            GenericArg::Type(ty) => self.ty(ty),
+            GenericArg::Const(constant) => self.str(&constant.expr),
+            GenericArg::Infer => self.str("_"),
+        }
+    }
+
+    fn term(&mut self, term: &Term) -> &mut Self {
+        match term {
+            Term::Type(ty) => self.ty(ty),
+            Term::Constant(constant) => self.str(&constant.expr),
+        }
+    }
+
+    pub(crate) fn bounds(&mut self, bounds: &[GenericBound]) -> &mut Self {
+        self.sep(bounds, " + ", |p, bound| {
+            p.bound(bound);
+        })
+    }
+
+    fn bound(&mut self, bound: &GenericBound) -> &mut Self {
+        match bound {
+            GenericBound::TraitBound { trait_, generic_params, modifier } => {
+                self.for_binder(generic_params);
+                match modifier {
+                    TraitBoundModifier::None => {}
+                    TraitBoundModifier::Maybe => {
+                        self.str("?");
+                    }
+                    TraitBoundModifier::MaybeConst => {
+                        self.str("~const ");
+                    }
+                }
+                self.path(trait_)
+            }
+            GenericBound::Outlives(lifetime) => self.str(lifetime),
+            GenericBound::Use(args) => self.str("use<").str(&args.join(", ")).str(">"),
+        }
+    }
+
+    fn generic_param(&mut self, param: &GenericParamDef) -> &mut Self {
+        match &param.kind {
+            GenericParamDefKind::Lifetime { outlives } => {
+                self.str(&param.name);
+                if !outlives.is_empty() {
+                    self.str(": ").str(&outlives.join(" + "));
+                }
+            }
+            GenericParamDefKind::Type { bounds, default, synthetic: _ } => {
+                self.str(&param.name);
+                if !bounds.is_empty() {
+                    self.str(": ").bounds(bounds);
+                }
+                if let Some(default) = default {
+                    self.str(" = ").ty(default);
+                }
+            }
+            GenericParamDefKind::Const { type_, default } => {
+                self.str("const ").str(&param.name).str(": ").ty(type_);
+                if let Some(default) = default {
+                    self.str(" = ").str(default);
+                }
+            }
+        }
+        self
+    }
+
+    /// Prints the generic parameters of `generics`, without the where clause.
+    pub(crate) fn generic_params(&mut self, generics: &Generics) -> &mut Self {
+        // Synthetic parameters come from `impl Trait` in argument position, which is printed in
+        // place.
+        let params: Vec<_> = generics
+            .params
+            .iter()
+            .filter(|param| {
+                !matches!(param.kind, GenericParamDefKind::Type { synthetic: true, .. })
+            })
+            .collect();
+        if !params.is_empty() {
+            self.str("<")
+                .sep(&params, ", ", |p, param| {
+                    p.generic_param(param);
+                })
+                .str(">");
+        }
+        self
+    }
+
+    pub(crate) fn where_clause(&mut self, generics: &Generics) -> &mut Self {
+        if generics.where_predicates.is_empty() {
+            return self;
+        }
+        self.str(" where ").sep(&generics.where_predicates, ", ", |p, predicate| match predicate {
+            WherePredicate::BoundPredicate { type_, bounds, generic_params } => {
+                p.for_binder(generic_params).ty(type_).str(": ").bounds(bounds);
+            }
+            WherePredicate::LifetimePredicate { lifetime, outlives } => {
+                p.str(lifetime).str(": ").str(&outlives.join(" + "));
+            }
+            WherePredicate::EqPredicate { lhs, rhs } => {
+                p.ty(lhs).str(" = ").term(rhs);
+            }
+        })
+    }
+
+    pub(crate) fn header(&mut self, header: &Header) -> &mut Self {
+        if header.const_ {
+            self.str("const ");
+        }
+        if header.async_ {
+            self.str("async ");
+        }
+        if header.unsafe_ {
+            self.str("unsafe ");
+        }
+        let (abi, unwind) = match &header.abi {
+            Abi::Rust => return self,
+            Abi::C { unwind } => ("C", *unwind),
+            Abi::Cdecl { unwind } => ("cdecl", *unwind),
+            Abi::Stdcall { unwind } => ("stdcall", *unwind),
+            Abi::Fastcall { unwind } => ("fastcall", *unwind),
+            Abi::Aapcs { unwind } => ("aapcs", *unwind),
+            Abi::Win64 { unwind } => ("win64", *unwind),
+            Abi::SysV64 { unwind } => ("sysv64", *unwind),
+            Abi::System { unwind } => ("system", *unwind),
+            Abi::Other(abi) => (abi.as_str(), false),
+        };
+        let _ = write!(self.out, "extern \"{abi}{}\" ", if unwind { "-unwind" } else { "" });
+        self
+    }
+
+    /// Prints the parameters and return type of a function.
+    pub(crate) fn fn_decl(&mut self, decl: &FnDecl) -> &mut Self {
+        self.str("(").sep(&decl.inputs, ", ", |p, (name, ty)| {
+            match (name.as_str(), ty) {
+                ("self", Type::Generic(self_ty)) if self_ty == "Self" => {
+                    p.str("self");
+                }
+                ("self", Type::BorrowedRef { lifetime, mutable, type_ })
+                    if matches!(&**type_, Type::Generic(self_ty) if self_ty == "Self") =>
+                {
+                    p.str("&");
+                    if let Some(lifetime) = lifetime {
+                        p.str(lifetime).str(" ");
+                    }
+                    p.str(if *mutable { "mut self" } else { "self" });
+                }
+                _ => {
+                    p.str(name).str(": ").ty(ty);
+                }
+            }
+        });
+        if decl.c_variadic {
+            self.str(if decl.inputs.is_empty() { "..." } else { ", ..." });
+        }
+        self.str(")");
+        if let Some(output) = &decl.output {
+            self.str(" -> ").ty(output);
+        }
+        self
+    }
+}
@@ -0,0 +1,210 @@
+use rustdoc_json_types::{Impl, Path, Struct, Type};
+
+use super::*;
+use crate::json::test_items::{function, generics, item, krate};
+
+fn plain_struct(id: &str, fields: &[&str], fields_stripped: bool, impls: &[&str]) -> Item {
+    item(
+        id,
+        Some("S"),
+        ItemEnum::Struct(Struct {
+            kind: StructKind::Plain {
+                fields: fields.iter().map(|id| Id(id.to_string())).collect(),
+                fields_stripped,
+            },
+            generics: generics(),
+            impls: impls.iter().map(|id| Id(id.to_string())).collect(),
+            layout: None,
+        }),
+    )
+}
+
+fn field(id: &str, name: &str) -> Item {
+    item(id, Some(name), ItemEnum::StructField(Type::Primitive("u8".to_owned())))
+}
+
+fn auto_trait_impl(id: &str, trait_: &str) -> Item {
+    let path =
+        |name: &str| Path { name: name.to_owned(), id: Id(format!("{name}-id")), args: None };
+    item(
+        id,
+        None,
+        ItemEnum::Impl(Impl {
+            is_unsafe: false,
+            generics: generics(),
+            provided_trait_methods: Vec::new(),
+            trait_: Some(path(trait_)),
+            for_: Type::ResolvedPath(path("S")),
+            items: Vec::new(),
+            negative: false,
+            synthetic: true,
+            blanket_impl: None,
+        }),
+    )
+}
+
+fn diff(old: Crate, new: Crate) -> Vec<(String, ChangeKind, SemverLevel)> {
+    ApiDiff::new(&ApiSurface::new(&old), &ApiSurface::new(&new))
+        .changes
+        .into_iter()
+        .map(|change| (change.path, change.change, change.level))
+        .collect()
+}
+
+#[test]
+fn functions() {
+    let old = krate(&["1", "2"], vec![function("1", "foo", vec![]), function("2", "bar", vec![])]);
+    let new = krate(
+        &["1", "3"],
+        vec![
+            function("1", "foo", vec![("x", Type::Primitive("u32".to_owned()))]),
+            function("3", "baz", vec![]),
+        ],
+    );
+    assert_eq!(
+        diff(old, new),
+        [
+            ("krate::bar".to_owned(), ChangeKind::Removed, SemverLevel::Major),
+            ("krate::baz".to_owned(), ChangeKind::Added, SemverLevel::Minor),
+            ("krate::foo".to_owned(), ChangeKind::Changed, SemverLevel::Major),
+        ]
+    );
+}
+
+#[test]
+fn signatures() {
+    let krate = krate(
+        &["1"],
+        vec![function(
+            "1",
+            "foo",
+            vec![
+                (
+                    "self",
+                    Type::BorrowedRef {
+                        lifetime: None,
+                        mutable: true,
+                        type_: Box::new(Type::Generic("Self".to_owned())),
+                    },
+                ),
+                ("x", Type::Tuple(vec![Type::Primitive("u8".to_owned())])),
+            ],
+        )],
+    );
+    let surface = ApiSurface::new(&krate);
+    assert_eq!(surface.items["krate::foo"].signature, "fn foo(&mut self, x: (u8,))");
+}
+
+#[test]
+fn struct_fields() {
+    // Adding a public field to a struct without private fields breaks struct literals.
+    let old = krate(&["1"], vec![plain_struct("1", &["2"], false, &[]), field("2", "a")]);
+    let new = krate(
+        &["1"],
+        vec![plain_struct("1", &["2", "3"], false, &[]), field("2", "a"), field("3", "b")],
+    );
+    assert_eq!(diff(old, new), [("krate::S::b".to_owned(), ChangeKind::Added, SemverLevel::Major)]);
+
+    // With private fields, it doesn't.
+    let old = krate(&["1"], vec![plain_struct("1", &["2"], true, &[]), field("2", "a")]);
+    let new = krate(
+        &["1"],
+        vec![plain_struct("1", &["2", "3"], true, &[]), field("2", "a"), field("3", "b")],
+    );
+    assert_eq!(diff(old, new), [("krate::S::b".to_owned(), ChangeKind::Added, SemverLevel::Minor)]);
+}
+
+#[test]
+fn auto_trait_impls() {
+    let old = krate(
+        &["1"],
+        vec![
+            plain_struct("1", &[], false, &["2", "3"]),
+            auto_trait_impl("2", "Send"),
+            auto_trait_impl("3", "Sync"),
+        ],
+    );
+    let new =
+        krate(&["1"], vec![plain_struct("1", &[], false, &["2"]), auto_trait_impl("2", "Send")]);
+    assert_eq!(
+        diff(old, new),
+        [("impl Sync for S".to_owned(), ChangeKind::Removed, SemverLevel::Major)]
+    );
+}
+
+#[test]
+fn removed_parent() {
+    // Only the struct is reported, not its fields and impls.
+    let old = krate(
+        &["1"],
+        vec![
+            plain_struct("1", &["2"], false, &["3"]),
+            field("2", "a"),
+            auto_trait_impl("3", "Send"),
+        ],
+    );
+    let new = krate(&[], vec![]);
+    assert_eq!(diff(old, new), [("krate::S".to_owned(), ChangeKind::Removed, SemverLevel::Major)]);
+}
+
+#[test]
+fn version_history() {
+    let v1 = krate(&["1", "4"], vec![function("1", "a", vec![]), function("4", "d", vec![])]);
+    let v2 = krate(&["2", "4"], vec![function("2", "b", vec![]), function("4", "d", vec![])]);
+    let v3 = krate(
+        &["2", "3", "4"],
+        vec![function("2", "b", vec![]), function("3", "a", vec![]), function("4", "d", vec![])],
+    );
+    let history = VersionHistory::new(
+        &[
+            ("1.0.0".to_owned(), ApiSurface::new(&v1)),
+            ("1.1.0".to_owned(), ApiSurface::new(&v2)),
+            ("2.0.0".to_owned(), ApiSurface::new(&v3)),
+        ],
+        Some("2.1.0".to_owned()),
+    );
+    // `a` was removed in 1.1.0 and added back in 2.0.0, so it only counts from there.
+    assert_eq!(history.since("krate::a"), Some("2.0.0"));
+    assert_eq!(history.since("krate::b"), Some("1.1.0"));
+    // `c` is new in the version being documented.
+    assert_eq!(history.since("krate::c"), Some("2.1.0"));
+    // `d` might be older than the oldest version we know about.
+    assert_eq!(history.since("krate::d"), None);
+}
+
+#[test]
+fn cfg_variants() {
+    let unix = krate(
+        &["1", "2", "3"],
+        vec![
+            function("1", "open", vec![("fd", Type::Primitive("i32".to_owned()))]),
+            function("2", "close", vec![]),
+            function("3", "pipe", vec![]),
+        ],
+    );
+    let windows = krate(
+        &["1", "2"],
+        vec![
+            function("1", "open", vec![("handle", Type::Primitive("isize".to_owned()))]),
+            function("2", "close", vec![]),
+        ],
+    );
+    let variants = CfgVariants::new(vec![
+        ("unix".to_owned(), ApiSurface::new(&unix)),
+        ("windows".to_owned(), ApiSurface::new(&windows)),
+    ]);
+    let open = variants.differing("krate::open").unwrap();
+    assert_eq!(open.iter().map(|(label, _)| *label).collect::<Vec<_>>(), ["unix", "windows"]);
+    assert_ne!(open[0].1, open[1].1);
+    assert_eq!(variants.differing("krate::close"), None);
+    let pipe = variants.differing("krate::pipe").unwrap();
+    assert!(pipe[0].1.is_some());
+    assert_eq!(pipe[1], ("windows", None));
+    assert_eq!(variants.differing("krate::missing"), None);
+    let children = variants.children("krate");
+    assert_eq!(
+        children.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+        ["close", "open", "pipe"]
+    );
+    assert_eq!(children[2].1, pipe);
+}
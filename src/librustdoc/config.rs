@@ -33,12 +33,22 @@ pub(crate) enum OutputFormat {
     Json,
     #[default]
     Html,
+    /// One Markdown file per module.
+    Markdown,
+    /// One man page per module.
+    Man,
 }
 
 impl OutputFormat {
     pub(crate) fn is_json(&self) -> bool {
         matches!(self, OutputFormat::Json)
     }
+
+    /// Whether the crate is rendered from the JSON model, which keeps re-exports as they are
+    /// instead of inlining them. The Markdown and man page backends are built on top of it.
+    pub(crate) fn is_json_model(&self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::Markdown | OutputFormat::Man)
+    }
 }
 
 impl TryFrom<&str> for OutputFormat {
@@ -48,6 +58,8 @@ fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             "json" => Ok(OutputFormat::Json),
             "html" => Ok(OutputFormat::Html),
+            "markdown" => Ok(OutputFormat::Markdown),
+            "man" => Ok(OutputFormat::Man),
             _ => Err(format!("unknown output format `{value}`")),
         }
     }
@@ -123,6 +135,8 @@ pub(crate) struct Options {
     pub(crate) enable_per_target_ignores: bool,
     /// Do not run doctests, compile them if should_test is active.
     pub(crate) no_run: bool,
+    /// Directory to write a report about the outcome of every doctest into.
+    pub(crate) doctest_report: Option<PathBuf>,
     /// What sources are being mapped.
     pub(crate) remap_path_prefix: Vec<(PathBuf, PathBuf)>,
 
@@ -137,6 +151,9 @@ pub(crate) struct Options {
     /// Whether to run the `calculate-doc-coverage` pass, which counts the number of public items
     /// with and without documentation.
     pub(crate) show_coverage: bool,
+    /// Instead of documenting a crate, compare the JSON documentation given as input against the
+    /// older JSON documentation at this path, and write a report of the API changes.
+    pub(crate) api_diff: Option<PathBuf>,
 
     // Options that alter generated documentation pages
     /// Crate version to note on the sidebar of generated docs.
@@ -201,12 +218,14 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             .field("test_run_directory", &self.test_run_directory)
             .field("persist_doctests", &self.persist_doctests)
             .field("show_coverage", &self.show_coverage)
+            .field("api_diff", &self.api_diff)
             .field("crate_version", &self.crate_version)
             .field("runtool", &self.runtool)
             .field("runtool_args", &self.runtool_args)
             .field("enable-per-target-ignores", &self.enable_per_target_ignores)
             .field("run_check", &self.run_check)
             .field("no_run", &self.no_run)
+            .field("doctest_report", &self.doctest_report)
             .field("test_builder_wrappers", &self.test_builder_wrappers)
             .field("remap-file-prefix", &self.remap_path_prefix)
             .field("nocapture", &self.nocapture)
@@ -280,12 +299,25 @@ pub(crate) struct RenderOptions {
     pub(crate) emit: Vec<EmitType>,
     /// If `true`, HTML source pages will generate links for items to their definition.
     pub(crate) generate_link_to_definition: bool,
+    /// If `true`, the JSON output lists the items referred to by the body of each function.
+    pub(crate) json_body_references: bool,
+    /// If `true`, the search index includes a full-text index of the documentation.
+    pub(crate) full_text_search: bool,
     /// Set of function-call locations to include as examples
     pub(crate) call_locations: AllCallLocations,
     /// If `true`, Context::init will not emit shared files.
     pub(crate) no_emit_shared: bool,
     /// If `true`, HTML source code pages won't be generated.
     pub(crate) html_no_source: bool,
+    /// With `--versioned-docs`, the directory that contains the documentation of all versions,
+    /// while `output` is the subdirectory for the version being documented.
+    pub(crate) versioned_docs_root: Option<PathBuf>,
+    /// JSON documentation of earlier versions of the crate, oldest first, given with
+    /// `--version-history`.
+    pub(crate) version_history: Vec<(String, PathBuf)>,
+    /// JSON documentation of the crate built with other `cfg`s, with the label of each
+    /// configuration, given with `--cfg-variant`.
+    pub(crate) cfg_variants: Vec<(String, PathBuf)>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -546,6 +578,10 @@ fn println_condition(condition: Condition) {
         if !should_test && no_run {
             dcx.fatal("the `--test` flag must be passed to enable `--no-run`");
         }
+        let doctest_report = matches.opt_str("doctest-report").map(PathBuf::from);
+        if !should_test && doctest_report.is_some() {
+            dcx.fatal("the `--test` flag must be passed to enable `--doctest-report`");
+        }
 
         let test_builder_wrappers =
             matches.opt_strs("test-builder-wrapper").iter().map(PathBuf::from).collect();
@@ -560,6 +596,38 @@ fn println_condition(condition: Condition) {
             (None, None) => PathBuf::from("doc"),
         };
 
+        let api_diff = matches.opt_str("api-diff").map(PathBuf::from);
+        if api_diff.is_some() {
+            if input.opt_path().is_none() {
+                dcx.fatal("`--api-diff` cannot read the new JSON documentation from stdin");
+            }
+            // `--api-diff` only reads two JSON files, so none of the options that affect how a
+            // crate is compiled, tested or documented would have an effect.
+            let incompatible = [
+                "cfg",
+                "cfg-variant",
+                "check-cfg",
+                "crate-type",
+                "edition",
+                "extern",
+                "L",
+                "output-format",
+                "show-coverage",
+                "target",
+                "test",
+            ];
+            for flag in incompatible {
+                if matches.opt_present(flag) {
+                    let dashes = if flag.len() == 1 { "-" } else { "--" };
+                    dcx.fatal(format!("`--api-diff` cannot be combined with `{dashes}{flag}`"));
+                }
+            }
+            if let Some(flag) = matches.opt_strs("Z").into_iter().find(|z| z != "unstable-options")
+            {
+                dcx.fatal(format!("`--api-diff` cannot be combined with `-Z {flag}`"));
+            }
+        }
+
         let cfgs = matches.opt_strs("cfg");
         let check_cfgs = matches.opt_strs("check-cfg");
 
@@ -684,9 +752,9 @@ fn println_condition(condition: Condition) {
             Some(s) => match OutputFormat::try_from(s.as_str()) {
                 Ok(out_fmt) => {
                     if !out_fmt.is_json() && show_coverage {
-                        dcx.fatal(
-                            "html output format isn't supported for the --show-coverage option",
-                        );
+                        dcx.fatal(format!(
+                            "{s} output format isn't supported for the --show-coverage option"
+                        ));
                     }
                     out_fmt
                 }
@@ -727,15 +795,52 @@ fn println_condition(condition: Condition) {
         let show_type_layout = matches.opt_present("show-type-layout");
         let nocapture = matches.opt_present("nocapture");
         let generate_link_to_definition = matches.opt_present("generate-link-to-definition");
+        let json_body_references = matches.opt_present("json-body-references");
+        let full_text_search = matches.opt_present("full-text-search");
         let extern_html_root_takes_precedence =
             matches.opt_present("extern-html-root-takes-precedence");
         let html_no_source = matches.opt_present("html-no-source");
+        let (output, versioned_docs_root) = if matches.opt_present("versioned-docs") {
+            let Some(crate_version) = &crate_version else {
+                dcx.fatal("`--versioned-docs` requires `--crate-version`");
+            };
+            (output.join(crate_version), Some(output))
+        } else {
+            (output, None)
+        };
+        let version_history = matches
+            .opt_strs("version-history")
+            .into_iter()
+            .map(|arg| match arg.split_once('=') {
+                Some((version, path)) => (version.to_owned(), PathBuf::from(path)),
+                None => dcx.fatal(format!(
+                    "`--version-history` expects `VERSION=PATH`, found `{arg}`"
+                )),
+            })
+            .collect();
+        let cfg_variants: Vec<_> = matches
+            .opt_strs("cfg-variant")
+            .into_iter()
+            .map(|arg| match arg.split_once('=') {
+                Some((label, path)) => (label.to_owned(), PathBuf::from(path)),
+                None => dcx.fatal(format!("`--cfg-variant` expects `LABEL=PATH`, found `{arg}`")),
+            })
+            .collect();
 
         if generate_link_to_definition && (show_coverage || output_format != OutputFormat::Html) {
             dcx.fatal(
                 "--generate-link-to-definition option can only be used with HTML output format",
             );
         }
+        if json_body_references && output_format != OutputFormat::Json {
+            dcx.fatal("--json-body-references option can only be used with JSON output format");
+        }
+        if full_text_search && (show_coverage || output_format != OutputFormat::Html) {
+            dcx.fatal("--full-text-search option can only be used with HTML output format");
+        }
+        if !cfg_variants.is_empty() && (show_coverage || output_format != OutputFormat::Html) {
+            dcx.fatal("--cfg-variant option can only be used with HTML output format");
+        }
 
         let scrape_examples_options = ScrapeExamplesOptions::new(matches, dcx);
         let with_examples = matches.opt_strs("with-examples");
@@ -768,6 +873,7 @@ fn println_condition(condition: Condition) {
             should_test,
             test_args,
             show_coverage,
+            api_diff,
             crate_version,
             test_run_directory,
             persist_doctests,
@@ -777,6 +883,7 @@ fn println_condition(condition: Condition) {
             test_builder,
             run_check,
             no_run,
+            doctest_report,
             test_builder_wrappers,
             remap_path_prefix,
             nocapture,
@@ -812,9 +919,14 @@ fn println_condition(condition: Condition) {
             unstable_features,
             emit,
             generate_link_to_definition,
+            json_body_references,
+            full_text_search,
             call_locations,
             no_emit_shared: false,
             html_no_source,
+            versioned_docs_root,
+            version_history,
+            cfg_variants,
         };
         Some((options, render_options))
     }
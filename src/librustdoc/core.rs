@@ -339,7 +339,10 @@ pub(crate) fn run_global_ctxt(
         impl_trait_bounds: Default::default(),
         generated_synthetics: Default::default(),
         auto_traits,
-        cache: Cache::new(render_options.document_private, render_options.document_hidden),
+        cache: Cache {
+            full_text_search: render_options.full_text_search,
+            ..Cache::new(render_options.document_private, render_options.document_hidden)
+        },
         inlined: FxHashSet::default(),
         output_format,
         render_options,
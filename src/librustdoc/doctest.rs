@@ -1,5 +1,6 @@
 mod make;
 mod markdown;
+mod report;
 mod rust;
 
 use std::fs::File;
@@ -8,6 +9,7 @@
 use std::process::{self, Command, Stdio};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use std::{panic, str};
 
 pub(crate) use make::make_test;
@@ -26,6 +28,7 @@
 use rustc_target::spec::{Target, TargetTriple};
 use tempfile::{Builder as TempFileBuilder, TempDir};
 
+use self::report::{DoctestReport, RecordHandle, TestOutcome, TestRecord};
 use self::rust::HirCollector;
 use crate::config::Options as RustdocOptions;
 use crate::html::markdown::{ErrorCodes, Ignore, LangString, MdRelLine};
@@ -164,7 +167,7 @@ pub(crate) fn run(dcx: DiagCtxtHandle<'_>, options: RustdocOptions) -> Result<()
     let args_path = temp_dir.path().join("rustdoc-cfgs");
     crate::wrap_return(dcx, generate_args_file(&args_path, &options))?;
 
-    let (tests, unused_extern_reports, compiling_test_count) =
+    let (tests, unused_extern_reports, compiling_test_count, report) =
         interface::run_compiler(config, |compiler| {
             compiler.enter(|queries| {
                 let collector = queries.global_ctxt()?.enter(|tcx| {
@@ -181,8 +184,11 @@ pub(crate) fn run(dcx: DiagCtxtHandle<'_>, options: RustdocOptions) -> Result<()
                         enable_per_target_ignores,
                         tcx,
                     );
-                    let tests = hir_collector.collect_crate();
+                    let (tests, items) = hir_collector.collect_crate();
                     tests.into_iter().for_each(|t| collector.add_test(t));
+                    if let Some(report) = &mut collector.report {
+                        report.set_items(items);
+                    }
 
                     collector
                 });
@@ -192,11 +198,11 @@ pub(crate) fn run(dcx: DiagCtxtHandle<'_>, options: RustdocOptions) -> Result<()
 
                 let unused_extern_reports = collector.unused_extern_reports.clone();
                 let compiling_test_count = collector.compiling_test_count.load(Ordering::SeqCst);
-                Ok((collector.tests, unused_extern_reports, compiling_test_count))
+                Ok((collector.tests, unused_extern_reports, compiling_test_count, collector.report))
             })
         })?;
 
-    run_tests(test_args, nocapture, tests);
+    run_tests(test_args, nocapture, tests, report);
 
     // Collect and warn about unused externs, but only if we've gotten
     // reports for each doctest
@@ -239,17 +245,52 @@ pub(crate) fn run(dcx: DiagCtxtHandle<'_>, options: RustdocOptions) -> Result<()
     Ok(())
 }
 
+/// The exit code of the test harness when a test fails, as used by `test::test_main`.
+const TEST_ERROR_EXIT_CODE: i32 = 101;
+
 pub(crate) fn run_tests(
     mut test_args: Vec<String>,
     nocapture: bool,
     mut tests: Vec<test::TestDescAndFn>,
+    report: Option<DoctestReport>,
 ) {
     test_args.insert(0, "rustdoctest".to_string());
     if nocapture {
         test_args.push("--nocapture".to_string());
     }
     tests.sort_by(|a, b| a.desc.name.as_slice().cmp(&b.desc.name.as_slice()));
-    test::test_main(&test_args, tests, None);
+    let Some(report) = report else {
+        test::test_main(&test_args, tests, None);
+        return;
+    };
+
+    // `test_main` exits the process if a test fails, so run the tests ourselves to be able to
+    // write the report in any case.
+    let opts = match test::test::parse_opts(&test_args) {
+        Some(Ok(opts)) if !opts.list => opts,
+        Some(Ok(_)) => {
+            test::test_main(&test_args, tests, None);
+            return;
+        }
+        Some(Err(msg)) => {
+            eprintln!("error: {msg}");
+            process::exit(TEST_ERROR_EXIT_CODE);
+        }
+        None => return,
+    };
+    let result = test::run_tests_console(&opts, tests);
+    if let Err(err) = report.write() {
+        eprintln!("error: failed to write the doctest report: {err}");
+        process::exit(TEST_ERROR_EXIT_CODE);
+    }
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(TEST_ERROR_EXIT_CODE),
+        Err(err) => {
+            eprintln!("error: io error when running tests: {err:?}");
+            process::exit(TEST_ERROR_EXIT_CODE);
+        }
+    }
 }
 
 // Look for `#![doc(test(no_crate_inject))]`, used by crates in the std facade.
@@ -624,10 +665,16 @@ struct CreateRunnableDoctests {
     visited_tests: FxHashMap<(String, usize), usize>,
     unused_extern_reports: Arc<Mutex<Vec<UnusedExterns>>>,
     compiling_test_count: AtomicUsize,
+    /// With `--doctest-report`, the outcome of every test.
+    report: Option<DoctestReport>,
 }
 
 impl CreateRunnableDoctests {
     fn new(rustdoc_options: RustdocOptions, opts: GlobalTestOptions) -> CreateRunnableDoctests {
+        let report = rustdoc_options
+            .doctest_report
+            .clone()
+            .map(|out_dir| DoctestReport::new(out_dir, opts.crate_name.clone()));
         CreateRunnableDoctests {
             tests: Vec::new(),
             rustdoc_options: Arc::new(rustdoc_options),
@@ -635,6 +682,7 @@ fn new(rustdoc_options: RustdocOptions, opts: GlobalTestOptions) -> CreateRunnab
             visited_tests: FxHashMap::default(),
             unused_extern_reports: Default::default(),
             compiling_test_count: AtomicUsize::new(0),
+            report,
         }
     }
 
@@ -692,16 +740,25 @@ fn add_test(&mut self, test: ScrapedDoctest) {
 
         let rustdoc_options = self.rustdoc_options.clone();
         let rustdoc_test_options = IndividualTestOptions::new(&self.rustdoc_options, test_id, path);
+        let ignore = match test.langstr.ignore {
+            Ignore::All => true,
+            Ignore::None => false,
+            Ignore::Some(ref ignores) => ignores.iter().any(|s| target_str.contains(s)),
+        };
+        let record = self.report.as_ref().map(|report| {
+            report.add_test(TestRecord::new(
+                name.clone(),
+                &test,
+                ignore,
+                test.no_run(&rustdoc_options),
+            ))
+        });
 
         debug!("creating test {name}: {}", test.text);
         self.tests.push(test::TestDescAndFn {
             desc: test::TestDesc {
                 name: test::DynTestName(name),
-                ignore: match test.langstr.ignore {
-                    Ignore::All => true,
-                    Ignore::None => false,
-                    Ignore::Some(ref ignores) => ignores.iter().any(|s| target_str.contains(s)),
-                },
+                ignore,
                 ignore_message: None,
                 source_file: "",
                 start_line: 0,
@@ -715,7 +772,14 @@ fn add_test(&mut self, test: ScrapedDoctest) {
                 test_type: test::TestType::DocTest,
             },
             testfn: test::DynTestFn(Box::new(move || {
-                doctest_run_fn(rustdoc_test_options, opts, test, rustdoc_options, unused_externs)
+                doctest_run_fn(
+                    rustdoc_test_options,
+                    opts,
+                    test,
+                    rustdoc_options,
+                    unused_externs,
+                    record,
+                )
             })),
         });
     }
@@ -727,7 +791,9 @@ fn doctest_run_fn(
     scraped_test: ScrapedDoctest,
     rustdoc_options: Arc<RustdocOptions>,
     unused_externs: Arc<Mutex<Vec<UnusedExterns>>>,
+    record: Option<RecordHandle>,
 ) -> Result<(), String> {
+    let start = Instant::now();
     let report_unused_externs = |uext| {
         unused_externs.lock().unwrap().push(uext);
     };
@@ -747,7 +813,11 @@ fn doctest_run_fn(
         global_opts,
         scraped_test,
     };
+    let no_run = runnable_test.scraped_test.no_run(&rustdoc_options);
     let res = run_test(runnable_test, &rustdoc_options, supports_color, report_unused_externs);
+    if let Some(record) = record {
+        record.finish(TestOutcome::new(&res, no_run), start.elapsed());
+    }
 
     if let Err(err) = res {
         match err {
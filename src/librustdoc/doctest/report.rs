@@ -0,0 +1,255 @@
+//! The report written by `--doctest-report`: the outcome of every doctest, and which public items
+//! have no example that is actually run.
+
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{fmt, fs, io};
+
+use rinja::Template;
+use serde::Serialize;
+
+use super::{ScrapedDoctest, TestFailure};
+use crate::html::markdown::Ignore;
+
+/// What happened to a doctest.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub(crate) enum TestOutcome {
+    /// The test compiled and ran successfully (or failed to compile, for `compile_fail`).
+    Passed,
+    /// The test compiled, but wasn't run because of `no_run` or `--no-run`.
+    Compiled,
+    Failed {
+        reason: String,
+    },
+    Ignored,
+    /// The test was filtered out by the arguments given to the test harness.
+    NotRun,
+}
+
+impl TestOutcome {
+    pub(super) fn new(result: &Result<(), TestFailure>, no_run: bool) -> Self {
+        let reason = match result {
+            Ok(()) if no_run => return TestOutcome::Compiled,
+            Ok(()) => return TestOutcome::Passed,
+            Err(TestFailure::CompileError) => "failed to compile".to_owned(),
+            Err(TestFailure::UnexpectedCompilePass) => {
+                "compiled successfully, but is marked `compile_fail`".to_owned()
+            }
+            Err(TestFailure::MissingErrorCodes(codes)) => {
+                format!("expected error codes were not found: {}", codes.join(", "))
+            }
+            Err(TestFailure::ExecutionError(err)) => format!("could not be run: {err}"),
+            Err(TestFailure::ExecutionFailure(out)) => format!("failed ({})", out.status),
+            Err(TestFailure::UnexpectedRunPass) => {
+                "succeeded, but is marked `should_panic`".to_owned()
+            }
+        };
+        TestOutcome::Failed { reason }
+    }
+
+    fn class(&self) -> &'static str {
+        match self {
+            TestOutcome::Passed | TestOutcome::Compiled => "passed",
+            TestOutcome::Failed { .. } => "failed",
+            TestOutcome::Ignored | TestOutcome::NotRun => "skipped",
+        }
+    }
+}
+
+impl fmt::Display for TestOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestOutcome::Passed => f.write_str("passed"),
+            TestOutcome::Compiled => f.write_str("compiled"),
+            TestOutcome::Failed { reason } => write!(f, "failed: {reason}"),
+            TestOutcome::Ignored => f.write_str("ignored"),
+            TestOutcome::NotRun => f.write_str("not run"),
+        }
+    }
+}
+
+/// One doctest, as it appears in the report.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct TestRecord {
+    /// The name of the test, as shown by the test harness.
+    name: String,
+    /// The item whose documentation contains the test.
+    item: String,
+    file: String,
+    line: usize,
+    /// The attributes of the code block, like `should_panic` or `edition2021`.
+    attributes: Vec<String>,
+    /// Whether the test is run when it isn't ignored, i.e. it isn't `no_run` or `compile_fail`.
+    runnable: bool,
+    #[serde(flatten)]
+    outcome: TestOutcome,
+    /// How long compiling and running the test took, in seconds.
+    duration: Option<f64>,
+}
+
+impl TestRecord {
+    pub(super) fn new(name: String, test: &ScrapedDoctest, ignore: bool, no_run: bool) -> Self {
+        let langstr = &test.langstr;
+        let mut attributes = Vec::new();
+        match &langstr.ignore {
+            Ignore::All => attributes.push("ignore".to_owned()),
+            Ignore::None => {}
+            Ignore::Some(targets) => {
+                attributes.extend(targets.iter().map(|target| format!("ignore-{target}")))
+            }
+        }
+        for (attr, present) in [
+            ("no_run", langstr.no_run),
+            ("should_panic", langstr.should_panic),
+            ("compile_fail", langstr.compile_fail),
+            ("test_harness", langstr.test_harness),
+        ] {
+            if present {
+                attributes.push(attr.to_owned());
+            }
+        }
+        if let Some(edition) = langstr.edition {
+            attributes.push(format!("edition{edition}"));
+        }
+        attributes.extend(langstr.error_codes.iter().cloned());
+
+        TestRecord {
+            name,
+            item: test.logical_path.join("::"),
+            file: test.filename.prefer_remapped_unconditionaly().to_string(),
+            line: test.line,
+            attributes,
+            runnable: !no_run && !langstr.compile_fail,
+            outcome: if ignore { TestOutcome::Ignored } else { TestOutcome::NotRun },
+            duration: None,
+        }
+    }
+
+    fn display_duration(&self) -> String {
+        self.duration.map(|duration| format!("{duration:.2}s")).unwrap_or_default()
+    }
+}
+
+/// Lets a doctest write its outcome into the report once it's done.
+#[derive(Clone)]
+pub(super) struct RecordHandle {
+    records: Arc<Mutex<Vec<TestRecord>>>,
+    index: usize,
+}
+
+impl RecordHandle {
+    pub(super) fn finish(&self, outcome: TestOutcome, duration: Duration) {
+        let mut records = self.records.lock().unwrap();
+        let record = &mut records[self.index];
+        record.outcome = outcome;
+        record.duration = Some(duration.as_secs_f64());
+    }
+}
+
+/// A public item that should have a code example in its documentation, like for the
+/// `missing_doc_code_examples` lint.
+#[derive(Debug)]
+pub(crate) struct DocumentedItem {
+    pub(crate) path: String,
+    /// The indices of the doctests of this item.
+    pub(crate) tests: Range<usize>,
+}
+
+/// How well the examples in the docs of an item are tested.
+#[derive(Debug, Serialize)]
+struct ItemCoverage<'a> {
+    item: &'a str,
+    /// All code examples, including the ignored ones.
+    examples: usize,
+    /// The examples that are neither ignored, `no_run` nor `compile_fail`.
+    runnable: usize,
+    /// The runnable examples that passed.
+    passed: usize,
+}
+
+impl ItemCoverage<'_> {
+    fn class(&self) -> &'static str {
+        if self.runnable == 0 { "missing" } else { "" }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    crate_name: &'a str,
+    tests: &'a [TestRecord],
+    coverage: &'a [ItemCoverage<'a>],
+    /// The number of items in `coverage` that don't have a runnable example.
+    items_without_runnable_example: usize,
+}
+
+#[derive(Template)]
+#[template(path = "doctest_report.html")]
+struct ReportPage<'a> {
+    report: &'a JsonReport<'a>,
+}
+
+/// The report being collected while the doctests run, written to `out_dir` once they're all done.
+pub(crate) struct DoctestReport {
+    out_dir: PathBuf,
+    crate_name: String,
+    records: Arc<Mutex<Vec<TestRecord>>>,
+    items: Vec<DocumentedItem>,
+}
+
+impl DoctestReport {
+    pub(crate) fn new(out_dir: PathBuf, crate_name: String) -> Self {
+        DoctestReport { out_dir, crate_name, records: Default::default(), items: Vec::new() }
+    }
+
+    pub(crate) fn set_items(&mut self, items: Vec<DocumentedItem>) {
+        self.items = items;
+    }
+
+    /// Adds a test to the report, in the same order as the tests are scraped.
+    pub(super) fn add_test(&self, mut record: TestRecord) -> RecordHandle {
+        if record.item.is_empty() {
+            record.item = self.crate_name.clone();
+        }
+        let mut records = self.records.lock().unwrap();
+        records.push(record);
+        RecordHandle { records: Arc::clone(&self.records), index: records.len() - 1 }
+    }
+
+    /// Writes `doctests.json` and `doctests.html` into the output directory.
+    pub(crate) fn write(&self) -> io::Result<()> {
+        let tests = self.records.lock().unwrap();
+        let coverage: Vec<_> = self
+            .items
+            .iter()
+            .map(|item| {
+                let tests = &tests[item.tests.clone()];
+                let runnable = tests
+                    .iter()
+                    .filter(|test| test.runnable && !matches!(test.outcome, TestOutcome::Ignored));
+                ItemCoverage {
+                    item: if item.path.is_empty() { &self.crate_name } else { &item.path },
+                    examples: tests.len(),
+                    runnable: runnable.clone().count(),
+                    passed: runnable
+                        .filter(|test| matches!(test.outcome, TestOutcome::Passed))
+                        .count(),
+                }
+            })
+            .collect();
+        let report = JsonReport {
+            crate_name: &self.crate_name,
+            tests: &tests,
+            items_without_runnable_example: coverage.iter().filter(|c| c.runnable == 0).count(),
+            coverage: &coverage,
+        };
+
+        fs::create_dir_all(&self.out_dir)?;
+        let json = serde_json::to_string_pretty(&report).map_err(io::Error::from)?;
+        fs::write(self.out_dir.join("doctests.json"), json)?;
+        let html = ReportPage { report: &report }.render().map_err(io::Error::other)?;
+        fs::write(self.out_dir.join("doctests.html"), html)
+    }
+}
@@ -4,18 +4,22 @@
 
 use rustc_data_structures::fx::FxHashSet;
 use rustc_data_structures::sync::Lrc;
+use rustc_hir::def::DefKind;
 use rustc_hir::def_id::{LocalDefId, CRATE_DEF_ID};
 use rustc_hir::{self as hir, intravisit, CRATE_HIR_ID};
 use rustc_middle::hir::map::Map;
 use rustc_middle::hir::nested_filter;
+use rustc_middle::lint::LintLevelSource;
 use rustc_middle::ty::TyCtxt;
 use rustc_resolve::rustdoc::span_of_fragments;
-use rustc_session::Session;
+use rustc_session::{lint, Session};
 use rustc_span::source_map::SourceMap;
 use rustc_span::{BytePos, FileName, Pos, Span, DUMMY_SP};
 
+use super::report::DocumentedItem;
 use super::{DoctestVisitor, ScrapedDoctest};
 use crate::clean::types::AttributesExt;
+use crate::clean::utils::inherits_doc_hidden;
 use crate::clean::Attributes;
 use crate::html::markdown::{self, ErrorCodes, LangString, MdRelLine};
 
@@ -70,6 +74,8 @@ pub(super) struct HirCollector<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
     enable_per_target_ignores: bool,
     collector: RustCollector,
+    /// The public items that should have a code example, for `--doctest-report`.
+    items: Vec<DocumentedItem>,
 }
 
 impl<'a, 'tcx> HirCollector<'a, 'tcx> {
@@ -86,15 +92,52 @@ pub fn new(
             position: DUMMY_SP,
             tests: vec![],
         };
-        Self { sess, map, codes, enable_per_target_ignores, tcx, collector }
+        Self { sess, map, codes, enable_per_target_ignores, tcx, collector, items: Vec::new() }
     }
 
-    pub fn collect_crate(mut self) -> Vec<ScrapedDoctest> {
+    /// Returns the doctests of the crate, and the public items that should have a code example.
+    pub fn collect_crate(mut self) -> (Vec<ScrapedDoctest>, Vec<DocumentedItem>) {
         let tcx = self.tcx;
         self.visit_testable("".to_string(), CRATE_DEF_ID, tcx.hir().span(CRATE_HIR_ID), |this| {
             tcx.hir().walk_toplevel_module(this)
         });
-        self.collector.tests
+        (self.collector.tests, self.items)
+    }
+
+    /// Whether `def_id` should have a code example in its documentation. This follows
+    /// `should_have_doc_example` of the `check_doc_test_visibility` pass, except that all rustdoc
+    /// lints are allowed from the command line when running doctests, so only an explicit
+    /// `#[allow(rustdoc::missing_doc_code_examples)]` on an item counts.
+    fn should_have_doc_example(&self, def_id: LocalDefId) -> bool {
+        let tcx = self.tcx;
+        if !tcx.effective_visibilities(()).is_directly_public(def_id)
+            || matches!(
+                tcx.def_kind(def_id),
+                DefKind::Field
+                    | DefKind::Variant
+                    | DefKind::AssocConst
+                    | DefKind::AssocTy
+                    | DefKind::TyAlias
+                    | DefKind::Static { .. }
+                    | DefKind::Const
+                    | DefKind::ExternCrate
+                    | DefKind::Use
+                    | DefKind::Impl { of_trait: true }
+            )
+            || tcx
+                .opt_local_parent(def_id)
+                .is_some_and(|parent| tcx.def_kind(parent) == DefKind::Impl { of_trait: true })
+            || tcx.is_doc_hidden(def_id.to_def_id())
+            || inherits_doc_hidden(tcx, def_id, None)
+            || tcx.def_span(def_id).in_derive_expansion()
+        {
+            return false;
+        }
+        let (level, source) = tcx.lint_level_at_node(
+            crate::lint::MISSING_DOC_CODE_EXAMPLES,
+            tcx.local_def_id_to_hir_id(def_id),
+        );
+        !matches!((level, source), (lint::Level::Allow, LintLevelSource::Node { .. }))
     }
 }
 
@@ -117,6 +160,7 @@ fn visit_testable<F: FnOnce(&mut Self)>(
         if has_name {
             self.collector.cur_path.push(name);
         }
+        let first_test = self.collector.tests.len();
 
         // The collapse-docs pass won't combine sugared/raw doc attributes, or included files with
         // anything else, this will combine them for us.
@@ -141,6 +185,12 @@ fn visit_testable<F: FnOnce(&mut Self)>(
                 )),
             );
         }
+        if self.should_have_doc_example(def_id) {
+            self.items.push(DocumentedItem {
+                path: self.collector.cur_path.join("::"),
+                tests: first_test..self.collector.tests.len(),
+            });
+        }
 
         nested(self);
 
@@ -12,7 +12,7 @@
 use crate::formats::item_type::ItemType;
 use crate::formats::Impl;
 use crate::html::format::join_with_double_colon;
-use crate::html::markdown::short_markdown_summary;
+use crate::html::markdown::{plain_text_docs, short_markdown_summary};
 use crate::html::render::search_index::get_function_type_for_search;
 use crate::html::render::IndexItem;
 use crate::visit_lib::RustdocEffectiveVisibilities;
@@ -90,6 +90,9 @@ pub(crate) struct Cache {
     /// Whether to document hidden items.
     /// This is stored in `Cache` so it doesn't need to be passed through all rustdoc functions.
     pub(crate) document_hidden: bool,
+    /// Whether to keep the text of the docs of every item in the search index, for
+    /// `--full-text-search`.
+    pub(crate) full_text_search: bool,
 
     /// Crates marked with [`#[doc(masked)]`][doc_masked].
     ///
@@ -530,6 +533,8 @@ fn add_item_to_search_index(tcx: TyCtxt<'_>, cache: &mut Cache, item: &clean::It
     debug_assert!(!item.is_stripped());
 
     let desc = short_markdown_summary(&item.doc_value(), &item.link_names(cache));
+    let full_text =
+        cache.full_text_search.then(|| plain_text_docs(&item.doc_value(), &item.link_names(cache)));
     // For searching purposes, a re-export is a duplicate if:
     //
     // - It's either an inline, or a true re-export
@@ -560,6 +565,7 @@ fn add_item_to_search_index(tcx: TyCtxt<'_>, cache: &mut Cache, item: &clean::It
         name,
         path,
         desc,
+        full_text,
         parent: parent_did,
         parent_idx: None,
         exact_path: None,
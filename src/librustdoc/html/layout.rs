@@ -21,6 +21,9 @@ pub(crate) struct Layout {
     pub(crate) css_file_extension: Option<PathBuf>,
     /// If true, then scrape-examples.js will be included in the output HTML file
     pub(crate) scrape_examples_extension: bool,
+    /// If true, other versions of the crate are documented next to this one (`--versioned-docs`),
+    /// and the pages get a selector to switch between them.
+    pub(crate) versioned: bool,
 }
 
 pub(crate) struct Page<'a> {
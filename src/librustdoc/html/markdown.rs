@@ -1610,6 +1610,68 @@ pub(crate) fn plain_text_summary(md: &str, link_names: &[RenderedLink]) -> Strin
     s
 }
 
+/// Renders all of the provided markdown as plain text on a single line, for the full-text
+/// search index.
+///
+/// - Headings, links, and formatting are stripped.
+/// - Inline code is rendered as-is, surrounded by backticks.
+/// - HTML and code blocks are ignored.
+pub(crate) fn plain_text_docs(md: &str, link_names: &[RenderedLink]) -> String {
+    if md.is_empty() {
+        return String::new();
+    }
+
+    let mut s = String::with_capacity(md.len());
+
+    let mut replacer = |broken_link: BrokenLink<'_>| {
+        link_names
+            .iter()
+            .find(|link| &*link.original_text == &*broken_link.reference)
+            .map(|link| (link.href.as_str().into(), link.tooltip.as_str().into()))
+    };
+
+    let p = Parser::new_with_broken_link_callback(md, summary_opts(), Some(&mut replacer));
+
+    let mut in_code_block = false;
+    for event in p {
+        match &event {
+            Event::Start(Tag::CodeBlock(..)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            _ if in_code_block => {}
+            Event::Text(text) => s.push_str(text),
+            Event::Code(code) => {
+                s.push('`');
+                s.push_str(code);
+                s.push('`');
+            }
+            Event::HardBreak | Event::SoftBreak => s.push(' '),
+            Event::End(
+                TagEnd::Paragraph | TagEnd::Heading(..) | TagEnd::Item | TagEnd::TableCell,
+            ) => {
+                if !s.is_empty() && !s.ends_with(' ') {
+                    s.push(' ');
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let len = s.trim_end().len();
+    s.truncate(len);
+    s.replace('\n', " ")
+}
+
+/// Returns whether a code block with this info string is Rust code, whose lines starting with `# `
+/// are hidden in the rendered docs.
+pub(crate) fn is_rust_code_block(info: &str) -> bool {
+    LangString::parse_without_check(info, ErrorCodes::No, false).rust
+}
+
+/// Returns the lines of a Rust code block that are shown in the rendered docs.
+pub(crate) fn shown_code_lines(code: &str) -> impl Iterator<Item = Cow<'_, str>> {
+    code.lines().filter_map(|line| map_line(line).for_html())
+}
+
 #[derive(Debug)]
 pub(crate) struct MarkdownLink {
     pub kind: LinkType,
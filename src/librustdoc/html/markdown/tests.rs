@@ -1,8 +1,9 @@
 use rustc_span::edition::{Edition, DEFAULT_EDITION};
 
 use super::{
-    find_testable_code, plain_text_summary, short_markdown_summary, ErrorCodes, HeadingOffset,
-    IdMap, Ignore, LangString, LangStringToken, Markdown, MarkdownItemInfo, TagIterator,
+    find_testable_code, plain_text_docs, plain_text_summary, short_markdown_summary, ErrorCodes,
+    HeadingOffset, IdMap, Ignore, LangString, LangStringToken, Markdown, MarkdownItemInfo,
+    TagIterator,
 };
 
 #[test]
@@ -462,6 +463,23 @@ fn t(input: &str, expect: &str) {
     );
 }
 
+#[test]
+fn test_plain_text_docs() {
+    fn t(input: &str, expect: &str) {
+        let output = plain_text_docs(input, &[]);
+        assert_eq!(output, expect, "original: {}", input);
+    }
+
+    t("", "");
+    t("hello [Rust](https://www.rust-lang.org) :)", "hello Rust :)");
+    t("Multi-line\nsummary", "Multi-line summary");
+    t("# top header\n\nfollowed by some text", "top header followed by some text");
+    t("first paragraph\n\nsecond paragraph", "first paragraph second paragraph");
+    t("* first item\n* second item", "first item second item");
+    t("retries\n\n```\nfn main() {}\n```\n\nwith a backoff", "retries with a backoff");
+    t("<div>hello</div>", "");
+}
+
 #[test]
 fn test_markdown_html_escape() {
     fn t(input: &str, expect: &str) {
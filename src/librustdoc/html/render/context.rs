@@ -7,6 +7,7 @@
 
 use rinja::Template;
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def::DefKind;
 use rustc_hir::def_id::{DefIdMap, LOCAL_CRATE};
 use rustc_middle::ty::TyCtxt;
 use rustc_session::Session;
@@ -16,8 +17,10 @@
 use super::print_item::{full_path, item_path, print_item};
 use super::search_index::build_index;
 use super::sidebar::{print_sidebar, sidebar_module_like, Sidebar};
+use super::type_layout::{collect_layout_report, layout_report_tsv, render_layout_report};
 use super::write_shared::write_shared;
 use super::{collect_spans_and_sources, scrape_examples_help, AllTypes, LinkFromSrc, StylePath};
+use crate::api_diff::{load_cfg_variants, load_version_history, CfgVariants, VersionHistory};
 use crate::clean::types::ExternalLocation;
 use crate::clean::utils::has_doc_flag;
 use crate::clean::{self, ExternalCrate};
@@ -130,6 +133,12 @@ pub(crate) struct SharedContext<'tcx> {
     pub(crate) cache: Cache,
 
     pub(crate) call_locations: AllCallLocations,
+    /// With `--versioned-docs`, the directory that contains the documentation of all versions.
+    pub(crate) versioned_docs_root: Option<PathBuf>,
+    /// In which version each item was added, if `--version-history` was given.
+    pub(crate) version_history: Option<VersionHistory>,
+    /// The items in other configurations, if `--cfg-variant` was given.
+    pub(crate) cfg_variants: Option<CfgVariants>,
 }
 
 impl SharedContext<'_> {
@@ -171,6 +180,43 @@ pub(super) fn root_path(&self) -> String {
         "../".repeat(self.current.len())
     }
 
+    /// Returns the version in which `item` was added to the public API of the crate, according
+    /// to `--version-history`.
+    pub(super) fn version_since(&self, item: &clean::Item) -> Option<&str> {
+        let history = self.shared.version_history.as_ref()?;
+        history.since(&self.api_path(item)?)
+    }
+
+    /// Returns the path of `item` in the public API of the crate, as used by
+    /// `api_diff::ApiSurface`.
+    pub(super) fn api_path(&self, item: &clean::Item) -> Option<String> {
+        let def_id = item.item_id.as_def_id().filter(|def_id| def_id.is_local())?;
+        let tcx = self.tcx();
+        let paths = &self.shared.cache.paths;
+        // These must be the same paths as the ones used by `api_diff::ApiSurface`.
+        Some(match item.type_() {
+            ItemType::StructField
+            | ItemType::Variant
+            | ItemType::TyMethod
+            | ItemType::Method
+            | ItemType::AssocType
+            | ItemType::AssocConst => {
+                let parent = tcx.parent(def_id);
+                let owner = match tcx.def_kind(parent) {
+                    // The items of trait impls are part of the API of the trait.
+                    DefKind::Impl { of_trait: true } => return None,
+                    DefKind::Impl { of_trait: false } => {
+                        tcx.type_of(parent).instantiate_identity().ty_adt_def()?.did()
+                    }
+                    _ => parent,
+                };
+                let (owner_path, _) = paths.get(&owner)?;
+                format!("{}::{}", join_with_double_colon(owner_path), item.name?)
+            }
+            _ => join_with_double_colon(&paths.get(&def_id)?.0),
+        })
+    }
+
     fn render_item(&mut self, it: &clean::Item, is_module: bool) -> String {
         let mut render_redirect_pages = self.render_redirect_pages;
         // If the item is stripped but inlined, links won't point to the item so no need to generate
@@ -466,6 +512,9 @@ fn init(
             call_locations,
             no_emit_shared,
             html_no_source,
+            versioned_docs_root,
+            version_history,
+            cfg_variants,
             ..
         } = options;
 
@@ -491,6 +540,7 @@ fn init(
             krate_version: krate_version.to_string(),
             css_file_extension: extension_css,
             scrape_examples_extension: !call_locations.is_empty(),
+            versioned: versioned_docs_root.is_some(),
         };
         let mut issue_tracker_base_url = None;
         let mut include_sources = !html_no_source;
@@ -521,6 +571,14 @@ fn init(
             }
         }
 
+        let version_history = if version_history.is_empty() {
+            None
+        } else {
+            Some(load_version_history(&version_history, cache.crate_version.clone())?)
+        };
+        let cfg_variants =
+            if cfg_variants.is_empty() { None } else { Some(load_cfg_variants(&cfg_variants)?) };
+
         let (local_sources, matches) = collect_spans_and_sources(
             tcx,
             &krate,
@@ -551,6 +609,9 @@ fn init(
             span_correspondence_map: matches,
             cache,
             call_locations,
+            versioned_docs_root,
+            version_history,
+            cfg_variants,
         };
 
         let dst = output;
@@ -742,6 +803,25 @@ fn after_krate(&mut self) -> Result<(), Error> {
             shared.fs.write(scrape_examples_help_file, v)?;
         }
 
+        if shared.show_type_layout {
+            let entries = collect_layout_report(self);
+            page.title = "Layout of all types";
+            page.description = "The size, alignment and niches of all the types of this crate";
+            page.root_path = "../";
+            page.rust_logo = has_doc_flag(self.tcx(), LOCAL_CRATE.as_def_id(), sym::rust_logo);
+            let sidebar = "<h2 class=\"location\">Layout</h2><div class=\"sidebar-elems\"></div>";
+            let v = layout::render(
+                &shared.layout,
+                &page,
+                sidebar,
+                render_layout_report(&entries),
+                &shared.style_files,
+            );
+            let crate_dir = self.dst.join(crate_name.as_str());
+            shared.fs.write(crate_dir.join("layout-report.html"), v)?;
+            shared.fs.write(crate_dir.join("layout-report.tsv"), layout_report_tsv(&entries))?;
+        }
+
         if let Some(ref redirections) = shared.redirections
             && !redirections.borrow().is_empty()
         {
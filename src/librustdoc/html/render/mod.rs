@@ -57,7 +57,10 @@
 use serde::{Serialize, Serializer};
 
 pub(crate) use self::context::*;
-pub(crate) use self::span_map::{collect_spans_and_sources, LinkFromSrc};
+pub(crate) use self::span_map::{collect_body_references, collect_spans_and_sources, LinkFromSrc};
+pub(crate) use self::type_layout::type_layout;
+use crate::api_diff::ApiItem;
+use crate::clean::utils::has_doc_flag;
 use crate::clean::{self, ItemId, RenderedLink, SelfTy};
 use crate::error::Error;
 use crate::formats::cache::Cache;
@@ -74,7 +77,7 @@
 };
 use crate::html::static_files::SCRAPE_EXAMPLES_HELP_MD;
 use crate::html::{highlight, sources};
-use crate::scrape_examples::{CallData, CallLocation};
+use crate::scrape_examples::{rank_examples, CallData, CallLocation};
 use crate::{try_none, DOC_RUST_LANG_ORG_CHANNEL};
 
 pub(crate) fn ensure_trailing_slash(v: &str) -> impl fmt::Display + '_ {
@@ -110,6 +113,8 @@ pub(crate) struct IndexItem {
     pub(crate) name: Symbol,
     pub(crate) path: String,
     pub(crate) desc: String,
+    /// The plain text of the docs, only kept with `--full-text-search`.
+    pub(crate) full_text: Option<String>,
     pub(crate) parent: Option<DefId>,
     pub(crate) parent_idx: Option<isize>,
     pub(crate) exact_path: Option<String>,
@@ -679,10 +684,15 @@ enum ShortItemInfo {
     Portability {
         message: String,
     },
+    /// The signature and rendered documentation of the item in each configuration given with
+    /// `--cfg-variant`, or `None` for the configurations that don't have it.
+    CfgVariants {
+        variants: Vec<(String, Option<(String, String)>)>,
+    },
 }
 
 /// Render the stability, deprecation and portability information that is displayed at the top of
-/// the item's documentation.
+/// the item's documentation, and how the item differs between configurations.
 fn short_item_info(
     item: &clean::Item,
     cx: &mut Context<'_>,
@@ -738,9 +748,103 @@ fn short_item_info(
         extra_info.push(ShortItemInfo::Portability { message });
     }
 
+    let shared = Rc::clone(&cx.shared);
+    if let Some(variants) = &shared.cfg_variants
+        && let Some(path) = cx.api_path(item)
+        && let Some(variants) = variants.differing(&path)
+    {
+        extra_info.push(cfg_variants_info(cx, variants));
+    }
+
     extra_info
 }
 
+/// Renders the signature and documentation of an item in each configuration given with
+/// `--cfg-variant`.
+fn cfg_variants_info(
+    cx: &mut Context<'_>,
+    variants: Vec<(&str, Option<&ApiItem>)>,
+) -> ShortItemInfo {
+    let variants = variants
+        .into_iter()
+        .map(|(label, item)| {
+            let item = item.map(|item| {
+                // Intra-doc links can only be resolved in the configuration rustdoc is run with.
+                let docs = Markdown {
+                    content: item.docs.as_deref().unwrap_or_default(),
+                    links: &[],
+                    ids: &mut cx.id_map,
+                    error_codes: cx.shared.codes,
+                    edition: cx.shared.edition(),
+                    playground: &cx.shared.playground,
+                    heading_offset: HeadingOffset::H4,
+                }
+                .into_string();
+                (item.signature.clone(), docs)
+            });
+            (label.to_owned(), item)
+        })
+        .collect();
+    ShortItemInfo::CfgVariants { variants }
+}
+
+/// Renders the items inside of `parent` that don't exist in the configuration rustdoc is run
+/// with, but in some of the configurations given with `--cfg-variant`. `present` are the names of
+/// the items inside of `parent` in the configuration rustdoc is run with.
+fn render_cfg_variant_items(
+    w: &mut impl fmt::Write,
+    cx: &mut Context<'_>,
+    parent: &clean::Item,
+    present: &FxHashSet<Symbol>,
+) {
+    let shared = Rc::clone(&cx.shared);
+    let (Some(variants), Some(path)) = (&shared.cfg_variants, cx.api_path(parent)) else {
+        return;
+    };
+    let children = variants.children(&path);
+    let mut missing = children
+        .into_iter()
+        .filter(|(name, _)| !present.contains(&Symbol::intern(name)))
+        .peekable();
+    if missing.peek().is_none() {
+        return;
+    }
+    let id = cx.derive_id("cfg-variant-items");
+    write_section_heading(
+        w,
+        "Items in Other Configurations",
+        &id,
+        None,
+        "<ul class=\"item-table\">",
+    );
+    for (name, variants) in missing {
+        let available = variants
+            .iter()
+            .filter(|(_, item)| item.is_some())
+            .map(|(label, _)| *label)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let id = cx.derive_id(format!("cfg-variant.{name}"));
+        let info = ItemInfo { items: vec![cfg_variants_info(cx, variants)] };
+        write!(
+            w,
+            "<li>\
+                <div class=\"item-name\" id=\"{id}\">\
+                    <code>{name}</code>\
+                    <wbr><span class=\"stab portability\" title=\"Available with {available}\">\
+                        {available}\
+                    </span>\
+                </div>\
+                <div class=\"desc\">{info}</div>\
+            </li>",
+            name = Escape(name),
+            available = Escape(&available),
+        )
+        .unwrap();
+    }
+    w.write_str("</ul>").unwrap();
+}
+
 // Render the list of items inside one of the sections "Trait Implementations",
 // "Auto Trait Implementations," "Blanket Trait Implementations" (on struct/enum pages).
 pub(crate) fn render_impls(
@@ -982,9 +1086,13 @@ fn assoc_method(
 /// Note that it is possible for an unstable function to be const-stable. In that case, the span
 /// will include the const-stable version, but no stable version will be emitted, as a natural
 /// consequence of the above rules.
+///
+/// Items without a stability attribute show `version_since` instead, the version of the crate
+/// that added them according to `--version-history`.
 fn render_stability_since_raw_with_extra(
     w: &mut Buffer,
     stable_version: Option<StableSince>,
+    version_since: Option<&str>,
     const_stability: Option<ConstStability>,
     extra_class: &str,
 ) -> bool {
@@ -994,6 +1102,10 @@ fn render_stability_since_raw_with_extra(
     if let Some(version) = stable_version.and_then(|version| since_to_string(&version)) {
         stability.push_str(&version);
         title.push_str(&format!("Stable since Rust version {version}"));
+    } else if let Some(version) = version_since {
+        let version = Escape(version);
+        stability.push_str(&version.to_string());
+        title.push_str(&format!("Available since version {version}"));
     }
 
     let const_title_and_stability = match const_stability {
@@ -1056,9 +1168,10 @@ fn since_to_string(since: &StableSince) -> Option<String> {
 fn render_stability_since_raw(
     w: &mut Buffer,
     ver: Option<StableSince>,
+    version_since: Option<&str>,
     const_stability: Option<ConstStability>,
 ) -> bool {
-    render_stability_since_raw_with_extra(w, ver, const_stability, "")
+    render_stability_since_raw_with_extra(w, ver, version_since, const_stability, "")
 }
 
 fn render_assoc_item(
@@ -1228,7 +1341,27 @@ fn render_assoc_items<'a, 'cx: 'a>(
     let mut derefs = DefIdSet::default();
     derefs.insert(it);
     display_fn(move |f| {
+        let all = matches!(what, AssocItemRender::All);
         render_assoc_items_inner(f, cx, containing_item, it, what, &mut derefs);
+        if all {
+            // Fields, variants and inherent associated items that only exist in other
+            // configurations.
+            let inherent_items = cx
+                .cache()
+                .impls
+                .get(&it)
+                .into_iter()
+                .flatten()
+                .filter(|i| i.inner_impl().trait_.is_none())
+                .flat_map(|i| &i.inner_impl().items);
+            let present = containing_item
+                .kind
+                .inner_items()
+                .chain(inherent_items)
+                .filter_map(|item| item.name)
+                .collect();
+            render_cfg_variant_items(f, cx, containing_item, &present);
+        }
         Ok(())
     })
 }
@@ -1935,6 +2068,7 @@ fn render_rightside(w: &mut Buffer, cx: &Context<'_>, item: &clean::Item, render
     let has_stability = render_stability_since_raw_with_extra(
         &mut rightside,
         item.stable_since(tcx),
+        cx.version_since(item),
         const_stability,
         if has_src_ref { "" } else { " rightside" },
     );
@@ -2359,6 +2493,9 @@ fn render_call_locations<W: fmt::Write>(mut w: W, cx: &mut Context<'_>, item: &c
     let def_id = item.item_id.expect_def_id();
     let key = tcx.def_path_hash(def_id);
     let Some(call_locations) = cx.shared.call_locations.get(&key) else { return };
+    if has_doc_flag(tcx, def_id, sym::example_exclude) {
+        return;
+    }
 
     // Generate a unique ID so users can link to this section for a given method
     let id = cx.id_map.derive("scraped-examples");
@@ -2497,29 +2634,7 @@ fn render_call_locations<W: fmt::Write>(mut w: W, cx: &mut Context<'_>, item: &c
     };
 
     // The call locations are output in sequence, so that sequence needs to be determined.
-    // Ideally the most "relevant" examples would be shown first, but there's no general algorithm
-    // for determining relevance. We instead proxy relevance with the following heuristics:
-    //   1. Code written to be an example is better than code not written to be an example, e.g.
-    //      a snippet from examples/foo.rs is better than src/lib.rs. We don't know the Cargo
-    //      directory structure in Rustdoc, so we proxy this by prioritizing code that comes from
-    //      a --crate-type bin.
-    //   2. Smaller examples are better than large examples. So we prioritize snippets that have
-    //      the smallest number of lines in their enclosing item.
-    //   3. Finally we sort by the displayed file name, which is arbitrary but prevents the
-    //      ordering of examples from randomly changing between Rustdoc invocations.
-    let ordered_locations = {
-        fn sort_criterion<'a>(
-            (_, call_data): &(&PathBuf, &'a CallData),
-        ) -> (bool, u32, &'a String) {
-            // Use the first location because that's what the user will see initially
-            let (lo, hi) = call_data.locations[0].enclosing_item.byte_span;
-            (!call_data.is_bin, hi - lo, &call_data.display_name)
-        }
-
-        let mut locs = call_locations.iter().collect::<Vec<_>>();
-        locs.sort_by_key(sort_criterion);
-        locs
-    };
+    let ordered_locations = rank_examples(call_locations);
 
     let mut it = ordered_locations.into_iter().peekable();
 
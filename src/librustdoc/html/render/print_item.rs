@@ -21,7 +21,7 @@
     collect_paths_for_type, document, ensure_trailing_slash, get_filtered_impls_for_reference,
     item_ty_to_section, notable_traits_button, notable_traits_json, render_all_impls,
     render_assoc_item, render_assoc_items, render_attributes_in_code, render_attributes_in_pre,
-    render_impl, render_rightside, render_stability_since_raw,
+    render_cfg_variant_items, render_impl, render_rightside, render_stability_since_raw,
     render_stability_since_raw_with_extra, write_section_heading, AssocItemLink, AssocItemRender,
     Context, ImplRenderingParameters, RenderMode,
 };
@@ -212,6 +212,7 @@ pub(super) fn print_item(cx: &mut Context<'_>, item: &clean::Item, buf: &mut Buf
     render_stability_since_raw(
         &mut stability_since_raw,
         item.stable_since(cx.tcx()),
+        cx.version_since(item),
         item.const_stability(cx.tcx()),
     );
     let stability_since_raw: String = stability_since_raw.into_inner();
@@ -538,6 +539,9 @@ fn cmp(i1: &clean::Item, i2: &clean::Item, tcx: TyCtxt<'_>) -> Ordering {
     if last_section.is_some() {
         w.write_str(ITEM_TABLE_CLOSE);
     }
+
+    let present = not_stripped_items.iter().filter_map(|(_, item)| item.name).collect();
+    render_cfg_variant_items(w, cx, item, &present);
 }
 
 /// Render the stability, deprecation and portability tags that are displayed in the item's summary
@@ -1654,6 +1658,7 @@ fn item_variants(
         render_stability_since_raw_with_extra(
             w,
             variant.stable_since(tcx),
+            cx.version_since(variant),
             variant.const_stability(tcx),
             " rightside",
         );
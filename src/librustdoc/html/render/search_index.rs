@@ -1,9 +1,11 @@
 pub(crate) mod encode;
+pub(crate) mod full_text;
 
 use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, VecDeque};
 
 use encode::{bitmap_to_string, write_vlqhex_to_string};
+use full_text::{build_full_text_index, FullTextIndex};
 use rustc_data_structures::fx::{FxHashMap, FxIndexMap};
 use rustc_middle::ty::TyCtxt;
 use rustc_span::def_id::DefId;
@@ -17,7 +19,7 @@
 use crate::formats::cache::{Cache, OrphanImplItem};
 use crate::formats::item_type::ItemType;
 use crate::html::format::join_with_double_colon;
-use crate::html::markdown::short_markdown_summary;
+use crate::html::markdown::{plain_text_docs, short_markdown_summary};
 use crate::html::render::{self, IndexItem, IndexItemFunctionType, RenderType, RenderTypeId};
 
 /// The serialized search description sharded version
@@ -45,9 +47,13 @@
 /// [1]: https://en.wikipedia.org/wiki/Maximum_transmission_unit#MTUs_for_common_media
 /// [2]: https://en.wikipedia.org/wiki/Sliding_window_protocol#Basic_concept
 /// [3]: https://learn.microsoft.com/en-us/troubleshoot/windows-server/networking/description-tcp-features
+///
+/// With `--full-text-search`, the `full_text` index is sharded too. See the
+/// [`full_text`] module for its format.
 pub(crate) struct SerializedSearchIndex {
     pub(crate) index: String,
     pub(crate) desc: Vec<(usize, String)>,
+    pub(crate) full_text: Option<FullTextIndex>,
 }
 
 const DESC_INDEX_SHARD_LEN: usize = 128 * 1024;
@@ -72,12 +78,16 @@ pub(crate) fn build_index<'tcx>(
     {
         if let Some((fqp, _)) = cache.paths.get(&parent) {
             let desc = short_markdown_summary(&item.doc_value(), &item.link_names(cache));
+            let full_text = cache
+                .full_text_search
+                .then(|| plain_text_docs(&item.doc_value(), &item.link_names(cache)));
             cache.search_index.push(IndexItem {
                 ty: item.type_(),
                 defid: item.item_id.as_def_id(),
                 name: item.name.unwrap(),
                 path: join_with_double_colon(&fqp[..fqp.len() - 1]),
                 desc,
+                full_text,
                 parent: Some(parent),
                 parent_idx: None,
                 exact_path: None,
@@ -441,6 +451,8 @@ struct CrateData<'a> {
         desc_index: String,
         // A list of items with no description. This is eventually turned into a bitmap.
         empty_desc: Vec<u32>,
+        // The first word of every shard of the full-text index, if there is one.
+        full_text_shard_keys: Option<&'a [String]>,
     }
 
     struct Paths {
@@ -637,6 +649,9 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             if has_aliases {
                 crate_data.serialize_field("a", &self.aliases)?;
             }
+            if let Some(full_text_shard_keys) = self.full_text_shard_keys {
+                crate_data.serialize_field("x", full_text_shard_keys)?;
+            }
             crate_data.end()
         }
     }
@@ -680,6 +695,15 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         desc.iter().map(|(len, _)| *len).sum::<usize>() + empty_desc.len()
     );
 
+    let full_text = cache.full_text_search.then(|| {
+        let crate_text =
+            plain_text_docs(&krate.module.doc_value(), &krate.module.link_names(cache));
+        build_full_text_index(
+            std::iter::once(crate_text.as_str())
+                .chain(crate_items.iter().map(|item| item.full_text.as_deref().unwrap_or(""))),
+        )
+    });
+
     // The index, which is actually used to search, is JSON
     // It uses `JSON.parse(..)` to actually load, since JSON
     // parses faster than the full JavaScript syntax.
@@ -693,6 +717,7 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             associated_item_disambiguators: &associated_item_disambiguators,
             desc_index,
             empty_desc,
+            full_text_shard_keys: full_text.as_ref().map(|full_text| &full_text.shard_keys[..]),
         })
         .expect("failed serde conversion")
         // All these `replace` calls are because we have to go through JS string for JSON content.
@@ -701,7 +726,7 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         // We need to escape double quotes for the JSON.
         .replace("\\\"", "\\\\\"")
     );
-    SerializedSearchIndex { index, desc }
+    SerializedSearchIndex { index, desc, full_text }
 }
 
 pub(crate) fn get_function_type_for_search<'tcx>(
@@ -0,0 +1,119 @@
+//! The full-text index written with `--full-text-search`.
+//!
+//! It is an inverted index, which maps every word used in the docs to the list of items whose
+//! docs use it. The words are sorted and split into shards of about [`TERM_SHARD_LEN`] bytes.
+//! Only the first word of every shard is part of the search index, so the browser only loads the
+//! shards of the words it is looking for. The lists of items are stored as the difference
+//! between consecutive item indexes, encoded as vlqhex, with 0 being the crate itself like in
+//! the bitmaps of the search index.
+//!
+//! The plain text of the docs is stored separately, [`TEXT_SHARD_ITEMS`] items per shard, and is
+//! only loaded to show a snippet around the matched words of the displayed results.
+//!
+//! The way words are split and normalized has to be kept in sync with `fullTextTerms` in
+//! `search.js`.
+
+use std::collections::BTreeMap;
+
+use rustc_data_structures::fx::FxHashSet;
+
+use super::encode::write_vlqhex_to_string;
+
+/// The size, in bytes, after which a new shard of words is started.
+const TERM_SHARD_LEN: usize = 64 * 1024;
+
+/// The number of items in every shard of plain text. This number is also used by `search.js` to
+/// find the shard an item is in.
+pub(crate) const TEXT_SHARD_ITEMS: usize = 256;
+
+/// The docs of an item are cut to this many bytes before being stored for the snippets. Words
+/// found after that are still indexed.
+const MAX_TEXT_LEN: usize = 1024;
+
+/// Words shorter than this, in characters, are not indexed.
+const MIN_TERM_LEN: usize = 2;
+
+/// Words longer than this, in characters, are not indexed. These are mostly hashes, URLs and
+/// other strings nobody searches for.
+const MAX_TERM_LEN: usize = 40;
+
+/// Words too common to narrow down a search.
+const STOP_WORDS: &[&str] = &[
+    "an", "and", "are", "as", "at", "be", "by", "for", "from", "if", "in", "is", "it", "of", "on",
+    "or", "that", "the", "this", "to", "with",
+];
+
+pub(crate) struct FullTextIndex {
+    /// The first word of every shard in `terms`.
+    pub(crate) shard_keys: Vec<String>,
+    /// The shards of the map from words to the vlqhex-encoded list of items using them.
+    pub(crate) terms: Vec<BTreeMap<String, String>>,
+    /// The shards of plain text, with the text of every item on its own line.
+    pub(crate) text: Vec<String>,
+}
+
+/// Splits the plain text of some docs into the words that are indexed.
+fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| (MIN_TERM_LEN..=MAX_TERM_LEN).contains(&word.chars().count()))
+        .map(str::to_lowercase)
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+}
+
+/// Builds the full-text index from the plain text of the docs of every item, starting with the
+/// crate itself, in the order of the search index.
+pub(crate) fn build_full_text_index<'a>(docs: impl Iterator<Item = &'a str>) -> FullTextIndex {
+    let mut postings: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+    let mut text = Vec::new();
+    let mut shard = String::new();
+    let mut seen = FxHashSet::default();
+
+    for (index, doc) in docs.enumerate() {
+        let index = u32::try_from(index).unwrap();
+        seen.clear();
+        for term in terms(doc) {
+            if seen.insert(term.clone()) {
+                postings.entry(term).or_default().push(index);
+            }
+        }
+
+        if index as usize % TEXT_SHARD_ITEMS == 0 && index != 0 {
+            text.push(std::mem::take(&mut shard));
+        } else if index != 0 {
+            shard.push('\n');
+        }
+        let mut len = doc.len().min(MAX_TEXT_LEN);
+        while !doc.is_char_boundary(len) {
+            len -= 1;
+        }
+        shard.push_str(&doc[..len]);
+    }
+    text.push(shard);
+
+    let mut shard_keys = Vec::new();
+    let mut terms = Vec::new();
+    let mut shard = BTreeMap::new();
+    let mut shard_len = 0;
+    for (term, items) in postings {
+        if shard_len >= TERM_SHARD_LEN {
+            terms.push(std::mem::take(&mut shard));
+            shard_len = 0;
+        }
+        if shard.is_empty() {
+            shard_keys.push(term.clone());
+        }
+        let mut encoded = String::with_capacity(items.len() * 2);
+        let mut previous = 0;
+        for item in items {
+            write_vlqhex_to_string((item - previous).try_into().unwrap(), &mut encoded);
+            previous = item;
+        }
+        shard_len += term.len() + encoded.len();
+        shard.insert(term, encoded);
+    }
+    if !shard.is_empty() {
+        terms.push(shard);
+    }
+
+    FullTextIndex { shard_keys, terms, text }
+}
@@ -1,10 +1,10 @@
 use std::path::{Path, PathBuf};
 
-use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::fx::{FxHashMap, FxIndexSet};
 use rustc_hir::def::{DefKind, Res};
 use rustc_hir::def_id::{DefId, LOCAL_CRATE};
 use rustc_hir::intravisit::{self, Visitor};
-use rustc_hir::{ExprKind, HirId, Item, ItemKind, Mod, Node};
+use rustc_hir::{BodyId, ExprKind, HirId, Item, ItemKind, Mod, Node};
 use rustc_middle::hir::nested_filter;
 use rustc_middle::ty::TyCtxt;
 use rustc_span::hygiene::MacroKind;
@@ -45,7 +45,7 @@ pub(crate) fn collect_spans_and_sources(
     include_sources: bool,
     generate_link_to_definition: bool,
 ) -> (FxHashMap<PathBuf, String>, FxHashMap<Span, LinkFromSrc>) {
-    let mut visitor = SpanMapVisitor { tcx, matches: FxHashMap::default() };
+    let mut visitor = SpanMapVisitor::new(tcx, false);
 
     if include_sources {
         if generate_link_to_definition {
@@ -58,12 +58,60 @@ pub(crate) fn collect_spans_and_sources(
     }
 }
 
+/// Collects, for every function (and other item with a body) of the local crate, the items that
+/// its body refers to, in the order of their first use.
+///
+/// This uses the same resolution as the "jump to definition" links of the source pages, so it
+/// finds paths to items, method calls and macro invocations. Closures and constants nested in a
+/// body are attributed to the enclosing item. Constructors are reported as the struct or variant
+/// that they construct.
+pub(crate) fn collect_body_references(tcx: TyCtxt<'_>) -> FxHashMap<DefId, FxIndexSet<DefId>> {
+    let mut visitor = SpanMapVisitor::new(tcx, true);
+    tcx.hir().walk_toplevel_module(&mut visitor);
+    visitor.references.unwrap_or_default()
+}
+
 struct SpanMapVisitor<'tcx> {
     pub(crate) tcx: TyCtxt<'tcx>,
     pub(crate) matches: FxHashMap<Span, LinkFromSrc>,
+    /// If we're collecting the items referred to by bodies (see [`collect_body_references`]), the
+    /// references found so far, indexed by the item owning the body.
+    references: Option<FxHashMap<DefId, FxIndexSet<DefId>>>,
+    /// The item owning the body we're currently in, if any.
+    current_body: Option<DefId>,
 }
 
 impl<'tcx> SpanMapVisitor<'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, collect_references: bool) -> Self {
+        SpanMapVisitor {
+            tcx,
+            matches: FxHashMap::default(),
+            references: collect_references.then(FxHashMap::default),
+            current_body: None,
+        }
+    }
+
+    /// Links `span` to the definition of `def_id`, and records the reference if we're in a body.
+    fn link_to_def(&mut self, span: Span, def_id: DefId) {
+        let link = if def_id.as_local().is_some() {
+            LinkFromSrc::Local(rustc_span(def_id, self.tcx))
+        } else {
+            LinkFromSrc::External(def_id)
+        };
+        self.matches.insert(span, link);
+        self.add_reference(def_id);
+    }
+
+    fn add_reference(&mut self, mut def_id: DefId) {
+        let (Some(references), Some(body_owner)) = (&mut self.references, self.current_body) else {
+            return;
+        };
+        if let DefKind::Ctor(..) = self.tcx.def_kind(def_id) {
+            def_id = self.tcx.parent(def_id);
+        }
+        references.entry(body_owner).or_default().insert(def_id);
+    }
+
     /// This function is where we handle `hir::Path` elements and add them into the "span map".
     fn handle_path(&mut self, path: &rustc_hir::Path<'_>) {
         match path.res {
@@ -71,12 +119,7 @@ fn handle_path(&mut self, path: &rustc_hir::Path<'_>) {
             // Would be nice to support them too alongside the other `DefKind`
             // (such as primitive types!).
             Res::Def(kind, def_id) if kind != DefKind::TyParam => {
-                let link = if def_id.as_local().is_some() {
-                    LinkFromSrc::Local(rustc_span(def_id, self.tcx))
-                } else {
-                    LinkFromSrc::External(def_id)
-                };
-                self.matches.insert(path.span, link);
+                self.link_to_def(path.span, def_id);
             }
             Res::Local(_) => {
                 if let Some(span) = self.tcx.hir().res_span(path.res) {
@@ -138,6 +181,7 @@ fn handle_macro(&mut self, span: Span) -> bool {
         };
         let link_from_src = match data.macro_def_id {
             Some(macro_def_id) => {
+                self.add_reference(macro_def_id);
                 if macro_def_id.is_local() {
                     LinkFromSrc::Local(clean::Span::new(data.def_site))
                 } else {
@@ -166,12 +210,7 @@ fn handle_call(&mut self, hir_id: HirId, expr_hir_id: Option<HirId>, span: Span)
         // Interestingly enough, for method calls, we need the whole expression whereas for static
         // method/function calls, we need the call expression specifically.
         if let Some(def_id) = typeck_results.type_dependent_def_id(expr_hir_id.unwrap_or(hir_id)) {
-            let link = if def_id.as_local().is_some() {
-                LinkFromSrc::Local(rustc_span(def_id, self.tcx))
-            } else {
-                LinkFromSrc::External(def_id)
-            };
-            self.matches.insert(span, link);
+            self.link_to_def(span, def_id);
         }
     }
 }
@@ -183,6 +222,14 @@ fn nested_visit_map(&mut self) -> Self::Map {
         self.tcx.hir()
     }
 
+    fn visit_nested_body(&mut self, body_id: BodyId) {
+        let owner = self.tcx.hir().body_owner_def_id(body_id);
+        let owner = self.tcx.typeck_root_def_id(owner.to_def_id());
+        let outer_body = self.current_body.replace(owner);
+        self.visit_body(self.tcx.hir().body(body_id));
+        self.current_body = outer_body;
+    }
+
     fn visit_path(&mut self, path: &rustc_hir::Path<'tcx>, _id: HirId) {
         if self.handle_macro(path.span) {
             return;
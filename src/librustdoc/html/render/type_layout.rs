@@ -1,11 +1,13 @@
-use std::fmt;
+use std::fmt::{self, Write as _};
 
 use rinja::Template;
 use rustc_data_structures::captures::Captures;
-use rustc_hir::def_id::DefId;
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
 use rustc_middle::span_bug;
 use rustc_middle::ty::layout::LayoutError;
-use rustc_middle::ty::{self};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, TyCtxt};
 use rustc_span::symbol::Symbol;
 use rustc_target::abi::{Primitive, TagEncoding, Variants};
 
@@ -19,12 +21,69 @@ struct TypeLayout<'cx> {
     type_layout_size: Result<TypeLayoutSize, &'cx LayoutError<'cx>>,
 }
 
-#[derive(Template)]
+#[derive(Template, Clone, Copy)]
 #[template(path = "type_layout_size.html")]
-struct TypeLayoutSize {
-    is_unsized: bool,
-    is_uninhabited: bool,
-    size: u64,
+pub(crate) struct TypeLayoutSize {
+    pub(crate) is_unsized: bool,
+    pub(crate) is_uninhabited: bool,
+    pub(crate) size: u64,
+}
+
+/// The layout of a type, as shown in the "Layout" section of its page, in the layout report and
+/// in the JSON output.
+pub(crate) struct TypeLayoutInfo {
+    pub(crate) size: TypeLayoutSize,
+    pub(crate) align: u64,
+    /// How many invalid values of the type an enclosing enum can use to store its tag.
+    pub(crate) niche_values: u128,
+    /// The size of each variant of an enum with several variants, without the tag.
+    pub(crate) variants: Vec<(Symbol, TypeLayoutSize)>,
+}
+
+/// Computes the layout of the type defined by `ty_def_id`, which fails for generic types.
+pub(crate) fn type_layout<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    ty_def_id: DefId,
+) -> Result<TypeLayoutInfo, &'tcx LayoutError<'tcx>> {
+    let param_env = tcx.param_env(ty_def_id);
+    let ty = tcx.type_of(ty_def_id).instantiate_identity();
+    let type_layout = tcx.layout_of(param_env.and(ty))?;
+
+    let variants = if let Variants::Multiple { variants, tag, tag_encoding, .. } =
+        type_layout.layout.variants()
+        && !variants.is_empty()
+    {
+        let tag_size = if let TagEncoding::Niche { .. } = tag_encoding {
+            0
+        } else if let Primitive::Int(i, _) = tag.primitive() {
+            i.size().bytes()
+        } else {
+            span_bug!(tcx.def_span(ty_def_id), "tag is neither niche nor int")
+        };
+        variants
+            .iter_enumerated()
+            .map(|(variant_idx, variant_layout)| {
+                let ty::Adt(adt, _) = type_layout.ty.kind() else {
+                    span_bug!(tcx.def_span(ty_def_id), "not an adt")
+                };
+                let name = adt.variant(variant_idx).name;
+                let is_unsized = variant_layout.abi.is_unsized();
+                let is_uninhabited = variant_layout.abi.is_uninhabited();
+                let size = variant_layout.size.bytes() - tag_size;
+                (name, TypeLayoutSize { is_unsized, is_uninhabited, size })
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let size = TypeLayoutSize {
+        is_unsized: type_layout.abi.is_unsized(),
+        is_uninhabited: type_layout.abi.is_uninhabited(),
+        size: type_layout.size.bytes(),
+    };
+    let niche_values = type_layout.largest_niche.map_or(0, |niche| niche.available(&tcx));
+    Ok(TypeLayoutInfo { size, align: type_layout.align.abi.bytes(), niche_values, variants })
 }
 
 pub(crate) fn document_type_layout<'a, 'cx: 'a>(
@@ -36,49 +95,89 @@ pub(crate) fn document_type_layout<'a, 'cx: 'a>(
             return Ok(());
         }
 
-        let tcx = cx.tcx();
-        let param_env = tcx.param_env(ty_def_id);
-        let ty = tcx.type_of(ty_def_id).instantiate_identity();
-        let type_layout = tcx.layout_of(param_env.and(ty));
-
-        let variants = if let Ok(type_layout) = type_layout
-            && let Variants::Multiple { variants, tag, tag_encoding, .. } =
-                type_layout.layout.variants()
-            && !variants.is_empty()
-        {
-            let tag_size = if let TagEncoding::Niche { .. } = tag_encoding {
-                0
-            } else if let Primitive::Int(i, _) = tag.primitive() {
-                i.size().bytes()
-            } else {
-                span_bug!(tcx.def_span(ty_def_id), "tag is neither niche nor int")
-            };
-            variants
-                .iter_enumerated()
-                .map(|(variant_idx, variant_layout)| {
-                    let ty::Adt(adt, _) = type_layout.ty.kind() else {
-                        span_bug!(tcx.def_span(ty_def_id), "not an adt")
-                    };
-                    let name = adt.variant(variant_idx).name;
-                    let is_unsized = variant_layout.abi.is_unsized();
-                    let is_uninhabited = variant_layout.abi.is_uninhabited();
-                    let size = variant_layout.size.bytes() - tag_size;
-                    let type_layout_size = TypeLayoutSize { is_unsized, is_uninhabited, size };
-                    (name, type_layout_size)
-                })
-                .collect()
-        } else {
-            Vec::new()
+        let (variants, type_layout_size) = match type_layout(cx.tcx(), ty_def_id) {
+            Ok(layout) => (layout.variants, Ok(layout.size)),
+            Err(err) => (Vec::new(), Err(err)),
         };
-
-        let type_layout_size = tcx.layout_of(param_env.and(ty)).map(|layout| {
-            let is_unsized = layout.abi.is_unsized();
-            let is_uninhabited = layout.abi.is_uninhabited();
-            let size = layout.size.bytes();
-            TypeLayoutSize { is_unsized, is_uninhabited, size }
-        });
-
         TypeLayout { variants, type_layout_size }.render_into(f).unwrap();
         Ok(())
     })
 }
+
+/// A row of the layout report.
+pub(crate) struct LayoutReportEntry {
+    pub(crate) path: String,
+    /// The URL of the page of the type, relative to the directory of the crate, if it is
+    /// documented.
+    pub(crate) url: Option<String>,
+    pub(crate) layout: TypeLayoutInfo,
+}
+
+#[derive(Template)]
+#[template(path = "layout_report.html")]
+struct LayoutReport<'a> {
+    entries: &'a [LayoutReportEntry],
+}
+
+/// Collects the layout of all the structs, enums and unions of the crate, including the private
+/// ones and those that aren't documented, sorted by path. Generic types are left out since their
+/// layout can't be computed.
+pub(crate) fn collect_layout_report(cx: &Context<'_>) -> Vec<LayoutReportEntry> {
+    let tcx = cx.tcx();
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
+    let mut entries: Vec<_> = tcx
+        .hir_crate_items(())
+        .definitions()
+        .map(|def_id| def_id.to_def_id())
+        .filter(|&def_id| {
+            matches!(tcx.def_kind(def_id), DefKind::Struct | DefKind::Enum | DefKind::Union)
+        })
+        .filter_map(|def_id| {
+            let layout = type_layout(tcx, def_id).ok()?;
+            let path =
+                format!("{crate_name}::{}", with_no_trimmed_paths!(tcx.def_path_str(def_id)));
+            let url = cx.cache().paths.get(&def_id).map(|(fqp, item_type)| {
+                let mut url = String::new();
+                for module in &fqp[1..fqp.len() - 1] {
+                    let _ = write!(url, "{module}/");
+                }
+                let _ = write!(url, "{item_type}.{}.html", fqp[fqp.len() - 1]);
+                url
+            });
+            Some(LayoutReportEntry { path, url, layout })
+        })
+        .collect();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    entries
+}
+
+/// Renders the body of the layout report page.
+pub(crate) fn render_layout_report(entries: &[LayoutReportEntry]) -> String {
+    LayoutReport { entries }.render().unwrap()
+}
+
+/// Renders the layout report as tab-separated values, one type per line in the order of the
+/// page, so the reports of two builds can be compared with `diff`.
+pub(crate) fn layout_report_tsv(entries: &[LayoutReportEntry]) -> String {
+    let mut out = String::from("type\tsize\talign\tniche values\tvariant sizes\n");
+    for entry in entries {
+        let layout = &entry.layout;
+        let _ = write!(
+            out,
+            "{}\t{}\t{}\t{}\t",
+            entry.path,
+            if layout.size.is_unsized {
+                "unsized".to_owned()
+            } else {
+                layout.size.size.to_string()
+            },
+            layout.align,
+            layout.niche_values,
+        );
+        let variants: Vec<_> =
+            layout.variants.iter().map(|(name, size)| format!("{name}={}", size.size)).collect();
+        out.push_str(&variants.join(","));
+        out.push('\n');
+    }
+    out
+}
@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::prelude::*;
 use std::io::{self, BufReader};
@@ -362,11 +363,42 @@ fn add_path(self: &Rc<Self>, path: &Path) {
         );
     }
 
+    let full_text_dir =
+        cx.dst.join(format!("search.fulltext/{krate}", krate = krate.name(cx.tcx())));
+    if Path::new(&full_text_dir).exists() {
+        try_err!(std::fs::remove_dir_all(&full_text_dir), &full_text_dir);
+    }
+    if let Some(full_text) = search_index.full_text {
+        try_err!(std::fs::create_dir_all(&full_text_dir), &full_text_dir);
+        let terms = full_text.terms.iter().map(|terms| serde_json::to_string(terms).unwrap());
+        let text = full_text.text.iter().map(|text| serde_json::to_string(text).unwrap());
+        for (kind, shards) in [("terms", terms.collect::<Vec<_>>()), ("text", text.collect())] {
+            for (i, data) in shards.into_iter().enumerate() {
+                let output_filename = static_files::suffix_path(
+                    &format!("{kratename}-{kind}-{i}-.js"),
+                    &cx.shared.resource_suffix,
+                );
+                let path = full_text_dir.join(output_filename);
+                let contents = format!(
+                    r##"searchState.loadedFullTextShard({kratename}, "{kind}", {i}, {data})"##,
+                    kratename = serde_json::to_string(&kratename).unwrap(),
+                );
+                try_err!(std::fs::write(&path, contents.into_bytes()), &path);
+            }
+        }
+    }
+
     write_invocation_specific("crates.js", &|| {
         let krates = krates.iter().map(|k| format!("\"{k}\"")).join(",");
         Ok(format!("window.ALL_CRATES = [{krates}];").into_bytes())
     })?;
 
+    if let Some(versioned_docs_root) = &cx.shared.versioned_docs_root {
+        // `--versioned-docs` can't be used without `--crate-version`.
+        let version = cx.shared.cache.crate_version.as_deref().unwrap();
+        write_versions(cx, krate.name(cx.tcx()).as_str(), version, versioned_docs_root)?;
+    }
+
     if options.enable_index_page {
         if let Some(index_page) = options.index_page.clone() {
             let mut md_opts = options.clone();
@@ -774,3 +806,76 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     }
     Ok(())
 }
+
+/// Adds `version` to the list of documented versions of `krate` in `versions.js`, which is read by
+/// the version selector, in the directory that contains the documentation of all versions
+/// (`--versioned-docs`). Also makes `<krate>/index.html` in that directory redirect to the docs of
+/// the newest version.
+fn write_versions(cx: &Context<'_>, krate: &str, version: &str, root: &Path) -> Result<(), Error> {
+    // Documentation of several versions can be generated in parallel.
+    let lock_file = root.join(".lock");
+    let _lock = try_err!(flock::Lock::new(&lock_file, true, true, true), &lock_file);
+
+    // The file looks like this, with one line per crate:
+    //
+    // ```javascript
+    // window.ALL_VERSIONS = {
+    // "{crate1}":["{version1}","{version2}"],
+    // "{crate2}":["{version1}"]
+    // };
+    // ```
+    let dst = root.join(static_files::suffix_path("versions.js", &cx.shared.resource_suffix));
+    let mut all_versions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    if dst.exists() {
+        for line in try_err!(fs::read_to_string(&dst), &dst).lines() {
+            if !line.starts_with('"') {
+                continue;
+            }
+            let entry = line.strip_suffix(',').unwrap_or(line);
+            let entry: BTreeMap<String, Vec<String>> =
+                try_err!(serde_json::from_str(&format!("{{{entry}}}")), &dst);
+            all_versions.extend(entry);
+        }
+    }
+    let versions = all_versions.entry(krate.to_owned()).or_default();
+    if !versions.iter().any(|v| v == version) {
+        versions.push(version.to_owned());
+    }
+    versions.sort_by(|a, b| version_sort_key(a).cmp(&version_sort_key(b)));
+    let latest = versions.last().unwrap().clone();
+
+    let mut v = String::from("window.ALL_VERSIONS = {\n");
+    v.push_str(
+        &all_versions
+            .iter()
+            .map(|(krate, versions)| {
+                format!(
+                    "{}:{}",
+                    serde_json::to_string(krate).unwrap(),
+                    serde_json::to_string(versions).unwrap()
+                )
+            })
+            .join(",\n"),
+    );
+    v.push_str("\n};");
+    cx.shared.fs.write(dst, v)?;
+
+    let redirect_dir = root.join(krate);
+    cx.shared.ensure_dir(&redirect_dir)?;
+    cx.shared.fs.write(
+        redirect_dir.join("index.html"),
+        layout::redirect(&format!("../{latest}/{krate}/index.html")),
+    )?;
+    Ok(())
+}
+
+/// Orders versions like `1.10.0` after `1.9.0`, and pre-releases like `2.0.0-beta` before their
+/// release.
+fn version_sort_key(version: &str) -> (Vec<Result<u64, &str>>, bool, Option<&str>) {
+    let (release, pre) = match version.split_once('-') {
+        Some((release, pre)) => (release, Some(pre)),
+        None => (version, None),
+    };
+    let release = release.split('.').map(|part| part.parse().map_err(|_| part)).collect();
+    (release, pre.is_none(), pre)
+}
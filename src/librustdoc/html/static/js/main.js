@@ -240,6 +240,7 @@ function preLoadCss(cssUrl) {
         // 0 for "In Names"
         // 1 for "In Parameters"
         // 2 for "In Return Types"
+        // The last tab is "In Documentation" when there is a full-text index
         currentTab: 0,
         // tab and back preserves the element that was focused.
         focusedByTab: [null, null, null],
@@ -353,6 +354,27 @@ function preLoadCss(cssUrl) {
         loadedDescShard: function(crate, shard, data) {
             this.descShards.get(crate)[shard].resolve(data.split("\n"));
         },
+        // Shards of the full-text index, generated with `--full-text-search`, by file name.
+        fullTextShards: new Map(),
+        loadFullTextShard: function(crate, kind, shard) {
+            const fname = `${crate}-${kind}-${shard}-`;
+            let fullTextShard = this.fullTextShards.get(fname);
+            if (fullTextShard === undefined) {
+                fullTextShard = {promise: null, resolve: null};
+                fullTextShard.promise = new Promise((resolve, reject) => {
+                    // The `resolve` callback is called in `loadedFullTextShard` by the
+                    // search.fulltext script.
+                    fullTextShard.resolve = resolve;
+                    loadScript(resourcePath(`search.fulltext/${crate}/${fname}`, ".js"), reject);
+                });
+                this.fullTextShards.set(fname, fullTextShard);
+            }
+            return fullTextShard.promise;
+        },
+        loadedFullTextShard: function(crate, kind, shard, data) {
+            this.fullTextShards.get(`${crate}-${kind}-${shard}-`)
+                .resolve(kind === "text" ? data.split("\n") : data);
+        },
     };
 
     const toggleAllDocsId = "toggle-all-docs";
@@ -905,6 +927,97 @@ function preLoadCss(cssUrl) {
         sidebarElems.appendChild(ul);
     }
 
+    function addVersionSelector() {
+        const versions = window.ALL_VERSIONS && window.ALL_VERSIONS[window.currentCrate];
+        const currentVersion = document.querySelector(".sidebar-crate h2 .version");
+        if (!versions || versions.length < 2 || !currentVersion) {
+            return;
+        }
+        // Every version is documented into its own directory, and these directories are next to
+        // each other. So the same page of another version is at the same path relative to the
+        // directory of that version.
+        const versionRoot = new URL(window.rootPath, window.location.href).href;
+        const pagePath = window.location.href.slice(versionRoot.length);
+
+        const select = document.createElement("select");
+        select.className = "version";
+        select.title = "Show the documentation of another version";
+        for (const version of versions.slice().reverse()) {
+            const option = document.createElement("option");
+            option.value = version;
+            option.textContent = version;
+            option.selected = version === currentVersion.textContent.trim();
+            select.appendChild(option);
+        }
+        select.addEventListener("change", () => {
+            const otherRoot = new URL("../" + encodeURIComponent(select.value) + "/", versionRoot);
+            window.location.href = otherRoot.href + pagePath;
+        });
+        currentVersion.replaceWith(select);
+    }
+
+    function setupCfgVariants() {
+        const selects = document.querySelectorAll(".cfg-variants > select.cfg-variant-select");
+        if (selects.length === 0) {
+            return;
+        }
+        // The chosen configuration is remembered, so all the items of all the pages show the
+        // same one.
+        const show = label => {
+            onEach(selects, select => {
+                const options = Array.prototype.map.call(select.options, option => option.value);
+                // Items that don't differ in the chosen configuration show the first one.
+                const shown = options.includes(label) ? label : options[0];
+                select.value = shown;
+                onEachLazy(select.parentElement.querySelectorAll("div.cfg-variant"), variant => {
+                    variant.style.display = variant.dataset.cfgVariant === shown ? "" : "none";
+                });
+            });
+        };
+        onEach(selects, select => {
+            select.addEventListener("change", () => {
+                updateLocalStorage("cfg-variant", select.value);
+                show(select.value);
+            });
+        });
+        show(getSettingValue("cfg-variant"));
+    }
+
+    function setupLayoutReport() {
+        for (const table of document.querySelectorAll("table.layout-report")) {
+            const body = table.tBodies[0];
+            const headers = table.querySelectorAll("thead th");
+            Array.prototype.forEach.call(headers, (th, column) => {
+                th.addEventListener("click", () => {
+                    const numeric = th.hasAttribute("data-numeric");
+                    // Clicking on the header of the sorted column again reverses the order.
+                    const descending = th.dataset.sorted === "ascending";
+                    for (const other of headers) {
+                        other.removeAttribute("data-sorted");
+                    }
+                    th.dataset.sorted = descending ? "descending" : "ascending";
+                    const key = row => row.cells[column].dataset.sort;
+                    const rows = Array.prototype.slice.call(body.rows);
+                    rows.sort((a, b) => {
+                        let order;
+                        if (numeric) {
+                            // Niche counts can be too big for numbers, compare them as strings
+                            // of digits.
+                            order = key(a).length - key(b).length ||
+                                (key(a) < key(b) ? -1 : key(a) > key(b) ? 1 : 0);
+                        } else {
+                            order = key(a).localeCompare(key(b));
+                        }
+                        return descending ? -order : order;
+                    });
+                    for (const row of rows) {
+                        body.appendChild(row);
+                    }
+                });
+            });
+        }
+    }
+
     function expandAllDocs() {
         const innerToggle = document.getElementById(toggleAllDocsId);
         removeClass(innerToggle, "will-expand");
@@ -1501,6 +1614,9 @@ href="https://doc.rust-lang.org/${channel}/rustdoc/read-documentation/search.htm
     setMobileTopbar();
     addSidebarItems();
     addSidebarCrates();
+    addVersionSelector();
+    setupCfgVariants();
+    setupLayoutReport();
     onHashChange(null);
     window.addEventListener("hashchange", onHashChange);
     searchState.setup();
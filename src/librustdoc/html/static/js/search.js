@@ -1,5 +1,5 @@
 // ignore-tidy-filelength
-/* global addClass, getNakedUrl, getSettingValue */
+/* global addClass, getNakedUrl, getSettingValue, hasClass */
 /* global onEachLazy, removeClass, searchState, browserSupportsHistoryApi, exports */
 
 "use strict";
@@ -89,6 +89,32 @@ const ROOT_PATH = typeof window !== "undefined" ? window.rootPath : "../";
 // of permutations we need to check.
 const UNBOXING_LIMIT = 5;
 
+// The full-text index, generated with `--full-text-search`, has to be split into words the same
+// way as in `librustdoc/html/render/search_index/full_text.rs`.
+const FULL_TEXT_SHARD_ITEMS = 256;
+const FULL_TEXT_MIN_TERM_LEN = 2;
+const FULL_TEXT_MAX_TERM_LEN = 40;
+const FULL_TEXT_STOP_WORDS = new Set([
+    "an", "and", "are", "as", "at", "be", "by", "for", "from", "if", "in", "is", "it", "of", "on",
+    "or", "that", "the", "this", "to", "with",
+]);
+
+/**
+ * Splits some text into the words of the full-text index.
+ *
+ * @param {string} text
+ * @return {Array<string>}
+ */
+function fullTextTerms(text) {
+    return text.split(/[^\p{Alphabetic}\p{N}]+/u)
+        .filter(word => {
+            const len = [...word].length;
+            return len >= FULL_TEXT_MIN_TERM_LEN && len <= FULL_TEXT_MAX_TERM_LEN;
+        })
+        .map(word => word.toLowerCase())
+        .filter(word => !FULL_TEXT_STOP_WORDS.has(word));
+}
+
 // used for search query verification
 const REGEX_IDENT = /\p{ID_Start}\p{ID_Continue}*|_\p{ID_Continue}+/uy;
 const REGEX_INVALID_TYPE_FILTER = /[^a-z]/ui;
@@ -107,9 +133,15 @@ function printTab(nb) {
         }
         iter += 1;
     });
-    const isTypeSearch = (nb > 0 || iter === 1);
     iter = 0;
+    // The "In Documentation" tab, shown with `--full-text-search`, doesn't search types.
+    let hasDocsTab = false;
+    let isDocsTab = false;
     onEachLazy(document.getElementById("results").childNodes, elem => {
+        if (hasClass(elem, "in-docs")) {
+            hasDocsTab = true;
+            isDocsTab = nb === iter;
+        }
         if (nb === iter) {
             addClass(elem, "active");
             foundCurrentResultSet = true;
@@ -118,6 +150,7 @@ function printTab(nb) {
         }
         iter += 1;
     });
+    const isTypeSearch = !isDocsTab && (nb > 0 || iter === (hasDocsTab ? 2 : 1));
     if (foundCurrentTab && foundCurrentResultSet) {
         searchState.currentTab = nb;
         // Corrections only kick in on type-based searches.
@@ -255,6 +288,13 @@ function initSearch(rawSearchIndex) {
      * @type {Map<String, RoaringBitmap>}
      */
     let searchIndexEmptyDesc;
+    /**
+     * The first word of every shard of the full-text index of a crate, and the position of the
+     * crate in `searchIndex`.
+     *
+     * @type {Map<String, {shardKeys: Array<string>, start: integer}>}
+     */
+    let searchIndexFullText;
     /**
      *  @type {Uint32Array}
      */
@@ -1320,6 +1360,7 @@ function initSearch(rawSearchIndex) {
             "in_args": results_in_args,
             "returned": results_returned,
             "others": results_others,
+            "in_docs": null,
             "query": parsedQuery,
         };
     }
@@ -2571,11 +2612,167 @@ function initSearch(rawSearchIndex) {
             // It means some doc aliases were found so let's "remove" the error!
             ret.query.error = null;
         }
+        ret.in_docs = await fullTextSearch(parsedQuery, filterCrates, currentCrate);
         return ret;
     }
 
+    /**
+     * Finds the index of the shard of the full-text index that contains a word.
+     *
+     * @param {Array<string>} shardKeys - The first word of every shard, sorted
+     * @param {string} term
+     * @return {integer} - `-1` if the word comes before the first shard
+     */
+    function findFullTextShard(shardKeys, term) {
+        let low = 0;
+        let high = shardKeys.length;
+        while (low < high) {
+            const mid = (low + high) >> 1;
+            if (shardKeys[mid] <= term) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        return low - 1;
+    }
+
+    /**
+     * Renders the part of the docs of an item around the first word of the query it contains,
+     * with the words of the query in bold.
+     *
+     * @param {string} text - The plain text of the docs
+     * @param {Array<string>} terms - The words of the query
+     * @return {string|null} - `null` if none of the words are in the stored text
+     */
+    function fullTextSnippet(text, terms) {
+        const lowerText = text.toLowerCase();
+        let pos = -1;
+        for (const term of terms) {
+            const termPos = lowerText.indexOf(term);
+            if (termPos !== -1 && (pos === -1 || termPos < pos)) {
+                pos = termPos;
+            }
+        }
+        if (pos === -1) {
+            return null;
+        }
+        const start = pos < 40 ? 0 : text.lastIndexOf(" ", pos - 40) + 1;
+        let end = text.indexOf(" ", pos + 100);
+        if (end === -1) {
+            end = text.length;
+        }
+        const escape = str => str.replace(/&/g, "&amp;")
+            .replace(/</g, "&lt;")
+            .replace(/>/g, "&gt;");
+        // The words are only made of letters and digits, so they don't need to be escaped.
+        const html = text.slice(start, end)
+            .split(new RegExp(`(${terms.join("|")})`, "giu"))
+            .map((part, i) => (i % 2 === 0 ? escape(part) : `<b>${escape(part)}</b>`))
+            .join("");
+        return (start > 0 ? "…" : "") + html + (end < text.length ? "…" : "");
+    }
+
+    /**
+     * Looks for the items whose docs contain every word of the query, using the full-text index
+     * generated with `--full-text-search`.
+     *
+     * @param {ParsedQuery} parsedQuery - The parsed user query
+     * @param {string|null} filterCrates - Crate to search in if not `null`
+     * @param {string} currentCrate - Current crate, to rank results from this crate higher
+     *
+     * @return {Promise<Array<ResultObject>|null>} - `null` if no crate has a full-text index
+     */
+    async function fullTextSearch(parsedQuery, filterCrates, currentCrate) {
+        const crates = [...searchIndexFullText.keys()]
+            .filter(crate => filterCrates === null || crate === filterCrates);
+        const terms = [...new Set(fullTextTerms(parsedQuery.original))];
+        if (crates.length === 0) {
+            return null;
+        }
+        if (terms.length === 0) {
+            return [];
+        }
+
+        const matches = [];
+        await Promise.all(crates.map(async crate => {
+            const {shardKeys, start} = searchIndexFullText.get(crate);
+            const lists = await Promise.all(terms.map(async term => {
+                const shard = findFullTextShard(shardKeys, term);
+                if (shard === -1) {
+                    return [];
+                }
+                const shardTerms = await searchState.loadFullTextShard(crate, "terms", shard);
+                if (!Object.prototype.hasOwnProperty.call(shardTerms, term)) {
+                    return [];
+                }
+                // The items are stored as the difference with the previous one.
+                const decoder = new VlqHexDecoder(shardTerms[term], noop => noop);
+                const items = [];
+                let item = 0;
+                while (decoder.offset < decoder.string.length) {
+                    item += decoder.decode();
+                    items.push(item);
+                }
+                return items;
+            }));
+            let items = lists[0];
+            for (const list of lists.slice(1)) {
+                const set = new Set(list);
+                items = items.filter(item => set.has(item));
+            }
+            for (const bitIndex of items) {
+                matches.push(start + bitIndex);
+            }
+        }));
+
+        // Items with a word of the query in their name come first, then the items of the
+        // current crate.
+        const rank = id => {
+            const row = searchIndex[id];
+            return (terms.some(term => row.word.includes(term)) ? 0 : 2) +
+                (row.crate === currentCrate ? 0 : 1);
+        };
+        matches.sort((a, b) => (rank(a) - rank(b)) || (a - b));
+
+        const duplicates = new Set();
+        const out = [];
+        for (const id of matches) {
+            const row = searchIndex[id];
+            const [displayPath, href, fullPath] = buildHrefAndPath(row);
+            if (duplicates.has(fullPath + "|" + row.ty)) {
+                continue;
+            }
+            duplicates.add(fullPath + "|" + row.ty);
+            // The rows are shared with the other tabs, which show the regular description.
+            out.push(Object.assign({}, row, {
+                displayPath: pathSplitter(displayPath),
+                fullPath: fullPath + "|" + row.ty,
+                href,
+            }));
+            if (out.length >= MAX_RESULTS) {
+                break;
+            }
+        }
+
+        await Promise.all(out.map(async result => {
+            const shard = Math.floor(result.bitIndex / FULL_TEXT_SHARD_ITEMS);
+            const text = await searchState.loadFullTextShard(result.crate, "text", shard);
+            const snippet = fullTextSnippet(text[result.bitIndex % FULL_TEXT_SHARD_ITEMS], terms);
+            if (snippet !== null) {
+                result.desc = snippet;
+            } else {
+                result.desc = searchIndexEmptyDesc.get(result.crate).contains(result.bitIndex) ?
+                    "" :
+                    await searchState.loadDesc(result);
+            }
+        }));
+        return out;
+    }
+
     function nextTab(direction) {
-        const next = (searchState.currentTab + direction + 3) % searchState.focusedByTab.length;
+        const tabs = searchState.focusedByTab.length;
+        const next = (searchState.currentTab + direction + tabs) % tabs;
         searchState.focusedByTab[searchState.currentTab] = document.activeElement;
         printTab(next);
         focusSearchResult();
@@ -2785,27 +2982,14 @@ ${item.displayPath}<span class="${type}">${name}</span>\
 
         currentResults = results.query.userQuery;
 
-        const [ret_others, ret_in_args, ret_returned] = await Promise.all([
+        const [ret_others, ret_in_args, ret_returned, ret_in_docs] = await Promise.all([
             addTab(results.others, results.query, true),
             addTab(results.in_args, results.query, false),
             addTab(results.returned, results.query, false),
+            results.in_docs === null ? null : addTab(results.in_docs, results.query, false),
         ]);
 
-        // Navigate to the relevant tab if the current tab is empty, like in case users search
-        // for "-> String". If they had selected another tab previously, they have to click on
-        // it again.
         let currentTab = searchState.currentTab;
-        if ((currentTab === 0 && ret_others[1] === 0) ||
-                (currentTab === 1 && ret_in_args[1] === 0) ||
-                (currentTab === 2 && ret_returned[1] === 0)) {
-            if (ret_others[1] !== 0) {
-                currentTab = 0;
-            } else if (ret_in_args[1] !== 0) {
-                currentTab = 1;
-            } else if (ret_returned[1] !== 0) {
-                currentTab = 2;
-            }
-        }
 
         let crates = "";
         if (rawSearchIndex.size > 1) {
@@ -2818,6 +3002,7 @@ ${item.displayPath}<span class="${type}">${name}</span>\
         }
 
         let output = `<h1 class="search-results-title">Results${crates}</h1>`;
+        let tabs;
         if (results.query.error !== null) {
             const error = results.query.error;
             error.forEach((value, index) => {
@@ -2829,26 +3014,41 @@ ${item.displayPath}<span class="${type}">${name}</span>\
                 }
             });
             output += `<h3 class="error">Query parser error: "${error.join("")}".</h3>`;
-            output += "<div id=\"search-tabs\">" +
-                makeTabHeader(0, "In Names", ret_others[1]) +
-                "</div>";
+            tabs = [["In Names", ret_others]];
             currentTab = 0;
         } else if (results.query.foundElems <= 1 && results.query.returned.length === 0) {
-            output += "<div id=\"search-tabs\">" +
-                makeTabHeader(0, "In Names", ret_others[1]) +
-                makeTabHeader(1, "In Parameters", ret_in_args[1]) +
-                makeTabHeader(2, "In Return Types", ret_returned[1]) +
-                "</div>";
+            tabs = [
+                ["In Names", ret_others],
+                ["In Parameters", ret_in_args],
+                ["In Return Types", ret_returned],
+            ];
         } else {
             const signatureTabTitle =
                 results.query.elems.length === 0 ? "In Function Return Types" :
                 results.query.returned.length === 0 ? "In Function Parameters" :
                 "In Function Signatures";
-            output += "<div id=\"search-tabs\">" +
-                makeTabHeader(0, signatureTabTitle, ret_others[1]) +
-                "</div>";
+            tabs = [[signatureTabTitle, ret_others]];
             currentTab = 0;
         }
+        if (ret_in_docs !== null) {
+            addClass(ret_in_docs[0], "in-docs");
+            tabs.push(["In Documentation", ret_in_docs]);
+        }
+
+        // Navigate to the relevant tab if the current tab is empty, like in case users search
+        // for "-> String". If they had selected another tab previously, they have to click on
+        // it again.
+        if (currentTab >= tabs.length || tabs[currentTab][1][1] === 0) {
+            const nonEmptyTab = tabs.findIndex(([, ret]) => ret[1] !== 0);
+            if (nonEmptyTab !== -1) {
+                currentTab = nonEmptyTab;
+            } else if (currentTab >= tabs.length) {
+                currentTab = 0;
+            }
+        }
+        output += "<div id=\"search-tabs\">" +
+            tabs.map(([text, ret], tabNb) => makeTabHeader(tabNb, text, ret[1])).join("") +
+            "</div>";
 
         if (results.query.correction !== null) {
             const orig = results.query.returned.length > 0
@@ -2869,9 +3069,9 @@ ${item.displayPath}<span class="${type}">${name}</span>\
 
         const resultsElem = document.createElement("div");
         resultsElem.id = "results";
-        resultsElem.appendChild(ret_others[0]);
-        resultsElem.appendChild(ret_in_args[0]);
-        resultsElem.appendChild(ret_returned[0]);
+        for (const [, ret] of tabs) {
+            resultsElem.appendChild(ret[0]);
+        }
 
         search.innerHTML = output;
         const crateSearch = document.getElementById("crate-search");
@@ -3469,6 +3669,7 @@ ${item.displayPath}<span class="${type}">${name}</span>\
         searchIndex = [];
         searchIndexDeprecated = new Map();
         searchIndexEmptyDesc = new Map();
+        searchIndexFullText = new Map();
         let currentIndex = 0;
         let id = 0;
 
@@ -3528,6 +3729,9 @@ ${item.displayPath}<span class="${type}">${name}</span>\
             id += 1;
             searchIndex.push(crateRow);
             currentIndex += 1;
+            if (crateCorpus.x) {
+                searchIndexFullText.set(crate, {shardKeys: crateCorpus.x, start: crateRow.id});
+            }
             if (!searchIndexEmptyDesc.get(crate).contains(0)) {
                 descIndex += 1;
             }
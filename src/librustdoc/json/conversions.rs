@@ -401,6 +401,7 @@ fn from_tcx(struct_: clean::Struct, tcx: TyCtxt<'_>) -> Self {
             kind,
             generics: generics.into_tcx(tcx),
             impls: Vec::new(), // Added in JsonRenderer::item
+            layout: None,      // Added in JsonRenderer::item
         }
     }
 }
@@ -414,6 +415,7 @@ fn from_tcx(union_: clean::Union, tcx: TyCtxt<'_>) -> Self {
             fields_stripped,
             fields: ids(fields, tcx),
             impls: Vec::new(), // Added in JsonRenderer::item
+            layout: None,      // Added in JsonRenderer::item
         }
     }
 }
@@ -738,6 +740,7 @@ pub(crate) fn from_function(
         generics: generics.into_tcx(tcx),
         header: from_fn_header(&header),
         has_body,
+        references: None, // Added in JsonRenderer::item
     }
 }
 
@@ -750,6 +753,7 @@ fn from_tcx(enum_: clean::Enum, tcx: TyCtxt<'_>) -> Self {
             variants_stripped,
             variants: ids(variants, tcx),
             impls: Vec::new(), // Added in JsonRenderer::item
+            layout: None,      // Added in JsonRenderer::item
         }
     }
 }
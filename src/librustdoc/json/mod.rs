@@ -6,6 +6,8 @@
 
 mod conversions;
 mod import_finder;
+#[cfg(test)]
+pub(crate) mod test_items;
 
 use std::cell::RefCell;
 use std::fs::{create_dir_all, File};
@@ -13,7 +15,7 @@
 use std::path::PathBuf;
 use std::rc::Rc;
 
-use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexSet};
 use rustc_hir::def_id::{DefId, DefIdSet};
 use rustc_middle::ty::TyCtxt;
 use rustc_session::Session;
@@ -27,6 +29,7 @@
 use crate::error::Error;
 use crate::formats::cache::Cache;
 use crate::formats::FormatRenderer;
+use crate::html::render::{collect_body_references, type_layout};
 use crate::json::conversions::{id_from_item, id_from_item_default, IntoWithTcx};
 use crate::{clean, try_err};
 
@@ -40,6 +43,10 @@ pub(crate) struct JsonRenderer<'tcx> {
     out_path: PathBuf,
     cache: Rc<Cache>,
     imported_items: DefIdSet,
+    /// With `--json-body-references`, the items referred to by the body of each local item.
+    body_references: Option<Rc<FxHashMap<DefId, FxIndexSet<DefId>>>>,
+    /// Whether to compute the layout of structs, enums and unions (`--show-type-layout`).
+    show_type_layout: bool,
 }
 
 impl<'tcx> JsonRenderer<'tcx> {
@@ -97,6 +104,120 @@ fn get_impls(&mut self, id: DefId) -> Vec<types::Id> {
             })
             .unwrap_or_default()
     }
+
+    fn get_body_references(&self, id: DefId) -> Option<Vec<types::Id>> {
+        let body_references = self.body_references.as_ref()?;
+        if !id.is_local() {
+            return None;
+        }
+        Some(
+            body_references
+                .get(&id)
+                .into_iter()
+                .flatten()
+                .map(|&did| id_from_item_default(did.into(), self.tcx))
+                .collect(),
+        )
+    }
+
+    fn get_layout(&self, id: DefId) -> Option<types::Layout> {
+        if !self.show_type_layout {
+            return None;
+        }
+        let layout = type_layout(self.tcx, id).ok()?;
+        Some(types::Layout {
+            size: layout.size.size,
+            align: layout.align,
+            is_unsized: layout.size.is_unsized,
+            is_uninhabited: layout.size.is_uninhabited,
+            niche_values: layout.niche_values,
+            variants: layout
+                .variants
+                .into_iter()
+                .map(|(name, size)| types::VariantLayout {
+                    name: name.to_string(),
+                    size: size.size,
+                    is_uninhabited: size.is_uninhabited,
+                })
+                .collect(),
+        })
+    }
+
+    /// Builds the JSON output from all the items rendered so far. This is what `after_krate`
+    /// writes to disk, and what the Markdown and man page backends are built on.
+    pub(crate) fn build_crate(&mut self) -> types::Crate {
+        debug!("Done with crate");
+
+        debug!("Adding Primitive impls");
+        for primitive in Rc::clone(&self.cache).primitive_locations.values() {
+            self.get_impls(*primitive);
+        }
+
+        let e = ExternalCrate { crate_num: LOCAL_CRATE };
+
+        let mut index = (*self.index).clone().into_inner();
+        let paths: FxHashMap<_, _> = self
+            .cache
+            .paths
+            .iter()
+            .chain(&self.cache.external_paths)
+            .map(|(&k, &(ref path, kind))| {
+                (
+                    id_from_item_default(k.into(), self.tcx),
+                    types::ItemSummary {
+                        crate_id: k.krate.as_u32(),
+                        path: path.iter().map(|s| s.to_string()).collect(),
+                        kind: kind.into_tcx(self.tcx),
+                    },
+                )
+            })
+            .collect();
+
+        if self.body_references.is_some() {
+            // Bodies can refer to items that aren't documented, like private items or the methods
+            // of external types. Drop them, so that every `Id` can be looked up.
+            let known: FxHashSet<types::Id> = index.keys().chain(paths.keys()).cloned().collect();
+            for item in index.values_mut() {
+                if let types::ItemEnum::Function(types::Function {
+                    references: Some(references), ..
+                }) = &mut item.inner
+                {
+                    references.retain(|id| known.contains(id));
+                }
+            }
+        }
+
+        debug!("Constructing Output");
+        // This needs to be the default HashMap for compatibility with the public interface for
+        // rustdoc-json-types
+        #[allow(rustc::default_hash_types)]
+        types::Crate {
+            root: types::Id(format!("0:0:{}", e.name(self.tcx).as_u32())),
+            crate_version: self.cache.crate_version.clone(),
+            includes_private: self.cache.document_private,
+            index: index.into_iter().collect(),
+            paths: paths.into_iter().collect(),
+            external_crates: self
+                .cache
+                .extern_locations
+                .iter()
+                .map(|(crate_num, external_location)| {
+                    let e = ExternalCrate { crate_num: *crate_num };
+                    (
+                        crate_num.as_u32(),
+                        types::ExternalCrate {
+                            name: e.name(self.tcx).to_string(),
+                            html_root_url: match external_location {
+                                ExternalLocation::Remote(s) => Some(s.clone()),
+                                _ => None,
+                            },
+                        },
+                    )
+                })
+                .collect(),
+            format_version: types::FORMAT_VERSION,
+        }
+    }
 }
 
 impl<'tcx> FormatRenderer<'tcx> for JsonRenderer<'tcx> {
@@ -115,6 +236,9 @@ fn init(
         debug!("Initializing json renderer");
 
         let (krate, imported_items) = import_finder::get_imports(krate);
+        let body_references = options.json_body_references.then(|| {
+            Rc::new(tcx.sess.time("collect_body_references", || collect_body_references(tcx)))
+        });
 
         Ok((
             JsonRenderer {
@@ -123,6 +247,8 @@ fn init(
                 out_path: options.output,
                 cache: Rc::new(cache),
                 imported_items,
+                body_references,
+                show_type_layout: options.show_type_layout,
             },
             krate,
         ))
@@ -158,14 +284,17 @@ fn item(&mut self, item: clean::Item) -> Result<(), Error> {
                 }
                 types::ItemEnum::Struct(ref mut s) => {
                     s.impls = self.get_impls(item_id.expect_def_id());
+                    s.layout = self.get_layout(item_id.expect_def_id());
                     false
                 }
                 types::ItemEnum::Enum(ref mut e) => {
                     e.impls = self.get_impls(item_id.expect_def_id());
+                    e.layout = self.get_layout(item_id.expect_def_id());
                     false
                 }
                 types::ItemEnum::Union(ref mut u) => {
                     u.impls = self.get_impls(item_id.expect_def_id());
+                    u.layout = self.get_layout(item_id.expect_def_id());
                     false
                 }
                 types::ItemEnum::Primitive(ref mut p) => {
@@ -173,8 +302,11 @@ fn item(&mut self, item: clean::Item) -> Result<(), Error> {
                     false
                 }
 
-                types::ItemEnum::Function(_)
-                | types::ItemEnum::Module(_)
+                types::ItemEnum::Function(ref mut f) => {
+                    f.references = self.get_body_references(item_id.expect_def_id());
+                    true
+                }
+                types::ItemEnum::Module(_)
                 | types::ItemEnum::Import(_)
                 | types::ItemEnum::AssocConst { .. }
                 | types::ItemEnum::AssocType { .. } => true,
@@ -214,62 +346,7 @@ fn mod_item_in(&mut self, _item: &clean::Item) -> Result<(), Error> {
     }
 
     fn after_krate(&mut self) -> Result<(), Error> {
-        debug!("Done with crate");
-
-        debug!("Adding Primitive impls");
-        for primitive in Rc::clone(&self.cache).primitive_locations.values() {
-            self.get_impls(*primitive);
-        }
-
-        let e = ExternalCrate { crate_num: LOCAL_CRATE };
-
-        let index = (*self.index).clone().into_inner();
-
-        debug!("Constructing Output");
-        // This needs to be the default HashMap for compatibility with the public interface for
-        // rustdoc-json-types
-        #[allow(rustc::default_hash_types)]
-        let output = types::Crate {
-            root: types::Id(format!("0:0:{}", e.name(self.tcx).as_u32())),
-            crate_version: self.cache.crate_version.clone(),
-            includes_private: self.cache.document_private,
-            index: index.into_iter().collect(),
-            paths: self
-                .cache
-                .paths
-                .iter()
-                .chain(&self.cache.external_paths)
-                .map(|(&k, &(ref path, kind))| {
-                    (
-                        id_from_item_default(k.into(), self.tcx),
-                        types::ItemSummary {
-                            crate_id: k.krate.as_u32(),
-                            path: path.iter().map(|s| s.to_string()).collect(),
-                            kind: kind.into_tcx(self.tcx),
-                        },
-                    )
-                })
-                .collect(),
-            external_crates: self
-                .cache
-                .extern_locations
-                .iter()
-                .map(|(crate_num, external_location)| {
-                    let e = ExternalCrate { crate_num: *crate_num };
-                    (
-                        crate_num.as_u32(),
-                        types::ExternalCrate {
-                            name: e.name(self.tcx).to_string(),
-                            html_root_url: match external_location {
-                                ExternalLocation::Remote(s) => Some(s.clone()),
-                                _ => None,
-                            },
-                        },
-                    )
-                })
-                .collect(),
-            format_version: types::FORMAT_VERSION,
-        };
+        let output = self.build_crate();
         let out_dir = self.out_path.clone();
         try_err!(create_dir_all(&out_dir), out_dir);
 
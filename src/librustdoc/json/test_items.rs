@@ -0,0 +1,90 @@
+//! Builders for the items of rustdoc's JSON output, for the tests of the modules that read it.
+
+use rustc_data_structures::fx::FxHashMap;
+use rustdoc_json_types::{
+    Abi, Crate, FnDecl, Function, Generics, Header, Id, Import, Item, ItemEnum, Module, Struct,
+    StructKind, Type, Visibility, FORMAT_VERSION,
+};
+
+pub(crate) fn item(id: &str, name: Option<&str>, inner: ItemEnum) -> Item {
+    Item {
+        id: Id(id.to_owned()),
+        crate_id: 0,
+        name: name.map(str::to_owned),
+        span: None,
+        visibility: Visibility::Public,
+        docs: None,
+        links: FxHashMap::default(),
+        attrs: Vec::new(),
+        deprecation: None,
+        inner,
+    }
+}
+
+/// Builds a crate named `krate` whose root module (with id `0`) contains the items with the
+/// given ids.
+pub(crate) fn krate(root_items: &[&str], items: Vec<Item>) -> Crate {
+    let root = module("0", "krate", root_items);
+    Crate {
+        root: root.id.clone(),
+        crate_version: None,
+        includes_private: false,
+        index: items.into_iter().chain([root]).map(|item| (item.id.clone(), item)).collect(),
+        paths: FxHashMap::default(),
+        external_crates: FxHashMap::default(),
+        format_version: FORMAT_VERSION,
+    }
+}
+
+pub(crate) fn generics() -> Generics {
+    Generics { params: Vec::new(), where_predicates: Vec::new() }
+}
+
+pub(crate) fn module(id: &str, name: &str, items: &[&str]) -> Item {
+    item(
+        id,
+        Some(name),
+        ItemEnum::Module(Module {
+            is_crate: id == "0",
+            items: items.iter().map(|id| Id(id.to_string())).collect(),
+            is_stripped: false,
+        }),
+    )
+}
+
+pub(crate) fn unit_struct(id: &str, name: &str) -> Item {
+    item(
+        id,
+        Some(name),
+        ItemEnum::Struct(Struct {
+            kind: StructKind::Unit,
+            generics: generics(),
+            impls: Vec::new(),
+            layout: None,
+        }),
+    )
+}
+
+pub(crate) fn function(id: &str, name: &str, inputs: Vec<(&str, Type)>) -> Item {
+    item(
+        id,
+        Some(name),
+        ItemEnum::Function(Function {
+            decl: FnDecl {
+                inputs: inputs.into_iter().map(|(name, ty)| (name.to_owned(), ty)).collect(),
+                output: None,
+                c_variadic: false,
+            },
+            generics: generics(),
+            header: Header { const_: false, unsafe_: false, async_: false, abi: Abi::Rust },
+            has_body: true,
+            references: None,
+        }),
+    )
+}
+
+pub(crate) fn import(id: &str, source: &str, name: &str, target: Option<&str>, glob: bool) -> Item {
+    let target = target.map(|target| Id(target.to_owned()));
+    let import = Import { source: source.to_owned(), name: name.to_owned(), id: target, glob };
+    item(id, None, ItemEnum::Import(import))
+}
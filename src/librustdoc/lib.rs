@@ -104,6 +104,7 @@ macro_rules! map {
     }}
 }
 
+mod api_diff;
 mod clean;
 mod config;
 mod core;
@@ -120,6 +121,7 @@ macro_rules! map {
 mod markdown;
 mod passes;
 mod scrape_examples;
+mod text;
 mod theme;
 mod visit;
 mod visit_ast;
@@ -556,6 +558,14 @@ fn opts() -> Vec<RustcOptGroup> {
         unstable("no-run", |o| {
             o.optflagmulti("", "no-run", "Compile doctests without running them")
         }),
+        unstable("doctest-report", |o| {
+            o.optopt(
+                "",
+                "doctest-report",
+                "Write a JSON and HTML report about the doctests to the given directory",
+                "PATH",
+            )
+        }),
         unstable("remap-path-prefix", |o| {
             o.optmulti(
                 "",
@@ -577,6 +587,20 @@ fn opts() -> Vec<RustcOptGroup> {
                 "Make the identifiers in the HTML source code pages navigable",
             )
         }),
+        unstable("json-body-references", |o| {
+            o.optflag(
+                "",
+                "json-body-references",
+                "List the items used by each function body in the JSON output",
+            )
+        }),
+        unstable("full-text-search", |o| {
+            o.optflag(
+                "",
+                "full-text-search",
+                "Include the text of the documentation in the search index",
+            )
+        }),
         unstable("scrape-examples-output-path", |o| {
             o.optopt(
                 "",
@@ -596,6 +620,14 @@ fn opts() -> Vec<RustcOptGroup> {
         unstable("scrape-tests", |o| {
             o.optflag("", "scrape-tests", "Include test code when scraping examples")
         }),
+        unstable("scrape-examples-kind", |o| {
+            o.optopt(
+                "",
+                "scrape-examples-kind",
+                "What kind of target the examples are scraped from, used to rank them",
+                "[example|test|bench]",
+            )
+        }),
         unstable("with-examples", |o| {
             o.optmulti(
                 "",
@@ -653,6 +685,41 @@ fn opts() -> Vec<RustcOptGroup> {
         unstable("html-no-source", |o| {
             o.optflag("", "html-no-source", "Disable HTML source code pages generation")
         }),
+        unstable("versioned-docs", |o| {
+            o.optflag(
+                "",
+                "versioned-docs",
+                "Document this version of the crate into its own subdirectory of the output \
+                directory, with a selector to switch between the versions documented there",
+            )
+        }),
+        unstable("version-history", |o| {
+            o.optmulti(
+                "",
+                "version-history",
+                "JSON documentation of an earlier version of the crate, to show in which version \
+                items were added; pass it once per version, oldest first",
+                "VERSION=PATH",
+            )
+        }),
+        unstable("cfg-variant", |o| {
+            o.optmulti(
+                "",
+                "cfg-variant",
+                "JSON documentation of the crate built with other cfgs, e.g. for another target, \
+                to show the items that differ; pass it once per configuration",
+                "LABEL=PATH",
+            )
+        }),
+        unstable("api-diff", |o| {
+            o.optopt(
+                "",
+                "api-diff",
+                "Compare the JSON documentation given as input against this older JSON \
+                documentation and write a report of the API changes",
+                "PATH",
+            )
+        }),
     ]
 }
 
@@ -737,6 +804,11 @@ fn main_args(
         core::new_dcx(options.error_format, None, options.diagnostic_width, &options.unstable_opts);
     let dcx = dcx.handle();
 
+    if let Some(old_json) = &options.api_diff {
+        let new_json = options.input.opt_path().expect("checked in `Options::from_matches`");
+        return wrap_return(dcx, api_diff::run(old_json, new_json, &render_options.output));
+    }
+
     match (options.should_test, options.markdown_input()) {
         (true, Some(_)) => return wrap_return(dcx, doctest::test_markdown(options)),
         (true, None) => return doctest::run(dcx, options),
@@ -828,6 +900,22 @@ fn main_args(
                     config::OutputFormat::Json => sess.time("render_json", || {
                         run_renderer::<json::JsonRenderer<'_>>(krate, render_opts, cache, tcx)
                     }),
+                    config::OutputFormat::Markdown => sess.time("render_markdown", || {
+                        run_renderer::<text::TextRenderer<'_, text::MarkdownFormat>>(
+                            krate,
+                            render_opts,
+                            cache,
+                            tcx,
+                        )
+                    }),
+                    config::OutputFormat::Man => sess.time("render_man", || {
+                        run_renderer::<text::TextRenderer<'_, text::ManFormat>>(
+                            krate,
+                            render_opts,
+                            cache,
+                            tcx,
+                        )
+                    }),
                 }
             })
         })
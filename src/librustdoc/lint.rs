@@ -97,6 +97,17 @@ macro_rules! declare_rustdoc_lint {
     "linking from a public item to a private one"
 }
 
+declare_rustdoc_lint! {
+    /// The `shadowed_intra_doc_links` lint detects intra-doc links that resolve to a different
+    /// item in the module where the documented item is re-exported. This is a `rustdoc` only
+    /// lint, see the documentation in the [rustdoc book].
+    ///
+    /// [rustdoc book]: ../../../rustdoc/lints.html#shadowed_intra_doc_links
+    SHADOWED_INTRA_DOC_LINKS,
+    Allow,
+    "intra-doc links resolving to a different item where the documented item is re-exported"
+}
+
 declare_rustdoc_lint! {
     /// The `invalid_codeblock_attributes` lint detects code block attributes
     /// in documentation examples that have potentially mis-typed values. This
@@ -208,6 +219,7 @@ macro_rules! declare_rustdoc_lint {
     vec![
         BROKEN_INTRA_DOC_LINKS,
         PRIVATE_INTRA_DOC_LINKS,
+        SHADOWED_INTRA_DOC_LINKS,
         MISSING_DOC_CODE_EXAMPLES,
         PRIVATE_DOC_TESTS,
         INVALID_CODEBLOCK_ATTRIBUTES,
@@ -14,8 +14,9 @@
 use rustc_errors::{Applicability, Diag, DiagMessage};
 use rustc_hir::def::Namespace::*;
 use rustc_hir::def::{DefKind, Namespace, PerNS};
-use rustc_hir::def_id::{DefId, CRATE_DEF_ID};
+use rustc_hir::def_id::{DefId, LocalDefId, CRATE_DEF_ID};
 use rustc_hir::{Mutability, Safety};
+use rustc_middle::ty::print::{with_crate_prefix, with_no_trimmed_paths};
 use rustc_middle::ty::{Ty, TyCtxt};
 use rustc_middle::{bug, span_bug, ty};
 use rustc_resolve::rustdoc::{
@@ -32,7 +33,7 @@
 use crate::clean::{self, Crate, Item, ItemLink, PrimitiveType};
 use crate::core::DocContext;
 use crate::html::markdown::{markdown_links, MarkdownLink, MarkdownLinkRange};
-use crate::lint::{BROKEN_INTRA_DOC_LINKS, PRIVATE_INTRA_DOC_LINKS};
+use crate::lint::{BROKEN_INTRA_DOC_LINKS, PRIVATE_INTRA_DOC_LINKS, SHADOWED_INTRA_DOC_LINKS};
 use crate::passes::Pass;
 use crate::visit::DocVisitor;
 
@@ -1109,6 +1110,8 @@ fn resolve_link(
                     &diag_info,
                 )?;
 
+                self.check_reexport_shadowing(path_str, id, item_id, module_id, &diag_info);
+
                 let page_id = clean::register_res(self.cx, rustc_hir::def::Res::Def(kind, id));
                 Some(ItemLink {
                     link: Box::<str>::from(&*ori_link.link),
@@ -1120,6 +1123,69 @@ fn resolve_link(
         }
     }
 
+    /// When an item is inlined at a re-export, its docs are still resolved in the scope of its
+    /// original module. Check that the first segment of `path_str` doesn't refer to another item
+    /// in the module of the re-export, where readers will see the docs.
+    fn check_reexport_shadowing(
+        &self,
+        path_str: &str,
+        res_id: DefId,
+        item_id: DefId,
+        module_id: DefId,
+        diag_info: &DiagnosticInfo<'_>,
+    ) {
+        let tcx = self.cx.tcx;
+        // Docs written on the `use` itself are resolved in the scope of the re-export.
+        let Some(reexport_id) = diag_info.item.inline_stmt_id.filter(|&id| id != item_id) else {
+            return;
+        };
+        let Some(reexport_id) = reexport_id.as_local() else { return };
+        let reexport_module = find_nearest_parent_module(tcx, reexport_id.to_def_id()).unwrap();
+        if reexport_module == module_id {
+            return;
+        }
+
+        let mut segments = path_str.split("::");
+        let first = segments.next().unwrap();
+        if first.is_empty() || matches!(first, "crate" | "self" | "super" | "Self") {
+            return;
+        }
+        // With more segments, the first one is a module or a type, whose resolution has been
+        // cached if it was needed to resolve the link.
+        let (ns, original_id) = if segments.next().is_none() {
+            let Some(ns) = tcx.def_kind(res_id).ns() else { return };
+            (ns, res_id)
+        } else {
+            let Some(original_id) = tcx
+                .doc_link_resolutions(module_id)
+                .get(&(Symbol::intern(first), TypeNS))
+                .and_then(|&res| res?.opt_def_id())
+            else {
+                return;
+            };
+            (TypeNS, original_id)
+        };
+
+        let shadowing_id = tcx
+            .module_children_local(reexport_module.expect_local())
+            .iter()
+            .filter(|child| child.ident.name.as_str() == first && child.res.ns() == Some(ns))
+            .find_map(|child| child.res.opt_def_id());
+        if let Some(shadowing_id) = shadowing_id
+            && shadowing_id != original_id
+        {
+            shadowed_link_error(
+                self.cx,
+                diag_info,
+                path_str,
+                first,
+                original_id,
+                shadowing_id,
+                reexport_id,
+            );
+        }
+    }
+
     fn verify_disambiguator(
         &self,
         path_str: &str,
@@ -2174,6 +2240,64 @@ fn suggest_disambiguator(
     }
 }
 
+/// Report a link resolving to another item where the documented item is re-exported.
+fn shadowed_link_error(
+    cx: &DocContext<'_>,
+    diag_info: &DiagnosticInfo<'_>,
+    path_str: &str,
+    first_segment: &str,
+    original_id: DefId,
+    shadowing_id: DefId,
+    reexport_id: LocalDefId,
+) {
+    let tcx = cx.tcx;
+    let path_of =
+        |def_id: DefId| with_no_trimmed_paths!(with_crate_prefix!(tcx.def_path_str(def_id)));
+    let original_path = path_of(original_id);
+    let full_path = format!("{original_path}{}", &path_str[first_segment.len()..]);
+    let msg = format!("`{path_str}` resolves to a different item where this item is re-exported");
+    let shadowing_path = path_of(shadowing_id);
+    let note =
+        format!("`{first_segment}` refers to `{shadowing_path}` where this item is re-exported");
+    let help = format!("to link to `{original_path}` wherever the docs are shown, use its path");
+    let reexport_span = tcx.def_span(reexport_id);
+
+    if DocContext::as_local_hir_id(tcx, diag_info.item.item_id).is_none() {
+        // The docs come from another crate, so the re-export is the only place to report this.
+        let hir_id = tcx.local_def_id_to_hir_id(reexport_id);
+        tcx.node_span_lint(SHADOWED_INTRA_DOC_LINKS, hir_id, reexport_span, |lint| {
+            lint.primary_message(msg);
+            lint.span_label(reexport_span, note);
+            lint.note(format!("in the docs of the re-exported item, this is `{original_path}`"));
+            lint.help(format!("{help}: `{full_path}`"));
+        });
+        return;
+    }
+
+    report_diagnostic(tcx, SHADOWED_INTRA_DOC_LINKS, msg, diag_info, |diag, sp, link_range| {
+        if let Some(sp) = sp {
+            diag.span_label(sp, format!("`{first_segment}` is `{original_path}` here"));
+        }
+        diag.span_note(reexport_span, note);
+
+        // Skip a disambiguator prefix like `struct@` to find the path in the link.
+        let ori_link = match link_range {
+            MarkdownLinkRange::Destination(range) => Some(&diag_info.dox[range]),
+            MarkdownLinkRange::WholeLink(_) => None,
+        };
+        let offset = ori_link.map(|ori_link| ori_link.find('@').map_or(0, |at| at + 1));
+        if let (Some(sp), Some(ori_link), Some(offset)) = (sp, ori_link, offset)
+            && ori_link[offset..].starts_with(first_segment)
+        {
+            let lo = sp.lo() + BytePos(offset as u32);
+            let sp = sp.with_lo(lo).with_hi(lo + BytePos(first_segment.len() as u32));
+            diag.span_suggestion_verbose(sp, help, original_path, Applicability::MaybeIncorrect);
+        } else {
+            diag.help(format!("{help}: `{full_path}`"));
+        }
+    });
+}
+
 /// Report a link from a public item to a private one.
 fn privacy_error(cx: &DocContext<'_>, diag_info: &DiagnosticInfo<'_>, path_str: &str) {
     let sym;
@@ -22,7 +22,7 @@
 /// Strip items marked `#[doc(hidden)]`
 pub(crate) fn strip_hidden(krate: clean::Crate, cx: &mut DocContext<'_>) -> clean::Crate {
     let mut retained = ItemIdSet::default();
-    let is_json_output = cx.output_format.is_json() && !cx.show_coverage;
+    let is_json_output = cx.output_format.is_json_model() && !cx.show_coverage;
 
     // strip all #[doc(hidden)] items
     let krate = {
@@ -13,7 +13,7 @@
 };
 
 pub(crate) fn strip_priv_imports(krate: clean::Crate, cx: &mut DocContext<'_>) -> clean::Crate {
-    let is_json_output = cx.output_format.is_json() && !cx.show_coverage;
+    let is_json_output = cx.output_format.is_json_model() && !cx.show_coverage;
     ImportStripper {
         tcx: cx.tcx,
         is_json_output,
@@ -3,7 +3,7 @@
 use std::fs;
 use std::path::PathBuf;
 
-use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_errors::DiagCtxtHandle;
 use rustc_hir::intravisit::{self, Visitor};
 use rustc_hir::{self as hir};
@@ -17,8 +17,10 @@
 use rustc_session::getopts;
 use rustc_span::def_id::{CrateNum, DefPathHash, LOCAL_CRATE};
 use rustc_span::edition::Edition;
+use rustc_span::symbol::sym;
 use rustc_span::{BytePos, FileName, SourceFile};
 
+use crate::clean::utils::attrs_have_doc_flag;
 use crate::formats::renderer::FormatRenderer;
 use crate::html::render::Context;
 use crate::{clean, config, formats};
@@ -28,18 +30,43 @@ pub(crate) struct ScrapeExamplesOptions {
     output_path: PathBuf,
     target_crates: Vec<String>,
     pub(crate) scrape_tests: bool,
+    kind: ExampleKind,
+}
+
+/// Where the code that examples are scraped from comes from, in the order in which its examples
+/// are preferred.
+#[derive(Encodable, Decodable, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ExampleKind {
+    /// A crate in `examples/`.
+    Example,
+    /// An integration test in `tests/`.
+    Test,
+    /// A benchmark in `benches/`.
+    Bench,
 }
 
 impl ScrapeExamplesOptions {
     pub(crate) fn new(matches: &getopts::Matches, dcx: DiagCtxtHandle<'_>) -> Option<Self> {
         let output_path = matches.opt_str("scrape-examples-output-path");
         let target_crates = matches.opt_strs("scrape-examples-target-crate");
-        let scrape_tests = matches.opt_present("scrape-tests");
+        let kind = match matches.opt_str("scrape-examples-kind").as_deref() {
+            None => None,
+            Some("example") => Some(ExampleKind::Example),
+            Some("test") => Some(ExampleKind::Test),
+            Some("bench") => Some(ExampleKind::Bench),
+            Some(kind) => dcx.fatal(format!(
+                "unknown --scrape-examples-kind `{kind}`, expected `example`, `test` or `bench`"
+            )),
+        };
+        // Tests and benchmarks only exist when compiling with `--test`.
+        let scrape_tests = matches.opt_present("scrape-tests")
+            || matches!(kind, Some(ExampleKind::Test | ExampleKind::Bench));
         match (output_path, !target_crates.is_empty(), scrape_tests) {
             (Some(output_path), true, _) => Some(ScrapeExamplesOptions {
                 output_path: PathBuf::from(output_path),
                 target_crates,
                 scrape_tests,
+                kind: kind.unwrap_or(ExampleKind::Example),
             }),
             (Some(_), false, _) | (None, true, _) => {
                 dcx.fatal("must use --scrape-examples-output-path and --scrape-examples-target-crate together");
@@ -75,6 +102,8 @@ pub(crate) struct CallLocation {
     pub(crate) call_expr: SyntaxRange,
     pub(crate) call_ident: SyntaxRange,
     pub(crate) enclosing_item: SyntaxRange,
+    /// The shape of the arguments of the call, e.g. `lit, &path`. See [`arg_shape`].
+    pub(crate) arg_pattern: String,
 }
 
 impl CallLocation {
@@ -82,16 +111,41 @@ fn new(
         expr_span: rustc_span::Span,
         ident_span: rustc_span::Span,
         enclosing_item_span: rustc_span::Span,
+        args: &[hir::Expr<'_>],
         source_file: &SourceFile,
     ) -> Option<Self> {
         Some(CallLocation {
             call_expr: SyntaxRange::new(expr_span, source_file)?,
             call_ident: SyntaxRange::new(ident_span, source_file)?,
             enclosing_item: SyntaxRange::new(enclosing_item_span, source_file)?,
+            arg_pattern: args.iter().map(arg_shape).collect::<Vec<_>>().join(", "),
         })
     }
 }
 
+/// Describes what kind of expression is passed as an argument, so that examples passing different
+/// kinds of arguments to an item can be preferred over examples that all call it the same way.
+fn arg_shape(arg: &hir::Expr<'_>) -> String {
+    match arg.kind {
+        hir::ExprKind::Lit(_) => "lit".to_owned(),
+        hir::ExprKind::Unary(hir::UnOp::Neg, inner)
+            if matches!(inner.kind, hir::ExprKind::Lit(_)) =>
+        {
+            "lit".to_owned()
+        }
+        hir::ExprKind::Path(_) => "path".to_owned(),
+        hir::ExprKind::AddrOf(_, mutbl, inner) => {
+            format!("&{}{}", mutbl.prefix_str(), arg_shape(inner))
+        }
+        hir::ExprKind::Closure(_) => "closure".to_owned(),
+        hir::ExprKind::Call(..) | hir::ExprKind::MethodCall(..) => "call".to_owned(),
+        hir::ExprKind::Struct(..) => "struct".to_owned(),
+        hir::ExprKind::Array(_) | hir::ExprKind::Repeat(..) => "array".to_owned(),
+        hir::ExprKind::Tup(_) => "tuple".to_owned(),
+        _ => "expr".to_owned(),
+    }
+}
+
 #[derive(Encodable, Decodable, Debug, Clone)]
 pub(crate) struct CallData {
     pub(crate) locations: Vec<CallLocation>,
@@ -99,6 +153,7 @@ pub(crate) struct CallData {
     pub(crate) display_name: String,
     pub(crate) edition: Edition,
     pub(crate) is_bin: bool,
+    pub(crate) kind: ExampleKind,
 }
 
 pub(crate) type FnCallLocations = FxHashMap<PathBuf, CallData>;
@@ -112,6 +167,7 @@ struct FindCalls<'a, 'tcx> {
     target_crates: Vec<CrateNum>,
     calls: &'a mut AllCallLocations,
     bin_crate: bool,
+    kind: ExampleKind,
 }
 
 impl<'a, 'tcx> Visitor<'tcx> for FindCalls<'a, 'tcx>
@@ -138,18 +194,18 @@ fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
         }
 
         // Get type of function if expression is a function call
-        let (ty, call_span, ident_span) = match ex.kind {
-            hir::ExprKind::Call(f, _) => {
+        let (ty, call_span, ident_span, args) = match ex.kind {
+            hir::ExprKind::Call(f, args) => {
                 let types = tcx.typeck(ex.hir_id.owner.def_id);
 
                 if let Some(ty) = types.node_type_opt(f.hir_id) {
-                    (ty, ex.span, f.span)
+                    (ty, ex.span, f.span, args)
                 } else {
                     trace!("node_type_opt({}) = None", f.hir_id);
                     return;
                 }
             }
-            hir::ExprKind::MethodCall(path, _, _, call_span) => {
+            hir::ExprKind::MethodCall(path, _, args, call_span) => {
                 let types = tcx.typeck(ex.hir_id.owner.def_id);
                 let Some(def_id) = types.type_dependent_def_id(ex.hir_id) else {
                     trace!("type_dependent_def_id({}) = None", ex.hir_id);
@@ -157,7 +213,7 @@ fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
                 };
 
                 let ident_span = path.ident.span;
-                (tcx.type_of(def_id).instantiate_identity(), call_span, ident_span)
+                (tcx.type_of(def_id).instantiate_identity(), call_span, ident_span, args)
             }
             _ => {
                 return;
@@ -197,6 +253,16 @@ fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
             return;
         }
 
+        // Calls in items marked `#[doc(example_exclude)]`, or in their children, were opted out
+        // of being examples, e.g. because they exercise edge cases.
+        let excluded = hir.parent_owner_iter(ex.hir_id).any(|(owner, _)| {
+            attrs_have_doc_flag(hir.attrs(owner.into()).iter(), sym::example_exclude)
+        });
+        if excluded {
+            trace!("Rejecting expr from item with #[doc(example_exclude)]: {call_span:?}");
+            return;
+        }
+
         // Save call site if the function resolves to a concrete definition
         if let ty::FnDef(def_id, _) = ty.kind() {
             if self.target_crates.iter().all(|krate| *krate != def_id.krate) {
@@ -236,8 +302,9 @@ fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
                     let display_name = file_path.display().to_string();
                     let edition = call_span.edition();
                     let is_bin = self.bin_crate;
+                    let kind = self.kind;
 
-                    CallData { locations: Vec::new(), url, display_name, edition, is_bin }
+                    CallData { locations: Vec::new(), url, display_name, edition, is_bin, kind }
                 };
 
                 let fn_key = tcx.def_path_hash(*def_id);
@@ -246,14 +313,19 @@ fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
                 trace!("Including expr: {call_span:?}");
                 let enclosing_item_span =
                     source_map.span_extend_to_prev_char(enclosing_item_span, '\n', false);
-                let location =
-                    match CallLocation::new(call_span, ident_span, enclosing_item_span, &file) {
-                        Some(location) => location,
-                        None => {
-                            trace!("Could not get serializable call location for {call_span:?}");
-                            return;
-                        }
-                    };
+                let location = match CallLocation::new(
+                    call_span,
+                    ident_span,
+                    enclosing_item_span,
+                    args,
+                    &file,
+                ) {
+                    Some(location) => location,
+                    None => {
+                        trace!("Could not get serializable call location for {call_span:?}");
+                        return;
+                    }
+                };
                 fn_entries.entry(abs_path).or_insert_with(mk_call_data).locations.push(location);
             }
         }
@@ -293,8 +365,15 @@ pub(crate) fn run(
 
         // Run call-finder on all items
         let mut calls = FxHashMap::default();
-        let mut finder =
-            FindCalls { calls: &mut calls, tcx, map: tcx.hir(), cx, target_crates, bin_crate };
+        let mut finder = FindCalls {
+            calls: &mut calls,
+            tcx,
+            map: tcx.hir(),
+            cx,
+            target_crates,
+            bin_crate,
+            kind: options.kind,
+        };
         tcx.hir().visit_all_item_likes_in_crate(&mut finder);
 
         // The visitor might have found a type error, which we need to
@@ -325,6 +404,44 @@ pub(crate) fn run(
     Ok(())
 }
 
+/// Orders the files that call an item by how good they are as examples of the item, best first.
+///
+/// Ideally the most "relevant" examples would be shown first, but there's no general algorithm
+/// for determining relevance. We instead proxy relevance with the following heuristics:
+///   1. Code written to be an example is better than tests, which are better than benchmarks.
+///      Among code of the same kind, binaries are preferred, e.g. a snippet from examples/foo.rs
+///      is better than src/lib.rs.
+///   2. Smaller examples are better than large examples. So we prioritize snippets that have
+///      the smallest number of lines in their enclosing item.
+///   3. Finally we sort by the displayed file name, which is arbitrary but prevents the
+///      ordering of examples from randomly changing between Rustdoc invocations.
+///
+/// Then, so that the first few examples show different ways of using the item, files that call it
+/// with a shape of arguments that wasn't shown yet are moved before the files that don't.
+pub(crate) fn rank_examples(call_locations: &FnCallLocations) -> Vec<(&PathBuf, &CallData)> {
+    let mut remaining = call_locations.iter().collect::<Vec<_>>();
+    remaining.sort_by_key(|&(_, call_data)| {
+        // Use the first location because that's what the user will see initially
+        let (lo, hi) = call_data.locations[0].enclosing_item.byte_span;
+        (call_data.kind, !call_data.is_bin, hi - lo, &call_data.display_name)
+    });
+
+    let mut seen_patterns = FxHashSet::default();
+    let mut ranked = Vec::with_capacity(remaining.len());
+    while !remaining.is_empty() {
+        let next = remaining
+            .iter()
+            .position(|(_, call_data)| {
+                call_data.locations.iter().any(|loc| !seen_patterns.contains(&loc.arg_pattern))
+            })
+            .unwrap_or(0);
+        let (path, call_data) = remaining.remove(next);
+        seen_patterns.extend(call_data.locations.iter().map(|loc| &loc.arg_pattern));
+        ranked.push((path, call_data));
+    }
+    ranked
+}
+
 // Note: the DiagCtxt must be passed in explicitly because sess isn't available while parsing
 // options.
 pub(crate) fn load_call_locations(
@@ -0,0 +1,322 @@
+//! The declarations of the items shown in the Markdown and man page output, printed from the
+//! JSON model.
+
+use rustdoc_json_types::{
+    Crate, Function, Id, Impl, Item, ItemEnum, MacroKind, StructKind, Variant, VariantKind,
+    Visibility,
+};
+
+use crate::api_diff::signature::Printer;
+
+/// Prints the declaration of `item`, which is named `name` where it is documented, as Rust
+/// source code. The attributes shown by rustdoc come first, on their own lines.
+pub(super) fn declaration(krate: &Crate, name: &str, item: &Item) -> String {
+    let mut p = Printer::new(krate, false);
+    for attr in &item.attrs {
+        p.str(attr).str("\n");
+    }
+    if !matches!(item.inner, ItemEnum::Macro(_) | ItemEnum::Variant(_) | ItemEnum::Impl(_)) {
+        visibility(&mut p, &item.visibility);
+    }
+    match &item.inner {
+        ItemEnum::Module(_) => {
+            p.str("mod ").str(name);
+        }
+        ItemEnum::ExternCrate { name: krate_name, rename } => {
+            p.str("extern crate ").str(krate_name);
+            if rename.is_some() {
+                p.str(" as ").str(name);
+            }
+            p.str(";");
+        }
+        ItemEnum::Import(import) => {
+            p.str("use ").str(&import.source);
+            if import.glob {
+                p.str("::*");
+            } else if import.source != import.name
+                && !import.source.ends_with(&format!("::{}", import.name))
+            {
+                p.str(" as ").str(&import.name);
+            }
+            p.str(";");
+        }
+        ItemEnum::Struct(struct_) => {
+            p.str("struct ").str(name).generic_params(&struct_.generics);
+            match &struct_.kind {
+                StructKind::Unit => {
+                    p.where_clause(&struct_.generics).str(";");
+                }
+                StructKind::Tuple(fields) => {
+                    p.str("(");
+                    for (i, field) in fields.iter().enumerate() {
+                        if i != 0 {
+                            p.str(", ");
+                        }
+                        match field.as_ref().and_then(|id| krate.index.get(id)) {
+                            Some(field @ Item { inner: ItemEnum::StructField(ty), .. }) => {
+                                visibility(&mut p, &field.visibility).ty(ty);
+                            }
+                            _ => {
+                                p.str("_");
+                            }
+                        }
+                    }
+                    p.str(")").where_clause(&struct_.generics).str(";");
+                }
+                StructKind::Plain { fields, fields_stripped } => {
+                    p.where_clause(&struct_.generics);
+                    fields_block(&mut p, krate, fields, *fields_stripped);
+                }
+            }
+        }
+        ItemEnum::Union(union) => {
+            p.str("union ").str(name).generic_params(&union.generics).where_clause(&union.generics);
+            fields_block(&mut p, krate, &union.fields, union.fields_stripped);
+        }
+        ItemEnum::Enum(enum_) => {
+            p.str("enum ").str(name).generic_params(&enum_.generics).where_clause(&enum_.generics);
+            let variants: Vec<_> = enum_
+                .variants
+                .iter()
+                .filter_map(|id| krate.index.get(id))
+                .filter_map(|item| match &item.inner {
+                    ItemEnum::Variant(variant) => Some((item.name.as_deref()?, variant)),
+                    _ => None,
+                })
+                .collect();
+            if variants.is_empty() && !enum_.variants_stripped {
+                p.str(" {}");
+            } else {
+                p.str(" {\n");
+                for (name, variant) in variants {
+                    p.str("    ");
+                    variant_declaration(&mut p, krate, name, variant);
+                    p.str(",\n");
+                }
+                if enum_.variants_stripped {
+                    p.str("    // some variants omitted\n");
+                }
+                p.str("}");
+            }
+        }
+        ItemEnum::Variant(variant) => {
+            variant_declaration(&mut p, krate, name, variant);
+        }
+        ItemEnum::StructField(ty) => {
+            p.str(name).str(": ").ty(ty);
+        }
+        ItemEnum::Function(function) => {
+            function_declaration(&mut p, name, function);
+        }
+        ItemEnum::Trait(trait_) => {
+            if trait_.is_unsafe {
+                p.str("unsafe ");
+            }
+            if trait_.is_auto {
+                p.str("auto ");
+            }
+            p.str("trait ").str(name).generic_params(&trait_.generics);
+            if !trait_.bounds.is_empty() {
+                p.str(": ").bounds(&trait_.bounds);
+            }
+            p.where_clause(&trait_.generics);
+            let items: Vec<_> = trait_.items.iter().filter_map(|id| krate.index.get(id)).collect();
+            if items.is_empty() {
+                p.str(" {}");
+            } else {
+                p.str(" {\n");
+                for item in items {
+                    if let Some(line) = trait_item_declaration(krate, item) {
+                        p.str("    ").str(&line).str("\n");
+                    }
+                }
+                p.str("}");
+            }
+        }
+        ItemEnum::TraitAlias(alias) => {
+            p.str("trait ")
+                .str(name)
+                .generic_params(&alias.generics)
+                .str(" = ")
+                .bounds(&alias.params)
+                .where_clause(&alias.generics)
+                .str(";");
+        }
+        ItemEnum::Impl(impl_) => {
+            impl_header(&mut p, impl_);
+        }
+        ItemEnum::TypeAlias(alias) => {
+            p.str("type ")
+                .str(name)
+                .generic_params(&alias.generics)
+                .where_clause(&alias.generics)
+                .str(" = ")
+                .ty(&alias.type_)
+                .str(";");
+        }
+        ItemEnum::Constant { type_, const_ } => {
+            p.str("const ").str(name).str(": ").ty(type_).str(" = ").str(&const_.expr).str(";");
+        }
+        ItemEnum::Static(static_) => {
+            p.str(if static_.mutable { "static mut " } else { "static " })
+                .str(name)
+                .str(": ")
+                .ty(&static_.type_)
+                .str(";");
+        }
+        ItemEnum::ForeignType => {
+            p.str("type ").str(name).str(";");
+        }
+        ItemEnum::Macro(source) => {
+            p.str(source);
+        }
+        ItemEnum::ProcMacro(proc_macro) => match proc_macro.kind {
+            MacroKind::Bang => {
+                p.str(name).str("!() { /* proc-macro */ }");
+            }
+            MacroKind::Attr => {
+                p.str("#[").str(name).str("]");
+            }
+            MacroKind::Derive => {
+                p.str("#[derive(").str(name).str(")]");
+                for helper in &proc_macro.helpers {
+                    p.str("\n// helper attribute: #[").str(helper).str("]");
+                }
+            }
+        },
+        ItemEnum::Primitive(primitive) => {
+            p.str(&primitive.name);
+        }
+        ItemEnum::AssocConst { type_, default } => {
+            p.str("const ").str(name).str(": ").ty(type_);
+            if let Some(default) = default {
+                p.str(" = ").str(default);
+            }
+            p.str(";");
+        }
+        ItemEnum::AssocType { generics, bounds, default } => {
+            p.str("type ").str(name).generic_params(generics);
+            if !bounds.is_empty() {
+                p.str(": ").bounds(bounds);
+            }
+            p.where_clause(generics);
+            if let Some(default) = default {
+                p.str(" = ").ty(default);
+            }
+            p.str(";");
+        }
+    }
+    p.finish()
+}
+
+/// Prints the header of an impl block, e.g. `impl<T: Clone> Clone for Wrapper<T>`.
+pub(super) fn impl_declaration(krate: &Crate, impl_: &Impl) -> String {
+    let mut p = Printer::new(krate, false);
+    impl_header(&mut p, impl_);
+    p.finish()
+}
+
+fn impl_header(p: &mut Printer<'_>, impl_: &Impl) {
+    if impl_.is_unsafe {
+        p.str("unsafe ");
+    }
+    p.str("impl").generic_params(&impl_.generics).str(" ");
+    if let Some(trait_) = &impl_.trait_ {
+        if impl_.negative {
+            p.str("!");
+        }
+        p.path(trait_).str(" for ");
+    }
+    p.ty(&impl_.for_).where_clause(&impl_.generics);
+}
+
+/// Prints an item of a trait the way it appears in the declaration of the trait.
+fn trait_item_declaration(krate: &Crate, item: &Item) -> Option<String> {
+    let name = item.name.as_deref()?;
+    let mut p = Printer::new(krate, false);
+    match &item.inner {
+        ItemEnum::Function(function) => {
+            function_declaration(&mut p, name, function);
+            p.str(if function.has_body { " { ... }" } else { ";" });
+        }
+        ItemEnum::AssocConst { .. } | ItemEnum::AssocType { .. } => {
+            return Some(declaration(krate, name, item));
+        }
+        _ => return None,
+    }
+    Some(p.finish())
+}
+
+fn function_declaration(p: &mut Printer<'_>, name: &str, function: &Function) {
+    p.header(&function.header)
+        .str("fn ")
+        .str(name)
+        .generic_params(&function.generics)
+        .fn_decl(&function.decl)
+        .where_clause(&function.generics);
+}
+
+fn variant_declaration(p: &mut Printer<'_>, krate: &Crate, name: &str, variant: &Variant) {
+    p.str(name);
+    match &variant.kind {
+        VariantKind::Plain => {}
+        VariantKind::Tuple(fields) => {
+            p.str("(");
+            for (i, field) in fields.iter().enumerate() {
+                if i != 0 {
+                    p.str(", ");
+                }
+                match field.as_ref().and_then(|id| krate.index.get(id)) {
+                    Some(Item { inner: ItemEnum::StructField(ty), .. }) => {
+                        p.ty(ty);
+                    }
+                    _ => {
+                        p.str("_");
+                    }
+                }
+            }
+            p.str(")");
+        }
+        VariantKind::Struct { fields, fields_stripped } => {
+            p.str(" { ");
+            for field in fields.iter().filter_map(|id| krate.index.get(id)) {
+                if let (Some(name), ItemEnum::StructField(ty)) = (&field.name, &field.inner) {
+                    p.str(name).str(": ").ty(ty).str(", ");
+                }
+            }
+            p.str(if *fields_stripped { ".. }" } else { "}" });
+        }
+    }
+    if let Some(discriminant) = &variant.discriminant {
+        p.str(" = ").str(&discriminant.expr);
+    }
+}
+
+/// Prints the named fields of a struct or union, one per line.
+fn fields_block(p: &mut Printer<'_>, krate: &Crate, fields: &[Id], fields_stripped: bool) {
+    let fields: Vec<_> = fields.iter().filter_map(|id| krate.index.get(id)).collect();
+    if fields.is_empty() {
+        p.str(if fields_stripped { " { /* private fields */ }" } else { " {}" });
+        return;
+    }
+    p.str(" {\n");
+    for field in fields {
+        if let (Some(name), ItemEnum::StructField(ty)) = (&field.name, &field.inner) {
+            p.str("    ");
+            visibility(p, &field.visibility).str(name).str(": ").ty(ty).str(",\n");
+        }
+    }
+    if fields_stripped {
+        p.str("    /* private fields */\n");
+    }
+    p.str("}");
+}
+
+fn visibility<'p, 'a>(p: &'p mut Printer<'a>, visibility: &Visibility) -> &'p mut Printer<'a> {
+    match visibility {
+        Visibility::Public => p.str("pub "),
+        Visibility::Default => p,
+        Visibility::Crate => p.str("pub(crate) "),
+        Visibility::Restricted { path, .. } => p.str("pub(in ").str(path).str(") "),
+    }
+}
@@ -0,0 +1,285 @@
+//! The man page backend, which writes one page per module to the `man3` directory, named after
+//! the path of the module like the pages of Perl modules (e.g. `man3/std::collections.3`).
+
+use std::fmt::Write;
+use std::fs::{self, create_dir_all};
+use std::path::Path;
+
+use pulldown_cmark::{BrokenLink, CodeBlockKind, Event, Parser, Tag, TagEnd};
+use rustdoc_json_types::{Item, ItemEnum};
+
+use super::declaration::{declaration, impl_declaration};
+use super::{CrateDocs, ModulePage, Section, TextFormat};
+use crate::docfs::PathError;
+use crate::error::Error;
+use crate::html::markdown::{is_rust_code_block, shown_code_lines, summary_opts};
+use crate::try_err;
+
+pub(crate) struct ManFormat;
+
+impl TextFormat for ManFormat {
+    fn descr() -> &'static str {
+        "man"
+    }
+
+    fn write(docs: &CrateDocs<'_>, out_dir: &Path) -> Result<(), Error> {
+        let dir = out_dir.join("man3");
+        try_err!(create_dir_all(&dir), &dir);
+        for page in &docs.pages {
+            let file = dir.join(format!("{}.3", page.path.join("::")));
+            try_err!(fs::write(&file, render_page(docs, page)), &file);
+        }
+        Ok(())
+    }
+}
+
+fn render_page(docs: &CrateDocs<'_>, page: &ModulePage<'_>) -> String {
+    let krate = docs.krate;
+    let name = page.path.join("::");
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        ".TH \"{}\" 3 \"\" \"{}\" \"Rust Documentation\"",
+        escape(&name),
+        page.path[0]
+    );
+    out.push_str(".SH NAME\n");
+    push_text(&mut out, &name);
+    let summary = docs.summary(page.module);
+    if !summary.is_empty() {
+        out.push_str(" \\- ");
+        push_text(&mut out, &summary);
+    }
+    out.push('\n');
+    if page.module.docs.as_deref().is_some_and(|docs| !docs.trim().is_empty()) {
+        out.push_str(".SH DESCRIPTION\n");
+        item_docs(&mut out, page.module);
+    }
+
+    if !page.reexports.is_empty() {
+        out.push_str(".SH RE-EXPORTS\n");
+        let reexports: Vec<_> = page
+            .reexports
+            .iter()
+            .map(|item| declaration(krate, item.name.as_deref().unwrap_or_default(), item))
+            .collect();
+        code_block(&mut out, &reexports.join("\n"));
+    }
+
+    let mut section = None;
+    let mut see_also = Vec::new();
+    for item in &page.items {
+        if section != Some(item.section) {
+            section = Some(item.section);
+            let _ = writeln!(out, ".SH {}", item.section.title().to_uppercase());
+        }
+        out.push_str(".SS ");
+        push_text(&mut out, item.name);
+        out.push('\n');
+        match docs.location(&item.item.id) {
+            // Modules only get a summary here, their docs are on their own page.
+            Some(location) if location.anchor.is_none() => {
+                let path = docs.pages[location.page].path.join("::");
+                push_text(&mut out, &docs.summary(item.item));
+                let _ = writeln!(out, "\nSee \\fB{}\\fP(3).", escape(&path));
+                see_also.push(path);
+                continue;
+            }
+            _ => {}
+        }
+        code_block(&mut out, &declaration(krate, item.name, item.item));
+        item_docs(&mut out, item.item);
+
+        for group in &item.members {
+            let _ = writeln!(out, ".PP\n.B {}\n.RS 4", group.title);
+            for member in &group.members {
+                out.push_str(".PP\n.B ");
+                push_text(&mut out, member.name);
+                out.push('\n');
+                code_block(&mut out, &declaration(krate, member.name, member.item));
+                item_docs(&mut out, member.item);
+            }
+            out.push_str(".RE\n");
+        }
+        if !item.impls.is_empty() {
+            let title = if item.section == Section::Traits {
+                "Implementors"
+            } else {
+                "Trait Implementations"
+            };
+            let _ = writeln!(out, ".PP\n.B {title}");
+            let impls: Vec<_> = item
+                .impls
+                .iter()
+                .filter_map(|item| match &item.inner {
+                    ItemEnum::Impl(impl_) => Some(impl_declaration(krate, impl_)),
+                    _ => None,
+                })
+                .collect();
+            code_block(&mut out, &impls.join("\n"));
+        }
+    }
+
+    if page.path.len() > 1 {
+        see_also.push(page.path[..page.path.len() - 1].join("::"));
+    }
+    if !see_also.is_empty() {
+        out.push_str(".SH SEE ALSO\n");
+        let pages: Vec<_> =
+            see_also.iter().map(|path| format!("\\fB{}\\fP(3)", escape(path))).collect();
+        out.push_str(&pages.join(",\n"));
+        out.push('\n');
+    }
+    out
+}
+
+fn code_block(out: &mut String, code: &str) {
+    out.push_str(".PP\n.RS 4\n.nf\n");
+    for line in code.lines() {
+        push_text(out, line);
+        out.push('\n');
+    }
+    out.push_str(".fi\n.RE\n");
+}
+
+/// Writes the deprecation notice and the docs of `item`.
+fn item_docs(out: &mut String, item: &Item) {
+    if let Some(deprecation) = &item.deprecation {
+        out.push_str(".PP\n\\fBDeprecated\\fP");
+        if let Some(since) = &deprecation.since {
+            push_text(out, &format!(" since {since}"));
+        }
+        if let Some(note) = &deprecation.note {
+            push_text(out, &format!(": {note}"));
+        }
+        out.push('\n');
+    }
+    if let Some(docs) = &item.docs {
+        let links: Vec<_> = item.links.keys().map(String::as_str).collect();
+        out.push_str(&markdown_to_roff(docs, &links));
+    }
+}
+
+/// Converts the Markdown of a doc comment to roff. Roff has no links, so only the text of the
+/// intra-doc links in `links` is kept.
+pub(super) fn markdown_to_roff(md: &str, links: &[&str]) -> String {
+    let mut out = String::with_capacity(md.len());
+    let mut replacer = |broken_link: BrokenLink<'_>| {
+        links.contains(&&*broken_link.reference).then(|| (broken_link.reference.clone(), "".into()))
+    };
+    let parser = Parser::new_with_broken_link_callback(md, summary_opts(), Some(&mut replacer));
+
+    // The numbers of the items of the lists we are in, `None` for bullet lists.
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut item_start = false;
+    // The text of the code block we are in, and whether it is Rust code.
+    let mut in_code_block: Option<(String, bool)> = None;
+    for event in parser {
+        if let Some((code, rust)) = &mut in_code_block {
+            match event {
+                Event::Text(text) => code.push_str(&text),
+                Event::End(TagEnd::CodeBlock) => {
+                    request(&mut out, ".PP\n.RS 4\n.nf");
+                    if *rust {
+                        for line in shown_code_lines(code) {
+                            push_text(&mut out, &line);
+                            out.push('\n');
+                        }
+                    } else {
+                        for line in code.lines() {
+                            push_text(&mut out, line);
+                            out.push('\n');
+                        }
+                    }
+                    out.push_str(".fi\n.RE\n");
+                    in_code_block = None;
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match event {
+            Event::Start(Tag::Paragraph) => {
+                if !item_start {
+                    request(&mut out, if lists.is_empty() { ".PP" } else { ".IP \"\" 4" });
+                }
+                item_start = false;
+            }
+            Event::End(TagEnd::Paragraph) => out.push('\n'),
+            Event::Start(Tag::Heading { .. }) => request(&mut out, ".PP\n.B"),
+            Event::End(TagEnd::Heading(_)) => out.push('\n'),
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let rust = match kind {
+                    CodeBlockKind::Fenced(info) => is_rust_code_block(&info),
+                    CodeBlockKind::Indented => true,
+                };
+                in_code_block = Some((String::new(), rust));
+            }
+            Event::Start(Tag::List(start)) => lists.push(start),
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+            }
+            Event::End(TagEnd::Item) => item_start = false,
+            Event::Start(Tag::Item) => {
+                match lists.last_mut() {
+                    Some(Some(number)) => {
+                        request(&mut out, &format!(".IP {number}. 4"));
+                        *number += 1;
+                    }
+                    _ => request(&mut out, ".IP \\(bu 4"),
+                }
+                item_start = true;
+            }
+            Event::Start(Tag::BlockQuote(_)) => request(&mut out, ".RS 4"),
+            Event::End(TagEnd::BlockQuote) => request(&mut out, ".RE"),
+            Event::Start(Tag::Emphasis) => out.push_str("\\fI"),
+            Event::Start(Tag::Strong) => out.push_str("\\fB"),
+            Event::End(TagEnd::Emphasis | TagEnd::Strong) => out.push_str("\\fP"),
+            Event::Code(code) => {
+                out.push_str("\\fB");
+                push_text(&mut out, &code);
+                out.push_str("\\fP");
+            }
+            Event::Text(text) => push_text(&mut out, &text),
+            Event::SoftBreak => out.push('\n'),
+            Event::HardBreak => request(&mut out, ".br"),
+            Event::End(TagEnd::TableCell) => out.push(' '),
+            Event::End(TagEnd::TableRow | TagEnd::TableHead) => request(&mut out, ".br"),
+            _ => {}
+        }
+    }
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+/// Starts a request (or macro) such as `.PP` on a new line.
+fn request(out: &mut String, request: &str) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(request);
+    out.push('\n');
+}
+
+/// Writes text, escaping the characters that have a meaning in roff.
+fn push_text(out: &mut String, text: &str) {
+    for c in text.chars() {
+        if (c == '.' || c == '\'') && (out.is_empty() || out.ends_with('\n')) {
+            // A control character at the start of a line would start a request.
+            out.push_str("\\&");
+        }
+        match c {
+            '\\' => out.push_str("\\e"),
+            '-' => out.push_str("\\-"),
+            c => out.push(c),
+        }
+    }
+}
+
+pub(super) fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    push_text(&mut out, text);
+    out
+}
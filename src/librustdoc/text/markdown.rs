@@ -0,0 +1,286 @@
+//! The Markdown backend, which writes one `index.md` file per module, in the same directories as
+//! the HTML output.
+
+use std::fmt::Write;
+use std::fs::{self, create_dir_all};
+use std::path::Path;
+
+use rustc_data_structures::fx::FxHashSet;
+use rustdoc_json_types::{Id, Item, ItemEnum};
+
+use super::declaration::{declaration, impl_declaration};
+use super::{CrateDocs, ModulePage, Section, TextFormat};
+use crate::docfs::PathError;
+use crate::error::Error;
+use crate::html::markdown::{is_rust_code_block, shown_code_lines};
+use crate::try_err;
+
+pub(crate) struct MarkdownFormat;
+
+impl TextFormat for MarkdownFormat {
+    fn descr() -> &'static str {
+        "markdown"
+    }
+
+    fn write(docs: &CrateDocs<'_>, out_dir: &Path) -> Result<(), Error> {
+        for page in &docs.pages {
+            let mut dir = out_dir.to_path_buf();
+            dir.extend(&page.path);
+            try_err!(create_dir_all(&dir), &dir);
+            let file = dir.join("index.md");
+            try_err!(fs::write(&file, render_page(docs, page)), &file);
+        }
+        Ok(())
+    }
+}
+
+fn render_page(docs: &CrateDocs<'_>, page: &ModulePage<'_>) -> String {
+    let krate = docs.krate;
+    let mut out = String::new();
+    let kind = if page.path.len() == 1 { "Crate" } else { "Module" };
+    let _ = writeln!(out, "# {kind} `{}`\n", page.path.join("::"));
+    item_docs(&mut out, docs, page, page.module, 1);
+
+    if !page.reexports.is_empty() {
+        out.push_str("## Re-exports\n\n```rust\n");
+        for item in &page.reexports {
+            let name = item.name.as_deref().unwrap_or_default();
+            let _ = writeln!(out, "{}", declaration(krate, name, item));
+        }
+        out.push_str("```\n\n");
+    }
+
+    let mut section = None;
+    for item in &page.items {
+        if section != Some(item.section) {
+            section = Some(item.section);
+            let _ = writeln!(out, "## {}\n", item.section.title());
+        }
+        let _ = writeln!(out, "<a id=\"{}\"></a>", item.anchor);
+        match docs.location(&item.item.id) {
+            // Modules only get a summary here, their docs are on their own page.
+            Some(location) if location.anchor.is_none() => {
+                let url = relative_url(&page.path, &docs.pages[location.page].path);
+                let _ = writeln!(out, "### [`{}`]({url})\n", item.name);
+                let summary = docs.summary(item.item);
+                if !summary.is_empty() {
+                    let _ = writeln!(out, "{summary}\n");
+                }
+                continue;
+            }
+            _ => {
+                let _ = writeln!(out, "### `{}`\n", item.name);
+            }
+        }
+        code_block(&mut out, &declaration(krate, item.name, item.item));
+        item_docs(&mut out, docs, page, item.item, 3);
+
+        for group in &item.members {
+            let _ = writeln!(out, "#### {}\n", group.title);
+            for member in &group.members {
+                let _ = writeln!(out, "<a id=\"{}\"></a>", member.anchor);
+                let _ = writeln!(out, "##### `{}`\n", member.name);
+                code_block(&mut out, &declaration(krate, member.name, member.item));
+                item_docs(&mut out, docs, page, member.item, 5);
+            }
+        }
+        if !item.impls.is_empty() {
+            let title = if item.section == Section::Traits {
+                "Implementors"
+            } else {
+                "Trait Implementations"
+            };
+            let _ = writeln!(out, "#### {title}\n");
+            for impl_ in &item.impls {
+                if let ItemEnum::Impl(impl_) = &impl_.inner {
+                    let _ = writeln!(out, "- `{}`", impl_declaration(krate, impl_));
+                }
+            }
+            out.push('\n');
+        }
+    }
+    let len = out.trim_end().len();
+    out.truncate(len);
+    out.push('\n');
+    out
+}
+
+fn code_block(out: &mut String, code: &str) {
+    let _ = writeln!(out, "```rust\n{code}\n```\n");
+}
+
+/// Writes the deprecation notice and the docs of `item`, below a heading of level `level`.
+fn item_docs(
+    out: &mut String,
+    docs: &CrateDocs<'_>,
+    page: &ModulePage<'_>,
+    item: &Item,
+    level: usize,
+) {
+    if let Some(deprecation) = &item.deprecation {
+        out.push_str("> **Deprecated**");
+        if let Some(since) = &deprecation.since {
+            let _ = write!(out, " since {since}");
+        }
+        if let Some(note) = &deprecation.note {
+            let _ = write!(out, ": {note}");
+        }
+        out.push_str("\n\n");
+    }
+    let Some(text) = item.docs.as_deref().filter(|text| !text.trim().is_empty()) else { return };
+    let mut links: Vec<_> = item
+        .links
+        .iter()
+        .filter_map(|(key, id)| Some((key.as_str(), link_url(docs, &page.path, id)?)))
+        .collect();
+    links.sort();
+    out.push_str(&rewrite_docs(text, level, &links));
+    out.push_str("\n\n");
+}
+
+/// Returns the URL of the docs of `id`, relative to the page of the module at `from`.
+fn link_url(docs: &CrateDocs<'_>, from: &[&str], id: &Id) -> Option<String> {
+    let Some(location) = docs.location(id) else { return docs.external_url(id) };
+    let mut url = relative_url(from, &docs.pages[location.page].path);
+    if let Some(anchor) = &location.anchor {
+        url.push('#');
+        url.push_str(anchor);
+    }
+    Some(url)
+}
+
+/// Returns the URL of the page of the module at `to`, relative to the page of the module at
+/// `from`.
+pub(super) fn relative_url(from: &[&str], to: &[&str]) -> String {
+    let common = from.iter().zip(to).take_while(|(a, b)| a == b).count();
+    let mut url = "../".repeat(from.len() - common);
+    for segment in &to[common..] {
+        url.push_str(segment);
+        url.push('/');
+    }
+    url.push_str("index.md");
+    url
+}
+
+/// Adapts the Markdown of a doc comment for a page where it is shown below a heading of level
+/// `level`:
+///
+/// - Headings are moved down by `level` levels.
+/// - The hidden lines of Rust code blocks are removed, and the code blocks are marked as `rust`.
+/// - Intra-doc links are replaced with the URLs in `links`, either in place or by adding
+///   reference definitions at the end.
+pub(super) fn rewrite_docs(md: &str, level: usize, links: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(md.len());
+    // The fence of the code block we are in, and whether it is Rust code.
+    let mut fence: Option<(&str, bool)> = None;
+    let mut code = String::new();
+    let mut replaced = FxHashSet::default();
+    let mut defined = FxHashSet::default();
+
+    for line in md.lines() {
+        let trimmed = line.trim_start();
+        if let Some((marker, rust)) = fence {
+            if trimmed.starts_with(marker)
+                && trimmed.trim_end().trim_start_matches(marker.as_bytes()[0] as char).is_empty()
+            {
+                if rust {
+                    for line in shown_code_lines(&code) {
+                        out.push_str(&line);
+                        out.push('\n');
+                    }
+                } else {
+                    out.push_str(&code);
+                }
+                out.push_str(line);
+                out.push('\n');
+                code.clear();
+                fence = None;
+            } else {
+                code.push_str(line);
+                code.push('\n');
+            }
+            continue;
+        }
+
+        if let Some(marker) = code_fence(trimmed) {
+            let info = trimmed[marker.len()..].trim();
+            let rust = is_rust_code_block(info);
+            fence = Some((marker, rust));
+            if rust {
+                let indent = &line[..line.len() - trimmed.len()];
+                let _ = writeln!(out, "{indent}{marker}rust");
+            } else {
+                out.push_str(line);
+                out.push('\n');
+            }
+        } else if let Some(heading) = shifted_heading(line, level) {
+            out.push_str(&heading);
+            out.push('\n');
+        } else if let Some((label, dest)) = reference_definition(trimmed) {
+            defined.insert(label.to_lowercase());
+            match links.iter().find(|(key, _)| *key == dest) {
+                Some((key, url)) => {
+                    replaced.insert(*key);
+                    let _ = writeln!(out, "[{label}]: {url}");
+                }
+                None => {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+            }
+        } else {
+            let mut line = line.to_owned();
+            for (key, url) in links {
+                let inline = format!("]({key})");
+                if line.contains(&inline) {
+                    replaced.insert(*key);
+                    line = line.replace(&inline, &format!("]({url})"));
+                }
+            }
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    // An unclosed code block runs until the end of the docs.
+    out.push_str(&code);
+
+    let mut definitions = links
+        .iter()
+        .filter(|(key, _)| !replaced.contains(key) && !defined.contains(&key.to_lowercase()))
+        .peekable();
+    if definitions.peek().is_some() {
+        out.push('\n');
+        for (key, url) in definitions {
+            let _ = writeln!(out, "[{key}]: {url}");
+        }
+    }
+    let len = out.trim_end().len();
+    out.truncate(len);
+    out
+}
+
+/// Returns the fence that opens a code block on this line, if any.
+fn code_fence(trimmed: &str) -> Option<&str> {
+    let c = trimmed.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let len = trimmed.len() - trimmed.trim_start_matches(c).len();
+    (len >= 3).then(|| &trimmed[..len])
+}
+
+/// Returns the line of a heading moved down by `level` levels. Headings below level 6 don't exist
+/// in Markdown, so they stay at level 6.
+fn shifted_heading(line: &str, level: usize) -> Option<String> {
+    let text = line.trim_start_matches('#');
+    let depth = line.len() - text.len();
+    if depth == 0 || depth > 6 || !(text.is_empty() || text.starts_with(' ')) {
+        return None;
+    }
+    Some(format!("{}{text}", "#".repeat((depth + level).min(6))))
+}
+
+/// Parses a link reference definition such as `[label]: destination`.
+fn reference_definition(trimmed: &str) -> Option<(&str, &str)> {
+    let (label, rest) = trimmed.strip_prefix('[')?.split_once("]:")?;
+    let dest = rest.trim();
+    let dest = dest.split_once(' ').map_or(dest, |(dest, _title)| dest);
+    (!label.is_empty() && !dest.is_empty()).then_some((label, dest))
+}
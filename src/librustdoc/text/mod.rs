@@ -0,0 +1,489 @@
+//! The Markdown and man page backends.
+//!
+//! Both are built on top of the JSON backend: the crate is rendered to the JSON model first, which
+//! is then split into one page per module by [`CrateDocs`] and written out by a [`TextFormat`].
+
+mod declaration;
+mod man;
+mod markdown;
+#[cfg(test)]
+mod tests;
+
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::ty::TyCtxt;
+use rustdoc_json_types::{Crate, Id, Item, ItemEnum, ItemKind, MacroKind, StructKind};
+
+pub(crate) use self::man::ManFormat;
+pub(crate) use self::markdown::MarkdownFormat;
+use crate::clean;
+use crate::clean::types::RenderedLink;
+use crate::config::RenderOptions;
+use crate::error::Error;
+use crate::formats::cache::Cache;
+use crate::formats::FormatRenderer;
+use crate::html::markdown::plain_text_summary;
+use crate::json::JsonRenderer;
+
+/// A way of writing the documentation of a crate to disk.
+pub(crate) trait TextFormat {
+    fn descr() -> &'static str;
+
+    fn write(docs: &CrateDocs<'_>, out_dir: &Path) -> Result<(), Error>;
+}
+
+pub(crate) struct TextRenderer<'tcx, F> {
+    json: JsonRenderer<'tcx>,
+    out_path: PathBuf,
+    format: PhantomData<F>,
+}
+
+impl<'tcx, F: TextFormat> FormatRenderer<'tcx> for TextRenderer<'tcx, F> {
+    fn descr() -> &'static str {
+        F::descr()
+    }
+
+    const RUN_ON_MODULE: bool = false;
+
+    fn init(
+        krate: clean::Crate,
+        options: RenderOptions,
+        cache: Cache,
+        tcx: TyCtxt<'tcx>,
+    ) -> Result<(Self, clean::Crate), Error> {
+        let out_path = options.output.clone();
+        let (json, krate) = JsonRenderer::init(krate, options, cache, tcx)?;
+        Ok((TextRenderer { json, out_path, format: PhantomData }, krate))
+    }
+
+    fn make_child_renderer(&self) -> Self {
+        TextRenderer {
+            json: self.json.make_child_renderer(),
+            out_path: self.out_path.clone(),
+            format: PhantomData,
+        }
+    }
+
+    fn item(&mut self, item: clean::Item) -> Result<(), Error> {
+        self.json.item(item)
+    }
+
+    fn mod_item_in(&mut self, _item: &clean::Item) -> Result<(), Error> {
+        unreachable!("RUN_ON_MODULE = false should never call mod_item_in")
+    }
+
+    fn after_krate(&mut self) -> Result<(), Error> {
+        let krate = self.json.build_crate();
+        let docs = CrateDocs::new(&krate);
+        F::write(&docs, &self.out_path)
+    }
+
+    fn cache(&self) -> &Cache {
+        self.json.cache()
+    }
+}
+
+/// The kinds of items listed on a module page, in the order of the sections of the page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Section {
+    Modules,
+    Macros,
+    Structs,
+    Enums,
+    Unions,
+    Primitives,
+    Traits,
+    TraitAliases,
+    TypeAliases,
+    Constants,
+    Statics,
+    Functions,
+    AttributeMacros,
+    DeriveMacros,
+    ForeignTypes,
+}
+
+impl Section {
+    fn of(item: &ItemEnum) -> Option<Section> {
+        Some(match item {
+            ItemEnum::Module(_) => Section::Modules,
+            ItemEnum::Macro(_) => Section::Macros,
+            ItemEnum::ProcMacro(proc_macro) => match proc_macro.kind {
+                MacroKind::Bang => Section::Macros,
+                MacroKind::Attr => Section::AttributeMacros,
+                MacroKind::Derive => Section::DeriveMacros,
+            },
+            ItemEnum::Struct(_) => Section::Structs,
+            ItemEnum::Enum(_) => Section::Enums,
+            ItemEnum::Union(_) => Section::Unions,
+            ItemEnum::Primitive(_) => Section::Primitives,
+            ItemEnum::Trait(_) => Section::Traits,
+            ItemEnum::TraitAlias(_) => Section::TraitAliases,
+            ItemEnum::TypeAlias(_) => Section::TypeAliases,
+            ItemEnum::Constant { .. } => Section::Constants,
+            ItemEnum::Static(_) => Section::Statics,
+            ItemEnum::Function(_) => Section::Functions,
+            ItemEnum::ForeignType => Section::ForeignTypes,
+            ItemEnum::ExternCrate { .. }
+            | ItemEnum::Import(_)
+            | ItemEnum::StructField(_)
+            | ItemEnum::Variant(_)
+            | ItemEnum::Impl(_)
+            | ItemEnum::AssocConst { .. }
+            | ItemEnum::AssocType { .. } => return None,
+        })
+    }
+
+    pub(crate) fn title(self) -> &'static str {
+        match self {
+            Section::Modules => "Modules",
+            Section::Macros => "Macros",
+            Section::Structs => "Structs",
+            Section::Enums => "Enums",
+            Section::Unions => "Unions",
+            Section::Primitives => "Primitive Types",
+            Section::Traits => "Traits",
+            Section::TraitAliases => "Trait Aliases",
+            Section::TypeAliases => "Type Aliases",
+            Section::Constants => "Constants",
+            Section::Statics => "Statics",
+            Section::Functions => "Functions",
+            Section::AttributeMacros => "Attribute Macros",
+            Section::DeriveMacros => "Derive Macros",
+            Section::ForeignTypes => "Foreign Types",
+        }
+    }
+
+    /// The prefix of the anchors of the items in this section, which is the same as the prefix of
+    /// their file names in the HTML output.
+    fn anchor_kind(self) -> &'static str {
+        match self {
+            Section::Modules => "mod",
+            Section::Macros => "macro",
+            Section::Structs => "struct",
+            Section::Enums => "enum",
+            Section::Unions => "union",
+            Section::Primitives => "primitive",
+            Section::Traits => "trait",
+            Section::TraitAliases => "traitalias",
+            Section::TypeAliases => "type",
+            Section::Constants => "constant",
+            Section::Statics => "static",
+            Section::Functions => "fn",
+            Section::AttributeMacros => "attr",
+            Section::DeriveMacros => "derive",
+            Section::ForeignTypes => "foreigntype",
+        }
+    }
+}
+
+/// The documentation of a crate, split into one page per module.
+pub(crate) struct CrateDocs<'a> {
+    pub(crate) krate: &'a Crate,
+    pub(crate) pages: Vec<ModulePage<'a>>,
+    /// Where each documented item ended up, for resolving links.
+    locations: FxHashMap<&'a Id, Location>,
+}
+
+pub(crate) struct ModulePage<'a> {
+    /// The path of the module, starting with the name of the crate.
+    pub(crate) path: Vec<&'a str>,
+    pub(crate) module: &'a Item,
+    /// The items shown on the page, sorted by section and then by name.
+    pub(crate) items: Vec<PageItem<'a>>,
+    /// Re-exports of items that aren't documented in this crate.
+    pub(crate) reexports: Vec<&'a Item>,
+}
+
+pub(crate) struct PageItem<'a> {
+    pub(crate) section: Section,
+    pub(crate) name: &'a str,
+    pub(crate) item: &'a Item,
+    pub(crate) anchor: String,
+    pub(crate) members: Vec<MemberGroup<'a>>,
+    /// The trait implementations of a type, or the implementors of a trait.
+    pub(crate) impls: Vec<&'a Item>,
+}
+
+pub(crate) struct MemberGroup<'a> {
+    pub(crate) title: &'static str,
+    pub(crate) members: Vec<Member<'a>>,
+}
+
+pub(crate) struct Member<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) item: &'a Item,
+    pub(crate) anchor: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Location {
+    pub(crate) page: usize,
+    pub(crate) anchor: Option<String>,
+}
+
+impl<'a> CrateDocs<'a> {
+    pub(crate) fn new(krate: &'a Crate) -> Self {
+        let mut docs = CrateDocs { krate, pages: Vec::new(), locations: FxHashMap::default() };
+        let root = &krate.index[&krate.root];
+        let mut queued = FxHashSet::default();
+        queued.insert(&root.id);
+        let mut queue = vec![(vec![root.name.as_deref().unwrap_or_default()], root)];
+        // Pages are written in the order the modules are found, breadth first.
+        let mut next = 0;
+        while next < queue.len() {
+            let (path, module) = queue[next].clone();
+            next += 1;
+            let page = docs.pages.len();
+            docs.locations.insert(&module.id, Location { page, anchor: None });
+
+            let mut found = Vec::new();
+            let mut reexports = Vec::new();
+            let ItemEnum::Module(inner) = &module.inner else { unreachable!() };
+            let mut globs = FxHashSet::default();
+            collect_module_items(krate, &inner.items, &mut found, &mut reexports, &mut globs);
+            found.sort_by_key(|&(section, name, _)| (section, name));
+            found.dedup_by_key(|&mut (section, name, _)| (section, name));
+
+            let mut items = Vec::with_capacity(found.len());
+            for (section, name, item) in found {
+                if section == Section::Modules {
+                    if queued.insert(&item.id) {
+                        let mut path = path.clone();
+                        path.push(name);
+                        queue.push((path, item));
+                    }
+                }
+                let anchor = format!("{}.{name}", section.anchor_kind());
+                let members = docs.members(page, &anchor, item);
+                if section != Section::Modules {
+                    docs.locate(&item.id, page, anchor.clone());
+                }
+                let impls = impls(krate, item);
+                items.push(PageItem { section, name, item, anchor, members, impls });
+            }
+            docs.pages.push(ModulePage { path, module, items, reexports });
+        }
+        docs
+    }
+
+    fn locate(&mut self, id: &'a Id, page: usize, anchor: String) {
+        self.locations.entry(id).or_insert(Location { page, anchor: Some(anchor) });
+    }
+
+    /// Collects the fields, variants, associated items and methods shown below an item.
+    fn members(&mut self, page: usize, parent: &str, item: &'a Item) -> Vec<MemberGroup<'a>> {
+        let krate = self.krate;
+        let get = |ids: &'a [Id]| -> Vec<&'a Item> {
+            ids.iter().filter_map(|id| krate.index.get(id)).collect()
+        };
+        let (fields, variants, assoc_items) = match &item.inner {
+            ItemEnum::Struct(struct_) => {
+                let fields = match &struct_.kind {
+                    StructKind::Unit => Vec::new(),
+                    StructKind::Tuple(fields) => {
+                        fields.iter().flatten().filter_map(|id| krate.index.get(id)).collect()
+                    }
+                    StructKind::Plain { fields, .. } => get(fields),
+                };
+                (fields, Vec::new(), inherent_items(krate, &struct_.impls))
+            }
+            ItemEnum::Union(union) => {
+                (get(&union.fields), Vec::new(), inherent_items(krate, &union.impls))
+            }
+            ItemEnum::Enum(enum_) => {
+                (Vec::new(), get(&enum_.variants), inherent_items(krate, &enum_.impls))
+            }
+            ItemEnum::Primitive(primitive) => {
+                (Vec::new(), Vec::new(), inherent_items(krate, &primitive.impls))
+            }
+            ItemEnum::Trait(trait_) => (Vec::new(), Vec::new(), get(&trait_.items)),
+            _ => return Vec::new(),
+        };
+        let is_trait = matches!(item.inner, ItemEnum::Trait(_));
+        let of_kind = |pred: fn(&ItemEnum) -> bool| -> Vec<&'a Item> {
+            assoc_items.iter().copied().filter(|item| pred(&item.inner)).collect()
+        };
+
+        let groups = [
+            ("Fields", "field", fields),
+            ("Variants", "variant", variants),
+            (
+                "Associated Types",
+                "associatedtype",
+                of_kind(|item| matches!(item, ItemEnum::AssocType { .. })),
+            ),
+            (
+                "Associated Constants",
+                "associatedconstant",
+                of_kind(|item| matches!(item, ItemEnum::AssocConst { .. })),
+            ),
+            (
+                if is_trait { "Required Methods" } else { "Methods" },
+                "method",
+                if is_trait {
+                    of_kind(|item| matches!(item, ItemEnum::Function(f) if !f.has_body))
+                } else {
+                    of_kind(|item| matches!(item, ItemEnum::Function(_)))
+                },
+            ),
+            (
+                "Provided Methods",
+                "method",
+                if is_trait {
+                    of_kind(|item| matches!(item, ItemEnum::Function(f) if f.has_body))
+                } else {
+                    Vec::new()
+                },
+            ),
+        ];
+        groups
+            .into_iter()
+            .filter(|(_, _, items)| !items.is_empty())
+            .map(|(title, kind, items)| {
+                let members = items
+                    .into_iter()
+                    .filter_map(|item| {
+                        let name = item.name.as_deref()?;
+                        let anchor = format!("{parent}.{kind}.{name}");
+                        self.locate(&item.id, page, anchor.clone());
+                        Some(Member { name, item, anchor })
+                    })
+                    .collect();
+                MemberGroup { title, members }
+            })
+            .collect()
+    }
+
+    /// Returns where `id` is documented, if it is documented by this crate.
+    pub(crate) fn location(&self, id: &Id) -> Option<&Location> {
+        self.locations.get(id)
+    }
+
+    /// Returns the URL of the HTML documentation of an item of another crate, if the other crate
+    /// has an `html_root_url`.
+    pub(crate) fn external_url(&self, id: &Id) -> Option<String> {
+        let summary = self.krate.paths.get(id)?;
+        let root = self.krate.external_crates.get(&summary.crate_id)?.html_root_url.as_deref()?;
+        let root = root.trim_end_matches('/');
+        let (name, parents) = summary.path.split_last()?;
+        let kind = match summary.kind {
+            ItemKind::Module => {
+                return Some(format!("{root}/{}/index.html", summary.path.join("/")));
+            }
+            ItemKind::Struct => "struct",
+            ItemKind::Union => "union",
+            ItemKind::Enum => "enum",
+            ItemKind::Function => "fn",
+            ItemKind::TypeAlias => "type",
+            ItemKind::Constant => "constant",
+            ItemKind::Trait => "trait",
+            ItemKind::TraitAlias => "traitalias",
+            ItemKind::Static => "static",
+            ItemKind::ForeignType => "foreigntype",
+            ItemKind::Macro => "macro",
+            ItemKind::ProcAttribute => "attr",
+            ItemKind::ProcDerive => "derive",
+            ItemKind::Primitive => "primitive",
+            ItemKind::Keyword => "keyword",
+            ItemKind::ExternCrate
+            | ItemKind::Import
+            | ItemKind::StructField
+            | ItemKind::Variant
+            | ItemKind::Impl
+            | ItemKind::AssocConst
+            | ItemKind::AssocType => return None,
+        };
+        Some(format!("{root}/{}/{kind}.{name}.html", parents.join("/")))
+    }
+
+    /// Returns the plain text summary of the docs of `item`, i.e. its first paragraph.
+    pub(crate) fn summary(&self, item: &Item) -> String {
+        let Some(docs) = &item.docs else { return String::new() };
+        let links: Vec<_> = item
+            .links
+            .keys()
+            .map(|link| RenderedLink {
+                original_text: link.as_str().into(),
+                new_text: link.as_str().into(),
+                href: String::new(),
+                tooltip: String::new(),
+            })
+            .collect();
+        plain_text_summary(docs, &links)
+    }
+}
+
+/// Collects the items shown on the page of a module, inlining the re-exports of items that are
+/// documented by this crate.
+fn collect_module_items<'a>(
+    krate: &'a Crate,
+    ids: &'a [Id],
+    found: &mut Vec<(Section, &'a str, &'a Item)>,
+    reexports: &mut Vec<&'a Item>,
+    globs: &mut FxHashSet<&'a Id>,
+) {
+    for item in ids.iter().filter_map(|id| krate.index.get(id)) {
+        match &item.inner {
+            ItemEnum::Module(module) if module.is_stripped => {}
+            ItemEnum::Import(import) => {
+                let target = import.id.as_ref().and_then(|id| krate.index.get(id));
+                match target.map(|target| (target, &target.inner)) {
+                    Some((target, ItemEnum::Module(module))) if import.glob => {
+                        if globs.insert(&target.id) {
+                            collect_module_items(krate, &module.items, found, reexports, globs);
+                        }
+                    }
+                    Some((target, inner)) if !import.glob => match Section::of(inner) {
+                        Some(section) => found.push((section, &import.name, target)),
+                        None => reexports.push(item),
+                    },
+                    _ => reexports.push(item),
+                }
+            }
+            ItemEnum::ExternCrate { .. } => reexports.push(item),
+            inner => {
+                if let (Some(section), Some(name)) = (Section::of(inner), &item.name) {
+                    found.push((section, name, item));
+                }
+            }
+        }
+    }
+}
+
+/// Returns the items of the inherent impls of a type.
+fn inherent_items<'a>(krate: &'a Crate, impls: &'a [Id]) -> Vec<&'a Item> {
+    impls
+        .iter()
+        .filter_map(|id| match &krate.index.get(id)?.inner {
+            ItemEnum::Impl(impl_) if impl_.trait_.is_none() => Some(&impl_.items),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|id| krate.index.get(id))
+        .collect()
+}
+
+/// Returns the trait impls shown below a type, or the implementors shown below a trait. Auto trait
+/// and blanket impls are left out.
+fn impls<'a>(krate: &'a Crate, item: &'a Item) -> Vec<&'a Item> {
+    let ids = match &item.inner {
+        ItemEnum::Struct(struct_) => &struct_.impls,
+        ItemEnum::Union(union) => &union.impls,
+        ItemEnum::Enum(enum_) => &enum_.impls,
+        ItemEnum::Primitive(primitive) => &primitive.impls,
+        ItemEnum::Trait(trait_) => &trait_.implementations,
+        _ => return Vec::new(),
+    };
+    ids.iter()
+        .filter_map(|id| krate.index.get(id))
+        .filter(|item| match &item.inner {
+            ItemEnum::Impl(impl_) => {
+                impl_.trait_.is_some() && !impl_.synthetic && impl_.blanket_impl.is_none()
+            }
+            _ => false,
+        })
+        .collect()
+}
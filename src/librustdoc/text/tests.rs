@@ -0,0 +1,95 @@
+use super::man::{escape, markdown_to_roff};
+use super::markdown::{relative_url, rewrite_docs};
+use super::*;
+use crate::json::test_items::{import, krate, module, unit_struct};
+
+#[test]
+fn pages() {
+    let krate = krate(
+        &["1", "4", "5", "6"],
+        vec![
+            module("1", "inner", &["2", "3"]),
+            unit_struct("2", "Inner"),
+            unit_struct("3", "Other"),
+            import("4", "inner::Inner", "Renamed", Some("2"), false),
+            import("5", "inner::*", "inner", Some("1"), true),
+            import("6", "std::vec::Vec", "Vec", None, false),
+        ],
+    );
+    let docs = CrateDocs::new(&krate);
+
+    let paths: Vec<_> = docs.pages.iter().map(|page| page.path.join("::")).collect();
+    assert_eq!(paths, ["krate", "krate::inner"]);
+    let root_items: Vec<_> = docs.pages[0].items.iter().map(|item| item.anchor.as_str()).collect();
+    assert_eq!(root_items, ["mod.inner", "struct.Inner", "struct.Other", "struct.Renamed"]);
+    assert_eq!(docs.pages[0].reexports.len(), 1);
+
+    // Items that are shown on several pages are linked to the first one.
+    let location = docs.location(&Id("2".to_owned())).unwrap();
+    assert_eq!(location, &Location { page: 0, anchor: Some("struct.Inner".to_owned()) });
+    let location = docs.location(&Id("1".to_owned())).unwrap();
+    assert_eq!(location, &Location { page: 1, anchor: None });
+}
+
+#[test]
+fn urls() {
+    assert_eq!(relative_url(&["krate"], &["krate"]), "index.md");
+    assert_eq!(relative_url(&["krate"], &["krate", "a", "b"]), "a/b/index.md");
+    assert_eq!(relative_url(&["krate", "a", "b"], &["krate", "c"]), "../../c/index.md");
+}
+
+#[test]
+fn markdown_docs() {
+    let docs = "\
+Uses [`Foo`] and [bar](crate::bar), see [other][o].
+
+# Examples
+
+```
+# use krate::Foo;
+let foo = Foo;
+```
+
+```text
+# not hidden
+```
+
+[o]: crate::other";
+    let links = [
+        ("`Foo`", "index.md#struct.Foo".to_owned()),
+        ("crate::bar", "index.md#fn.bar".to_owned()),
+        ("crate::other", "a/index.md".to_owned()),
+    ];
+    let expected = "\
+Uses [`Foo`] and [bar](index.md#fn.bar), see [other][o].
+
+#### Examples
+
+```rust
+let foo = Foo;
+```
+
+```text
+# not hidden
+```
+
+[o]: a/index.md
+
+[`Foo`]: index.md#struct.Foo";
+    assert_eq!(rewrite_docs(docs, 3, &links), expected);
+    assert_eq!(rewrite_docs("###### Deep", 3, &[]), "###### Deep");
+    assert_eq!(rewrite_docs("#[attr]", 3, &[]), "#[attr]");
+}
+
+#[test]
+fn roff() {
+    assert_eq!(escape(".hidden -flag \\n"), "\\&.hidden \\-flag \\en");
+    assert_eq!(
+        markdown_to_roff("Uses [`Foo`] *a lot*.\n\n- one\n- two", &["`Foo`"]),
+        ".PP\nUses \\fBFoo\\fP \\fIa lot\\fP.\n.IP \\(bu 4\none\n.IP \\(bu 4\ntwo\n",
+    );
+    assert_eq!(
+        markdown_to_roff("```\n# hidden\n.shown\n```", &[]),
+        ".PP\n.RS 4\n.nf\n\\&.shown\n.fi\n.RE\n",
+    );
+}
@@ -234,7 +234,7 @@ fn maybe_inline_local(
             return false;
         }
 
-        if self.cx.output_format.is_json() {
+        if self.cx.output_format.is_json_model() {
             return false;
         }
 
@@ -13,7 +13,7 @@
 /// This integer is incremented with every breaking change to the API,
 /// and is returned along with the JSON blob as [`Crate::format_version`].
 /// Consuming code should assert that this value matches the format version(s) that it supports.
-pub const FORMAT_VERSION: u32 = 33;
+pub const FORMAT_VERSION: u32 = 35;
 
 /// The root of the emitted JSON blob.
 ///
@@ -506,6 +506,8 @@ pub struct Union {
     ///
     /// All of the corresponding [`Item`]s are of kind [`ItemEnum::Impl`].
     pub impls: Vec<Id>,
+    /// The memory layout of this union, if rustdoc is given `--show-type-layout`.
+    pub layout: Option<Layout>,
 }
 
 /// A `struct`.
@@ -519,6 +521,8 @@ pub struct Struct {
     /// All impls (both of traits and inherent) for this struct.
     /// All of the corresponding [`Item`]s are of kind [`ItemEnum::Impl`].
     pub impls: Vec<Id>,
+    /// The memory layout of this struct, if rustdoc is given `--show-type-layout`.
+    pub layout: Option<Layout>,
 }
 
 /// The kind of a [`Struct`] and the data specific to it, i.e. fields.
@@ -571,6 +575,45 @@ pub struct Enum {
     pub variants: Vec<Id>,
     /// `impl`s for the enum.
     pub impls: Vec<Id>,
+    /// The memory layout of this enum, if rustdoc is given `--show-type-layout`.
+    pub layout: Option<Layout>,
+}
+
+/// The memory layout of a [`Struct`], [`Enum`] or [`Union`], as computed by the compiler for the
+/// target of the documentation.
+///
+/// Layout is only computed for types without generic parameters, and is `None` for the others.
+/// Most of it is unstable: unless the type has a `repr` attribute that guarantees its layout, it
+/// may differ between compilations.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Layout {
+    /// The size of the type in bytes. For unsized types, this is the size of the sized part.
+    pub size: u64,
+    /// The alignment of the type in bytes.
+    pub align: u64,
+    /// Whether the type is dynamically sized.
+    pub is_unsized: bool,
+    /// Whether the type has no values.
+    pub is_uninhabited: bool,
+    /// The number of invalid values of the type that an enclosing enum can use to store its
+    /// discriminant, e.g. 254 for `bool`. When it isn't 0, `Option<Self>` has the same size as
+    /// the type.
+    pub niche_values: u128,
+    /// The layout of each variant of an enum with several variants, in declaration order.
+    ///
+    /// This is empty for structs, unions and enums with at most one variant.
+    pub variants: Vec<VariantLayout>,
+}
+
+/// The layout of a variant of an [`Enum`], see [`Layout::variants`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VariantLayout {
+    /// The name of the variant.
+    pub name: String,
+    /// The size of the variant in bytes, without the discriminant.
+    pub size: u64,
+    /// Whether the variant has no values.
+    pub is_uninhabited: bool,
 }
 
 /// A variant of an enum.
@@ -704,6 +747,14 @@ pub struct Function {
     pub header: Header,
     /// Whether the function has a body, i.e. an implementation.
     pub has_body: bool,
+    /// The items that the body of the function refers to: the functions and methods it calls, the
+    /// types, constants and statics it uses, and the macros it invokes. Every item is listed once,
+    /// in the order of its first use.
+    ///
+    /// This is only computed for functions of the local crate when rustdoc is given
+    /// `--json-body-references`, and is `None` otherwise. Only items that have an entry in
+    /// [`Crate::index`] or [`Crate::paths`] are listed.
+    pub references: Option<Vec<Id>>,
 }
 
 /// Generic parameters accepted by an item and `where` clauses imposed on it and the parameters.
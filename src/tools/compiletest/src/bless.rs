@@ -0,0 +1,215 @@
+//! Bookkeeping of the snapshot files (`.stderr`, `.stdout`, `.fixed`, MIR dumps...) written
+//! by `--bless`.
+//!
+//! Tests run in parallel, so two tests (for instance two revisions sharing an output file)
+//! may try to bless the same file with different contents, and the result would depend on
+//! which one finishes last. Every write goes through a [`BlessLog`], which rejects such
+//! conflicting writes, and summarizes all the changes once the tests have run.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::common::Config;
+use crate::compute_diff::write_diff;
+
+#[cfg(test)]
+mod tests;
+
+/// The changes blessed by all the tests of this compiletest run.
+pub(crate) static BLESS_LOG: Mutex<BlessLog> = Mutex::new(BlessLog::new());
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Change {
+    Created,
+    Modified,
+    Deleted,
+}
+
+impl Change {
+    fn describe(self) -> &'static str {
+        match self {
+            Change::Created => "created",
+            Change::Modified => "modified",
+            Change::Deleted => "deleted",
+        }
+    }
+}
+
+/// The test blessing a file, identifying the group it is listed in by the summary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Blesser {
+    pub(crate) suite: String,
+    pub(crate) revision: Option<String>,
+    pub(crate) test_name: String,
+}
+
+#[derive(Debug)]
+struct BlessedFile {
+    blesser: Blesser,
+    /// The contents of the file before it was blessed, `None` if it did not exist.
+    original: Option<String>,
+    /// The blessed contents, `None` if the file is deleted.
+    contents: Option<String>,
+}
+
+impl BlessedFile {
+    fn change(&self) -> Option<Change> {
+        match (&self.original, &self.contents) {
+            (None, Some(_)) => Some(Change::Created),
+            (Some(_), None) => Some(Change::Deleted),
+            (Some(original), Some(contents)) if original != contents => Some(Change::Modified),
+            _ => None,
+        }
+    }
+}
+
+/// Two tests blessed the same file with different contents. Only the first is kept.
+#[derive(Debug)]
+struct Conflict {
+    path: PathBuf,
+    first: String,
+    second: String,
+}
+
+#[derive(Debug)]
+pub(crate) struct BlessLog {
+    files: BTreeMap<PathBuf, BlessedFile>,
+    conflicts: Vec<Conflict>,
+    /// The suites with tests that bless their snapshots themselves (htmldocck in `rustdoc`,
+    /// `RUSTC_BLESS_TEST` in `run-make`), which `--bless-dry-run` can't show.
+    skipped_suites: BTreeSet<String>,
+}
+
+impl BlessLog {
+    pub(crate) const fn new() -> BlessLog {
+        BlessLog { files: BTreeMap::new(), conflicts: Vec::new(), skipped_suites: BTreeSet::new() }
+    }
+
+    /// Records that a test of `suite` would have blessed its snapshots itself, so the dry run
+    /// doesn't show its changes.
+    pub(crate) fn skip_dry_run(&mut self, suite: &str) {
+        self.skipped_suites.insert(suite.to_owned());
+    }
+
+    /// Blesses the file at `path` with `contents`, or deletes it if `contents` is `None`.
+    /// With `--bless-dry-run`, the file is left untouched and only the change is recorded.
+    ///
+    /// Returns an error if another test already blessed the file with different contents.
+    pub(crate) fn bless(
+        &mut self,
+        config: &Config,
+        blesser: &Blesser,
+        path: &Path,
+        contents: Option<&str>,
+    ) -> Result<(), String> {
+        if let Some(file) = self.files.get_mut(path) {
+            if file.blesser.test_name == blesser.test_name {
+                file.contents = contents.map(str::to_owned);
+            } else if file.contents.as_deref() != contents {
+                let message = format!(
+                    "`{}` was already blessed by `{}` with different contents",
+                    path.display(),
+                    file.blesser.test_name,
+                );
+                self.conflicts.push(Conflict {
+                    path: path.to_owned(),
+                    first: file.blesser.test_name.clone(),
+                    second: blesser.test_name.clone(),
+                });
+                return Err(message);
+            } else {
+                return Ok(());
+            }
+        } else {
+            self.files.insert(
+                path.to_owned(),
+                BlessedFile {
+                    blesser: blesser.clone(),
+                    original: fs::read_to_string(path).ok(),
+                    contents: contents.map(str::to_owned),
+                },
+            );
+        }
+
+        if config.bless_dry_run {
+            return Ok(());
+        }
+        match contents {
+            Some(contents) => fs::write(path, contents)
+                .map_err(|e| format!("failed to write `{}`: {}", path.display(), e)),
+            None if path.exists() => fs::remove_file(path)
+                .map_err(|e| format!("failed to delete `{}`: {}", path.display(), e)),
+            None => Ok(()),
+        }
+    }
+
+    /// Describes the blessed files, grouped by test suite and revision, along with their
+    /// diffs on a dry run. Returns `None` if nothing was blessed.
+    pub(crate) fn summary(&self, config: &Config) -> Option<String> {
+        let mut groups: BTreeMap<(&str, Option<&str>), Vec<(Change, &Path, &BlessedFile)>> =
+            BTreeMap::new();
+        for (path, file) in &self.files {
+            if let Some(change) = file.change() {
+                let group = (file.blesser.suite.as_str(), file.blesser.revision.as_deref());
+                groups.entry(group).or_default().push((change, path, file));
+            }
+        }
+        if groups.is_empty() && self.conflicts.is_empty() && self.skipped_suites.is_empty() {
+            return None;
+        }
+
+        let count = |change| groups.values().flatten().filter(|(c, ..)| *c == change).count();
+        let mut summary = String::new();
+        writeln!(
+            summary,
+            "\n{} {} snapshot files ({} modified, {} created, {} deleted)",
+            if config.bless_dry_run { "Would bless" } else { "Blessed" },
+            groups.values().map(Vec::len).sum::<usize>(),
+            count(Change::Modified),
+            count(Change::Created),
+            count(Change::Deleted),
+        )
+        .unwrap();
+        for ((suite, revision), mut files) in groups {
+            match revision {
+                Some(revision) => writeln!(summary, "\n{suite} (revision `{revision}`):"),
+                None => writeln!(summary, "\n{suite}:"),
+            }
+            .unwrap();
+            files.sort_by_key(|&(change, path, _)| (change, path));
+            for (change, path, file) in files {
+                let path = path.strip_prefix(&config.src_base).unwrap_or(path);
+                writeln!(summary, "    {:<8} {}", change.describe(), path.display()).unwrap();
+                if config.bless_dry_run {
+                    let original = file.original.as_deref().unwrap_or("");
+                    let contents = file.contents.as_deref().unwrap_or("");
+                    for line in write_diff(original, contents, 3).lines() {
+                        writeln!(summary, "        {line}").unwrap();
+                    }
+                }
+            }
+        }
+        if !self.conflicts.is_empty() {
+            writeln!(summary, "\nConflicting blessed contents (only the first was kept):").unwrap();
+            for Conflict { path, first, second } in &self.conflicts {
+                let path = path.strip_prefix(&config.src_base).unwrap_or(path);
+                writeln!(summary, "    {} by `{first}` and `{second}`", path.display()).unwrap();
+            }
+        }
+        if !self.skipped_suites.is_empty() {
+            let suites =
+                self.skipped_suites.iter().map(|suite| format!("`{suite}`")).collect::<Vec<_>>();
+            writeln!(
+                summary,
+                "\nNote: the tests of {} bless their snapshots themselves, so their changes are \
+                 not shown by the dry run",
+                suites.join(", "),
+            )
+            .unwrap();
+        }
+        Some(summary)
+    }
+}
@@ -0,0 +1,104 @@
+use std::env;
+
+use super::*;
+
+fn blesser(test_name: &str, revision: Option<&str>) -> Blesser {
+    Blesser {
+        suite: "ui".to_owned(),
+        revision: revision.map(str::to_owned),
+        test_name: test_name.to_owned(),
+    }
+}
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("compiletest-bless-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn conflicting_blesses() {
+    let dir = test_dir("conflicts");
+    let config = Config { src_base: dir.clone(), ..Config::default() };
+    let path = dir.join("shared.stderr");
+    let (a, b) = (blesser("[ui] shared.rs#a", Some("a")), blesser("[ui] shared.rs#b", Some("b")));
+
+    let mut log = BlessLog::new();
+    log.bless(&config, &a, &path, Some("error\n")).unwrap();
+    // The same contents from another test are fine.
+    log.bless(&config, &b, &path, Some("error\n")).unwrap();
+    // A test may overwrite its own file.
+    log.bless(&config, &a, &path, Some("error: a\n")).unwrap();
+    assert!(log.bless(&config, &b, &path, Some("error: b\n")).is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "error: a\n");
+
+    let summary = log.summary(&config).unwrap();
+    assert!(summary.contains("shared.stderr by `[ui] shared.rs#a` and `[ui] shared.rs#b`"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn summary_groups_by_suite_and_revision() {
+    let dir = test_dir("summary");
+    let config = Config { src_base: dir.clone(), ..Config::default() };
+    fs::write(dir.join("modified.stderr"), "old\n").unwrap();
+    fs::write(dir.join("deleted.stderr"), "old\n").unwrap();
+    fs::write(dir.join("unchanged.stderr"), "same\n").unwrap();
+
+    let mut log = BlessLog::new();
+    let plain = blesser("[ui] test.rs", None);
+    let next = blesser("[ui] test.rs#next", Some("next"));
+    log.bless(&config, &plain, &dir.join("modified.stderr"), Some("new\n")).unwrap();
+    log.bless(&config, &plain, &dir.join("deleted.stderr"), None).unwrap();
+    log.bless(&config, &plain, &dir.join("unchanged.stderr"), Some("same\n")).unwrap();
+    log.bless(&config, &next, &dir.join("created.next.stderr"), Some("new\n")).unwrap();
+
+    assert_eq!(
+        log.summary(&config).unwrap(),
+        "\nBlessed 3 snapshot files (1 modified, 1 created, 1 deleted)\n\
+         \nui:\n    \
+         modified modified.stderr\n    \
+         deleted  deleted.stderr\n\
+         \nui (revision `next`):\n    \
+         created  created.next.stderr\n"
+    );
+    assert!(!dir.join("deleted.stderr").exists());
+    assert_eq!(fs::read_to_string(dir.join("created.next.stderr")).unwrap(), "new\n");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dry_run_leaves_files_untouched() {
+    let dir = test_dir("dry-run");
+    let config = Config { src_base: dir.clone(), bless_dry_run: true, ..Config::default() };
+    let path = dir.join("test.stderr");
+    fs::write(&path, "old\n").unwrap();
+
+    let mut log = BlessLog::new();
+    log.bless(&config, &blesser("[ui] test.rs", None), &path, Some("new\n")).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "old\n");
+
+    let summary = log.summary(&config).unwrap();
+    assert!(summary.starts_with("\nWould bless 1 snapshot files (1 modified"));
+    assert!(summary.contains("modified test.stderr\n        -\told\n        +\tnew\n"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dry_run_notes_skipped_suites() {
+    let config = Config { bless_dry_run: true, ..Config::default() };
+    let mut log = BlessLog::new();
+    assert_eq!(log.summary(&config), None);
+    log.skip_dry_run("run-make");
+    log.skip_dry_run("rustdoc");
+    log.skip_dry_run("run-make");
+    let summary = log.summary(&config).unwrap();
+    assert!(summary.ends_with(
+        "\nNote: the tests of `run-make`, `rustdoc` bless their snapshots themselves, so their \
+         changes are not shown by the dry run\n"
+    ));
+}
@@ -12,6 +12,7 @@
     "build-fail",
     "build-pass",
     "check-fail",
+    "check-fixed-idempotent",
     "check-pass",
     "check-run-results",
     "check-stdout",
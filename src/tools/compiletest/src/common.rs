@@ -3,7 +3,7 @@
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::str::FromStr;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 use std::{fmt, iter};
 
 use build_helper::git::GitConfig;
@@ -11,6 +11,7 @@
 use test::{ColorConfig, OutputFormat};
 
 pub use self::Mode::*;
+use crate::external::DirectiveExtension;
 use crate::util::{add_dylib_path, PathBufExt};
 
 macro_rules! string_enum {
@@ -174,6 +175,9 @@ pub struct Config {
     /// `true` to overwrite stderr/stdout files instead of complaining about changes in output.
     pub bless: bool,
 
+    /// `true` to only show what `bless` would change, without writing any file. Implies `bless`.
+    pub bless_dry_run: bool,
+
     /// The library paths required for running the compiler.
     pub compile_lib_path: PathBuf,
 
@@ -262,6 +266,9 @@ pub struct Config {
     /// Write out a parseable log of tests that were run
     pub logfile: Option<PathBuf>,
 
+    /// Write a JSON report of the outcome and timings of each test to this file
+    pub json_report: Option<PathBuf>,
+
     /// A command line to prefix program execution with,
     /// for running under valgrind for example.
     ///
@@ -346,6 +353,10 @@ pub struct Config {
     /// created in `/<build_base>/rustfix_missing_coverage.txt`
     pub rustfix_coverage: bool,
 
+    /// If true, failing `ui` tests and crashing `crashes` tests are reduced while their
+    /// compilation fails the same way, and the reduced files are written to the build directory.
+    pub minimize: bool,
+
     /// whether to run `tidy` when a rustdoc test fails
     pub has_tidy: bool,
 
@@ -391,6 +402,10 @@ pub struct Config {
     /// True if the profiler runtime is enabled for this target.
     /// Used by the "needs-profiler-support" header in test files.
     pub profiler_support: bool,
+
+    /// Directives added by a project running its own suites with compiletest,
+    /// see [`crate::external`].
+    pub directive_extension: Option<Arc<dyn DirectiveExtension>>,
 }
 
 impl Config {
@@ -651,7 +666,11 @@ pub enum Endian {
     Big,
 }
 
-fn rustc_output(config: &Config, args: &[&str], envs: HashMap<String, String>) -> String {
+pub(crate) fn rustc_output(
+    config: &Config,
+    args: &[&str],
+    envs: HashMap<String, String>,
+) -> String {
     let mut command = Command::new(&config.rustc_path);
     add_dylib_path(&mut command, iter::once(&config.compile_lib_path));
     command.args(&config.target_rustcflags).args(args);
@@ -0,0 +1,170 @@
+//! Running `ui`- and `mir-opt`-style suites outside of the rust-lang/rust tree.
+//!
+//! A project with its own compiler driver (a lint driver, a codegen backend wrapping
+//! `rustc`...) can test it with compiletest from a `harness = false` test target:
+//!
+//! ```no_run
+//! use compiletest::common::Mode;
+//! use compiletest::external::ExternalSuite;
+//!
+//! ExternalSuite::new(Mode::Ui, "tests/ui", "target/ui", env!("CARGO_BIN_EXE_my-driver"))
+//!     .filters(std::env::args().skip(1).collect())
+//!     .bless(std::env::var_os("BLESS").is_some())
+//!     .run();
+//! ```
+//!
+//! The driver is invoked like `rustc`, and must accept the same flags as the nightly `rustc`
+//! it is built on. Tests use the same directives as the tests of the rust-lang/rust tree, and
+//! the suite can add its own with a [`DirectiveExtension`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::common::{rustc_output, Config, Mode};
+use crate::header::TestProps;
+
+/// Directives added by a suite to the ones known by compiletest.
+pub trait DirectiveExtension: fmt::Debug + Send + Sync {
+    /// The names of the added directives, e.g. `lint-level` for `//@ lint-level: deny`.
+    /// Tests using any other directive unknown to compiletest are rejected.
+    fn directive_names(&self) -> &[&'static str];
+
+    /// Updates the properties of a test from one of its directives. This is called for every
+    /// directive of the test, including the ones handled by compiletest, so directives
+    /// not added by the extension should be ignored.
+    ///
+    /// [`Config::parse_name_directive`] and [`Config::parse_name_value_directive`] can be used
+    /// to parse the directive.
+    fn apply(&self, config: &Config, directive: &str, props: &mut TestProps);
+
+    /// Returns the reason to ignore the test because of one of its directives, if it should be.
+    fn ignore(&self, _config: &Config, _directive: &str) -> Option<String> {
+        None
+    }
+}
+
+/// A suite of tests run with a driver built outside of the rust-lang/rust tree.
+#[derive(Debug)]
+pub struct ExternalSuite {
+    config: Config,
+}
+
+impl ExternalSuite {
+    /// Creates a suite running the tests in `src_base` with `driver`, which writes its outputs
+    /// to `build_base`.
+    ///
+    /// The host, target and sysroot default to the ones of the driver.
+    pub fn new(
+        mode: Mode,
+        src_base: impl Into<PathBuf>,
+        build_base: impl Into<PathBuf>,
+        driver: impl Into<PathBuf>,
+    ) -> ExternalSuite {
+        ExternalSuite {
+            config: Config {
+                mode,
+                suite: mode.to_string(),
+                src_base: src_base.into(),
+                build_base: build_base.into(),
+                rustc_path: driver.into(),
+                python: "python3".to_owned(),
+                cc: "cc".to_owned(),
+                cxx: "c++".to_owned(),
+                ar: "ar".to_owned(),
+                ..Config::default()
+            },
+        }
+    }
+
+    /// Sets the name of the suite, shown when tests fail. Defaults to the name of the mode.
+    pub fn suite(mut self, suite: impl Into<String>) -> Self {
+        self.config.suite = suite.into();
+        self
+    }
+
+    /// Sets the target the tests are built for.
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.config.target = target.into();
+        self
+    }
+
+    /// Sets the sysroot the driver runs with.
+    pub fn sysroot(mut self, sysroot: impl Into<PathBuf>) -> Self {
+        self.config.sysroot_base = sysroot.into();
+        self
+    }
+
+    /// Sets extra flags passed to the driver when building the tests.
+    pub fn rustcflags(mut self, flags: Vec<String>) -> Self {
+        self.config.host_rustcflags = flags.clone();
+        self.config.target_rustcflags = flags;
+        self
+    }
+
+    /// Only runs the tests whose name contains one of `filters`.
+    pub fn filters(mut self, filters: Vec<String>) -> Self {
+        self.config.filters = filters;
+        self
+    }
+
+    /// Overwrites the expected outputs of the tests instead of failing when they differ.
+    pub fn bless(mut self, bless: bool) -> Self {
+        self.config.bless = bless;
+        self
+    }
+
+    /// Adds the directives of `extension` to the ones known by compiletest.
+    pub fn directive_extension(mut self, extension: impl DirectiveExtension + 'static) -> Self {
+        self.config.directive_extension = Some(Arc::new(extension));
+        self
+    }
+
+    /// Gives access to the whole configuration, for the settings without a dedicated method.
+    pub fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+
+    /// Returns the configuration of the suite, querying the driver for the settings
+    /// which were not set.
+    pub fn into_config(self) -> Config {
+        let mut config = self.config;
+
+        let version = rustc_output(&config, &["-vV"], HashMap::new());
+        let version_field = |name: &str| {
+            version
+                .lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+                .unwrap_or_else(|| panic!("`{} -vV` has no `{name}`", config.rustc_path.display()))
+                .trim()
+                .to_owned()
+        };
+        config.host = version_field("host");
+        if config.target.is_empty() {
+            config.target = config.host.clone();
+        }
+        let release = version_field("release");
+        config.channel = ["nightly", "beta", "dev"]
+            .into_iter()
+            .find(|channel| release.contains(channel))
+            .unwrap_or("stable")
+            .to_owned();
+
+        if config.sysroot_base.as_os_str().is_empty() {
+            let sysroot = rustc_output(&config, &["--print", "sysroot"], HashMap::new());
+            config.sysroot_base = PathBuf::from(sysroot.trim());
+        }
+        config.compile_lib_path = config.sysroot_base.join("lib");
+        config.run_lib_path =
+            config.sysroot_base.join("lib").join("rustlib").join(&config.target).join("lib");
+        config.stage_id = format!("external-{}", config.target);
+        config
+    }
+
+    /// Runs the suite, printing the results like compiletest does. The process exits with
+    /// an error if any test fails.
+    pub fn run(self) {
+        crate::run_tests(Arc::new(self.into_config()));
+    }
+}
@@ -53,6 +53,7 @@ pub fn from_reader<R: Read>(config: &Config, testfile: &Path, rdr: R) -> Self {
         iter_header(
             config.mode,
             &config.suite,
+            config.extension_directive_names(),
             &mut poisoned,
             testfile,
             rdr,
@@ -201,6 +202,11 @@ pub struct TestProps {
     pub run_rustfix: bool,
     // If true, `rustfix` will only apply `MachineApplicable` suggestions.
     pub rustfix_only_machine_applicable: bool,
+    // If true, the code fixed by `rustfix` must not get any further `MachineApplicable`
+    // suggestions, i.e. applying the suggestions must reach a fixpoint. Only useful with
+    // `rustfix-only-machine-applicable`: otherwise, the fixed code must not get any
+    // suggestions at all anyway.
+    pub check_fixed_idempotent: bool,
     pub assembly_output: Option<String>,
     // If true, the test is expected to ICE
     pub should_ice: bool,
@@ -253,6 +259,7 @@ mod directives {
     pub const DONT_CHECK_FAILURE_STATUS: &'static str = "dont-check-failure-status";
     pub const RUN_RUSTFIX: &'static str = "run-rustfix";
     pub const RUSTFIX_ONLY_MACHINE_APPLICABLE: &'static str = "rustfix-only-machine-applicable";
+    pub const CHECK_FIXED_IDEMPOTENT: &'static str = "check-fixed-idempotent";
     pub const ASSEMBLY_OUTPUT: &'static str = "assembly-output";
     pub const STDERR_PER_BITWIDTH: &'static str = "stderr-per-bitwidth";
     pub const INCREMENTAL: &'static str = "incremental";
@@ -314,6 +321,7 @@ pub fn new() -> Self {
             dont_check_failure_status: false,
             run_rustfix: false,
             rustfix_only_machine_applicable: false,
+            check_fixed_idempotent: false,
             assembly_output: None,
             should_ice: false,
             stderr_per_bitwidth: false,
@@ -364,6 +372,7 @@ fn load_from(&mut self, testfile: &Path, test_revision: Option<&str>, config: &C
             iter_header(
                 config.mode,
                 &config.suite,
+                config.extension_directive_names(),
                 &mut poisoned,
                 testfile,
                 file,
@@ -535,6 +544,11 @@ fn split_flags(flags: &str) -> Vec<String> {
                         RUSTFIX_ONLY_MACHINE_APPLICABLE,
                         &mut self.rustfix_only_machine_applicable,
                     );
+                    config.set_name_directive(
+                        ln,
+                        CHECK_FIXED_IDEMPOTENT,
+                        &mut self.check_fixed_idempotent,
+                    );
                     config.set_name_value_directive(
                         ln,
                         ASSEMBLY_OUTPUT,
@@ -597,6 +611,10 @@ fn split_flags(flags: &str) -> Vec<String> {
                     }
 
                     config.set_name_directive(ln, NO_AUTO_CHECK_CFG, &mut self.no_auto_check_cfg);
+
+                    if let Some(extension) = &config.directive_extension {
+                        extension.apply(config, ln, self);
+                    }
                 },
             );
 
@@ -610,6 +628,10 @@ fn split_flags(flags: &str) -> Vec<String> {
             self.failure_status = Some(101);
         }
 
+        if self.check_fixed_idempotent && !self.run_rustfix {
+            panic!("`check-fixed-idempotent` requires `run-rustfix` in {}", testfile.display());
+        }
+
         if config.mode == Mode::Incremental {
             self.incremental = true;
         }
@@ -801,6 +823,7 @@ pub(crate) struct CheckDirectiveResult<'ln> {
 pub(crate) fn check_directive<'a>(
     directive_ln: &'a str,
     mode: Mode,
+    extension_directives: &[&str],
     original_line: &str,
 ) -> CheckDirectiveResult<'a> {
     let (directive_name, post) = directive_ln.split_once([':', ' ']).unwrap_or((directive_ln, ""));
@@ -808,6 +831,7 @@ pub(crate) fn check_directive<'a>(
     let trailing = post.trim().split_once(' ').map(|(pre, _)| pre).unwrap_or(post);
     let is_known = |s: &str| {
         KNOWN_DIRECTIVE_NAMES.contains(&s)
+            || extension_directives.contains(&s)
             || match mode {
                 Mode::Rustdoc | Mode::RustdocJson => {
                     original_line.starts_with("//@")
@@ -839,6 +863,7 @@ pub(crate) fn check_directive<'a>(
 fn iter_header(
     mode: Mode,
     _suite: &str,
+    extension_directives: &[&str],
     poisoned: &mut bool,
     testfile: &Path,
     rdr: impl Read,
@@ -901,7 +926,7 @@ fn iter_header(
                 let directive_ln = non_revisioned_directive_line.trim();
 
                 let CheckDirectiveResult { is_known_directive, trailing_directive, .. } =
-                    check_directive(directive_ln, mode, ln);
+                    check_directive(directive_ln, mode, extension_directives, ln);
 
                 if !is_known_directive {
                     *poisoned = true;
@@ -954,7 +979,7 @@ fn iter_header(
             let rest = rest.trim_start();
 
             let CheckDirectiveResult { is_known_directive, directive_name, .. } =
-                check_directive(rest, mode, ln);
+                check_directive(rest, mode, extension_directives, ln);
 
             if is_known_directive {
                 *poisoned = true;
@@ -1032,7 +1057,7 @@ fn parse_custom_normalization(&self, line: &str, prefix: &str) -> Option<(String
         Some((regex, replacement))
     }
 
-    fn parse_name_directive(&self, line: &str, directive: &str) -> bool {
+    pub fn parse_name_directive(&self, line: &str, directive: &str) -> bool {
         // Ensure the directive is a whole word. Do not match "ignore-x86" when
         // the line says "ignore-x86_64".
         line.starts_with(directive)
@@ -1054,6 +1079,14 @@ pub fn parse_name_value_directive(&self, line: &str, directive: &str) -> Option<
         }
     }
 
+    /// The names of the directives added by [`Config::directive_extension`].
+    fn extension_directive_names(&self) -> &[&'static str] {
+        match &self.directive_extension {
+            Some(extension) => extension.directive_names(),
+            None => &[],
+        }
+    }
+
     pub fn find_rust_src_root(&self) -> Option<PathBuf> {
         let mut path = self.src_base.clone();
         let path_postfix = Path::new("src/etc/lldb_batchmode.py");
@@ -1253,6 +1286,7 @@ pub fn make_test_description<R: Read>(
     iter_header(
         config.mode,
         &config.suite,
+        config.extension_directive_names(),
         &mut local_poisoned,
         path,
         src,
@@ -1298,6 +1332,12 @@ macro_rules! decision {
             decision!(ignore_cdb(config, ln));
             decision!(ignore_gdb(config, ln));
             decision!(ignore_lldb(config, ln));
+            if let Some(extension) = &config.directive_extension {
+                decision!(match extension.ignore(config, ln) {
+                    Some(reason) => IgnoreDecision::Ignore { reason },
+                    None => IgnoreDecision::Continue,
+                });
+            }
 
             if config.target == "wasm32-unknown-unknown"
                 && config.parse_name_directive(ln, directives::CHECK_RUN_RESULTS)
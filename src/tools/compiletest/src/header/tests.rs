@@ -1,10 +1,13 @@
 use std::io::Read;
+use std::panic::AssertUnwindSafe;
 use std::path::Path;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use super::iter_header;
 use crate::common::{Config, Debugger, Mode};
-use crate::header::{parse_normalize_rule, EarlyProps, HeadersCache};
+use crate::external::DirectiveExtension;
+use crate::header::{parse_normalize_rule, EarlyProps, HeadersCache, TestProps};
 
 fn make_test_description<R: Read>(
     config: &Config,
@@ -196,6 +199,17 @@ fn parse_rs(config: &Config, contents: &str) -> EarlyProps {
     EarlyProps::from_reader(config, Path::new("a.rs"), bytes)
 }
 
+/// Parses the `TestProps` of a test file named `name` containing `contents`.
+fn props_from_str(config: &Config, name: &str, contents: &str) -> TestProps {
+    let testfile = std::env::temp_dir().join(format!("compiletest-{}-{name}", std::process::id()));
+    std::fs::write(&testfile, contents).unwrap();
+    let props = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        TestProps::from_file(&testfile, None, config)
+    }));
+    std::fs::remove_file(&testfile).unwrap();
+    props.unwrap_or_else(|e| std::panic::resume_unwind(e))
+}
+
 fn check_ignore(config: &Config, contents: &str) -> bool {
     let tn = test::DynTestName(String::new());
     let p = Path::new("a.rs");
@@ -607,7 +621,7 @@ fn threads_support() {
 
 fn run_path(poisoned: &mut bool, path: &Path, buf: &[u8]) {
     let rdr = std::io::Cursor::new(&buf);
-    iter_header(Mode::Ui, "ui", poisoned, path, rdr, &mut |_| {});
+    iter_header(Mode::Ui, "ui", &[], poisoned, path, rdr, &mut |_| {});
 }
 
 #[test]
@@ -685,3 +699,62 @@ fn test_not_trailing_directive() {
     run_path(&mut poisoned, Path::new("a.rs"), b"//@ revisions: incremental");
     assert!(!poisoned);
 }
+
+#[derive(Debug)]
+struct LintDriverDirectives;
+
+impl DirectiveExtension for LintDriverDirectives {
+    fn directive_names(&self) -> &[&'static str] {
+        &["lint-level", "needs-lint-driver"]
+    }
+
+    fn apply(&self, config: &Config, directive: &str, props: &mut TestProps) {
+        if let Some(level) = config.parse_name_value_directive(directive, "lint-level") {
+            props.compile_flags.push(format!("--cap-lints={}", level.trim()));
+        }
+    }
+
+    fn ignore(&self, config: &Config, directive: &str) -> Option<String> {
+        config
+            .parse_name_directive(directive, "needs-lint-driver")
+            .then(|| "the lint driver is not available".to_owned())
+    }
+}
+
+#[test]
+fn test_directive_extension() {
+    let mut poisoned = false;
+    run_path(&mut poisoned, Path::new("a.rs"), b"//@ lint-level: warn");
+    assert!(poisoned);
+
+    let mut config = cfg().build();
+    config.directive_extension = Some(Arc::new(LintDriverDirectives));
+    let mut poisoned = false;
+    let names = config.extension_directive_names();
+    let rdr = std::io::Cursor::new(b"//@ lint-level: warn");
+    iter_header(Mode::Ui, "ui", names, &mut poisoned, Path::new("a.rs"), rdr, &mut |_| {});
+    assert!(!poisoned);
+
+    assert!(check_ignore(&config, "//@ needs-lint-driver"));
+    assert!(!check_ignore(&config, "//@ lint-level: warn"));
+
+    let props =
+        props_from_str(&config, "extension.rs", "//@ compile-flags: -O\n//@ lint-level: warn\n");
+    assert_eq!(props.compile_flags, ["-O", "--cap-lints=warn"]);
+}
+
+#[test]
+fn test_check_fixed_idempotent() {
+    let config = cfg().build();
+    let props =
+        props_from_str(&config, "idempotent.rs", "//@ run-rustfix\n//@ check-fixed-idempotent\n");
+    assert!(props.check_fixed_idempotent);
+    let props = props_from_str(&config, "not-idempotent.rs", "//@ run-rustfix\n");
+    assert!(!props.check_fixed_idempotent);
+}
+
+#[test]
+#[should_panic(expected = "`check-fixed-idempotent` requires `run-rustfix`")]
+fn test_check_fixed_idempotent_without_rustfix() {
+    props_from_str(&cfg().build(), "no-rustfix.rs", "//@ check-fixed-idempotent\n");
+}
@@ -8,13 +8,17 @@
 #[cfg(test)]
 mod tests;
 
+mod bless;
 pub mod common;
 pub mod compute_diff;
 pub mod errors;
+pub mod external;
 pub mod header;
 mod json;
+mod minimize;
 mod raise_fd_limit;
 mod read2;
+mod report;
 pub mod runtest;
 pub mod util;
 
@@ -106,10 +110,22 @@ pub fn parse_config(args: Vec<String>) -> Config {
             "bless",
             "overwrite stderr/stdout files instead of complaining about a mismatch",
         )
+        .optflag(
+            "",
+            "bless-dry-run",
+            "show the changes `--bless` would make to stderr/stdout files, without writing them \
+             (except in rustdoc and run-make tests)",
+        )
         .optflag("", "quiet", "print one character per test instead of one line")
         .optopt("", "color", "coloring: auto, always, never", "WHEN")
         .optflag("", "json", "emit json output instead of plaintext output")
         .optopt("", "logfile", "file to log test execution to", "FILE")
+        .optopt(
+            "",
+            "json-report",
+            "file to write a JSON report of the outcome and timings of each test to",
+            "FILE",
+        )
         .optopt("", "target", "the target to build for", "TARGET")
         .optopt("", "host", "the host to build for", "HOST")
         .optopt("", "cdb", "path to CDB to use for CDB debuginfo tests", "PATH")
@@ -145,6 +161,12 @@ pub fn parse_config(args: Vec<String>) -> Config {
             "enable this to generate a Rustfix coverage file, which is saved in \
             `./<build_base>/rustfix_missing_coverage.txt`",
         )
+        .optflag(
+            "",
+            "minimize",
+            "reduce failing and crash tests while they fail the same way, \
+            writing the reduced files to the build directory",
+        )
         .optflag("", "force-rerun", "rerun tests even if the inputs are unchanged")
         .optflag("", "only-modified", "only run tests that result been modified")
         .optflag("", "nocapture", "")
@@ -251,7 +273,8 @@ fn make_absolute(path: PathBuf) -> PathBuf {
         matches.free.clone()
     };
     Config {
-        bless: matches.opt_present("bless"),
+        bless: matches.opt_present("bless") || matches.opt_present("bless-dry-run"),
+        bless_dry_run: matches.opt_present("bless-dry-run"),
         compile_lib_path: make_absolute(opt_path(matches, "compile-lib-path")),
         run_lib_path: make_absolute(opt_path(matches, "run-lib-path")),
         rustc_path: opt_path(matches, "rustc-path"),
@@ -288,6 +311,7 @@ fn make_absolute(path: PathBuf) -> PathBuf {
             _ => panic!("unknown `--run` option `{}` given", mode),
         }),
         logfile: matches.opt_str("logfile").map(|s| PathBuf::from(&s)),
+        json_report: matches.opt_str("json-report").map(PathBuf::from),
         runner: matches.opt_str("runner"),
         host_rustcflags: matches.opt_strs("host-rustcflags"),
         target_rustcflags: matches.opt_strs("target-rustcflags"),
@@ -324,6 +348,7 @@ fn make_absolute(path: PathBuf) -> PathBuf {
             .opt_str("compare-mode")
             .map(|s| s.parse().expect("invalid --compare-mode provided")),
         rustfix_coverage: matches.opt_present("rustfix-coverage"),
+        minimize: matches.opt_present("minimize"),
         has_tidy,
         channel: matches.opt_str("channel").unwrap(),
         git_hash: matches.opt_present("git-hash"),
@@ -350,6 +375,8 @@ fn make_absolute(path: PathBuf) -> PathBuf {
         nightly_branch: matches.opt_str("nightly-branch").unwrap(),
 
         profiler_support: matches.opt_present("profiler-support"),
+
+        directive_extension: None,
     }
 }
 
@@ -454,6 +481,17 @@ pub fn run_tests(config: Arc<Config>) {
     tests.sort_by(|a, b| a.desc.name.as_slice().cmp(&b.desc.name.as_slice()));
 
     let res = test::run_tests_console(&opts, tests);
+
+    if let Some(summary) = bless::BLESS_LOG.lock().unwrap().summary(&config) {
+        print!("{summary}");
+    }
+    if let Some(summary) = minimize::summary() {
+        print!("{summary}");
+    }
+    if let Some(path) = &config.json_report {
+        report::write(path);
+    }
+
     match res {
         Ok(true) => {}
         Ok(false) => {
@@ -614,9 +652,17 @@ pub fn make_tests(
 
 /// Returns a stamp constructed from input files common to all test cases.
 fn common_inputs_stamp(config: &Config) -> Stamp {
-    let rust_src_dir = config.find_rust_src_root().expect("Could not find Rust source root");
-
     let mut stamp = Stamp::from_path(&config.rustc_path);
+    stamp.add_dir(&config.run_lib_path);
+
+    // Suites run outside of the rust-lang/rust tree depend on their driver, and on the
+    // binary running compiletest, which contains their directive extension.
+    let Some(rust_src_dir) = config.find_rust_src_root() else {
+        if let Ok(current_exe) = env::current_exe() {
+            stamp.add_path(&current_exe);
+        }
+        return stamp;
+    };
 
     // Relevant pretty printer files
     let pretty_printer_files = [
@@ -635,8 +681,6 @@ fn common_inputs_stamp(config: &Config) -> Stamp {
 
     stamp.add_dir(&rust_src_dir.join("src/etc/natvis"));
 
-    stamp.add_dir(&config.run_lib_path);
-
     if let Some(ref rustdoc_path) = config.rustdoc_path {
         stamp.add_path(&rustdoc_path);
         stamp.add_path(&rust_src_dir.join("src/etc/htmldocck.py"));
@@ -810,12 +854,23 @@ fn make_test(
                 &config, cache, test_name, &test_path, src_file, revision, poisoned,
             );
             // Ignore tests that already run and are up to date with respect to inputs.
-            if !config.force_rerun {
-                desc.ignore |= is_up_to_date(&config, testpaths, &early_props, revision, inputs);
+            let up_to_date = !config.force_rerun
+                && is_up_to_date(&config, testpaths, &early_props, revision, inputs);
+            desc.ignore |= up_to_date;
+            if desc.ignore {
+                let reason = desc.ignore_message.or(up_to_date.then_some("up-to-date"));
+                report::record_ignored(&config, desc.name.as_slice(), revision, reason);
             }
+            let should_panic = !matches!(desc.should_panic, test::ShouldPanic::No);
             test::TestDescAndFn {
+                testfn: make_test_closure(
+                    config.clone(),
+                    testpaths,
+                    revision,
+                    desc.name.as_slice(),
+                    should_panic,
+                ),
                 desc,
-                testfn: make_test_closure(config.clone(), testpaths, revision),
             }
         })
         .collect()
@@ -928,7 +983,8 @@ fn make_test_name(
 ) -> test::TestName {
     // Print the name of the file, relative to the repository root.
     // `src_base` looks like `/path/to/rust/tests/ui`
-    let root_directory = config.src_base.parent().unwrap().parent().unwrap();
+    let root_directory =
+        config.src_base.parent().and_then(Path::parent).unwrap_or(&config.src_base);
     let path = testpaths.file.strip_prefix(root_directory).unwrap();
     let debugger = match config.debugger {
         Some(d) => format!("-{}", d),
@@ -953,12 +1009,17 @@ fn make_test_closure(
     config: Arc<Config>,
     testpaths: &TestPaths,
     revision: Option<&str>,
+    name: &str,
+    should_panic: bool,
 ) -> test::TestFn {
     let config = config.clone();
     let testpaths = testpaths.clone();
     let revision = revision.map(str::to_owned);
+    let name = name.to_owned();
     test::DynTestFn(Box::new(move || {
-        runtest::run(config, &testpaths, revision.as_deref());
+        report::run_test(&config, &name, revision.as_deref(), should_panic, || {
+            runtest::run(config.clone(), &testpaths, revision.as_deref())
+        });
         Ok(())
     }))
 }
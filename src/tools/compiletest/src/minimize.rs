@@ -0,0 +1,295 @@
+//! Reduction of failing and crashing tests with `--minimize`.
+//!
+//! The test is parsed, and its items, statements, generic parameters and where clauses are
+//! removed one at a time, keeping each removal after which the compilation still fails with
+//! the same [failure signature](failure_signature). The reduced test is printed back from
+//! its syntax tree, so it loses its comments (except for its directives) and its formatting.
+//! Tests which `syn` cannot parse, e.g. because they use nightly syntax, are reduced line by
+//! line instead. Each revision of a test is minimized on its own, into a test without revisions.
+
+use std::fmt::Write as _;
+use std::mem;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use quote::ToTokens;
+use regex::Regex;
+use syn::punctuated::Punctuated;
+use syn::visit_mut::{self, VisitMut};
+
+#[cfg(test)]
+mod tests;
+
+/// The tests minimized during this compiletest run.
+static MINIMIZED: Mutex<Vec<Minimized>> = Mutex::new(Vec::new());
+
+struct Minimized {
+    test_name: String,
+    /// The reduced file, or the reason the test could not be minimized.
+    outcome: Result<Written, String>,
+}
+
+/// A reduced test written to disk.
+pub(crate) struct Written {
+    pub(crate) path: PathBuf,
+    /// The number of lines of the test before and after the reduction.
+    pub(crate) lines: (usize, usize),
+    /// Why the test was reduced line by line rather than through its syntax tree, if it was.
+    pub(crate) line_based: Option<String>,
+}
+
+/// A test reduced by [`minimize`].
+pub(crate) struct Reduced {
+    pub(crate) text: String,
+    /// Why the test was reduced line by line rather than through its syntax tree, if it was.
+    pub(crate) line_based: Option<String>,
+}
+
+/// Records the outcome of the minimization of `test_name`, shown by [`summary`].
+pub(crate) fn record(test_name: String, outcome: Result<Written, String>) {
+    MINIMIZED.lock().unwrap().push(Minimized { test_name, outcome });
+}
+
+/// Lists the minimized tests. Returns `None` if no test was minimized.
+pub(crate) fn summary() -> Option<String> {
+    let mut minimized = MINIMIZED.lock().unwrap();
+    if minimized.is_empty() {
+        return None;
+    }
+    minimized.sort_by(|a, b| a.test_name.cmp(&b.test_name));
+
+    let mut summary = String::from("\nMinimized tests:\n");
+    for Minimized { test_name, outcome } in minimized.iter() {
+        match outcome {
+            Ok(Written { path, lines: (before, after), line_based }) => {
+                writeln!(
+                    summary,
+                    "    {test_name}: {before} -> {after} lines, written to {}",
+                    path.display()
+                )
+                .unwrap();
+                if let Some(reason) = line_based {
+                    writeln!(summary, "        reduced line by line, {reason}").unwrap();
+                }
+            }
+            Err(reason) => writeln!(summary, "    {test_name}: not minimized, {reason}").unwrap(),
+        }
+    }
+    Some(summary)
+}
+
+/// Describes how a compilation failed, from its stderr with the diagnostics rendered,
+/// so that the reductions of a test can be checked to fail in the same way.
+///
+/// An ICE is identified by its message and the location of the panic, and any other
+/// failure by its first error. The spans in the test file named `file_name` are left
+/// out, since they change as the test gets reduced.
+pub(crate) fn failure_signature(success: bool, stderr: &str, file_name: &str) -> Option<String> {
+    let spans =
+        Regex::new(&format!(r"\S*{}:\w+:\w+(: \w+:\w+)?", regex::escape(file_name))).unwrap();
+    let ice = stderr.lines().find(|line| line.starts_with("error: internal compiler error"));
+    let panic = stderr.lines().find_map(|line| Some(&line[line.find("panicked at ")?..]));
+    let signature = match (ice, panic) {
+        (None, None) if success => return None,
+        (None, None) => stderr.lines().find(|line| line.starts_with("error"))?.to_owned(),
+        (Some(ice), None) => ice.to_owned(),
+        (None, Some(panic)) => panic.to_owned(),
+        (Some(ice), Some(panic)) => format!("{ice}\n{panic}"),
+    };
+    Some(spans.replace_all(&signature, "$$SPAN").into_owned())
+}
+
+/// Reduces the test `source` while `is_interesting` holds for the reduced test.
+///
+/// The test is reduced through its syntax tree if `syn` can parse it and `is_interesting` still
+/// holds once it is printed back, and line by line otherwise. Returns an error if
+/// `is_interesting` does not hold for `source` itself.
+pub(crate) fn minimize(
+    source: &str,
+    mut is_interesting: impl FnMut(&str) -> bool,
+) -> Result<Reduced, String> {
+    let directives: String = source
+        .lines()
+        .map(str::trim_start)
+        .filter(|line| is_directive(line))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    let reason = match syn::parse_file(source) {
+        Ok(file) if is_interesting(&render(&directives, &file)) => {
+            let text = minimize_syntax_tree(&directives, file, is_interesting);
+            return Ok(Reduced { text, line_based: None });
+        }
+        Ok(_) => {
+            "the failure is lost when the test is printed back from its syntax tree".to_owned()
+        }
+        Err(e) => format!("`syn` cannot parse the test: {e}"),
+    };
+    if !is_interesting(source) {
+        return Err("the failure cannot be reproduced".to_owned());
+    }
+    Ok(Reduced { text: minimize_lines(source, is_interesting), line_based: Some(reason) })
+}
+
+fn is_directive(line: &str) -> bool {
+    line.trim_start().starts_with("//@")
+}
+
+/// Removes the items, statements, generic parameters and where clauses of `file` one at a
+/// time, and prints the reduced file back.
+fn minimize_syntax_tree(
+    directives: &str,
+    mut file: syn::File,
+    mut is_interesting: impl FnMut(&str) -> bool,
+) -> String {
+    // Removing a node may allow removing nodes which were tried before (e.g. a function
+    // and the last use of a type), so go over the nodes again until none can be removed.
+    loop {
+        let mut reduced = false;
+        let mut target = 0;
+        loop {
+            let mut candidate = file.clone();
+            let mut removal = Removal { target, seen: 0 };
+            removal.visit_file_mut(&mut candidate);
+            if target >= removal.seen {
+                break;
+            }
+            if is_interesting(&render(directives, &candidate)) {
+                // The next node now has the index of the removed one.
+                file = candidate;
+                reduced = true;
+            } else {
+                target += 1;
+            }
+        }
+        if !reduced {
+            return render(directives, &file);
+        }
+    }
+}
+
+/// Removes the lines of `source` in chunks, halving their size down to single lines, and keeps
+/// each removal after which `is_interesting` still holds. Directives are never removed. This
+/// doesn't need to parse the test, so it works for any syntax the compiler accepts.
+fn minimize_lines(source: &str, mut is_interesting: impl FnMut(&str) -> bool) -> String {
+    let render = |lines: &[&str]| lines.iter().map(|line| format!("{line}\n")).collect::<String>();
+    let mut lines: Vec<&str> = source.lines().collect();
+    let mut chunk = lines.len();
+    while chunk > 0 {
+        let mut start = 0;
+        while start < lines.len() {
+            let end = usize::min(start + chunk, lines.len());
+            let kept: Vec<&str> =
+                lines[start..end].iter().copied().filter(|line| is_directive(line)).collect();
+            let mut candidate = lines.clone();
+            candidate.splice(start..end, kept.iter().copied());
+            if kept.len() < end - start && is_interesting(&render(&candidate)) {
+                // The next chunk now starts after the directives which were kept.
+                lines = candidate;
+                start += kept.len();
+            } else {
+                start = end;
+            }
+        }
+        chunk /= 2;
+    }
+    render(&lines)
+}
+
+/// Turns the reduced `source` of one revision of a test into a test without revisions, which
+/// fails on its own: the directives of the other revisions are dropped, and the `cfg` of the
+/// revision is passed explicitly.
+pub(crate) fn single_revision(source: &str, revision: &str) -> String {
+    let mut text = String::new();
+    for line in source.lines() {
+        let Some(directive) = line.strip_prefix("//@") else {
+            writeln!(text, "{line}").unwrap();
+            continue;
+        };
+        if directive.trim_start().starts_with("revisions:") {
+            // Revisions are passed as `cfg`s the same way in `TestCx::set_revision_flags`.
+            let cfg = revision.to_lowercase().replace('-', "_");
+            writeln!(text, "//@ compile-flags: --cfg {cfg}").unwrap();
+        } else if let Some((revisions, directive)) =
+            directive.strip_prefix('[').and_then(|directive| directive.split_once(']'))
+        {
+            if revisions.split(',').any(|name| name.trim() == revision) {
+                writeln!(text, "//@{directive}").unwrap();
+            }
+        } else {
+            writeln!(text, "{line}").unwrap();
+        }
+    }
+    text
+}
+
+/// Prints a test back from its syntax tree, one item per line.
+fn render(directives: &str, file: &syn::File) -> String {
+    let mut text = directives.to_owned();
+    for attr in &file.attrs {
+        writeln!(text, "{}", attr.to_token_stream()).unwrap();
+    }
+    for item in &file.items {
+        writeln!(text, "{}", item.to_token_stream()).unwrap();
+    }
+    text
+}
+
+/// Removes the removable node with the index `target`, the nodes being numbered
+/// in the order they are visited. Counts the removable nodes in `seen`.
+struct Removal {
+    target: usize,
+    seen: usize,
+}
+
+impl Removal {
+    fn remove_from<T>(&mut self, nodes: &mut Vec<T>) {
+        let first = self.seen;
+        self.seen += nodes.len();
+        if (first..self.seen).contains(&self.target) {
+            nodes.remove(self.target - first);
+        }
+    }
+
+    fn remove_from_punctuated<T, P: Default>(&mut self, nodes: &mut Punctuated<T, P>) {
+        let mut list: Vec<T> = mem::take(nodes).into_iter().collect();
+        self.remove_from(&mut list);
+        *nodes = list.into_iter().collect();
+    }
+}
+
+impl VisitMut for Removal {
+    fn visit_file_mut(&mut self, file: &mut syn::File) {
+        self.remove_from(&mut file.items);
+        visit_mut::visit_file_mut(self, file);
+    }
+
+    fn visit_item_mod_mut(&mut self, item: &mut syn::ItemMod) {
+        if let Some((_, items)) = &mut item.content {
+            self.remove_from(items);
+        }
+        visit_mut::visit_item_mod_mut(self, item);
+    }
+
+    fn visit_item_impl_mut(&mut self, item: &mut syn::ItemImpl) {
+        self.remove_from(&mut item.items);
+        visit_mut::visit_item_impl_mut(self, item);
+    }
+
+    fn visit_item_trait_mut(&mut self, item: &mut syn::ItemTrait) {
+        self.remove_from(&mut item.items);
+        visit_mut::visit_item_trait_mut(self, item);
+    }
+
+    fn visit_block_mut(&mut self, block: &mut syn::Block) {
+        self.remove_from(&mut block.stmts);
+        visit_mut::visit_block_mut(self, block);
+    }
+
+    fn visit_generics_mut(&mut self, generics: &mut syn::Generics) {
+        self.remove_from_punctuated(&mut generics.params);
+        if let Some(where_clause) = &mut generics.where_clause {
+            self.remove_from_punctuated(&mut where_clause.predicates);
+        }
+        visit_mut::visit_generics_mut(self, generics);
+    }
+}
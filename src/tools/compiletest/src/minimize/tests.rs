@@ -0,0 +1,114 @@
+use super::{failure_signature, minimize, single_revision};
+
+#[test]
+fn ice_signature() {
+    let stderr = "\
+error: internal compiler error: /home/user/tests/crashes/123.rs:4:9: 4:12: unexpected type
+thread 'rustc' panicked at compiler/rustc_hir_typeck/src/lib.rs:42:5:
+Box<dyn Any>
+note: we would appreciate a bug report";
+    assert_eq!(
+        failure_signature(false, stderr, "123.rs").unwrap(),
+        "error: internal compiler error: $SPAN: unexpected type\n\
+         panicked at compiler/rustc_hir_typeck/src/lib.rs:42:5:",
+    );
+
+    // The same ICE in the reduced test, which is written to another directory.
+    let reduced =
+        stderr.replace("/home/user/tests/crashes/123.rs:4:9: 4:12", "$DIR/123.rs:1:2: 1:5");
+    assert_eq!(
+        failure_signature(false, &reduced, "123.rs"),
+        failure_signature(false, stderr, "123.rs")
+    );
+}
+
+#[test]
+fn error_signature() {
+    let stderr = "\
+error[E0308]: mismatched types
+ --> a.rs:3:5
+error: aborting due to 1 previous error";
+    assert_eq!(failure_signature(false, stderr, "a.rs").unwrap(), "error[E0308]: mismatched types");
+    assert_eq!(failure_signature(true, "warning: unused variable: `x`", "a.rs"), None);
+}
+
+#[test]
+fn minimize_items_statements_and_generics() {
+    let source = "\
+//@ compile-flags: -Zfoo
+// A comment which is not kept.
+struct Unused;
+trait Trait {
+    fn method(&self);
+}
+mod m {
+    pub fn helper() {}
+}
+fn crash<T: Trait, U>(x: T) where U: Copy {
+    let a = 1;
+    m::helper();
+    ice();
+    let b = a + 1;
+}
+";
+    let reduced = minimize(source, |text| text.contains("ice ()")).unwrap();
+    assert_eq!(reduced.line_based, None);
+    assert_eq!(reduced.text, "//@ compile-flags: -Zfoo\nfn crash (x : T) { ice () ; }\n");
+}
+
+#[test]
+fn minimize_keeps_what_the_failure_needs() {
+    let source = "struct S; impl S { fn a() {} fn b() {} } fn main() { S::b(); }";
+    let reduced =
+        minimize(source, |text| text.contains("fn b") && text.contains("S :: b")).unwrap();
+    assert_eq!(reduced.text, "impl S { fn b () { } }\nfn main () { S :: b () ; }\n");
+}
+
+#[test]
+fn minimize_unparsable_test_line_by_line() {
+    // `syn` doesn't know about `gen` blocks.
+    let source = "\
+//@ edition: 2024
+#![feature(gen_blocks)]
+fn unused() {}
+fn main() {
+    let a = 1;
+    let it = gen { yield a; };
+    ice();
+}
+";
+    let reduced =
+        minimize(source, |text| text.contains("gen {") && text.contains("ice()")).unwrap();
+    assert!(reduced.line_based.unwrap().starts_with("`syn` cannot parse the test"));
+    assert_eq!(reduced.text, "//@ edition: 2024\n    let it = gen { yield a; };\n    ice();\n");
+}
+
+#[test]
+fn minimize_uninteresting_test() {
+    assert!(minimize("fn main() {", |_| false).is_err());
+    assert!(minimize("fn main() {}", |_| false).is_err());
+}
+
+#[test]
+fn single_revision_drops_other_revisions() {
+    let source = "\
+//@ revisions: old-solver next
+//@ edition: 2021
+//@[next] compile-flags: -Znext-solver
+//@[old-solver,next] check-fail
+//@[old-solver] known-bug: #1
+#[cfg(next)]
+fn f() {}
+";
+    assert_eq!(
+        single_revision(source, "old-solver"),
+        "\
+//@ compile-flags: --cfg old_solver
+//@ edition: 2021
+//@ check-fail
+//@ known-bug: #1
+#[cfg(next)]
+fn f() {}
+"
+    );
+}
@@ -0,0 +1,172 @@
+//! The JSON report written with `--json-report`, recording the outcome and the timings of
+//! every test, to track slow and flaky tests over time.
+//!
+//! Each line of the report is a test event in the format of libtest's `--format json`, along
+//! with the suite, mode and revision of the test, and the time spent compiling the test
+//! (including its auxiliary crates) and running it (the compiled program, the debugger of
+//! debuginfo tests, or the recipe of run-make tests).
+
+use std::cell::Cell;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::common::Config;
+
+#[cfg(test)]
+mod tests;
+
+/// The events of the tests run or ignored so far.
+static EVENTS: Mutex<Vec<TestEvent>> = Mutex::new(Vec::new());
+
+thread_local! {
+    /// The time spent in each phase of the test running on this thread.
+    static TIMINGS: Cell<Timings> = const { Cell::new(Timings::ZERO) };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Timings {
+    compile: Duration,
+    run: Duration,
+}
+
+impl Timings {
+    const ZERO: Timings = Timings { compile: Duration::ZERO, run: Duration::ZERO };
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Phase {
+    /// Building the test and its auxiliary crates.
+    Compile,
+    /// Running the compiled test program, in a debugger for debuginfo tests, or the recipe of a
+    /// run-make test.
+    Run,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct TestEvent {
+    #[serde(rename = "type")]
+    ty: &'static str,
+    event: &'static str,
+    name: String,
+    suite: String,
+    mode: String,
+    revision: Option<String>,
+    /// Why the test was ignored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    /// The wall time of the test, in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exec_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compile_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    run_time: Option<f64>,
+}
+
+impl TestEvent {
+    fn new(config: &Config, event: &'static str, name: &str, revision: Option<&str>) -> Self {
+        TestEvent {
+            ty: "test",
+            event,
+            name: name.to_owned(),
+            suite: config.suite.clone(),
+            mode: config.mode.to_string(),
+            revision: revision.map(str::to_owned),
+            message: None,
+            exec_time: None,
+            compile_time: None,
+            run_time: None,
+        }
+    }
+}
+
+/// Runs `f`, adding the time it takes to the given phase of the current test. The time spent in
+/// nested calls only counts for their own phase, e.g. compiling the test before running it in a
+/// debugger.
+pub(crate) fn timed<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    let before = TIMINGS.with(Cell::get);
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    TIMINGS.with(|timings| {
+        let mut t = timings.get();
+        let nested = (t.compile - before.compile) + (t.run - before.run);
+        let own = elapsed.saturating_sub(nested);
+        match phase {
+            Phase::Compile => t.compile += own,
+            Phase::Run => t.run += own,
+        }
+        timings.set(t);
+    });
+    result
+}
+
+/// Runs the test `name` with `run_test`, recording its outcome and timings.
+pub(crate) fn run_test(
+    config: &Config,
+    name: &str,
+    revision: Option<&str>,
+    should_panic: bool,
+    run_test: impl FnOnce(),
+) {
+    if config.json_report.is_none() {
+        return run_test();
+    }
+
+    TIMINGS.with(|timings| timings.set(Timings::ZERO));
+    let start = Instant::now();
+    let result = panic::catch_unwind(AssertUnwindSafe(run_test));
+    let exec_time = start.elapsed();
+    let timings = TIMINGS.with(Cell::get);
+
+    let passed = result.is_ok() != should_panic;
+    let mut event = TestEvent::new(config, if passed { "ok" } else { "failed" }, name, revision);
+    event.exec_time = Some(exec_time.as_secs_f64());
+    event.compile_time = Some(timings.compile.as_secs_f64());
+    event.run_time = Some(timings.run.as_secs_f64());
+    EVENTS.lock().unwrap().push(event);
+
+    if let Err(e) = result {
+        panic::resume_unwind(e);
+    }
+}
+
+/// Records that the test `name` is ignored, unless libtest runs it anyway or filters it out.
+pub(crate) fn record_ignored(
+    config: &Config,
+    name: &str,
+    revision: Option<&str>,
+    reason: Option<&str>,
+) {
+    if config.json_report.is_none() || config.run_ignored || is_filtered_out(config, name) {
+        return;
+    }
+    let mut event = TestEvent::new(config, "ignored", name, revision);
+    event.message = reason.map(str::to_owned);
+    EVENTS.lock().unwrap().push(event);
+}
+
+/// Returns `true` if libtest does not run the test `name`, with the same rules as
+/// `test::TestOpts::filters` and `test::TestOpts::skip`.
+fn is_filtered_out(config: &Config, name: &str) -> bool {
+    let matches_filter =
+        |filter: &String| if config.filter_exact { name == filter } else { name.contains(filter) };
+    (!config.filters.is_empty() && !config.filters.iter().any(matches_filter))
+        || config.skip.iter().any(|skip| name.contains(skip))
+}
+
+/// Writes the events recorded so far to `path`, one per line.
+pub(crate) fn write(path: &Path) {
+    let mut events = EVENTS.lock().unwrap();
+    events.sort_by(|a, b| a.name.cmp(&b.name));
+    let report: String =
+        events.iter().map(|event| serde_json::to_string(event).unwrap() + "\n").collect();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).unwrap();
+    }
+    fs::write(path, report)
+        .unwrap_or_else(|e| panic!("failed to write the JSON report to {}: {e}", path.display()));
+}
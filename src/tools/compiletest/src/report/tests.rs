@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use super::{is_filtered_out, timed, Phase, TestEvent, Timings, TIMINGS};
+use crate::common::{Config, Mode};
+
+fn config() -> Config {
+    Config { mode: Mode::Ui, suite: "ui".to_owned(), ..Config::default() }
+}
+
+#[test]
+fn filters() {
+    let name = "[ui] tests/ui/traits/alias.rs#next";
+    let mut config = config();
+    assert!(!is_filtered_out(&config, name));
+
+    config.filters = vec!["traits".to_owned(), "lint".to_owned()];
+    assert!(!is_filtered_out(&config, name));
+    config.skip = vec!["alias".to_owned()];
+    assert!(is_filtered_out(&config, name));
+
+    config.skip.clear();
+    config.filter_exact = true;
+    assert!(is_filtered_out(&config, name));
+    config.filters.push(name.to_owned());
+    assert!(!is_filtered_out(&config, name));
+}
+
+#[test]
+fn events() {
+    let config = config();
+    let mut event = TestEvent::new(&config, "ok", "[ui] tests/ui/a.rs#x", Some("x"));
+    event.exec_time = Some(1.5);
+    event.compile_time = Some(1.0);
+    event.run_time = Some(0.25);
+    assert_eq!(
+        serde_json::to_string(&event).unwrap(),
+        r#"{"type":"test","event":"ok","name":"[ui] tests/ui/a.rs#x","suite":"ui","mode":"ui","revision":"x","exec_time":1.5,"compile_time":1.0,"run_time":0.25}"#
+    );
+
+    let mut event = TestEvent::new(&config, "ignored", "[ui] tests/ui/b.rs", None);
+    event.message = Some("only executed when the target is x86_64".to_owned());
+    assert_eq!(
+        serde_json::to_string(&event).unwrap(),
+        r#"{"type":"test","event":"ignored","name":"[ui] tests/ui/b.rs","suite":"ui","mode":"ui","revision":null,"message":"only executed when the target is x86_64"}"#
+    );
+}
+
+#[test]
+fn timings() {
+    TIMINGS.with(|timings| timings.set(Timings::ZERO));
+    let value = timed(Phase::Compile, || {
+        std::thread::sleep(Duration::from_millis(10));
+        42
+    });
+    assert_eq!(value, 42);
+    timed(Phase::Run, || ());
+    let timings = TIMINGS.with(|timings| timings.get());
+    assert!(timings.compile >= Duration::from_millis(10));
+    assert!(timings.run < timings.compile);
+}
+
+#[test]
+fn nested_timings() {
+    TIMINGS.with(|timings| timings.set(Timings::ZERO));
+    timed(Phase::Run, || {
+        timed(Phase::Compile, || std::thread::sleep(Duration::from_millis(20)));
+    });
+    let timings = TIMINGS.with(|timings| timings.get());
+    // The compilation is not counted twice.
+    assert!(timings.compile >= Duration::from_millis(20));
+    assert!(timings.run < Duration::from_millis(20));
+}
@@ -7,6 +7,7 @@
 use std::hash::{DefaultHasher, Hash, Hasher};
 use std::io::prelude::*;
 use std::io::{self, BufReader};
+use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, ExitStatus, Output, Stdio};
 use std::sync::Arc;
@@ -20,6 +21,7 @@
 use rustfix::{apply_suggestions, get_suggestions_from_json, Filter};
 use tracing::*;
 
+use crate::bless;
 use crate::common::{
     expected_output_path, incremental_dir, output_base_dir, output_base_name,
     output_testname_unique, Assembly, Codegen, CodegenUnits, CompareMode, Config, CoverageMap,
@@ -30,7 +32,9 @@
 use crate::compute_diff::{write_diff, write_filtered_diff};
 use crate::errors::{self, Error, ErrorKind};
 use crate::header::TestProps;
+use crate::minimize;
 use crate::read2::{read2_abbreviated, Truncated};
+use crate::report::{self, Phase};
 use crate::util::{add_dylib_path, copy_dir_all, dylib_env_var, logv, static_regex, PathBufExt};
 use crate::{extract_gdb_version, is_android_gdb_target, json, ColorConfig};
 
@@ -149,11 +153,35 @@ pub fn run(config: Arc<Config>, testpaths: &TestPaths, revision: Option<&str>) {
             };
             rev_cx.run_revision();
         }
+    } else if config.minimize && matches!(config.mode, Ui | Crashes) {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| cx.run_revision()));
+        // Crash tests pass when the compiler ICEs, other tests are minimized when they fail.
+        if result.is_ok() == (config.mode == Crashes) {
+            // A bug in the minimization must not hide how the test itself went.
+            if let Err(e) = panic::catch_unwind(AssertUnwindSafe(|| cx.minimize())) {
+                let message = e
+                    .downcast_ref::<String>()
+                    .map(String::as_str)
+                    .or_else(|| e.downcast_ref::<&str>().copied())
+                    .unwrap_or("unknown panic");
+                let test_name = crate::make_test_name(&config, testpaths, revision);
+                minimize::record(
+                    test_name.as_slice().to_owned(),
+                    Err(format!("the minimization panicked: {message}")),
+                );
+            }
+        }
+        if let Err(e) = result {
+            panic::resume_unwind(e);
+        }
     } else {
         cx.run_revision();
     }
 
-    cx.create_stamp();
+    // A dry run leaves the snapshots outdated, the test must run again.
+    if !config.bless_dry_run {
+        cx.create_stamp();
+    }
 }
 
 pub fn compute_stamp_hash(config: &Config) -> String {
@@ -241,13 +269,15 @@ fn run_revision(&self) {
         match self.config.mode {
             RunPassValgrind => self.run_valgrind_test(),
             Pretty => self.run_pretty_test(),
-            DebugInfo => self.run_debuginfo_test(),
+            // Debuggers and run-make recipes run the test, the compilations they do are timed
+            // on their own.
+            DebugInfo => report::timed(Phase::Run, || self.run_debuginfo_test()),
             Codegen => self.run_codegen_test(),
             Rustdoc => self.run_rustdoc_test(),
             RustdocJson => self.run_rustdoc_json_test(),
             CodegenUnits => self.run_codegen_units_test(),
             Incremental => self.run_incremental_test(),
-            RunMake => self.run_rmake_test(),
+            RunMake => report::timed(Phase::Run, || self.run_rmake_test()),
             Ui => self.run_ui_test(),
             MirOpt => self.run_mir_opt_test(),
             Assembly => self.run_assembly_test(),
@@ -374,6 +404,53 @@ fn run_crash_test(&self) {
         }
     }
 
+    /// Reduces the test while its compilation fails in the same way, and records the outcome
+    /// for the summary printed once all the tests have run.
+    fn minimize(&self) {
+        let test_name = crate::make_test_name(self.config, self.testpaths, self.revision);
+        let outcome = self.minimize_into(&self.output_base_dir().join("minimize"));
+        minimize::record(test_name.as_slice().to_owned(), outcome);
+    }
+
+    /// Writes the reduced test to `dir`.
+    fn minimize_into(&self, dir: &Path) -> Result<minimize::Written, String> {
+        remove_and_create_dir_all(dir);
+        let file_name = self.testpaths.file.file_name().unwrap();
+        let input = dir.join(file_name);
+        let failure_signature = |source: &str| {
+            fs::write(&input, source).unwrap();
+            let rustc = self.make_compile_args(
+                &input,
+                TargetLocation::ThisDirectory(dir.to_owned()),
+                self.should_emit_metadata(self.pass_mode()),
+                if self.config.mode == Ui { AllowUnused::Yes } else { AllowUnused::No },
+                LinkToAux::Yes,
+                Vec::new(),
+            );
+            let res = self.compose_and_run_compiler(rustc, None, self.testpaths);
+            minimize::failure_signature(
+                res.status.success(),
+                &json::extract_rendered(&res.stderr),
+                &file_name.to_string_lossy(),
+            )
+        };
+
+        let source = self.load_expected_output_from_path(&self.testpaths.file)?;
+        let Some(failure) = failure_signature(&source) else {
+            return Err("its compilation does not fail".to_owned());
+        };
+        let minimize::Reduced { mut text, line_based } =
+            minimize::minimize(&source, |candidate| {
+                failure_signature(candidate).as_ref() == Some(&failure)
+            })?;
+        if let Some(revision) = self.revision {
+            text = minimize::single_revision(&text, revision);
+        }
+        fs::write(&input, &text).unwrap();
+        let lines = (source.lines().count(), text.lines().count());
+        Ok(minimize::Written { path: input, lines, line_based })
+    }
+
     fn run_rfail_test(&self) {
         let pm = self.pass_mode();
         let should_run = self.run_if_enabled();
@@ -584,7 +661,7 @@ fn run_pretty_test(&self) {
                 }
                 None => &self.testpaths.file,
             };
-            fs::write(filepath, &actual).unwrap();
+            self.bless_file(filepath, Some(&actual));
         }
 
         // If we're only making sure that the output matches then just stop here
@@ -1679,7 +1756,7 @@ fn exec_compiled_test_general(
             }
         };
 
-        let proc_res = match &*self.config.target {
+        let proc_res = report::timed(Phase::Run, || match &*self.config.target {
             // This is pretty similar to below, we're transforming:
             //
             //      program arg1 arg2
@@ -1754,7 +1831,7 @@ fn exec_compiled_test_general(
                     None,
                 )
             }
-        };
+        });
 
         if delete_after_success && proc_res.status.success() {
             // delete the executable after running it to save space.
@@ -1860,17 +1937,19 @@ fn compose_and_run_compiler(
         input: Option<String>,
         root_testpaths: &TestPaths,
     ) -> ProcRes {
-        let aux_dir = self.aux_output_dir();
-        self.build_all_auxiliary(root_testpaths, &aux_dir, &mut rustc);
-
-        rustc.envs(self.props.rustc_env.clone());
-        self.props.unset_rustc_env.iter().fold(&mut rustc, Command::env_remove);
-        self.compose_and_run(
-            rustc,
-            self.config.compile_lib_path.to_str().unwrap(),
-            Some(aux_dir.to_str().unwrap()),
-            input,
-        )
+        report::timed(Phase::Compile, || {
+            let aux_dir = self.aux_output_dir();
+            self.build_all_auxiliary(root_testpaths, &aux_dir, &mut rustc);
+
+            rustc.envs(self.props.rustc_env.clone());
+            self.props.unset_rustc_env.iter().fold(&mut rustc, Command::env_remove);
+            self.compose_and_run(
+                rustc,
+                self.config.compile_lib_path.to_str().unwrap(),
+                Some(aux_dir.to_str().unwrap()),
+                input,
+            )
+        })
     }
 
     /// Builds an aux dependency.
@@ -2081,10 +2160,12 @@ fn make_compile_args(
             home::cargo_home().expect("failed to find cargo home").to_str().unwrap()
         ));
         // Similarly, vendored sources shouldn't be shown when running from a dist tarball.
-        rustc.arg("-Z").arg(format!(
-            "ignore-directory-in-diagnostics-source-blocks={}",
-            self.config.find_rust_src_root().unwrap().join("vendor").display(),
-        ));
+        if let Some(root) = self.config.find_rust_src_root() {
+            rustc.arg("-Z").arg(format!(
+                "ignore-directory-in-diagnostics-source-blocks={}",
+                root.join("vendor").display(),
+            ));
+        }
 
         // Optionally prevent default --sysroot if specified in test compile-flags.
         if !self.props.compile_flags.iter().any(|flag| flag.starts_with("--sysroot"))
@@ -2706,7 +2787,10 @@ fn run_rustdoc_test(&self) {
             let root = self.config.find_rust_src_root().unwrap();
             let mut cmd = Command::new(&self.config.python);
             cmd.arg(root.join("src/etc/htmldocck.py")).arg(&out_dir).arg(&self.testpaths.file);
-            if self.config.bless {
+            // htmldocck writes the blessed files itself.
+            if self.config.bless_dry_run {
+                bless::BLESS_LOG.lock().unwrap().skip_dry_run(&self.config.suite);
+            } else if self.config.bless {
                 cmd.arg("--bless");
             }
             let res = self.run_command_to_procres(&mut cmd);
@@ -3357,7 +3441,9 @@ fn run_rmake_legacy_test(&self) {
             cmd.env("RUSTFLAGS", "-Ctarget-feature=-crt-static").env("IS_MUSL_HOST", "1");
         }
 
-        if self.config.bless {
+        if self.config.bless_dry_run {
+            bless::BLESS_LOG.lock().unwrap().skip_dry_run(&self.config.suite);
+        } else if self.config.bless {
             cmd.env("RUSTC_BLESS_TEST", "--bless");
             // Assume this option is active if the environment variable is "defined", with _any_ value.
             // As an example, a `Makefile` can use this option by:
@@ -3632,7 +3718,7 @@ fn run_rmake_v2_test(&self) {
         }
 
         // Now run rustc to build the recipe.
-        let res = self.run_command_to_procres(&mut rustc);
+        let res = report::timed(Phase::Compile, || self.run_command_to_procres(&mut rustc));
         if !res.status.success() {
             self.fatal_proc_rec("run-make test failed: could not build `rmake.rs` recipe", &res);
         }
@@ -3728,7 +3814,9 @@ fn run_rmake_v2_test(&self) {
             cmd.env("RUSTFLAGS", "-Ctarget-feature=-crt-static").env("IS_MUSL_HOST", "1");
         }
 
-        if self.config.bless {
+        if self.config.bless_dry_run {
+            bless::BLESS_LOG.lock().unwrap().skip_dry_run(&self.config.suite);
+        } else if self.config.bless {
             cmd.env("RUSTC_BLESS_TEST", "--bless");
             // Assume this option is active if the environment variable is "defined", with _any_ value.
             // As an example, a `Makefile` can use this option by:
@@ -4123,6 +4211,9 @@ fn run_ui_test(&self) {
             if !res.status.success() {
                 self.fatal_proc_rec("failed to compile fixed code", &res);
             }
+            if self.props.check_fixed_idempotent {
+                self.check_fixed_idempotent(&res);
+            }
             if !res.stderr.is_empty()
                 && !self.props.rustfix_only_machine_applicable
                 && !json::rustfix_diagnostics_only(&res.stderr).is_empty()
@@ -4132,6 +4223,47 @@ fn run_ui_test(&self) {
         }
     }
 
+    /// Checks that the fixed code of a `run-rustfix` test, compiled into `res`, gets no
+    /// further machine-applicable suggestions, reporting the remaining ones otherwise.
+    fn check_fixed_idempotent(&self, res: &ProcRes) {
+        let suggestions = get_suggestions_from_json(
+            &json::rustfix_diagnostics_only(&res.stderr),
+            &HashSet::new(),
+            Filter::MachineApplicableOnly,
+        )
+        .unwrap();
+        if suggestions.is_empty() {
+            return;
+        }
+
+        let fixed_code =
+            self.load_expected_output_from_path(&self.expected_output_path(UI_FIXED)).unwrap();
+        let mut message = format!(
+            "the fixed code still gets {} machine-applicable suggestions:",
+            suggestions.len()
+        );
+        for suggestion in &suggestions {
+            message.push_str(&format!("\n    {}", suggestion.message));
+            for replacement in suggestion.solutions.iter().flat_map(|s| &s.replacements) {
+                let snippet = &replacement.snippet;
+                message.push_str(&format!(
+                    "\n        {}:{}:{}: replace `{}` with `{}`",
+                    snippet.file_name,
+                    snippet.line_range.start.line,
+                    snippet.line_range.start.column,
+                    fixed_code.get(snippet.range.clone()).unwrap_or_default(),
+                    replacement.replacement,
+                ));
+            }
+        }
+        // Suggestions undoing each other are the usual culprit, point them out.
+        let unfixed_code = self.load_expected_output_from_path(&self.testpaths.file).unwrap();
+        if apply_suggestions(&fixed_code, &suggestions).is_ok_and(|code| code == unfixed_code) {
+            message.push_str("\napplying them reverts the fixes, the suggestions flip-flop");
+        }
+        self.fatal_proc_rec(&message, res);
+    }
+
     fn run_mir_opt_test(&self) {
         let pm = self.pass_mode();
         let should_run = self.should_run(pm);
@@ -4166,16 +4298,18 @@ fn check_mir_dump(&self, test_info: MiroptTest) {
 
         let MiroptTest { run_filecheck, suffix, files, passes: _ } = test_info;
 
+        // When blessing, the dumps which are not produced anymore are deleted.
+        let mut stale_files = HashSet::new();
         if self.config.bless {
             for e in
                 glob(&format!("{}/{}.*{}.mir", test_dir.display(), test_crate, suffix)).unwrap()
             {
-                std::fs::remove_file(e.unwrap()).unwrap();
+                stale_files.insert(e.unwrap());
             }
             for e in
                 glob(&format!("{}/{}.*{}.diff", test_dir.display(), test_crate, suffix)).unwrap()
             {
-                std::fs::remove_file(e.unwrap()).unwrap();
+                stale_files.insert(e.unwrap());
             }
         }
 
@@ -4204,8 +4338,8 @@ fn check_mir_dump(&self, test_info: MiroptTest) {
             };
 
             if self.config.bless {
-                let _ = std::fs::remove_file(&expected_file);
-                std::fs::write(expected_file, dumped_string.as_bytes()).unwrap();
+                stale_files.remove(&expected_file);
+                self.bless_file(&expected_file, Some(&dumped_string));
             } else {
                 if !expected_file.exists() {
                     panic!("Output file `{}` from test does not exist", expected_file.display());
@@ -4221,6 +4355,10 @@ fn check_mir_dump(&self, test_info: MiroptTest) {
             }
         }
 
+        for stale_file in stale_files {
+            self.bless_file(&stale_file, None);
+        }
+
         if run_filecheck {
             let output_path = self.output_base_name().with_extension("mir");
             let proc_res = self.verify_with_filecheck(&output_path);
@@ -4493,6 +4631,22 @@ fn load_expected_output_from_path(&self, path: &Path) -> Result<String, String>
         })
     }
 
+    /// Writes a blessed snapshot file, or deletes it if `contents` is `None`. The change is
+    /// listed in the summary printed once all tests have run.
+    fn bless_file(&self, path: &Path, contents: Option<&str>) {
+        let blesser = bless::Blesser {
+            suite: self.config.suite.clone(),
+            revision: self.revision.map(str::to_owned),
+            test_name: crate::make_test_name(self.config, self.testpaths, self.revision)
+                .to_string(),
+        };
+        // Release the lock before `fatal` unwinds, so that other tests can still bless.
+        let result = bless::BLESS_LOG.lock().unwrap().bless(self.config, &blesser, path, contents);
+        if let Err(e) = result {
+            self.fatal(&e);
+        }
+    }
+
     fn delete_file(&self, file: &PathBuf) {
         if !file.exists() {
             // Deleting a nonexistent file would error.
@@ -4559,6 +4713,17 @@ fn compare_output(&self, kind: &str, actual: &str, expected: &str) -> usize {
             .with_extra_extension(mode)
             .with_extra_extension(kind);
 
+        if actual.is_empty() {
+            self.delete_file(&output_file);
+        } else if let Err(err) = fs::write(&output_file, &actual) {
+            self.fatal(&format!(
+                "failed to write {} to `{}`: {}",
+                kind,
+                output_file.display(),
+                err,
+            ));
+        }
+
         let mut files = vec![output_file];
         if self.config.bless {
             // Delete non-revision .stderr/.stdout file if revisions are used.
@@ -4566,26 +4731,17 @@ fn compare_output(&self, kind: &str, actual: &str, expected: &str) -> usize {
             if self.revision.is_some() {
                 let old =
                     expected_output_path(self.testpaths, None, &self.config.compare_mode, kind);
-                self.delete_file(&old);
+                self.bless_file(&old, None);
             }
-            files.push(expected_output_path(
+            let expected_file = expected_output_path(
                 self.testpaths,
                 self.revision,
                 &self.config.compare_mode,
                 kind,
-            ));
-        }
-
-        for output_file in &files {
-            if actual.is_empty() {
-                self.delete_file(output_file);
-            } else if let Err(err) = fs::write(&output_file, &actual) {
-                self.fatal(&format!(
-                    "failed to write {} to `{}`: {}",
-                    kind,
-                    output_file.display(),
-                    err,
-                ));
+            );
+            self.bless_file(&expected_file, Some(actual).filter(|actual| !actual.is_empty()));
+            if !self.config.bless_dry_run {
+                files.push(expected_file);
             }
         }
 
@@ -4626,7 +4782,7 @@ fn check_and_prune_duplicate_outputs(
                 match (bless, require_same, is_duplicate) {
                     // If we're blessing and the output is the same, then delete the file.
                     (true, _, true) => {
-                        self.delete_file(&examined_path);
+                        self.bless_file(&examined_path, None);
                     }
                     // If we want them to be the same, but they are different, then error.
                     // We do this wether we bless or not
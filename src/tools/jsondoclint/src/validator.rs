@@ -196,6 +196,7 @@ fn check_variant(&mut self, x: &'a Variant, id: &'a Id) {
     fn check_function(&mut self, x: &'a Function) {
         self.check_generics(&x.generics);
         self.check_fn_decl(&x.decl);
+        x.references.iter().flatten().for_each(|i| self.add_any_id(i));
     }
 
     fn check_trait(&mut self, x: &'a Trait, id: &Id) {
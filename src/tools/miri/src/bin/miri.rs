@@ -111,7 +111,12 @@ fn after_analysis<'tcx>(
                     optimizations is usually marginal at best.");
             }
 
-            if let Some(return_code) = miri::eval_entry(tcx, entry_def_id, entry_type, config) {
+            let return_code = if config.test_session {
+                miri::eval_tests(tcx, config)
+            } else {
+                miri::eval_entry(tcx, entry_def_id, entry_type, config)
+            };
+            if let Some(return_code) = return_code {
                 std::process::exit(
                     i32::try_from(return_code).expect("Return value was too large!"),
                 );
@@ -451,6 +456,8 @@ fn main() {
                 isolation_enabled = Some(false);
             }
             miri_config.isolated_op = miri::IsolatedOp::Allow;
+        } else if arg == "-Zmiri-disable-dealloc-history" {
+            miri_config.dealloc_history = false;
         } else if arg == "-Zmiri-disable-leak-backtraces" {
             miri_config.collect_leak_backtraces = false;
         } else if arg == "-Zmiri-disable-weak-memory-emulation" {
@@ -573,6 +580,10 @@ fn main() {
             miri_config.gc_interval = interval;
         } else if let Some(param) = arg.strip_prefix("-Zmiri-measureme=") {
             miri_config.measureme_out = Some(param.to_string());
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-coverage=") {
+            miri_config.coverage_out = Some(param.into());
+        } else if arg == "-Zmiri-test-session" {
+            miri_config.test_session = true;
         } else if let Some(param) = arg.strip_prefix("-Zmiri-backtrace=") {
             miri_config.backtrace_style = match param {
                 "0" => BacktraceStyle::Off,
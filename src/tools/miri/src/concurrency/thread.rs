@@ -1,5 +1,6 @@
 //! Implements threads.
 
+use std::fmt;
 use std::mem;
 use std::num::TryFromIntError;
 use std::sync::atomic::Ordering::Relaxed;
@@ -103,7 +104,35 @@ fn timeout(self: Box<Self>, $this_timeout: &mut MiriInterpCx<$tcx>) -> InterpRes
         }
 
         Callback { $($name,)* _phantom: std::marker::PhantomData }
-    }}
+    }};
+    (
+        @capture<$tcx:lifetime $(,)? $($lft:lifetime),*> { $($name:ident: $type:ty),* $(,)? }
+        @expired = |$this:ident| $expired:block
+    ) => {{
+        struct Callback<$tcx, $($lft),*> {
+            $($name: $type,)*
+            _phantom: std::marker::PhantomData<&$tcx ()>,
+        }
+
+        impl<$tcx, $($lft),*> VisitProvenance for Callback<$tcx, $($lft),*> {
+            #[allow(unused_variables)]
+            fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+                $(
+                    self.$name.visit_provenance(visit);
+                )*
+            }
+        }
+
+        impl<$tcx, $($lft),*> TimerCallback<$tcx> for Callback<$tcx, $($lft),*> {
+            fn expired(self: Box<Self>, $this: &mut MiriInterpCx<$tcx>) -> InterpResult<$tcx> {
+                #[allow(unused_variables)]
+                let Callback { $($name,)* _phantom } = *self;
+                $expired
+            }
+        }
+
+        Callback { $($name,)* _phantom: std::marker::PhantomData }
+    }};
 }
 
 /// A thread identifier.
@@ -172,6 +201,12 @@ pub enum BlockReason {
     Futex { addr: u64 },
     /// Blocked on an InitOnce.
     InitOnce(InitOnceId),
+    /// Blocked in `epoll_wait`, waiting for a file description to become ready.
+    Epoll,
+    /// Blocked in `pause`, waiting for a signal to be delivered.
+    Signal,
+    /// Blocked in `read`, waiting for a file description to become readable.
+    Read,
 }
 
 /// The state of a thread.
@@ -265,6 +300,31 @@ pub struct Thread<'tcx> {
 pub type StackEmptyCallback<'tcx> =
     Box<dyn FnMut(&mut MiriInterpCx<'tcx>) -> InterpResult<'tcx, Poll<()>> + 'tcx>;
 
+/// Trait for callbacks that are executed when a timer set with `set_timer` expires. They do not
+/// run on any particular thread.
+pub trait TimerCallback<'tcx>: VisitProvenance {
+    fn expired(self: Box<Self>, ecx: &mut InterpCx<'tcx, MiriMachine<'tcx>>) -> InterpResult<'tcx>;
+}
+type DynTimerCallback<'tcx> = Box<dyn TimerCallback<'tcx> + 'tcx>;
+
+/// Identifies a timer set with `set_timer`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimerId(u64);
+
+/// A timer that is not tied to a blocked thread, like the one set by `alarm`. The scheduler takes
+/// it into account like the timeout of a blocked thread.
+struct Timer<'tcx> {
+    id: TimerId,
+    deadline: Instant,
+    callback: DynTimerCallback<'tcx>,
+}
+
+impl fmt::Debug for Timer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timer").field("id", &self.id).field("deadline", &self.deadline).finish()
+    }
+}
+
 impl<'tcx> Thread<'tcx> {
     /// Get the name of the current thread if it was set.
     fn thread_name(&self) -> Option<&[u8]> {
@@ -355,7 +415,7 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             last_error,
             stack,
             top_user_relevant_frame: _,
-            state: _,
+            state,
             thread_name: _,
             join_status: _,
             on_stack_empty: _, // we assume the closure captures no GC-relevant state
@@ -368,6 +428,16 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
         for frame in stack {
             frame.visit_provenance(visit)
         }
+        state.visit_provenance(visit);
+    }
+}
+
+impl VisitProvenance for ThreadState<'_> {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        match self {
+            ThreadState::Blocked { callback, .. } => callback.visit_provenance(visit),
+            ThreadState::Enabled | ThreadState::Terminated => {}
+        }
     }
 }
 
@@ -464,6 +534,10 @@ pub struct ThreadManager<'tcx> {
     thread_local_allocs: FxHashMap<(DefId, ThreadId), StrictPointer>,
     /// A flag that indicates that we should change the active thread.
     yield_active_thread: bool,
+    /// The timers that have not expired yet.
+    timers: Vec<Timer<'tcx>>,
+    /// The id of the next timer to be set.
+    next_timer_id: u64,
 }
 
 impl VisitProvenance for ThreadManager<'_> {
@@ -473,11 +547,16 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             thread_local_allocs,
             active_thread: _,
             yield_active_thread: _,
+            timers,
+            next_timer_id: _,
         } = self;
 
         for thread in threads {
             thread.visit_provenance(visit);
         }
+        for timer in timers {
+            timer.callback.visit_provenance(visit);
+        }
         for ptr in thread_local_allocs.values() {
             ptr.visit_provenance(visit);
         }
@@ -494,6 +573,8 @@ fn default() -> Self {
             threads,
             thread_local_allocs: Default::default(),
             yield_active_thread: false,
+            timers: Vec::new(),
+            next_timer_id: 0,
         }
     }
 }
@@ -576,10 +657,15 @@ pub fn get_live_thread_count(&self) -> usize {
     }
 
     /// Has the given thread terminated?
-    fn has_terminated(&self, thread_id: ThreadId) -> bool {
+    pub fn has_terminated(&self, thread_id: ThreadId) -> bool {
         self.threads[thread_id].state.is_terminated()
     }
 
+    /// Is the given thread blocked for the given reason?
+    pub fn is_blocked_on(&self, thread_id: ThreadId, reason: BlockReason) -> bool {
+        self.threads[thread_id].state.is_blocked_on(reason)
+    }
+
     /// Have all threads terminated?
     fn have_all_terminated(&self) -> bool {
         self.threads.iter().all(|thread| thread.state.is_terminated())
@@ -731,8 +817,9 @@ fn yield_active_thread(&mut self) {
         self.yield_active_thread = true;
     }
 
-    /// Get the wait time for the next timeout, or `None` if no timeout is pending.
+    /// Get the wait time for the next timeout or timer, or `None` if none is pending.
     fn next_callback_wait_time(&self, clock: &Clock) -> Option<Duration> {
+        let timers = self.timers.iter().map(|timer| timer.deadline.duration_since(clock.now()));
         self.threads
             .iter()
             .filter_map(|t| {
@@ -742,6 +829,7 @@ fn next_callback_wait_time(&self, clock: &Clock) -> Option<Duration> {
                     _ => None,
                 }
             })
+            .chain(timers)
             .min()
     }
 
@@ -812,10 +900,21 @@ fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
 
 impl<'tcx> EvalContextPrivExt<'tcx> for MiriInterpCx<'tcx> {}
 trait EvalContextPrivExt<'tcx>: MiriInterpCxExt<'tcx> {
-    /// Execute a timeout callback on the callback's thread.
+    /// Execute a timeout callback on the callback's thread, or the callback of an expired timer.
     #[inline]
     fn run_timeout_callback(&mut self) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
+        let clock = &this.machine.clock;
+        if let Some(idx) = this
+            .machine
+            .threads
+            .timers
+            .iter()
+            .position(|timer| timer.deadline.duration_since(clock.now()) == Duration::ZERO)
+        {
+            let timer = this.machine.threads.timers.remove(idx);
+            return timer.callback.expired(this);
+        }
         let mut found_callback = None;
         // Find a blocked thread that has timed out.
         for (id, thread) in this.machine.threads.threads.iter_enumerated_mut() {
@@ -941,6 +1040,9 @@ fn start_regular_thread(
             this.machine.thread_cpu_affinity.insert(new_thread_id, cpuset);
         }
 
+        // The child inherits its parent's signal mask.
+        this.machine.signals.thread_created(old_thread_id, new_thread_id);
+
         // Perform the function pointer load in the new thread frame.
         let instance = this.get_ptr_fn(start_routine)?.as_instance()?;
 
@@ -1054,6 +1156,32 @@ fn block_thread(
         this.machine.threads.block_thread(reason, timeout, callback);
     }
 
+    /// Set a timer that invokes `callback` once `duration` has elapsed, unless it is cancelled
+    /// before. The timer is not tied to any thread, and keeps the program running while all
+    /// threads are blocked.
+    fn set_timer(
+        &mut self,
+        duration: Duration,
+        callback: impl TimerCallback<'tcx> + 'tcx,
+    ) -> TimerId {
+        let this = self.eval_context_mut();
+        let threads = &mut this.machine.threads;
+        let id = TimerId(threads.next_timer_id);
+        threads.next_timer_id = threads.next_timer_id.strict_add(1);
+        let deadline = this.machine.clock.now().add_lossy(duration);
+        threads.timers.push(Timer { id, deadline, callback: Box::new(callback) });
+        id
+    }
+
+    /// Cancel a timer. Returns the time it had left, or `None` if it already expired.
+    fn cancel_timer(&mut self, id: TimerId) -> Option<Duration> {
+        let this = self.eval_context_mut();
+        let threads = &mut this.machine.threads;
+        let idx = threads.timers.iter().position(|timer| timer.id == id)?;
+        let timer = threads.timers.remove(idx);
+        Some(timer.deadline.duration_since(this.machine.clock.now()))
+    }
+
     /// Put the blocked thread into the enabled state.
     /// Sanity-checks that the thread previously was blocked for the right reason.
     fn unblock_thread(&mut self, thread: ThreadId, reason: BlockReason) -> InterpResult<'tcx> {
@@ -1194,6 +1322,7 @@ fn run_threads(&mut self) -> InterpResult<'tcx, !> {
             }
             match this.machine.threads.schedule(&this.machine.clock)? {
                 SchedulingAction::ExecuteStep => {
+                    this.deliver_pending_signal()?;
                     if !this.step()? {
                         // See if this thread can do something else.
                         match this.run_on_stack_empty()? {
@@ -0,0 +1,243 @@
+//! Collection of a coverage-style report of the code executed by the interpreter.
+//!
+//! When `-Zmiri-coverage=<dir>` is set, we record which MIR basic blocks of the local crates
+//! were executed (and how often), as well as which unsupported operations (foreign functions,
+//! syscalls, ...) the program ran into. At the end of the execution, this is written out as an
+//! LCOV tracefile (which can be consumed by `genhtml`, `grcov`, codecov and friends) and a
+//! plain-text list of the unsupported operations.
+
+use std::collections::hash_map::Entry;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{CrateNum, DefId};
+use rustc_index::IndexVec;
+use rustc_middle::mir;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::{FileName, Span};
+
+use crate::*;
+
+/// Execution counts for a single function.
+struct FunctionCoverage<'tcx> {
+    /// The (polymorphic) MIR body of this function. All instances of a generic function share
+    /// the same body, so their counts are merged.
+    body: &'tcx mir::Body<'tcx>,
+    /// How often this function was called.
+    calls: u64,
+    /// How often each basic block was executed.
+    blocks: IndexVec<mir::BasicBlock, u64>,
+}
+
+impl<'tcx> FunctionCoverage<'tcx> {
+    fn new(body: &'tcx mir::Body<'tcx>) -> Self {
+        FunctionCoverage {
+            body,
+            calls: 0,
+            blocks: IndexVec::from_elem_n(0, body.basic_blocks.len()),
+        }
+    }
+}
+
+/// Global state of the coverage collection.
+pub struct CoverageState<'tcx> {
+    /// The directory the report is written to.
+    out_dir: PathBuf,
+    /// Execution counts for every function of a local crate that was entered at least once.
+    functions: FxHashMap<DefId, FunctionCoverage<'tcx>>,
+    /// The unsupported operations the program ran into, described by their error message, with
+    /// the number of times they were hit. Usually only the first one is recorded, since it stops
+    /// the execution, unless `-Zmiri-panic-on-unsupported` is set and it is a foreign function
+    /// or syscall.
+    unsupported: FxHashMap<String, u64>,
+}
+
+impl<'tcx> CoverageState<'tcx> {
+    pub fn new(out_dir: PathBuf) -> Self {
+        CoverageState {
+            out_dir,
+            functions: FxHashMap::default(),
+            unsupported: FxHashMap::default(),
+        }
+    }
+
+    /// Returns the counts for the function executing in `frame`, if it is one we are tracking.
+    fn function_mut(
+        &mut self,
+        frame: &Frame<'tcx, Provenance, FrameExtra<'tcx>>,
+        local_crates: &[CrateNum],
+    ) -> Option<&mut FunctionCoverage<'tcx>> {
+        // Shims (drop glue, vtable shims, ...) do not correspond to user-written code.
+        let ty::InstanceKind::Item(def_id) = frame.instance.def else { return None };
+        if !def_id.is_local() && !local_crates.contains(&def_id.krate) {
+            return None;
+        }
+        Some(self.functions.entry(def_id).or_insert_with(|| FunctionCoverage::new(frame.body)))
+    }
+
+    /// Called when a new stack frame was pushed.
+    pub fn record_call(
+        &mut self,
+        frame: &Frame<'tcx, Provenance, FrameExtra<'tcx>>,
+        local_crates: &[CrateNum],
+    ) {
+        if let Some(function) = self.function_mut(frame, local_crates) {
+            function.calls += 1;
+        }
+    }
+
+    /// Called when the terminator of the current basic block of `frame` is about to execute.
+    pub fn record_block(
+        &mut self,
+        frame: &Frame<'tcx, Provenance, FrameExtra<'tcx>>,
+        local_crates: &[CrateNum],
+    ) {
+        let either::Either::Left(loc) = frame.loc else { return };
+        if let Some(function) = self.function_mut(frame, local_crates) {
+            function.blocks[loc.block] += 1;
+        }
+    }
+
+    /// Called when the program performed an operation that Miri does not support.
+    pub fn record_unsupported(&mut self, error_msg: &str) {
+        if let Some(count) = self.unsupported.get_mut(error_msg) {
+            *count += 1;
+        } else {
+            self.unsupported.insert(error_msg.to_owned(), 1);
+        }
+    }
+
+    /// Adds the counts collected by `other` to ours.
+    pub fn merge(&mut self, other: CoverageState<'tcx>) {
+        for (def_id, function) in other.functions {
+            match self.functions.entry(def_id) {
+                Entry::Occupied(mut entry) => {
+                    let ours = entry.get_mut();
+                    ours.calls += function.calls;
+                    for (count, other_count) in ours.blocks.iter_mut().zip(function.blocks) {
+                        *count += other_count;
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(function);
+                }
+            }
+        }
+        for (error_msg, count) in other.unsupported {
+            *self.unsupported.entry(error_msg).or_insert(0) += count;
+        }
+    }
+
+    /// Writes the LCOV tracefile and the list of unsupported operations to the output directory.
+    pub fn write_report(&self, tcx: TyCtxt<'tcx>) -> std::io::Result<()> {
+        let crate_name =
+            tcx.sess.opts.crate_name.clone().unwrap_or_else(|| "unknown-crate".to_string());
+        // Same naming scheme as for `-Zmiri-measureme`.
+        let filename = format!("{crate_name}-{:07}", process::id());
+        fs::create_dir_all(&self.out_dir)?;
+        fs::write(self.out_dir.join(format!("{filename}.lcov")), self.render_lcov(tcx))?;
+        fs::write(self.out_dir.join(format!("{filename}.unsupported")), self.render_unsupported())?;
+        Ok(())
+    }
+
+    /// Renders the execution counts in the LCOV tracefile format, see `geninfo(1)`.
+    fn render_lcov(&self, tcx: TyCtxt<'tcx>) -> String {
+        // Functions of the crate under test that were never called should show up as
+        // uncovered, so we add them with a count of zero.
+        let mut never_called = Vec::new();
+        for def_id in tcx.hir().body_owners() {
+            let def_id = def_id.to_def_id();
+            if matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+                && !self.functions.contains_key(&def_id)
+                && tcx.is_mir_available(def_id)
+            {
+                never_called.push((def_id, FunctionCoverage::new(tcx.optimized_mir(def_id))));
+            }
+        }
+        let functions = self.functions.iter().map(|(&def_id, function)| (def_id, function));
+        let functions = functions.chain(never_called.iter().map(|(def_id, f)| (*def_id, f)));
+
+        // Group everything by source file. For each file we collect the functions (name,
+        // first line, call count) and the execution count of each line.
+        #[derive(Default)]
+        struct FileCoverage {
+            functions: Vec<(String, usize, u64)>,
+            lines: FxHashMap<usize, u64>,
+        }
+        let mut files: FxHashMap<String, FileCoverage> = FxHashMap::default();
+        for (def_id, function) in functions {
+            let Some((file, line)) = source_line(tcx, function.body.span) else { continue };
+            let name = tcx.def_path_str(def_id);
+            files.entry(file).or_default().functions.push((name, line, function.calls));
+            for (bb, data) in function.body.basic_blocks.iter_enumerated() {
+                let count = function.blocks[bb];
+                let spans = data.statements.iter().map(|stmt| stmt.source_info.span);
+                let spans = spans.chain(data.terminator.iter().map(|term| term.source_info.span));
+                for span in spans {
+                    let Some((file, line)) = source_line(tcx, span) else { continue };
+                    // A line is as covered as the most-executed block that touches it.
+                    let entry = files.entry(file).or_default().lines.entry(line).or_insert(0);
+                    *entry = (*entry).max(count);
+                }
+            }
+        }
+
+        let mut files: Vec<_> = files.into_iter().collect();
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut out = String::new();
+        for (file, mut coverage) in files {
+            writeln!(out, "TN:").unwrap();
+            writeln!(out, "SF:{file}").unwrap();
+            coverage
+                .functions
+                .sort_by(|(a, a_line, _), (b, b_line, _)| (a_line, a).cmp(&(b_line, b)));
+            for (name, line, _) in &coverage.functions {
+                writeln!(out, "FN:{line},{name}").unwrap();
+            }
+            for (name, _, calls) in &coverage.functions {
+                writeln!(out, "FNDA:{calls},{name}").unwrap();
+            }
+            writeln!(out, "FNF:{}", coverage.functions.len()).unwrap();
+            let fn_hit = coverage.functions.iter().filter(|(_, _, calls)| *calls > 0).count();
+            writeln!(out, "FNH:{fn_hit}").unwrap();
+            let mut lines: Vec<_> = coverage.lines.into_iter().collect();
+            lines.sort();
+            for (line, count) in &lines {
+                writeln!(out, "DA:{line},{count}").unwrap();
+            }
+            writeln!(out, "LF:{}", lines.len()).unwrap();
+            writeln!(out, "LH:{}", lines.iter().filter(|(_, count)| *count > 0).count()).unwrap();
+            writeln!(out, "end_of_record").unwrap();
+        }
+        out
+    }
+
+    /// Renders the unsupported operations, one per line as `<count> <error message>`,
+    /// most-hit first.
+    fn render_unsupported(&self) -> String {
+        let mut unsupported: Vec<_> = self.unsupported.iter().collect();
+        unsupported.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        let mut out = String::new();
+        for (error_msg, count) in unsupported {
+            writeln!(out, "{count} {error_msg}").unwrap();
+        }
+        out
+    }
+}
+
+/// Maps a span to the file and (1-based) line it starts on. Code from macro expansions is
+/// attributed to the macro call site. Returns `None` for spans that do not point into a real
+/// source file.
+fn source_line(tcx: TyCtxt<'_>, span: Span) -> Option<(String, usize)> {
+    let span = span.source_callsite();
+    if span.is_dummy() {
+        return None;
+    }
+    let loc = tcx.sess.source_map().lookup_char_pos(span.lo());
+    let FileName::Real(name) = &loc.file.name else { return None };
+    Some((name.local_path_if_available().display().to_string(), loc.line))
+}
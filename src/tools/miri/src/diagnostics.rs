@@ -2,7 +2,7 @@
 use std::num::NonZero;
 
 use rustc_errors::{Diag, DiagMessage, Level};
-use rustc_span::{SpanData, Symbol, DUMMY_SP};
+use rustc_span::{Span, SpanData, Symbol, DUMMY_SP};
 use rustc_target::abi::{Align, Size};
 
 use crate::borrow_tracker::stacked_borrows::diagnostics::TagHistory;
@@ -208,6 +208,72 @@ pub fn prune_stacktrace<'tcx>(
     }
 }
 
+/// Explains how the freed allocation `alloc_id` got into its current state: which thread freed it
+/// and with which backtrace, and the span of the last write to the part of it that was accessed.
+/// This is empty with `-Zmiri-disable-dealloc-history`.
+fn freed_alloc_history<'tcx>(
+    ecx: &InterpCx<'tcx, MiriMachine<'tcx>>,
+    alloc_id: AllocId,
+) -> Vec<(Option<SpanData>, String)> {
+    let freed_allocs = ecx.machine.freed_allocs.borrow();
+    let Some(freed) = freed_allocs.get(&alloc_id) else {
+        return vec![];
+    };
+    let thread_suffix = |thread: ThreadId| {
+        if thread == ecx.machine.threads.active_thread() {
+            String::new()
+        } else {
+            format!(" by thread `{}`", ecx.machine.threads.get_thread_display_name(thread))
+        }
+    };
+    let mut helps = vec![];
+    // The innermost local frame is already shown by the "deallocated here" span; only print the
+    // local frames of the backtrace if there is more to see than that.
+    let (backtrace, _pruned) = prune_stacktrace(freed.backtrace.clone(), &ecx.machine);
+    let sm = ecx.tcx.sess.source_map();
+    let frames = backtrace
+        .iter()
+        .filter(|frame| ecx.machine.is_local(frame))
+        .map(|frame| format!("{frame} at {}", sm.span_to_embeddable_string(frame.span)))
+        .collect::<Vec<_>>();
+    let other_thread = freed.thread != ecx.machine.threads.active_thread();
+    if frames.len() > 1 || (other_thread && !frames.is_empty()) {
+        helps.push(note!(
+            "{alloc_id:?} was deallocated{}, with this backtrace:\n{}",
+            thread_suffix(freed.thread),
+            frames.join("\n"),
+        ));
+    }
+    // Show the last writes to the part of the allocation that the failing access was about, or to
+    // the entire allocation if we do not know which part that is.
+    let alloc_size = freed.size.bytes();
+    let (start, end) = match ecx.machine.freed_alloc_access.get() {
+        Some((id, range)) if id == alloc_id =>
+            (range.start.bytes().min(alloc_size), range.end().bytes().min(alloc_size)),
+        _ => (0, alloc_size),
+    };
+    let (start, end) = if start < end { (start, end) } else { (0, alloc_size) };
+    let mut last_writes: Vec<(Span, ThreadId)> = vec![];
+    if start < end {
+        let range = freed.last_writes.iter(Size::from_bytes(start), Size::from_bytes(end - start));
+        for (_, &last_write) in range {
+            if let Some(last_write) = last_write
+                && !last_writes.contains(&last_write)
+            {
+                last_writes.push(last_write);
+            }
+        }
+    }
+    for (span, thread) in last_writes {
+        helps.push(note_span!(
+            span.data(),
+            "{alloc_id:?} was last written to{} here, before it was deallocated:",
+            thread_suffix(thread),
+        ));
+    }
+    helps
+}
+
 /// Emit a custom diagnostic without going through the miri-engine machinery.
 ///
 /// Returns `Some` if this was regular program termination with a given exit code and a `bool` indicating whether a leak check should happen; `None` otherwise.
@@ -363,6 +429,9 @@ pub fn report_error<'tcx>(
                         if let Some(span) = ecx.machine.deallocated_span(*alloc_id) {
                             helps.push(note_span!(span, "{:?} was deallocated here:", alloc_id));
                         }
+                        if matches!(info, PointerUseAfterFree(..)) {
+                            helps.extend(freed_alloc_history(ecx, *alloc_id));
+                        }
                     }
                     AbiMismatchArgument { .. } | AbiMismatchReturn { .. } => {
                         helps.push(note!("this means these two types are not *guaranteed* to be ABI-compatible across all targets"));
@@ -136,6 +136,9 @@ pub struct MiriConfig {
     /// If `Some`, enable the `measureme` profiler, writing results to a file
     /// with the specified prefix.
     pub measureme_out: Option<String>,
+    /// If `Some`, collect a coverage report of the executed code and the unsupported operations
+    /// that were performed, writing it to the given directory.
+    pub coverage_out: Option<PathBuf>,
     /// Panic when unsupported functionality is encountered.
     pub panic_on_unsupported: bool,
     /// Which style to use for printing backtraces.
@@ -162,10 +165,16 @@ pub struct MiriConfig {
     pub page_size: Option<u64>,
     /// Whether to collect a backtrace when each allocation is created, just in case it leaks.
     pub collect_leak_backtraces: bool,
+    /// Whether to remember where heap allocations were freed and the span of the last write to
+    /// each part of them, to explain use-after-free errors.
+    pub dealloc_history: bool,
     /// Probability for address reuse.
     pub address_reuse_rate: f64,
     /// Probability for address reuse across threads.
     pub address_reuse_cross_thread_rate: f64,
+    /// Run each `#[test]` function of a test crate in a fresh interpreter, instead of running
+    /// the libtest harness.
+    pub test_session: bool,
 }
 
 impl Default for MiriConfig {
@@ -191,6 +200,7 @@ fn default() -> MiriConfig {
             track_outdated_loads: false,
             cmpxchg_weak_failure_rate: 0.8, // 80%
             measureme_out: None,
+            coverage_out: None,
             panic_on_unsupported: false,
             backtrace_style: BacktraceStyle::Short,
             provenance_mode: ProvenanceMode::Default,
@@ -203,8 +213,10 @@ fn default() -> MiriConfig {
             num_cpus: 1,
             page_size: None,
             collect_leak_backtraces: true,
+            dealloc_history: true,
             address_reuse_rate: 0.5,
             address_reuse_cross_thread_rate: 0.1,
+            test_session: false,
         }
     }
 }
@@ -271,6 +283,13 @@ fn on_main_stack_empty(
     }
 }
 
+/// The arguments for the entry function that `init_ecx` sets up, and the place it returns to.
+pub(crate) struct EntryArgs<'tcx> {
+    pub argc: Scalar,
+    pub argv: Immediate<Provenance>,
+    pub ret_place: MPlaceTy<'tcx>,
+}
+
 /// Returns a freshly created `InterpCx`.
 /// Public because this is also used by `priroda`.
 pub fn create_ecx<'tcx>(
@@ -279,6 +298,38 @@ pub fn create_ecx<'tcx>(
     entry_type: EntryFnType,
     config: &MiriConfig,
 ) -> InterpResult<'tcx, InterpCx<'tcx, MiriMachine<'tcx>>> {
+    let (mut ecx, EntryArgs { argc, argv, ret_place }) = init_ecx(tcx, config)?;
+
+    // Setup first stack frame.
+    let entry_instance = ty::Instance::mono(tcx, entry_id);
+
+    match entry_type {
+        EntryFnType::Main { .. } => {
+            let main_ret_ty = tcx.fn_sig(entry_id).no_bound_vars().unwrap().output();
+            let main_ret_ty = main_ret_ty.no_bound_vars().unwrap();
+            let main_ptr = ecx.fn_ptr(FnVal::Instance(entry_instance));
+            let main_fn = Scalar::from_pointer(main_ptr, &ecx);
+            call_main(&mut ecx, main_fn, main_ret_ty, EntryArgs { argc, argv, ret_place })?;
+        }
+        EntryFnType::Start => {
+            ecx.call_function(
+                entry_instance,
+                Abi::Rust,
+                &[argc.into(), argv],
+                Some(&ret_place),
+                StackPopCleanup::Root { cleanup: true },
+            )?;
+        }
+    }
+
+    Ok(ecx)
+}
+
+/// Creates an `InterpCx` with the program arguments in memory, but without any stack frame.
+pub(crate) fn init_ecx<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    config: &MiriConfig,
+) -> InterpResult<'tcx, (InterpCx<'tcx, MiriMachine<'tcx>>, EntryArgs<'tcx>)> {
     let param_env = ty::ParamEnv::reveal_all();
     let layout_cx = LayoutCx { tcx, param_env };
     let mut ecx =
@@ -301,9 +352,6 @@ pub fn create_ecx<'tcx>(
         );
     }
 
-    // Setup first stack frame.
-    let entry_instance = ty::Instance::mono(tcx, entry_id);
-
     // First argument is constructed later, because it's skipped if the entry function uses #[start].
 
     // Second argument (argc): length of `config.args`.
@@ -375,57 +423,44 @@ pub fn create_ecx<'tcx>(
     // Return place (in static memory so that it does not count as leak).
     let ret_place = ecx.allocate(ecx.machine.layouts.isize, MiriMemoryKind::Machine.into())?;
     ecx.machine.main_fn_ret_place = Some(ret_place.clone());
-    // Call start function.
 
-    match entry_type {
-        EntryFnType::Main { .. } => {
-            let start_id = tcx.lang_items().start_fn().unwrap_or_else(|| {
-                tcx.dcx().fatal(
-                    "could not find start function. Make sure the entry point is marked with `#[start]`."
-                );
-            });
-            let main_ret_ty = tcx.fn_sig(entry_id).no_bound_vars().unwrap().output();
-            let main_ret_ty = main_ret_ty.no_bound_vars().unwrap();
-            let start_instance = ty::Instance::try_resolve(
-                tcx,
-                ty::ParamEnv::reveal_all(),
-                start_id,
-                tcx.mk_args(&[ty::GenericArg::from(main_ret_ty)]),
-            )
-            .unwrap()
-            .unwrap();
-
-            let main_ptr = ecx.fn_ptr(FnVal::Instance(entry_instance));
-
-            // Always using DEFAULT is okay since we don't support signals in Miri anyway.
-            // (This means we are effectively ignoring `-Zon-broken-pipe`.)
-            let sigpipe = rustc_session::config::sigpipe::DEFAULT;
-
-            ecx.call_function(
-                start_instance,
-                Abi::Rust,
-                &[
-                    Scalar::from_pointer(main_ptr, &ecx).into(),
-                    argc.into(),
-                    argv,
-                    Scalar::from_u8(sigpipe).into(),
-                ],
-                Some(&ret_place),
-                StackPopCleanup::Root { cleanup: true },
-            )?;
-        }
-        EntryFnType::Start => {
-            ecx.call_function(
-                entry_instance,
-                Abi::Rust,
-                &[argc.into(), argv],
-                Some(&ret_place),
-                StackPopCleanup::Root { cleanup: true },
-            )?;
-        }
-    }
+    Ok((ecx, EntryArgs { argc, argv, ret_place }))
+}
 
-    Ok(ecx)
+/// Pushes the stack frame of the `start` lang item, which will call the `main` function pointer
+/// `main_fn`, returning `main_ret_ty`.
+pub(crate) fn call_main<'tcx>(
+    ecx: &mut InterpCx<'tcx, MiriMachine<'tcx>>,
+    main_fn: Scalar,
+    main_ret_ty: Ty<'tcx>,
+    EntryArgs { argc, argv, ret_place }: EntryArgs<'tcx>,
+) -> InterpResult<'tcx> {
+    let tcx = ecx.tcx.tcx;
+    let start_id = tcx.lang_items().start_fn().unwrap_or_else(|| {
+        tcx.dcx().fatal(
+            "could not find start function. Make sure the entry point is marked with `#[start]`.",
+        );
+    });
+    let start_instance = ty::Instance::try_resolve(
+        tcx,
+        ty::ParamEnv::reveal_all(),
+        start_id,
+        tcx.mk_args(&[ty::GenericArg::from(main_ret_ty)]),
+    )
+    .unwrap()
+    .unwrap();
+
+    // Always using DEFAULT is okay since Miri never raises `SIGPIPE` by itself.
+    // (This means we are effectively ignoring `-Zon-broken-pipe`.)
+    let sigpipe = rustc_session::config::sigpipe::DEFAULT;
+
+    ecx.call_function(
+        start_instance,
+        Abi::Rust,
+        &[main_fn.into(), argc.into(), argv, Scalar::from_u8(sigpipe).into()],
+        Some(&ret_place),
+        StackPopCleanup::Root { cleanup: true },
+    )
 }
 
 /// Evaluates the entry function specified by `entry_id`.
@@ -450,6 +485,27 @@ pub fn eval_entry<'tcx>(
         }
     };
 
+    let return_code = run_ecx(&mut ecx, ignore_leaks);
+
+    // Write the coverage report, no matter how the execution ended.
+    if let Some(coverage) = &ecx.machine.coverage {
+        if let Err(err) = coverage.write_report(tcx) {
+            tcx.dcx().warn(format!("failed to write the coverage report: {err}"));
+        }
+    }
+
+    return_code
+}
+
+/// Runs the program set up in `ecx` until it terminates, and reports errors and leaks.
+/// Returns `Some(return_code)` if program executed completed.
+/// Returns `None` if an evaluation error occurred.
+pub(crate) fn run_ecx<'tcx>(
+    ecx: &mut InterpCx<'tcx, MiriMachine<'tcx>>,
+    ignore_leaks: bool,
+) -> Option<i64> {
+    let tcx = ecx.tcx.tcx;
+
     // Perform the main execution.
     let res: thread::Result<InterpResult<'_, !>> =
         panic::catch_unwind(AssertUnwindSafe(|| ecx.run_threads()));
@@ -463,6 +519,14 @@ pub fn eval_entry<'tcx>(
         Ok(never) => match never {},
     };
 
+    // Unsupported foreign functions and syscalls are recorded when they are called, the other
+    // unsupported operations stop the execution right away.
+    if let Some(coverage) = &mut ecx.machine.coverage {
+        if let InterpError::Unsupported(UnsupportedOpInfo::Unsupported(msg)) = res.kind() {
+            coverage.record_unsupported(msg);
+        }
+    }
+
     // Machine cleanup. Only do this if all threads have terminated; threads that are still running
     // might cause Stacked Borrows errors (https://github.com/rust-lang/miri/issues/2396).
     if ecx.have_all_terminated() {
@@ -470,11 +534,11 @@ pub fn eval_entry<'tcx>(
         // might not have joined the main thread (https://github.com/rust-lang/miri/issues/2020,
         // https://github.com/rust-lang/miri/issues/2508).
         ecx.allow_data_races_all_threads_done();
-        EnvVars::cleanup(&mut ecx).expect("error during env var cleanup");
+        EnvVars::cleanup(ecx).expect("error during env var cleanup");
     }
 
     // Process the result.
-    let (return_code, leak_check) = report_error(&ecx, res)?;
+    let (return_code, leak_check) = report_error(ecx, res)?;
     if leak_check && !ignore_leaks {
         // Check for thread leaks.
         if !ecx.have_all_terminated() {
@@ -486,7 +550,7 @@ pub fn eval_entry<'tcx>(
         info!("Additional static roots: {:?}", ecx.machine.static_roots);
         let leaks = ecx.find_leaked_allocations(&ecx.machine.static_roots);
         if !leaks.is_empty() {
-            report_leaks(&ecx, leaks);
+            report_leaks(ecx, leaks);
             tcx.dcx().note("set `MIRIFLAGS=-Zmiri-ignore-leaks` to disable this check");
             // Ignore the provided return code - let the reported error
             // determine the return code.
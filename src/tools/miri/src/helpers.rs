@@ -1136,6 +1136,9 @@ fn frame_in_std(&self) -> bool {
     /// or return an error in the Miri process context
     fn handle_unsupported_foreign_item(&mut self, error_msg: String) -> InterpResult<'tcx, ()> {
         let this = self.eval_context_mut();
+        if let Some(coverage) = &mut this.machine.coverage {
+            coverage.record_unsupported(&error_msg);
+        }
         if this.machine.panic_on_unsupported {
             // message is slightly different here to make automated analysis easier
             let error_msg = format!("unsupported Miri functionality: {error_msg}");
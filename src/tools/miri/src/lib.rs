@@ -77,6 +77,7 @@
 mod borrow_tracker;
 mod clock;
 mod concurrency;
+mod coverage;
 mod diagnostics;
 mod eval;
 mod helpers;
@@ -87,6 +88,7 @@
 mod provenance_gc;
 mod range_map;
 mod shims;
+mod test_session;
 
 // Establish a "crate-wide prelude": we often import `crate::*`.
 use rustc_middle::{bug, span_bug};
@@ -116,6 +118,7 @@
 pub use crate::shims::time::EvalContextExt as _;
 pub use crate::shims::tls::TlsData;
 pub use crate::shims::EmulateItemResult;
+pub use crate::shims::SignalEvalContextExt as _;
 
 pub use crate::alloc_addresses::{EvalContextExt as _, ProvenanceMode};
 pub use crate::alloc_bytes::MiriAllocBytes;
@@ -134,7 +137,7 @@
     sync::{CondvarId, EvalContextExt as _, MutexId, RwLockId, SynchronizationObjects},
     thread::{
         BlockReason, EvalContextExt as _, StackEmptyCallback, ThreadId, ThreadManager,
-        TimeoutAnchor, TimeoutClock, UnblockCallback,
+        TimeoutAnchor, TimeoutClock, TimerCallback, TimerId, UnblockCallback,
     },
 };
 pub use crate::diagnostics::{
@@ -153,6 +156,7 @@
 pub use crate::operator::EvalContextExt as _;
 pub use crate::provenance_gc::{EvalContextExt as _, LiveAllocs, VisitProvenance, VisitWith};
 pub use crate::range_map::RangeMap;
+pub use crate::test_session::eval_tests;
 
 /// Insert rustc arguments at the beginning of the argument list that Miri wants to be
 /// set per default, for maximal validation power.
@@ -1,7 +1,7 @@
 //! Global machine state as well as implementation of the interpreter engine
 //! `Machine` trait.
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::hash_map::Entry;
 use std::fmt;
 use std::path::Path;
@@ -62,6 +62,10 @@ pub struct FrameExtra<'tcx> {
     /// we stop unwinding, use the `CatchUnwindData` to handle catching.
     pub catch_unwind: Option<CatchUnwindData<'tcx>>,
 
+    /// If this is Some(), then this is the frame of a signal handler that was invoked by Miri.
+    /// When this frame is popped, we restore the signal mask of the interrupted code.
+    pub signal_handler: Option<shims::SignalHandlerFrame<'tcx>>,
+
     /// If `measureme` profiling is enabled, holds timing information
     /// for the start of this frame. When we finish executing this frame,
     /// we use this to register a completed event with `measureme`.
@@ -83,21 +87,35 @@ pub struct FrameExtra<'tcx> {
 impl<'tcx> std::fmt::Debug for FrameExtra<'tcx> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // Omitting `timing`, it does not support `Debug`.
-        let FrameExtra { borrow_tracker, catch_unwind, timing: _, is_user_relevant: _, salt: _ } =
-            self;
+        let FrameExtra {
+            borrow_tracker,
+            catch_unwind,
+            signal_handler,
+            timing: _,
+            is_user_relevant: _,
+            salt: _,
+        } = self;
         f.debug_struct("FrameData")
             .field("borrow_tracker", borrow_tracker)
             .field("catch_unwind", catch_unwind)
+            .field("signal_handler", signal_handler)
             .finish()
     }
 }
 
 impl VisitProvenance for FrameExtra<'_> {
     fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
-        let FrameExtra { catch_unwind, borrow_tracker, timing: _, is_user_relevant: _, salt: _ } =
-            self;
+        let FrameExtra {
+            catch_unwind,
+            signal_handler,
+            borrow_tracker,
+            timing: _,
+            is_user_relevant: _,
+            salt: _,
+        } = self;
 
         catch_unwind.visit_provenance(visit);
+        signal_handler.visit_provenance(visit);
         borrow_tracker.visit_provenance(visit);
     }
 }
@@ -327,11 +345,16 @@ pub struct AllocExtra<'tcx> {
     /// if this allocation is leakable. The backtrace is not
     /// pruned yet; that should be done before printing it.
     pub backtrace: Option<Vec<FrameInfo<'tcx>>>,
+    /// The span and thread of the last write to each part of this allocation. This is only
+    /// tracked for heap allocations, unless `-Zmiri-disable-dealloc-history` is set. When the allocation is freed,
+    /// this is moved to `MiriMachine::freed_allocs` so that use-after-free errors can show it.
+    pub last_writes: Option<RangeMap<Option<(Span, ThreadId)>>>,
 }
 
 impl VisitProvenance for AllocExtra<'_> {
     fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
-        let AllocExtra { borrow_tracker, data_race, weak_memory, backtrace: _ } = self;
+        let AllocExtra { borrow_tracker, data_race, weak_memory, backtrace: _, last_writes: _ } =
+            self;
 
         borrow_tracker.visit_provenance(visit);
         data_race.visit_provenance(visit);
@@ -339,6 +362,21 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
     }
 }
 
+/// What we remember about a freed allocation, for diagnostics.
+#[derive(Debug)]
+pub struct FreedAlloc<'tcx> {
+    /// A backtrace to where this allocation was deallocated. The backtrace is not pruned yet;
+    /// that should be done before printing it.
+    pub backtrace: Vec<FrameInfo<'tcx>>,
+    /// The thread that deallocated this allocation.
+    pub thread: ThreadId,
+    /// The size of this allocation.
+    pub size: Size,
+    /// The span and thread of the last write to each part of this allocation before it was
+    /// freed.
+    pub last_writes: RangeMap<Option<(Span, ThreadId)>>,
+}
+
 /// Precomputed layouts of primitive types
 pub struct PrimitiveLayouts<'tcx> {
     pub unit: TyAndLayout<'tcx>,
@@ -452,6 +490,8 @@ pub struct MiriMachine<'tcx> {
     pub(crate) fds: shims::FdTable,
     /// The table of directory descriptors.
     pub(crate) dirs: shims::DirTable,
+    /// Signal dispositions and the blocked and pending signals of each thread.
+    pub(crate) signals: shims::SignalState,
 
     /// This machine's monotone clock.
     pub(crate) clock: Clock,
@@ -480,6 +520,10 @@ pub struct MiriMachine<'tcx> {
     /// used with `measureme`.
     string_cache: FxHashMap<String, measureme::StringId>,
 
+    /// If `Some`, we record which code gets executed and which unsupported operations get
+    /// performed, for `-Zmiri-coverage`.
+    pub(crate) coverage: Option<coverage::CoverageState<'tcx>>,
+
     /// Cache of `Instance` exported under the given `Symbol` name.
     /// `None` means no `Instance` exported under the given name is found.
     pub(crate) exported_symbols_cache: FxHashMap<Symbol, Option<Instance<'tcx>>>,
@@ -554,6 +598,16 @@ pub struct MiriMachine<'tcx> {
     /// diagnostics.
     pub(crate) allocation_spans: RefCell<FxHashMap<AllocId, (Span, Option<Span>)>>,
 
+    /// Whether to remember where heap allocations were freed and the span of the last write to
+    /// each part of them, to explain use-after-free errors.
+    pub(crate) dealloc_history: bool,
+    /// What we remember about heap allocations that have been freed, to explain use-after-free
+    /// errors. Only used with `dealloc_history`.
+    pub(crate) freed_allocs: RefCell<FxHashMap<AllocId, FreedAlloc<'tcx>>>,
+    /// The last range of a freed allocation that a pointer was resolved to, i.e., the range a
+    /// use-after-free error is about. Only used with `dealloc_history`.
+    pub(crate) freed_alloc_access: Cell<Option<(AllocId, AllocRange)>>,
+
     /// Maps MIR consts to their evaluated result. We combine the const with a "salt" (`usize`)
     /// that is fixed per stack frame; this lets us have sometimes different results for the
     /// same const while ensuring consistent results within a single call.
@@ -648,6 +702,7 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>)
             validation: config.validation,
             fds: shims::FdTable::init(config.mute_stdout_stderr),
             dirs: Default::default(),
+            signals: Default::default(),
             layouts,
             threads,
             thread_cpu_affinity,
@@ -655,6 +710,7 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>)
             static_roots: Vec::new(),
             profiler,
             string_cache: Default::default(),
+            coverage: config.coverage_out.clone().map(coverage::CoverageState::new),
             exported_symbols_cache: FxHashMap::default(),
             panic_on_unsupported: config.panic_on_unsupported,
             backtrace_style: config.backtrace_style,
@@ -705,6 +761,9 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>)
             stack_size,
             collect_leak_backtraces: config.collect_leak_backtraces,
             allocation_spans: RefCell::new(FxHashMap::default()),
+            dealloc_history: config.dealloc_history,
+            freed_allocs: RefCell::new(FxHashMap::default()),
+            freed_alloc_access: Cell::new(None),
             const_cache: RefCell::new(FxHashMap::default()),
             symbolic_alignment: RefCell::new(FxHashMap::default()),
         }
@@ -781,6 +840,7 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             cmd_line,
             extern_statics,
             dirs,
+            signals,
             borrow_tracker,
             data_race,
             alloc_addresses,
@@ -793,6 +853,7 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             static_roots: _,
             profiler: _,
             string_cache: _,
+            coverage: _,
             exported_symbols_cache: _,
             panic_on_unsupported: _,
             backtrace_style: _,
@@ -816,6 +877,9 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             stack_size: _,
             collect_leak_backtraces: _,
             allocation_spans: _,
+            dealloc_history: _,
+            freed_allocs: _,
+            freed_alloc_access: _,
             const_cache: _,
             symbolic_alignment: _,
         } = self;
@@ -825,6 +889,7 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
         env_vars.visit_provenance(visit);
         dirs.visit_provenance(visit);
         fds.visit_provenance(visit);
+        signals.visit_provenance(visit);
         data_race.visit_provenance(visit);
         borrow_tracker.visit_provenance(visit);
         alloc_addresses.visit_provenance(visit);
@@ -1129,14 +1194,22 @@ fn init_alloc_extra(
             Some(ecx.generate_stacktrace())
         };
 
-        if matches!(kind, MemoryKind::Machine(kind) if kind.should_save_allocation_span()) {
+        let is_heap =
+            matches!(kind, MemoryKind::Machine(kind) if kind.should_save_allocation_span());
+        if is_heap {
             ecx.machine
                 .allocation_spans
                 .borrow_mut()
                 .insert(id, (ecx.machine.current_span(), None));
         }
 
-        Ok(AllocExtra { borrow_tracker, data_race, weak_memory, backtrace })
+        // Only heap allocations get a history: stack allocations are freed too often, and they get
+        // written to when they are first put in memory, which makes for confusing "last write"
+        // spans.
+        let last_writes =
+            (is_heap && ecx.machine.dealloc_history).then(|| RangeMap::new(size, None));
+
+        Ok(AllocExtra { borrow_tracker, data_race, weak_memory, backtrace, last_writes })
     }
 
     fn adjust_alloc_root_pointer(
@@ -1206,6 +1279,18 @@ fn ptr_get_alloc(
     ) -> Option<(AllocId, Size, Self::ProvenanceExtra)> {
         let rel = ecx.ptr_get_alloc(ptr, size);
 
+        if ecx.machine.dealloc_history
+            && let Some((alloc_id, offset)) = rel
+            && ecx.machine.freed_allocs.borrow().contains_key(&alloc_id)
+        {
+            // This access is about to fail; remember which part of the allocation it is about.
+            // A negative size means the access ends at `offset`.
+            let len = size.unsigned_abs();
+            let start = if size >= 0 { offset.bytes() } else { offset.bytes().saturating_sub(len) };
+            let range = alloc_range(Size::from_bytes(start), Size::from_bytes(len));
+            ecx.machine.freed_alloc_access.set(Some((alloc_id, range)));
+        }
+
         rel.map(|(alloc_id, size)| {
             let tag = match ptr.provenance {
                 Provenance::Concrete { tag, .. } => ProvenanceExtra::Concrete(tag),
@@ -1260,6 +1345,12 @@ fn before_memory_write(
         if let Some(weak_memory) = &alloc_extra.weak_memory {
             weak_memory.memory_accessed(range, machine.data_race.as_ref().unwrap());
         }
+        if let Some(last_writes) = &mut alloc_extra.last_writes {
+            let write = Some((machine.current_span(), machine.threads.active_thread()));
+            for (_, last_write) in last_writes.iter_mut(range.start, range.size) {
+                *last_write = write;
+            }
+        }
         Ok(())
     }
 
@@ -1292,6 +1383,19 @@ fn before_memory_deallocation(
         {
             *deallocated_at = Some(machine.current_span());
         }
+        if let Some(last_writes) = alloc_extra.last_writes.take() {
+            let backtrace =
+                Frame::generate_stacktrace_from_stack(machine.threads.active_thread_stack());
+            machine.freed_allocs.borrow_mut().insert(
+                alloc_id,
+                FreedAlloc {
+                    backtrace,
+                    thread: machine.threads.active_thread(),
+                    size,
+                    last_writes,
+                },
+            );
+        }
         machine.free_alloc_id(alloc_id, size, align, kind);
         Ok(())
     }
@@ -1367,6 +1471,7 @@ fn init_frame(
         let extra = FrameExtra {
             borrow_tracker: borrow_tracker.map(|bt| bt.borrow_mut().new_frame(&ecx.machine)),
             catch_unwind: None,
+            signal_handler: None,
             timing,
             is_user_relevant: ecx.machine.is_user_relevant(&frame),
             salt: ecx.machine.rng.borrow_mut().gen::<usize>() % ADDRS_PER_CONST,
@@ -1408,6 +1513,11 @@ fn before_terminator(ecx: &mut InterpCx<'tcx, Self>) -> InterpResult<'tcx> {
             ecx.run_provenance_gc();
         }
 
+        if let Some(coverage) = &mut ecx.machine.coverage {
+            let frame = ecx.machine.threads.active_thread_stack().last().unwrap();
+            coverage.record_block(frame, &ecx.machine.local_crates);
+        }
+
         // These are our preemption points.
         ecx.maybe_preempt_active_thread();
 
@@ -1419,6 +1529,10 @@ fn before_terminator(ecx: &mut InterpCx<'tcx, Self>) -> InterpResult<'tcx> {
 
     #[inline(always)]
     fn after_stack_push(ecx: &mut InterpCx<'tcx, Self>) -> InterpResult<'tcx> {
+        if let Some(coverage) = &mut ecx.machine.coverage {
+            let frame = ecx.machine.threads.active_thread_stack().last().unwrap();
+            coverage.record_call(frame, &ecx.machine.local_crates);
+        }
         if ecx.frame().extra.is_user_relevant {
             // We just pushed a local frame, so we know that the topmost local frame is the topmost
             // frame. If we push a non-local frame, there's no need to do anything.
@@ -1461,10 +1575,14 @@ fn after_stack_pop(
             // Move `frame`` into a sub-scope so we control when it will be dropped.
             let mut frame = frame;
             let timing = frame.extra.timing.take();
+            let signal_handler = frame.extra.signal_handler.take();
             let res = ecx.handle_stack_pop_unwind(frame.extra, unwinding);
             if let Some(profiler) = ecx.machine.profiler.as_ref() {
                 profiler.finish_recording_interval_event(timing.unwrap());
             }
+            if let Some(signal_handler) = signal_handler {
+                ecx.return_from_signal_handler(signal_handler)?;
+            }
             res
         };
         // Needs to be done after dropping frame to show up on the right nesting level.
@@ -47,6 +47,10 @@ fn emulate_foreign_item(
     ) -> InterpResult<'tcx, Option<(&'tcx mir::Body<'tcx>, ty::Instance<'tcx>)>> {
         let this = self.eval_context_mut();
 
+        if this.target_os_is_unix() {
+            this.check_async_signal_safety(link_name)?;
+        }
+
         // Some shims forward to other MIR bodies.
         match link_name.as_str() {
             "__rust_alloc_error_handler" => {
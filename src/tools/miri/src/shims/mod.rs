@@ -17,7 +17,7 @@
 pub mod time;
 pub mod tls;
 
-pub use unix::{DirTable, FdTable};
+pub use unix::{DirTable, FdTable, SignalEvalContextExt, SignalHandlerFrame, SignalState};
 
 /// What needs to be done after emulating an item (a shim or an intrinsic) is done.
 pub enum EmulateItemResult {
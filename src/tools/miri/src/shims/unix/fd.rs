@@ -6,9 +6,11 @@
 use std::collections::BTreeMap;
 use std::io::{self, ErrorKind, IsTerminal, Read, SeekFrom, Write};
 use std::rc::Rc;
+use std::time::Duration;
 
 use rustc_target::abi::Size;
 
+use crate::shims::unix::linux::epoll::EvalContextExt as _;
 use crate::shims::unix::*;
 use crate::*;
 
@@ -26,6 +28,14 @@ fn read<'tcx>(
         throw_unsup_format!("cannot read from {}", self.name());
     }
 
+    /// Called when a blocking `read` returned `WouldBlock`, and again when the blocked thread is
+    /// woken up. Returns how long the thread has to wait before the description is readable (zero
+    /// if it is readable now), or `None` if the read fails with `EAGAIN` instead. Only
+    /// descriptions that become readable at a known point in time, like timers, can block.
+    fn blocking_read_timeout<'tcx>(&self, _ecx: &MiriInterpCx<'tcx>) -> Option<Duration> {
+        None
+    }
+
     /// Writes as much as possible from the given buffer, and returns the number of bytes written.
     fn write<'tcx>(
         &mut self,
@@ -82,6 +92,23 @@ fn is_tty(&self, _communicate_allowed: bool) -> bool {
         // so we use a default impl here.
         false
     }
+
+    /// Returns the `EPOLL*` events (`EPOLLIN`, `EPOLLOUT`, ...) this file description is
+    /// currently ready for.
+    fn epoll_ready_events<'tcx>(&self, _ecx: &MiriInterpCx<'tcx>) -> InterpResult<'tcx, u32> {
+        throw_unsup_format!("{}: epoll does not support this file description", self.name());
+    }
+
+    /// Whether this is a regular file, which is always ready for reading and writing.
+    fn is_regular_file(&self) -> bool {
+        false
+    }
+
+    /// If this file description becomes ready by itself once some time has passed (like an armed
+    /// timer), returns how long that takes.
+    fn time_until_ready<'tcx>(&self, _ecx: &MiriInterpCx<'tcx>) -> Option<Duration> {
+        None
+    }
 }
 
 impl dyn FileDescription {
@@ -208,12 +235,27 @@ pub fn close<'ctx>(self, communicate_allowed: bool) -> InterpResult<'ctx, io::Re
             None => Ok(Ok(())),
         }
     }
+
+    /// Returns whether both refer to the same open file description.
+    pub fn is_same(&self, other: &FileDescriptor) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl VisitProvenance for FileDescriptor {
+    fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {
+        // Our file descriptions do not have any tags.
+    }
 }
 
 /// The file descriptor table
 #[derive(Debug)]
 pub struct FdTable {
     pub fds: BTreeMap<i32, FileDescriptor>,
+    /// The threads that are blocked in `epoll_wait`, and the epoll instance they are waiting on.
+    pub(crate) epoll_waiters: Vec<(ThreadId, i32)>,
+    /// The threads that are blocked in `read`, and the file description they are reading from.
+    pub(crate) read_waiters: Vec<(ThreadId, FileDescriptor)>,
 }
 
 impl VisitProvenance for FdTable {
@@ -224,7 +266,7 @@ fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {
 
 impl FdTable {
     fn new() -> Self {
-        FdTable { fds: BTreeMap::new() }
+        FdTable { fds: BTreeMap::new(), epoll_waiters: Vec::new(), read_waiters: Vec::new() }
     }
     pub(crate) fn init(mute_stdout_stderr: bool) -> FdTable {
         let mut fds = FdTable::new();
@@ -389,6 +431,8 @@ fn close(&mut self, fd_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
             return Ok(Scalar::from_i32(this.fd_not_found()?));
         };
         let result = file_descriptor.close(this.machine.communicate())?;
+        // Closing one end of a socketpair changes the readiness of the other end.
+        this.wake_epoll_waiters()?;
         // return `0` if close is successful
         let result = result.map(|()| 0i32);
         Ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
@@ -405,7 +449,8 @@ fn fd_not_found<T: From<i32>>(&mut self) -> InterpResult<'tcx, T> {
         Ok((-1).into())
     }
 
-    /// Read data from `fd` into buffer specified by `buf` and `count`.
+    /// Read data from `fd` into buffer specified by `buf` and `count`, and write the result of the
+    /// call to `dest`.
     ///
     /// If `offset` is `None`, reads data from current cursor position associated with `fd`
     /// and updates cursor position on completion. Otherwise, reads from the specified offset
@@ -416,7 +461,8 @@ fn read(
         buf: Pointer,
         count: u64,
         offset: Option<i128>,
-    ) -> InterpResult<'tcx, i64> {
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
 
         // Isolation check is done via `FileDescriptor` trait.
@@ -431,34 +477,63 @@ fn read(
         let count = count
             .min(u64::try_from(this.target_isize_max()).unwrap())
             .min(u64::try_from(isize::MAX).unwrap());
-        let communicate = this.machine.communicate();
 
         // We temporarily dup the FD to be able to retain mutable access to `this`.
         let Some(fd) = this.machine.fds.dup(fd) else {
             trace!("read: FD not found");
-            return this.fd_not_found();
+            let result = this.fd_not_found()?;
+            return this.write_scalar(Scalar::from_target_isize(result, this), dest);
+        };
+        let offset = match offset.map(u64::try_from) {
+            None => None,
+            Some(Ok(offset)) => Some(offset),
+            Some(Err(_)) => {
+                let einval = this.eval_libc("EINVAL");
+                this.set_last_error(einval)?;
+                return this.write_scalar(Scalar::from_target_isize(-1, this), dest);
+            }
         };
 
         trace!("read: FD mapped to {fd:?}");
+        this.read_from_description(fd, buf, count, offset, dest.clone())
+    }
+
+    /// Reads from the file description `fd` for `read`. If the read would block until a known
+    /// point in time, the thread is blocked until then (or until the description changes).
+    fn read_from_description(
+        &mut self,
+        fd: FileDescriptor,
+        buf: Pointer,
+        count: u64,
+        offset: Option<u64>,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let communicate = this.machine.communicate();
+
         // We want to read at most `count` bytes. We are sure that `count` is not negative
         // because it was a target's `usize`. Also we are sure that its smaller than
         // `usize::MAX` because it is bounded by the host's `isize`.
         let mut bytes = vec![0; usize::try_from(count).unwrap()];
         let result = match offset {
             None => fd.borrow_mut().read(communicate, &mut bytes, this),
-            Some(offset) => {
-                let Ok(offset) = u64::try_from(offset) else {
-                    let einval = this.eval_libc("EINVAL");
-                    this.set_last_error(einval)?;
-                    return Ok(-1);
-                };
-                fd.borrow_mut().pread(communicate, &mut bytes, offset, this)
+            Some(offset) => fd.borrow_mut().pread(communicate, &mut bytes, offset, this),
+        };
+        this.wake_epoll_waiters()?;
+
+        let result = match result? {
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                let timeout = fd.borrow().blocking_read_timeout(this);
+                if let Some(timeout) = timeout {
+                    return this.block_read(fd, buf, count, offset, dest, timeout);
+                }
+                Err(e)
             }
+            result => result,
         };
-        drop(fd);
 
         // `File::read` never returns a value larger than `count`, so this cannot fail.
-        match result?.map(|c| i64::try_from(c).unwrap()) {
+        match result.map(|c| i64::try_from(c).unwrap()) {
             Ok(read_bytes) => {
                 // If reading to `bytes` did not fail, we write those bytes to the buffer.
                 // Crucially, if fewer than `bytes.len()` bytes were read, only write
@@ -467,13 +542,90 @@ fn read(
                     buf,
                     bytes[..usize::try_from(read_bytes).unwrap()].iter().copied(),
                 )?;
-                Ok(read_bytes)
+                this.write_scalar(Scalar::from_target_isize(read_bytes, this), &dest)
             }
             Err(e) => {
                 this.set_last_error_from_io_error(e)?;
-                Ok(-1)
+                this.write_scalar(Scalar::from_target_isize(-1, this), &dest)
+            }
+        }
+    }
+
+    /// Blocks the active thread in `read` from `fd` for at most `timeout`. When it is woken up,
+    /// the read is retried if `fd` is readable by then, and the thread blocks again if it is not.
+    fn block_read(
+        &mut self,
+        fd: FileDescriptor,
+        buf: Pointer,
+        count: u64,
+        offset: Option<u64>,
+        dest: MPlaceTy<'tcx>,
+        timeout: Duration,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let active_thread = this.active_thread();
+        this.machine.fds.read_waiters.push((active_thread, fd.clone()));
+        this.block_thread(
+            BlockReason::Read,
+            Some((TimeoutClock::Monotonic, TimeoutAnchor::Relative, timeout)),
+            callback!(
+                @capture<'tcx> {
+                    fd: FileDescriptor,
+                    buf: Pointer,
+                    count: u64,
+                    offset: Option<u64>,
+                    dest: MPlaceTy<'tcx>,
+                }
+                @unblock = |this| {
+                    this.retry_blocked_read(fd, buf, count, offset, dest)
+                }
+                @timeout = |this| {
+                    let active_thread = this.active_thread();
+                    this.machine.fds.read_waiters.retain(|(thread, _)| *thread != active_thread);
+                    this.retry_blocked_read(fd, buf, count, offset, dest)
+                }
+            ),
+        );
+        Ok(())
+    }
+
+    /// Continues a `read` from `fd` after the thread was woken up. The description may have
+    /// changed in the meantime: if it will only become readable later, the thread blocks again,
+    /// and if it will not become readable by itself any more, the read fails with `EAGAIN`.
+    fn retry_blocked_read(
+        &mut self,
+        fd: FileDescriptor,
+        buf: Pointer,
+        count: u64,
+        offset: Option<u64>,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let timeout = fd.borrow().blocking_read_timeout(this);
+        match timeout {
+            None => {
+                this.set_last_error_from_io_error(ErrorKind::WouldBlock.into())?;
+                this.write_scalar(Scalar::from_target_isize(-1, this), &dest)
+            }
+            Some(timeout) if !timeout.is_zero() =>
+                this.block_read(fd, buf, count, offset, dest, timeout),
+            Some(_) => this.read_from_description(fd, buf, count, offset, dest),
+        }
+    }
+
+    /// Wakes up the threads blocked in `read` on the file description `fd` refers to, so that
+    /// they pick up a change to when it becomes readable.
+    fn wake_read_waiters(&mut self, fd: i32) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let Some(fd) = this.machine.fds.dup(fd) else { return Ok(()) };
+        // Waking up a thread can block it again, which adds it back to the waiters.
+        for (thread, description) in this.machine.fds.read_waiters.clone() {
+            if description.is_same(&fd) {
+                this.machine.fds.read_waiters.retain(|(waiter, _)| *waiter != thread);
+                this.unblock_thread(thread, BlockReason::Read)?;
             }
         }
+        Ok(())
     }
 
     fn write(
@@ -515,6 +667,7 @@ fn write(
             }
         };
         drop(fd);
+        this.wake_epoll_waiters()?;
 
         let result = result?.map(|c| i64::try_from(c).unwrap());
         this.try_unwrap_io_result(result)
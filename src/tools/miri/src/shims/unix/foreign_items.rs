@@ -92,8 +92,7 @@ fn emulate_foreign_item_inner(
                 let fd = this.read_scalar(fd)?.to_i32()?;
                 let buf = this.read_pointer(buf)?;
                 let count = this.read_target_usize(count)?;
-                let result = this.read(fd, buf, count, None)?;
-                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+                this.read(fd, buf, count, None, dest)?;
             }
             "write" => {
                 let [fd, buf, n] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -111,8 +110,7 @@ fn emulate_foreign_item_inner(
                 let buf = this.read_pointer(buf)?;
                 let count = this.read_target_usize(count)?;
                 let offset = this.read_scalar(offset)?.to_int(this.libc_ty_layout("off_t").size)?;
-                let result = this.read(fd, buf, count, Some(offset))?;
-                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+                this.read(fd, buf, count, Some(offset), dest)?;
             }
             "pwrite" => {
                 let [fd, buf, n, offset] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -131,8 +129,7 @@ fn emulate_foreign_item_inner(
                 let buf = this.read_pointer(buf)?;
                 let count = this.read_target_usize(count)?;
                 let offset = this.read_scalar(offset)?.to_int(this.libc_ty_layout("off64_t").size)?;
-                let result = this.read(fd, buf, count, Some(offset))?;
-                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+                this.read(fd, buf, count, Some(offset), dest)?;
             }
             "pwrite64" => {
                 let [fd, buf, n, offset] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -707,6 +704,66 @@ fn emulate_foreign_item_inner(
                 }
             }
 
+            // Signals
+            "sigaction" => {
+                let [signum, act, oldact] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigaction(signum, act, oldact)?;
+                this.write_scalar(result, dest)?;
+            }
+            "signal" => {
+                let [signum, handler] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.signal(signum, handler, dest)?;
+            }
+            "raise" => {
+                let [signum] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.raise(signum)?;
+                this.write_scalar(result, dest)?;
+            }
+            "kill" => {
+                let [pid, signum] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.kill(pid, signum)?;
+                this.write_scalar(result, dest)?;
+            }
+            "pthread_kill" => {
+                let [thread, signum] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.pthread_kill(thread, signum)?;
+                this.write_scalar(result, dest)?;
+            }
+            "alarm" => {
+                let [seconds] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.alarm(seconds)?;
+                this.write_scalar(result, dest)?;
+            }
+            "pause" => {
+                let [] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.pause(dest)?;
+            }
+            "sigemptyset" | "sigfillset" => {
+                let [set] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigset_init(set, link_name.as_str() == "sigfillset")?;
+                this.write_scalar(result, dest)?;
+            }
+            "sigaddset" | "sigdelset" | "sigismember" => {
+                let [set, signum] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigset_op(link_name, set, signum)?;
+                this.write_scalar(result, dest)?;
+            }
+            "pthread_sigmask" => {
+                let [how, set, oldset] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.pthread_sigmask(how, set, oldset)?;
+                this.write_scalar(result, dest)?;
+            }
+            "sigprocmask" => {
+                let [how, set, oldset] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigprocmask(how, set, oldset)?;
+                this.write_scalar(result, dest)?;
+            }
+
             // Miscellaneous
             "isatty" => {
                 let [fd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -857,13 +914,11 @@ fn emulate_foreign_item_inner(
                 this.write_null(dest)?;
             }
 
-            | "signal"
             | "sigaltstack"
             if this.frame_in_std() => {
                 let [_, _] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
                 this.write_null(dest)?;
             }
-            | "sigaction"
             | "mprotect"
             if this.frame_in_std() => {
                 let [_, _, _] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
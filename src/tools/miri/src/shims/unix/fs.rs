@@ -27,6 +27,10 @@ fn name(&self) -> &'static str {
         "file"
     }
 
+    fn is_regular_file(&self) -> bool {
+        true
+    }
+
     fn read<'tcx>(
         &mut self,
         communicate_allowed: bool,
@@ -1,6 +1,6 @@
+use std::collections::BTreeMap;
 use std::io;
-
-use rustc_data_structures::fx::FxHashMap;
+use std::time::Duration;
 
 use crate::shims::unix::*;
 use crate::*;
@@ -9,11 +9,10 @@
 #[derive(Clone, Debug, Default)]
 struct Epoll {
     /// The file descriptors we are watching, and what we are watching for.
-    file_descriptors: FxHashMap<i32, EpollEvent>,
+    file_descriptors: BTreeMap<i32, EpollEvent>,
 }
 
 /// Epoll Events associate events with data.
-/// These fields are currently unused by miri.
 /// This matches the `epoll_event` struct defined
 /// by the epoll_ctl man page. For more information
 /// see the man page:
@@ -21,12 +20,18 @@ struct Epoll {
 /// <https://man7.org/linux/man-pages/man2/epoll_ctl.2.html>
 #[derive(Clone, Debug)]
 struct EpollEvent {
-    #[allow(dead_code)]
+    /// The events we are interested in.
     events: u32,
     /// `Scalar` is used to represent the
     /// `epoll_data` type union.
-    #[allow(dead_code)]
     data: Scalar,
+    /// Set when an `EPOLLONESHOT` registration reported an event; no further events are reported
+    /// until the registration is modified with `EPOLL_CTL_MOD`.
+    disabled: bool,
+    /// For `EPOLLET` registrations, the events the file description was ready for when they were
+    /// last reported. Only a file description that became ready for another event since then is
+    /// reported again.
+    edge_ready: u32,
 }
 
 impl FileDescription for Epoll {
@@ -42,6 +47,188 @@ fn close<'tcx>(
     }
 }
 
+impl<'tcx> EvalContextPrivExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextPrivExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Returns up to `maxevents` ready file descriptors watched by `epfd`, with the events they
+    /// are ready for, their registered data and whether they were registered with `EPOLLONESHOT`.
+    /// Returns `None` if `epfd` has been closed.
+    ///
+    /// Readiness is level-triggered: a file descriptor is reported as long as it is ready. For
+    /// registrations with `EPOLLET`, it is only reported when it became ready for an event it was
+    /// not ready for when it was last reported (see `update_epoll_edges`).
+    fn epoll_ready_list(
+        &self,
+        epfd: i32,
+        maxevents: usize,
+    ) -> InterpResult<'tcx, Option<Vec<(i32, u32, Scalar, bool)>>> {
+        let this = self.eval_context_ref();
+
+        // We make a copy of the interest list, since the watched file descriptions need to be
+        // borrowed to check their readiness.
+        let Some(epoll) = this.machine.fds.get(epfd) else { return Ok(None) };
+        let watched = epoll.downcast_ref::<Epoll>().unwrap().file_descriptors.clone();
+        drop(epoll);
+
+        let always_reported = this.eval_libc_u32("EPOLLERR") | this.eval_libc_u32("EPOLLHUP");
+        let epolloneshot = this.eval_libc_u32("EPOLLONESHOT");
+        let epollet = this.eval_libc_u32("EPOLLET");
+
+        let mut ready_list = Vec::new();
+        for (fd, event) in watched {
+            if ready_list.len() == maxevents {
+                break;
+            }
+            if event.disabled {
+                continue;
+            }
+            // File descriptors that have been closed are not reported any more.
+            let Some(file_description) = this.machine.fds.dup(fd) else { continue };
+            let ready = file_description.borrow().epoll_ready_events(this)?;
+            let reported = ready & (event.events | always_reported);
+            if event.events & epollet != 0 && reported & !event.edge_ready == 0 {
+                // Nothing became ready since the last report.
+                continue;
+            }
+            if reported != 0 {
+                ready_list.push((fd, reported, event.data, event.events & epolloneshot != 0));
+            }
+        }
+        Ok(Some(ready_list))
+    }
+
+    /// Writes the events of up to `maxevents` ready file descriptors watched by `epfd` to
+    /// `events`, and returns how many were written. Returns `None` if `epfd` has been closed.
+    fn epoll_collect_ready_events(
+        &mut self,
+        epfd: i32,
+        events: Pointer,
+        maxevents: i32,
+    ) -> InterpResult<'tcx, Option<i32>> {
+        let this = self.eval_context_mut();
+
+        let maxevents = usize::try_from(maxevents).unwrap();
+        let Some(ready_list) = this.epoll_ready_list(epfd, maxevents)? else { return Ok(None) };
+
+        let event_layout = this.libc_ty_layout("epoll_event");
+        let count = i32::try_from(ready_list.len()).unwrap();
+        for (i, (fd, reported, data, oneshot)) in ready_list.into_iter().enumerate() {
+            let offset = event_layout.size.checked_mul(u64::try_from(i).unwrap(), this).unwrap();
+            let event = this.ptr_to_mplace(events.offset(offset, this)?, event_layout);
+            this.write_int(reported, &this.project_field(&event, 0)?)?;
+            this.write_scalar(data, &this.project_field(&event, 1)?)?;
+            let mut epoll = this.machine.fds.get_mut(epfd).unwrap();
+            let epoll = epoll.downcast_mut::<Epoll>().unwrap();
+            let registration = epoll.file_descriptors.get_mut(&fd).unwrap();
+            registration.edge_ready = reported;
+            if oneshot {
+                registration.disabled = true;
+            }
+        }
+        Ok(Some(count))
+    }
+
+    /// Forgets the events that the file descriptions watched with `EPOLLET` are no longer ready
+    /// for, so that they are reported again once they become ready for them again. This has to be
+    /// called whenever readiness may have changed, before the ready lists are checked.
+    fn update_epoll_edges(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let epollet = this.eval_libc_u32("EPOLLET");
+
+        let epfds: Vec<i32> = this
+            .machine
+            .fds
+            .fds
+            .iter()
+            .filter(|(_, fd)| fd.borrow().downcast_ref::<Epoll>().is_some())
+            .map(|(&epfd, _)| epfd)
+            .collect();
+        for epfd in epfds {
+            let epoll = this.machine.fds.get(epfd).unwrap();
+            let watched: Vec<i32> = epoll
+                .downcast_ref::<Epoll>()
+                .unwrap()
+                .file_descriptors
+                .iter()
+                .filter(|(_, event)| event.events & epollet != 0 && event.edge_ready != 0)
+                .map(|(&fd, _)| fd)
+                .collect();
+            drop(epoll);
+            for fd in watched {
+                let ready = match this.machine.fds.dup(fd) {
+                    Some(file_description) => file_description.borrow().epoll_ready_events(this)?,
+                    None => 0,
+                };
+                let mut epoll = this.machine.fds.get_mut(epfd).unwrap();
+                let epoll = epoll.downcast_mut::<Epoll>().unwrap();
+                epoll.file_descriptors.get_mut(&fd).unwrap().edge_ready &= ready;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reports the ready events to the program if there are any or if `deadline` has passed, and
+    /// blocks the active thread otherwise.
+    fn epoll_wait_until(
+        &mut self,
+        epfd: i32,
+        events: Pointer,
+        maxevents: i32,
+        deadline: Option<Instant>,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let Some(count) = this.epoll_collect_ready_events(epfd, events, maxevents)? else {
+            // The epoll instance was closed while we were waiting.
+            let ebadf = this.eval_libc("EBADF");
+            this.set_last_error(ebadf)?;
+            return this.write_scalar(Scalar::from_i32(-1), &dest);
+        };
+        let remaining =
+            deadline.as_ref().map(|deadline| deadline.duration_since(this.machine.clock.now()));
+        if count > 0 || remaining == Some(Duration::ZERO) {
+            return this.write_scalar(Scalar::from_i32(count), &dest);
+        }
+
+        // We need to wake up again when the first timer we are watching expires, since there is
+        // nobody who would tell us about that.
+        let epoll = this.machine.fds.get(epfd).unwrap();
+        let watched: Vec<i32> =
+            epoll.downcast_ref::<Epoll>().unwrap().file_descriptors.keys().copied().collect();
+        drop(epoll);
+        let timeout = watched
+            .into_iter()
+            .filter_map(|fd| this.machine.fds.get(fd)?.time_until_ready(this))
+            .chain(remaining)
+            .min();
+
+        let active_thread = this.active_thread();
+        this.machine.fds.epoll_waiters.push((active_thread, epfd));
+        this.block_thread(
+            BlockReason::Epoll,
+            timeout.map(|timeout| (TimeoutClock::Monotonic, TimeoutAnchor::Relative, timeout)),
+            callback!(
+                @capture<'tcx> {
+                    epfd: i32,
+                    events: Pointer,
+                    maxevents: i32,
+                    deadline: Option<Instant>,
+                    dest: MPlaceTy<'tcx>,
+                }
+                @unblock = |this| {
+                    this.epoll_wait_until(epfd, events, maxevents, deadline, dest)
+                }
+                @timeout = |this| {
+                    let active_thread = this.active_thread();
+                    this.machine.fds.epoll_waiters.retain(|&(thread, _)| thread != active_thread);
+                    this.epoll_wait_until(epfd, events, maxevents, deadline, dest)
+                }
+            ),
+        );
+        Ok(())
+    }
+}
+
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
 pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
     /// This function returns a file descriptor referring to the new `Epoll` instance. This file
@@ -93,39 +280,108 @@ fn epoll_ctl(
         let epfd = this.read_scalar(epfd)?.to_i32()?;
         let op = this.read_scalar(op)?.to_i32()?;
         let fd = this.read_scalar(fd)?.to_i32()?;
-        let _event = this.read_scalar(event)?.to_pointer(this)?;
 
         let epoll_ctl_add = this.eval_libc_i32("EPOLL_CTL_ADD");
         let epoll_ctl_mod = this.eval_libc_i32("EPOLL_CTL_MOD");
         let epoll_ctl_del = this.eval_libc_i32("EPOLL_CTL_DEL");
 
+        let Some(epoll) = this.machine.fds.get(epfd) else {
+            return Ok(Scalar::from_i32(this.fd_not_found()?));
+        };
+        let is_epoll = epoll.downcast_ref::<Epoll>().is_some();
+        drop(epoll);
+        if !is_epoll {
+            throw_unsup_format!("non-epoll FD passed to `epoll_ctl`");
+        }
+        if !this.machine.fds.is_fd(fd) {
+            return Ok(Scalar::from_i32(this.fd_not_found()?));
+        }
+        if fd == epfd {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let registered = this
+            .machine
+            .fds
+            .get(epfd)
+            .unwrap()
+            .downcast_ref::<Epoll>()
+            .unwrap()
+            .file_descriptors
+            .contains_key(&fd);
+
         if op == epoll_ctl_add || op == epoll_ctl_mod {
+            if op == epoll_ctl_add && registered {
+                let eexist = this.eval_libc("EEXIST");
+                this.set_last_error(eexist)?;
+                return Ok(Scalar::from_i32(-1));
+            }
+            if op == epoll_ctl_mod && !registered {
+                let enoent = this.eval_libc("ENOENT");
+                this.set_last_error(enoent)?;
+                return Ok(Scalar::from_i32(-1));
+            }
+
             let event = this.deref_pointer_as(event, this.libc_ty_layout("epoll_event"))?;
 
             let events = this.project_field(&event, 0)?;
             let events = this.read_scalar(&events)?.to_u32()?;
             let data = this.project_field(&event, 1)?;
             let data = this.read_scalar(&data)?;
-            let event = EpollEvent { events, data };
 
-            let Some(mut epfd) = this.machine.fds.get_mut(epfd) else {
-                return Ok(Scalar::from_i32(this.fd_not_found()?));
-            };
-            let epfd = epfd
-                .downcast_mut::<Epoll>()
-                .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_ctl`"))?;
+            let supported_events = [
+                "EPOLLIN",
+                "EPOLLOUT",
+                "EPOLLRDHUP",
+                "EPOLLPRI",
+                "EPOLLERR",
+                "EPOLLHUP",
+                "EPOLLET",
+                "EPOLLONESHOT",
+            ]
+            .iter()
+            .fold(0, |acc, name| acc | this.eval_libc_u32(name));
+            if events & !supported_events != 0 {
+                throw_unsup_format!(
+                    "epoll_ctl: events {:#x} are unsupported",
+                    events & !supported_events
+                );
+            }
 
-            epfd.file_descriptors.insert(fd, event);
+            let file_description = this.machine.fds.dup(fd).unwrap();
+            // Regular files are always ready, so Linux does not let them be watched.
+            if file_description.borrow().is_regular_file() {
+                let eperm = this.eval_libc("EPERM");
+                this.set_last_error(eperm)?;
+                return Ok(Scalar::from_i32(-1));
+            }
+            // Make sure we can actually tell when this file description is ready.
+            file_description.borrow().epoll_ready_events(this)?;
+            drop(file_description);
+
+            let mut epoll = this.machine.fds.get_mut(epfd).unwrap();
+            let epoll = epoll.downcast_mut::<Epoll>().unwrap();
+            epoll
+                .file_descriptors
+                .insert(fd, EpollEvent { events, data, disabled: false, edge_ready: 0 });
+            drop(epoll);
+
+            // The newly registered file description might already be ready, or become ready at a
+            // different time.
+            this.wake_epoll_waiters_watching(fd)?;
             Ok(Scalar::from_i32(0))
         } else if op == epoll_ctl_del {
-            let Some(mut epfd) = this.machine.fds.get_mut(epfd) else {
-                return Ok(Scalar::from_i32(this.fd_not_found()?));
-            };
-            let epfd = epfd
-                .downcast_mut::<Epoll>()
-                .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_ctl`"))?;
-
-            epfd.file_descriptors.remove(&fd);
+            if !registered {
+                let enoent = this.eval_libc("ENOENT");
+                this.set_last_error(enoent)?;
+                return Ok(Scalar::from_i32(-1));
+            }
+
+            let mut epoll = this.machine.fds.get_mut(epfd).unwrap();
+            let epoll = epoll.downcast_mut::<Epoll>().unwrap();
+            epoll.file_descriptors.remove(&fd);
             Ok(Scalar::from_i32(0))
         } else {
             let einval = this.eval_libc("EINVAL");
@@ -169,22 +425,69 @@ fn epoll_wait(
         events: &OpTy<'tcx>,
         maxevents: &OpTy<'tcx>,
         timeout: &OpTy<'tcx>,
-    ) -> InterpResult<'tcx, Scalar> {
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
 
         let epfd = this.read_scalar(epfd)?.to_i32()?;
-        let _events = this.read_scalar(events)?.to_pointer(this)?;
-        let _maxevents = this.read_scalar(maxevents)?.to_i32()?;
-        let _timeout = this.read_scalar(timeout)?.to_i32()?;
+        let events = this.read_pointer(events)?;
+        let maxevents = this.read_scalar(maxevents)?.to_i32()?;
+        let timeout = this.read_scalar(timeout)?.to_i32()?;
 
-        let Some(mut epfd) = this.machine.fds.get_mut(epfd) else {
-            return Ok(Scalar::from_i32(this.fd_not_found()?));
+        let Some(epoll) = this.machine.fds.get(epfd) else {
+            let result = this.fd_not_found()?;
+            return this.write_scalar(Scalar::from_i32(result), dest);
         };
-        let _epfd = epfd
-            .downcast_mut::<Epoll>()
-            .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_wait`"))?;
+        let is_epoll = epoll.downcast_ref::<Epoll>().is_some();
+        drop(epoll);
+        if !is_epoll {
+            throw_unsup_format!("non-epoll FD passed to `epoll_wait`");
+        }
+        if maxevents <= 0 {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return this.write_scalar(Scalar::from_i32(-1), dest);
+        }
+
+        // A negative timeout means we wait forever.
+        let deadline = u64::try_from(timeout)
+            .ok()
+            .map(|timeout| this.machine.clock.now().add_lossy(Duration::from_millis(timeout)));
+        this.epoll_wait_until(epfd, events, maxevents, deadline, dest.clone())
+    }
 
-        // FIXME return number of events ready when scheme for marking events ready exists
-        throw_unsup_format!("returning ready events from epoll_wait is not yet implemented");
+    /// Wakes up the threads blocked in `epoll_wait` whose interest list contains a file
+    /// descriptor that is ready now, or whose epoll instance has been closed. This must be called
+    /// after every operation that might change whether a file description is ready, except for
+    /// the passing of time.
+    fn wake_epoll_waiters(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        this.update_epoll_edges()?;
+        // Waking up a thread can block it again, which adds it back to the waiters.
+        for (thread, epfd) in this.machine.fds.epoll_waiters.clone() {
+            if this.epoll_ready_list(epfd, 1)?.is_none_or(|ready_list| !ready_list.is_empty()) {
+                this.machine.fds.epoll_waiters.retain(|&(waiter, _)| waiter != thread);
+                this.unblock_thread(thread, BlockReason::Epoll)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Wakes up the threads blocked in `epoll_wait` whose interest list contains `fd`, so that
+    /// they pick up a change to its registration or to when it becomes ready by itself.
+    fn wake_epoll_waiters_watching(&mut self, fd: i32) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        for (thread, epfd) in this.machine.fds.epoll_waiters.clone() {
+            let watches_fd = this.machine.fds.get(epfd).is_none_or(|epoll| {
+                epoll
+                    .downcast_ref::<Epoll>()
+                    .is_none_or(|epoll| epoll.file_descriptors.contains_key(&fd))
+            });
+            if watches_fd {
+                this.machine.fds.epoll_waiters.retain(|&(waiter, _)| waiter != thread);
+                this.unblock_thread(thread, BlockReason::Epoll)?;
+            }
+        }
+        Ok(())
     }
 }
@@ -126,6 +126,19 @@ fn write<'tcx>(
         };
         Ok(Ok(U64_ARRAY_SIZE))
     }
+
+    fn epoll_ready_events<'tcx>(&self, ecx: &MiriInterpCx<'tcx>) -> InterpResult<'tcx, u32> {
+        let mut ready = 0;
+        // A read succeeds if the counter is not zero...
+        if self.counter > 0 {
+            ready |= ecx.eval_libc_u32("EPOLLIN");
+        }
+        // ...and a write of at least 1 succeeds if the counter is not at its maximum.
+        if self.counter < MAX_COUNTER {
+            ready |= ecx.eval_libc_u32("EPOLLOUT");
+        }
+        Ok(ready)
+    }
 }
 
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
@@ -3,3 +3,4 @@
 pub mod foreign_items;
 pub mod mem;
 pub mod sync;
+pub mod timerfd;
@@ -0,0 +1,350 @@
+//! Linux `timerfd` implementation.
+use std::io;
+use std::io::{Error, ErrorKind};
+use std::mem;
+use std::time::{Duration, SystemTime};
+
+use rustc_target::abi::Endian;
+
+use crate::shims::time::system_time_to_duration;
+use crate::shims::unix::linux::epoll::EvalContextExt as _;
+use crate::shims::unix::*;
+use crate::*;
+
+// Reads return the number of expirations as a u64.
+const U64_ARRAY_SIZE: usize = mem::size_of::<u64>();
+
+/// A timer created by `timerfd_create`.
+///
+/// All points in time are represented as the time passed since the epoch of Miri's monotonic
+/// clock, so with isolation enabled timers are driven by the virtual clock.
+///
+/// <https://man7.org/linux/man-pages/man2/timerfd_create.2.html>
+#[derive(Debug)]
+struct Timer {
+    /// When the timer expires next, or `None` if it is disarmed.
+    next_expiry: Option<Duration>,
+    /// The period of the timer after the first expiration; zero for a one-shot timer.
+    interval: Duration,
+    /// The number of expirations before `next_expiry` that have not been read yet.
+    expirations: u64,
+    /// Whether this timer measures `CLOCK_REALTIME`. This only matters for absolute times.
+    realtime: bool,
+    is_nonblock: bool,
+}
+
+impl Timer {
+    /// Returns the number of unread expirations at time `now`, and when the timer expires next
+    /// after that.
+    fn state_at(&self, now: Duration) -> (u64, Option<Duration>) {
+        let Some(next_expiry) = self.next_expiry else { return (self.expirations, None) };
+        if now < next_expiry {
+            return (self.expirations, Some(next_expiry));
+        }
+        if self.interval.is_zero() {
+            return (self.expirations.saturating_add(1), None);
+        }
+        // The number of full periods that passed since `next_expiry`.
+        let periods =
+            now.saturating_sub(next_expiry).as_nanos().strict_div(self.interval.as_nanos());
+        let expirations = self
+            .expirations
+            .saturating_add(u64::try_from(periods).unwrap_or(u64::MAX))
+            .saturating_add(1);
+        // If the next expiry cannot be represented, the timer just never expires again.
+        let next_expiry = next_expiry
+            .as_nanos()
+            .checked_add(periods.saturating_add(1).saturating_mul(self.interval.as_nanos()))
+            .and_then(|nanos| u64::try_from(nanos).ok())
+            .map(Duration::from_nanos);
+        (expirations, next_expiry)
+    }
+}
+
+/// The current time as a duration since the epoch of the monotonic clock.
+fn monotonic_now(ecx: &MiriInterpCx<'_>) -> Duration {
+    ecx.machine.clock.now().duration_since(ecx.machine.clock.epoch())
+}
+
+impl FileDescription for Timer {
+    fn name(&self) -> &'static str {
+        "timerfd"
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        Ok(Ok(()))
+    }
+
+    /// Returns the number of expirations since the timer was set or last read.
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        // Check the size of slice, and return error only if the size of the slice < 8.
+        let Some(bytes) = bytes.first_chunk_mut::<U64_ARRAY_SIZE>() else {
+            return Ok(Err(Error::from(ErrorKind::InvalidInput)));
+        };
+        let (expirations, next_expiry) = self.state_at(monotonic_now(ecx));
+        if expirations == 0 {
+            if !self.is_nonblock && next_expiry.is_none() {
+                throw_unsup_format!("timerfd: blocking read of a disarmed timer is unsupported");
+            }
+            // For a blocking read, `blocking_read_timeout` tells `read` how long to wait.
+            return Ok(Err(Error::from(ErrorKind::WouldBlock)));
+        }
+        self.expirations = 0;
+        self.next_expiry = next_expiry;
+        // Return the count in the host endianness using the buffer provided by caller.
+        *bytes = match ecx.tcx.sess.target.endian {
+            Endian::Little => expirations.to_le_bytes(),
+            Endian::Big => expirations.to_be_bytes(),
+        };
+        Ok(Ok(U64_ARRAY_SIZE))
+    }
+
+    /// A blocking read waits until the next expiration. If the timer is disarmed in the meantime,
+    /// the read fails with `EAGAIN` rather than blocking forever.
+    fn blocking_read_timeout<'tcx>(&self, ecx: &MiriInterpCx<'tcx>) -> Option<Duration> {
+        if self.is_nonblock {
+            return None;
+        }
+        let now = monotonic_now(ecx);
+        let (expirations, next_expiry) = self.state_at(now);
+        if expirations > 0 { Some(Duration::ZERO) } else { Some(next_expiry?.saturating_sub(now)) }
+    }
+
+    fn epoll_ready_events<'tcx>(&self, ecx: &MiriInterpCx<'tcx>) -> InterpResult<'tcx, u32> {
+        let (expirations, _) = self.state_at(monotonic_now(ecx));
+        Ok(if expirations > 0 { ecx.eval_libc_u32("EPOLLIN") } else { 0 })
+    }
+
+    fn time_until_ready<'tcx>(&self, ecx: &MiriInterpCx<'tcx>) -> Option<Duration> {
+        let now = monotonic_now(ecx);
+        let (_, next_expiry) = self.state_at(now);
+        Some(next_expiry?.saturating_sub(now))
+    }
+}
+
+impl<'tcx> EvalContextPrivExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextPrivExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Writes the current setting of `timer` to the `itimerspec` that `curr_value` points to:
+    /// the time until the next expiration and the interval.
+    fn write_timer_setting(
+        &mut self,
+        timer: (Duration, Duration),
+        curr_value: Pointer,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let (value, interval) = timer;
+        let curr_value = this.ptr_to_mplace(curr_value, this.libc_ty_layout("itimerspec"));
+        for (field, duration) in [("it_value", value), ("it_interval", interval)] {
+            let timespec = this.project_field_named(&curr_value, field)?;
+            this.write_int_fields_named(
+                &[
+                    ("tv_sec", duration.as_secs().into()),
+                    ("tv_nsec", duration.subsec_nanos().into()),
+                ],
+                &timespec,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the current setting of the timer behind `fd` (see `write_timer_setting`), or `None`
+    /// if `fd` is not a timer.
+    fn timer_setting(&self, fd: i32) -> Option<(Duration, Duration)> {
+        let this = self.eval_context_ref();
+        let now = monotonic_now(this);
+        let timer = this.machine.fds.get(fd)?;
+        let timer = timer.downcast_ref::<Timer>()?;
+        let (_, next_expiry) = timer.state_at(now);
+        let value =
+            next_expiry.map_or(Duration::ZERO, |next_expiry| next_expiry.saturating_sub(now));
+        Some((value, timer.interval))
+    }
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// This function creates a new timer and returns a file descriptor referring to it. Reading
+    /// from the file descriptor returns the number of expirations since the timer was last set or
+    /// read, and the file descriptor is readable (for `epoll`) whenever that number is not zero.
+    ///
+    /// `CLOCK_MONOTONIC` and `CLOCK_BOOTTIME` timers use Miri's monotonic clock, which is a
+    /// virtual clock when isolation is enabled. `CLOCK_REALTIME` timers use the same clock, but
+    /// setting them to an absolute time requires isolation to be disabled.
+    ///
+    /// The `flags` may be bitwise ORed to change the behavior of `timerfd_create`:
+    /// `TFD_CLOEXEC` - Set the close-on-exec (`FD_CLOEXEC`) flag on the new file descriptor.
+    /// `TFD_NONBLOCK` - Set the `O_NONBLOCK` file status flag on the new open file description.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_create.2.html>
+    fn timerfd_create(
+        &mut self,
+        clockid: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        // timerfd is Linux specific.
+        this.assert_target_os("linux", "timerfd_create");
+
+        let clockid = this.read_scalar(clockid)?.to_i32()?;
+        let mut flags = this.read_scalar(flags)?.to_i32()?;
+
+        let realtime = if clockid == this.eval_libc_i32("CLOCK_REALTIME") {
+            true
+        } else if clockid == this.eval_libc_i32("CLOCK_MONOTONIC")
+            || clockid == this.eval_libc_i32("CLOCK_BOOTTIME")
+        {
+            false
+        } else if clockid == this.eval_libc_i32("CLOCK_REALTIME_ALARM")
+            || clockid == this.eval_libc_i32("CLOCK_BOOTTIME_ALARM")
+        {
+            throw_unsup_format!("timerfd_create: alarm clocks are unsupported");
+        } else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+
+        let tfd_cloexec = this.eval_libc_i32("TFD_CLOEXEC");
+        let tfd_nonblock = this.eval_libc_i32("TFD_NONBLOCK");
+
+        let mut is_nonblock = false;
+        // Unset the flags that we support.
+        // After unloading, flags != 0 means other flags are used.
+        if flags & tfd_cloexec == tfd_cloexec {
+            // cloexec is ignored because Miri does not support exec.
+            flags &= !tfd_cloexec;
+        }
+        if flags & tfd_nonblock == tfd_nonblock {
+            flags &= !tfd_nonblock;
+            is_nonblock = true;
+        }
+        if flags != 0 {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let fd = this.machine.fds.insert_fd(Timer {
+            next_expiry: None,
+            interval: Duration::ZERO,
+            expirations: 0,
+            realtime,
+            is_nonblock,
+        });
+        Ok(Scalar::from_i32(fd))
+    }
+
+    /// Arms or disarms the timer referred to by `fd`, and resets its expiration count. A zero
+    /// `it_value` disarms the timer. Unless `TFD_TIMER_ABSTIME` is set, `it_value` is relative to
+    /// the current time. The previous setting is written to `old_value` if it is not null.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_settime.2.html>
+    fn timerfd_settime(
+        &mut self,
+        fd: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+        new_value: &OpTy<'tcx>,
+        old_value: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let flags = this.read_scalar(flags)?.to_i32()?;
+        let new_value = this.deref_pointer_as(new_value, this.libc_ty_layout("itimerspec"))?;
+        let old_value = this.read_pointer(old_value)?;
+
+        let tfd_timer_abstime = this.eval_libc_i32("TFD_TIMER_ABSTIME");
+        let tfd_timer_cancel_on_set = this.eval_libc_i32("TFD_TIMER_CANCEL_ON_SET");
+        if flags & tfd_timer_cancel_on_set != 0 {
+            throw_unsup_format!("timerfd_settime: TFD_TIMER_CANCEL_ON_SET is unsupported");
+        }
+        if flags & !tfd_timer_abstime != 0 {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let Some(old_setting) = this.timer_setting(fd) else {
+            if this.machine.fds.is_fd(fd) {
+                let einval = this.eval_libc("EINVAL");
+                this.set_last_error(einval)?;
+                return Ok(Scalar::from_i32(-1));
+            }
+            return Ok(Scalar::from_i32(this.fd_not_found()?));
+        };
+
+        let value = this.read_timespec(&this.project_field_named(&new_value, "it_value")?)?;
+        let interval = this.read_timespec(&this.project_field_named(&new_value, "it_interval")?)?;
+        let (Some(value), Some(interval)) = (value, interval) else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+
+        if !this.ptr_is_null(old_value)? {
+            this.write_timer_setting(old_setting, old_value)?;
+        }
+
+        let now = monotonic_now(this);
+        let realtime = this.machine.fds.get(fd).unwrap().downcast_ref::<Timer>().unwrap().realtime;
+        let next_expiry = if value.is_zero() {
+            None
+        } else if flags & tfd_timer_abstime == 0 {
+            Some(now.saturating_add(value))
+        } else if realtime {
+            // Convert the absolute time to our monotonic clock.
+            this.check_no_isolation("`timerfd_settime` with an absolute `CLOCK_REALTIME` time")?;
+            let realtime_now = system_time_to_duration(&SystemTime::now())?;
+            Some(now.saturating_add(value.saturating_sub(realtime_now)))
+        } else {
+            Some(value)
+        };
+
+        let mut timer = this.machine.fds.get_mut(fd).unwrap();
+        let timer = timer.downcast_mut::<Timer>().unwrap();
+        timer.next_expiry = next_expiry;
+        timer.interval = interval;
+        timer.expirations = 0;
+        drop(timer);
+
+        // Blocked reads and `epoll_wait` calls have to pick up the new expiration time.
+        this.wake_read_waiters(fd)?;
+        this.wake_epoll_waiters_watching(fd)?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Writes the time until the next expiration and the interval of the timer referred to by
+    /// `fd` to `curr_value`.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_gettime.2.html>
+    fn timerfd_gettime(
+        &mut self,
+        fd: &OpTy<'tcx>,
+        curr_value: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let curr_value = this.read_pointer(curr_value)?;
+
+        let Some(setting) = this.timer_setting(fd) else {
+            if this.machine.fds.is_fd(fd) {
+                let einval = this.eval_libc("EINVAL");
+                this.set_last_error(einval)?;
+                return Ok(Scalar::from_i32(-1));
+            }
+            return Ok(Scalar::from_i32(this.fd_not_found()?));
+        };
+        this.write_timer_setting(setting, curr_value)?;
+        Ok(Scalar::from_i32(0))
+    }
+}
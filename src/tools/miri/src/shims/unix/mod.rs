@@ -4,6 +4,7 @@
 mod fd;
 mod fs;
 mod mem;
+mod signal;
 mod socket;
 mod sync;
 mod thread;
@@ -17,6 +18,7 @@
 pub use env::UnixEnvVars;
 pub use fd::{FdTable, FileDescription};
 pub use fs::DirTable;
+pub use signal::{EvalContextExt as SignalEvalContextExt, SignalHandlerFrame, SignalState};
 // All the Unix-specific extension traits
 pub use env::EvalContextExt as _;
 pub use fd::EvalContextExt as _;
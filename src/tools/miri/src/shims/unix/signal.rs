@@ -0,0 +1,854 @@
+//! Emulation of POSIX signals.
+//!
+//! We keep track of the disposition of each signal as set by `sigaction`/`signal`, and of the
+//! blocked and pending signals of each thread. Signals can only be raised by the program itself
+//! (`raise`, `kill` on the own process, `pthread_kill`, and `alarm`).
+//!
+//! Pending signals are delivered to their thread when that thread is about to start executing a
+//! basic block: the handler is pushed as a new stack frame, and when it returns, execution resumes
+//! at the beginning of that block. In particular, a signal raised by `raise` is handled before
+//! `raise` returns to its caller, like on a real system. Apart from `pause`, which waits for a
+//! signal, blocking operations are never interrupted: a signal sent to a blocked thread is only
+//! handled once the thread is unblocked, and no other operation ever fails with `EINTR`.
+//!
+//! While a thread is running a signal handler, calling a function that is not async-signal-safe is
+//! reported as Undefined Behavior.
+
+use std::time::Duration;
+
+use rustc_ast::expand::allocator::AllocatorKind;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::mir;
+use rustc_span::Symbol;
+use rustc_target::abi::Endian;
+use rustc_target::spec::abi::Abi;
+
+use crate::shims::unix::*;
+use crate::*;
+
+/// Functions emulated by Miri that are not async-signal-safe, i.e., that must not be called from a
+/// signal handler. See `signal-safety(7)` for the list of functions that *are* safe.
+const NOT_ASYNC_SIGNAL_SAFE: &[&str] = &[
+    // Memory allocation.
+    "malloc",
+    "calloc",
+    "realloc",
+    "reallocarray",
+    "free",
+    "posix_memalign",
+    "aligned_alloc",
+    "memalign",
+    // Locks, condition variables, and thread management.
+    "pthread_mutex_lock",
+    "pthread_mutex_trylock",
+    "pthread_mutex_unlock",
+    "pthread_rwlock_rdlock",
+    "pthread_rwlock_tryrdlock",
+    "pthread_rwlock_wrlock",
+    "pthread_rwlock_trywrlock",
+    "pthread_rwlock_unlock",
+    "pthread_cond_wait",
+    "pthread_cond_timedwait",
+    "pthread_cond_signal",
+    "pthread_cond_broadcast",
+    "pthread_create",
+    "pthread_join",
+    "pthread_detach",
+    "pthread_key_create",
+    "pthread_key_delete",
+    "pthread_getspecific",
+    "pthread_setspecific",
+    "__cxa_thread_atexit_impl",
+    "_tlv_atexit",
+    // Process environment and process exit (`_exit` is fine).
+    "getenv",
+    "setenv",
+    "unsetenv",
+    "exit",
+    // Miscellaneous.
+    "dlsym",
+    "getpwuid_r",
+    "localtime_r",
+    "strerror_r",
+    "opendir",
+    "readdir",
+    "readdir64",
+    "readdir_r",
+    "closedir",
+];
+
+/// The signals whose default action is to do nothing.
+const IGNORED_BY_DEFAULT: &[&str] = &["SIGCHLD", "SIGURG", "SIGWINCH", "SIGCONT"];
+
+/// The signals whose default action is to stop the process.
+const STOP_BY_DEFAULT: &[&str] = &["SIGSTOP", "SIGTSTP", "SIGTTIN", "SIGTTOU"];
+
+/// Names of the standard signals, for diagnostics.
+const SIGNAL_NAMES: &[&str] = &[
+    "SIGHUP",
+    "SIGINT",
+    "SIGQUIT",
+    "SIGILL",
+    "SIGTRAP",
+    "SIGABRT",
+    "SIGBUS",
+    "SIGFPE",
+    "SIGKILL",
+    "SIGUSR1",
+    "SIGSEGV",
+    "SIGUSR2",
+    "SIGPIPE",
+    "SIGALRM",
+    "SIGTERM",
+    "SIGCHLD",
+    "SIGCONT",
+    "SIGSTOP",
+    "SIGTSTP",
+    "SIGTTIN",
+    "SIGTTOU",
+    "SIGURG",
+    "SIGXCPU",
+    "SIGXFSZ",
+    "SIGVTALRM",
+    "SIGPROF",
+    "SIGWINCH",
+    "SIGIO",
+    "SIGSYS",
+];
+
+/// What happens when a signal is delivered.
+#[derive(Debug, Clone, Copy)]
+enum SignalHandler {
+    /// `SIG_DFL`
+    Default,
+    /// `SIG_IGN`
+    Ignore,
+    /// A function registered by the program.
+    Handler(Pointer),
+}
+
+/// The disposition of a signal, as set by `sigaction`.
+#[derive(Debug, Clone, Copy)]
+struct SignalAction {
+    handler: SignalHandler,
+    /// The `sa_flags` the handler was registered with.
+    flags: i32,
+    /// Signals that are blocked while the handler runs (`sa_mask`).
+    mask: u64,
+}
+
+impl SignalAction {
+    const DEFAULT: SignalAction =
+        SignalAction { handler: SignalHandler::Default, flags: 0, mask: 0 };
+}
+
+/// The per-thread signal state.
+#[derive(Debug, Default, Clone)]
+struct ThreadSignals {
+    /// The signals blocked by this thread (bit `n - 1` stands for signal `n`).
+    blocked: u64,
+    /// The signals waiting to be delivered to this thread.
+    pending: u64,
+    /// The number of signal handlers currently running on this thread.
+    running_handlers: u32,
+}
+
+/// Extra information for the stack frame of a signal handler that was invoked by Miri.
+#[derive(Debug)]
+pub struct SignalHandlerFrame<'tcx> {
+    /// The signal mask to restore when the handler returns.
+    prev_blocked: u64,
+    /// The `siginfo_t` passed to an `SA_SIGINFO` handler; it is freed when the handler returns.
+    siginfo: Option<MPlaceTy<'tcx>>,
+}
+
+impl VisitProvenance for SignalHandlerFrame<'_> {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        self.siginfo.visit_provenance(visit);
+    }
+}
+
+/// The global signal state.
+#[derive(Debug, Default)]
+pub struct SignalState {
+    /// Signals that do not have an entry here have their default disposition.
+    actions: FxHashMap<i32, SignalAction>,
+    /// Threads that do not have an entry here have no blocked or pending signals.
+    threads: FxHashMap<ThreadId, ThreadSignals>,
+    /// Whether any thread has a pending signal. This is checked on every step, so it has to be
+    /// fast.
+    any_pending: bool,
+    /// The timer set by `alarm`, which raises `SIGALRM` when it expires.
+    alarm: Option<TimerId>,
+}
+
+impl SignalState {
+    fn thread(&mut self, thread: ThreadId) -> &mut ThreadSignals {
+        self.threads.entry(thread).or_default()
+    }
+
+    fn action(&self, signum: i32) -> SignalAction {
+        self.actions.get(&signum).copied().unwrap_or(SignalAction::DEFAULT)
+    }
+
+    fn update_any_pending(&mut self) {
+        self.any_pending = self.threads.values().any(|thread| thread.pending != 0);
+    }
+
+    /// Whether the given thread is currently executing a signal handler.
+    pub fn in_handler(&self, thread: ThreadId) -> bool {
+        self.threads.get(&thread).is_some_and(|thread| thread.running_handlers > 0)
+    }
+
+    /// A new thread inherits the signal mask of the thread that created it.
+    pub fn thread_created(&mut self, parent: ThreadId, child: ThreadId) {
+        let blocked = self.threads.get(&parent).map_or(0, |parent| parent.blocked);
+        self.thread(child).blocked = blocked;
+    }
+}
+
+impl VisitProvenance for SignalState {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        for action in self.actions.values() {
+            if let SignalHandler::Handler(ptr) = action.handler {
+                ptr.visit_provenance(visit);
+            }
+        }
+    }
+}
+
+/// The bit representing `signum` in our signal bitmasks.
+fn signal_bit(signum: i32) -> u64 {
+    1u64.strict_shl(u32::try_from(signum).unwrap().strict_sub(1))
+}
+
+impl<'tcx> EvalContextPrivExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextPrivExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// The largest signal number we support.
+    fn max_signal(&self) -> i32 {
+        let this = self.eval_context_ref();
+        match &*this.tcx.sess.target.os {
+            "linux" | "android" => crate::machine::SIGRTMAX,
+            _ => 31,
+        }
+    }
+
+    /// Checks whether `signum` is a valid signal number.
+    fn is_valid_signal(&self, signum: i32) -> bool {
+        (1..=self.max_signal()).contains(&signum)
+    }
+
+    /// Returns whether this signal can have a handler or be blocked. This is not the case for
+    /// `SIGKILL` and `SIGSTOP`.
+    fn is_catchable_signal(&self, signum: i32) -> bool {
+        let this = self.eval_context_ref();
+        signum != this.eval_libc_i32("SIGKILL") && signum != this.eval_libc_i32("SIGSTOP")
+    }
+
+    fn signal_name(&self, signum: i32) -> String {
+        let this = self.eval_context_ref();
+        SIGNAL_NAMES
+            .iter()
+            .find(|name| this.eval_libc_i32(name) == signum)
+            .map_or_else(|| format!("signal {signum}"), |name| name.to_string())
+    }
+
+    /// Returns the byte offset and bit mask for the given signal in a `sigset_t`.
+    fn sigset_bit(&self, signum: i32) -> (u64, u8) {
+        let this = self.eval_context_ref();
+        // On Linux, `sigset_t` is an array of `unsigned long`; elsewhere it consists of `u32`.
+        let word_size = match &*this.tcx.sess.target.os {
+            "linux" | "android" => this.pointer_size().bytes(),
+            _ => 4,
+        };
+        let word_bits = word_size.strict_mul(8);
+        let bit = u64::try_from(signum).unwrap().strict_sub(1);
+        let word = bit.strict_div(word_bits);
+        let bit_in_word = bit.strict_rem(word_bits);
+        let byte_in_word = match this.tcx.sess.target.endian {
+            Endian::Little => bit_in_word.strict_div(8),
+            Endian::Big => word_size.strict_sub(1).strict_sub(bit_in_word.strict_div(8)),
+        };
+        let bit_in_byte = u32::try_from(bit_in_word.strict_rem(8)).unwrap();
+        (word.strict_mul(word_size).strict_add(byte_in_word), 1u8.strict_shl(bit_in_byte))
+    }
+
+    /// Reads a `sigset_t` into a bitmask.
+    fn read_sigset(&self, set: &MPlaceTy<'tcx>) -> InterpResult<'tcx, u64> {
+        let this = self.eval_context_ref();
+        let bytes = this.read_bytes_ptr_strip_provenance(set.ptr(), set.layout.size)?;
+        let mut mask = 0;
+        for signum in 1..=this.max_signal() {
+            let (offset, bit) = this.sigset_bit(signum);
+            if bytes[usize::try_from(offset).unwrap()] & bit != 0 {
+                mask |= signal_bit(signum);
+            }
+        }
+        Ok(mask)
+    }
+
+    /// Writes a bitmask into a `sigset_t`.
+    fn write_sigset(&mut self, mask: u64, set: &MPlaceTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let mut bytes = vec![0u8; usize::try_from(set.layout.size.bytes()).unwrap()];
+        for signum in 1..=this.max_signal() {
+            if mask & signal_bit(signum) != 0 {
+                let (offset, bit) = this.sigset_bit(signum);
+                bytes[usize::try_from(offset).unwrap()] |= bit;
+            }
+        }
+        this.write_bytes_ptr(set.ptr(), bytes)
+    }
+
+    /// Reads a `sighandler_t`.
+    fn read_sighandler(
+        &self,
+        op: &impl Readable<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, SignalHandler> {
+        let this = self.eval_context_ref();
+        let ptr = this.read_pointer(op)?;
+        let addr = ptr.addr().bytes();
+        Ok(if addr == this.eval_libc("SIG_DFL").to_target_usize(this)? {
+            SignalHandler::Default
+        } else if addr == this.eval_libc("SIG_IGN").to_target_usize(this)? {
+            SignalHandler::Ignore
+        } else {
+            SignalHandler::Handler(ptr)
+        })
+    }
+
+    /// Writes a `sighandler_t`.
+    fn write_sighandler(
+        &mut self,
+        handler: SignalHandler,
+        dest: &impl Writeable<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        match handler {
+            SignalHandler::Default => this.write_scalar(this.eval_libc("SIG_DFL"), dest),
+            SignalHandler::Ignore => this.write_scalar(this.eval_libc("SIG_IGN"), dest),
+            SignalHandler::Handler(ptr) => this.write_pointer(ptr, dest),
+        }
+    }
+
+    /// Whether the signal would currently be discarded upon delivery.
+    fn signal_is_ignored(&self, signum: i32) -> bool {
+        let this = self.eval_context_ref();
+        match this.machine.signals.action(signum).handler {
+            SignalHandler::Ignore => true,
+            SignalHandler::Default =>
+                IGNORED_BY_DEFAULT.iter().any(|name| this.eval_libc_i32(name) == signum),
+            SignalHandler::Handler(_) => false,
+        }
+    }
+
+    /// Makes the signal pending on the given thread.
+    fn send_signal_to_thread(&mut self, thread: ThreadId, signum: i32) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        // Ignored signals are discarded right away.
+        if this.signal_is_ignored(signum) {
+            return Ok(());
+        }
+        let thread_signals = this.machine.signals.thread(thread);
+        thread_signals.pending |= signal_bit(signum);
+        let deliverable = thread_signals.blocked & signal_bit(signum) == 0;
+        this.machine.signals.any_pending = true;
+        // A thread waiting in `pause` resumes as soon as it can handle the signal.
+        if deliverable && this.machine.threads.is_blocked_on(thread, BlockReason::Signal) {
+            this.unblock_thread(thread, BlockReason::Signal)?;
+        }
+        Ok(())
+    }
+
+    /// Sends a process-directed signal. It is delivered to the first thread that does not block
+    /// it, or left pending on the main thread if all threads block it.
+    fn send_signal_to_process(&mut self, signum: i32) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let threads = &this.machine.threads;
+        let signals = &this.machine.signals;
+        let thread = (0..threads.get_total_thread_count())
+            .map(|id| ThreadId::from(u32::try_from(id).unwrap()))
+            .find(|&id| {
+                !threads.has_terminated(id)
+                    && signals.threads.get(&id).is_none_or(|t| t.blocked & signal_bit(signum) == 0)
+            })
+            .unwrap_or(ThreadId::MAIN_THREAD);
+        this.send_signal_to_thread(thread, signum)
+    }
+
+    /// Performs the default action for the given signal.
+    fn signal_default_action(&mut self, signum: i32) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        if IGNORED_BY_DEFAULT.iter().any(|name| this.eval_libc_i32(name) == signum) {
+            return Ok(());
+        }
+        let name = this.signal_name(signum);
+        if STOP_BY_DEFAULT.iter().any(|name| this.eval_libc_i32(name) == signum) {
+            throw_unsup_format!("stopping the process with {name} is not supported");
+        }
+        throw_machine_stop!(TerminationInfo::Abort(format!(
+            "the program was terminated by {name}"
+        )));
+    }
+
+    /// Invokes the handler for the given signal on the active thread. The handler returns to the
+    /// beginning of `ret`.
+    fn invoke_signal_handler(
+        &mut self,
+        signum: i32,
+        action: SignalAction,
+        handler: Pointer,
+        ret: mir::BasicBlock,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let instance = this.get_ptr_fn(handler)?.as_instance()?;
+
+        let mut args: Vec<Immediate<Provenance>> = vec![Scalar::from_i32(signum).into()];
+        let siginfo = if action.flags & this.eval_libc_i32("SA_SIGINFO") != 0 {
+            let siginfo =
+                this.allocate(this.libc_ty_layout("siginfo_t"), MiriMemoryKind::Machine.into())?;
+            // All signals we can generate are sent by the process itself, so leaving `si_code` at
+            // 0 (`SI_USER` on Linux) and all other fields empty is good enough.
+            this.write_bytes_ptr(
+                siginfo.ptr(),
+                std::iter::repeat(0u8).take(usize::try_from(siginfo.layout.size.bytes()).unwrap()),
+            )?;
+            let signo = this.project_field_named(&siginfo, "si_signo")?;
+            this.write_int(signum, &signo)?;
+            args.push(Scalar::from_maybe_pointer(siginfo.ptr(), this).into());
+            // We do not provide a `ucontext_t`.
+            args.push(Scalar::null_ptr(this).into());
+            Some(siginfo)
+        } else {
+            None
+        };
+
+        this.call_function(
+            instance,
+            Abi::C { unwind: false },
+            &args,
+            None,
+            StackPopCleanup::Goto { ret: Some(ret), unwind: mir::UnwindAction::Unreachable },
+        )?;
+
+        // While the handler runs, the signal itself (unless `SA_NODEFER` is set) as well as the
+        // signals in its `sa_mask` are blocked.
+        let defer = action.flags & this.eval_libc_i32("SA_NODEFER") == 0;
+        let active_thread = this.active_thread();
+        let thread = this.machine.signals.thread(active_thread);
+        let prev_blocked = thread.blocked;
+        thread.blocked |= action.mask;
+        if defer {
+            thread.blocked |= signal_bit(signum);
+        }
+        thread.running_handlers = thread.running_handlers.strict_add(1);
+        this.frame_mut().extra.signal_handler = Some(SignalHandlerFrame { prev_blocked, siginfo });
+
+        // `SA_RESETHAND` resets the disposition to the default once the signal is delivered.
+        if action.flags & this.eval_libc_i32("SA_RESETHAND") != 0 {
+            this.machine.signals.actions.remove(&signum);
+        }
+        Ok(())
+    }
+
+    /// Implements `pthread_sigmask` and `sigprocmask` for the active thread. Returns `false` if
+    /// `how` is invalid.
+    fn update_signal_mask(
+        &mut self,
+        how: i32,
+        set: &OpTy<'tcx>,
+        oldset: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, bool> {
+        let this = self.eval_context_mut();
+        let active_thread = this.active_thread();
+        let old_mask = this.machine.signals.thread(active_thread).blocked;
+
+        let set = this.read_pointer(set)?;
+        if !this.ptr_is_null(set)? {
+            let set = this.ptr_to_mplace(set, this.libc_ty_layout("sigset_t"));
+            let mut mask = this.read_sigset(&set)?;
+            // `SIGKILL` and `SIGSTOP` cannot be blocked; this is silently ignored.
+            mask &= !signal_bit(this.eval_libc_i32("SIGKILL"));
+            mask &= !signal_bit(this.eval_libc_i32("SIGSTOP"));
+            let new_mask = if how == this.eval_libc_i32("SIG_BLOCK") {
+                old_mask | mask
+            } else if how == this.eval_libc_i32("SIG_UNBLOCK") {
+                old_mask & !mask
+            } else if how == this.eval_libc_i32("SIG_SETMASK") {
+                mask
+            } else {
+                return Ok(false);
+            };
+            this.machine.signals.thread(active_thread).blocked = new_mask;
+        }
+
+        let oldset = this.read_pointer(oldset)?;
+        if !this.ptr_is_null(oldset)? {
+            let oldset = this.ptr_to_mplace(oldset, this.libc_ty_layout("sigset_t"));
+            this.write_sigset(old_mask, &oldset)?;
+        }
+        Ok(true)
+    }
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    fn sigaction(
+        &mut self,
+        signum: &OpTy<'tcx>,
+        act: &OpTy<'tcx>,
+        oldact: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let signum = this.read_scalar(signum)?.to_i32()?;
+        let act = this.read_pointer(act)?;
+        let oldact = this.read_pointer(oldact)?;
+
+        if !this.is_valid_signal(signum)
+            || (!this.is_catchable_signal(signum) && !this.ptr_is_null(act)?)
+        {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let old_action = this.machine.signals.action(signum);
+        if !this.ptr_is_null(act)? {
+            let act = this.ptr_to_mplace(act, this.libc_ty_layout("sigaction"));
+            let handler = this.read_sighandler(&this.project_field_named(&act, "sa_sigaction")?)?;
+            let flags = this.read_scalar(&this.project_field_named(&act, "sa_flags")?)?.to_i32()?;
+            let mask = this.read_sigset(&this.project_field_named(&act, "sa_mask")?)?;
+
+            let supported_flags =
+                ["SA_SIGINFO", "SA_RESETHAND", "SA_NODEFER", "SA_RESTART", "SA_ONSTACK"]
+                    .iter()
+                    .fold(0, |acc, name| acc | this.eval_libc_i32(name));
+            if flags & !supported_flags != 0 {
+                throw_unsup_format!(
+                    "`sigaction` flags {:#x} are not supported",
+                    flags & !supported_flags
+                );
+            }
+
+            this.machine.signals.actions.insert(signum, SignalAction { handler, flags, mask });
+            // Setting a signal to be ignored discards pending instances of it.
+            if this.signal_is_ignored(signum) {
+                for thread in this.machine.signals.threads.values_mut() {
+                    thread.pending &= !signal_bit(signum);
+                }
+                this.machine.signals.update_any_pending();
+            }
+        }
+
+        if !this.ptr_is_null(oldact)? {
+            let oldact = this.ptr_to_mplace(oldact, this.libc_ty_layout("sigaction"));
+            this.write_bytes_ptr(
+                oldact.ptr(),
+                std::iter::repeat(0u8).take(usize::try_from(oldact.layout.size.bytes()).unwrap()),
+            )?;
+            this.write_sighandler(
+                old_action.handler,
+                &this.project_field_named(&oldact, "sa_sigaction")?,
+            )?;
+            this.write_int(old_action.flags, &this.project_field_named(&oldact, "sa_flags")?)?;
+            this.write_sigset(old_action.mask, &this.project_field_named(&oldact, "sa_mask")?)?;
+        }
+
+        Ok(Scalar::from_i32(0))
+    }
+
+    fn signal(
+        &mut self,
+        signum: &OpTy<'tcx>,
+        handler: &OpTy<'tcx>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let signum = this.read_scalar(signum)?.to_i32()?;
+        let handler = this.read_sighandler(handler)?;
+
+        if !this.is_valid_signal(signum) || !this.is_catchable_signal(signum) {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return this.write_scalar(this.eval_libc("SIG_ERR"), dest);
+        }
+
+        // `signal` has BSD semantics on all the targets we support: the handler stays installed
+        // and the signal is blocked while the handler runs.
+        let flags = this.eval_libc_i32("SA_RESTART");
+        let old_action =
+            this.machine.signals.actions.insert(signum, SignalAction { handler, flags, mask: 0 });
+        this.write_sighandler(old_action.unwrap_or(SignalAction::DEFAULT).handler, dest)
+    }
+
+    fn raise(&mut self, signum: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let signum = this.read_scalar(signum)?.to_i32()?;
+        if !this.is_valid_signal(signum) {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let active_thread = this.active_thread();
+        this.send_signal_to_thread(active_thread, signum)?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    fn kill(&mut self, pid: &OpTy<'tcx>, signum: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let pid = this.read_scalar(pid)?.to_int(this.libc_ty_layout("pid_t").size)?;
+        let signum = this.read_scalar(signum)?.to_i32()?;
+
+        // We are the only process in our process group, so sending a signal to the process group
+        // (0) or to all processes we may signal (-1) is the same as signaling ourselves.
+        if pid != i128::from(this.getpid()?) && pid != 0 && pid != -1 {
+            throw_unsup_format!("`kill` is only supported for the current process");
+        }
+        // Signal 0 only checks whether we are allowed to send signals to `pid`.
+        if signum != 0 {
+            if !this.is_valid_signal(signum) {
+                let einval = this.eval_libc("EINVAL");
+                this.set_last_error(einval)?;
+                return Ok(Scalar::from_i32(-1));
+            }
+            // A signal sent to the own process by one of its threads is delivered to that
+            // thread, unless that thread blocks it.
+            let active_thread = this.active_thread();
+            if this.machine.signals.thread(active_thread).blocked & signal_bit(signum) == 0 {
+                this.send_signal_to_thread(active_thread, signum)?;
+            } else {
+                this.send_signal_to_process(signum)?;
+            }
+        }
+        Ok(Scalar::from_i32(0))
+    }
+
+    fn pthread_kill(
+        &mut self,
+        thread: &OpTy<'tcx>,
+        signum: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let thread = this.read_scalar(thread)?.to_int(this.libc_ty_layout("pthread_t").size)?;
+        let signum = this.read_scalar(signum)?.to_i32()?;
+
+        let thread = match ThreadId::try_from(thread) {
+            Ok(thread)
+                if usize::try_from(thread.to_u32()).unwrap() < this.get_total_thread_count()
+                    && !this.machine.threads.has_terminated(thread) =>
+                thread,
+            _ => return Ok(this.eval_libc("ESRCH")),
+        };
+        // Signal 0 only checks whether the thread exists.
+        if signum != 0 {
+            if !this.is_valid_signal(signum) {
+                return Ok(this.eval_libc("EINVAL"));
+            }
+            this.send_signal_to_thread(thread, signum)?;
+        }
+        Ok(Scalar::from_i32(0))
+    }
+
+    fn alarm(&mut self, seconds: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let seconds = this.read_scalar(seconds)?.to_u32()?;
+
+        // Report the remaining time of the previous alarm, rounded up to whole seconds.
+        let remaining =
+            this.machine.signals.alarm.take().and_then(|timer| this.cancel_timer(timer));
+        let remaining = remaining.map_or(0, |remaining| {
+            let secs = remaining.as_secs().strict_add(u64::from(remaining.subsec_nanos() > 0));
+            u32::try_from(secs).unwrap_or(u32::MAX)
+        });
+        // An argument of 0 just cancels the previous alarm.
+        if seconds > 0 {
+            let timer = this.set_timer(
+                Duration::from_secs(u64::from(seconds)),
+                callback!(
+                    @capture<'tcx> {}
+                    @expired = |this| {
+                        this.machine.signals.alarm = None;
+                        let sigalrm = this.eval_libc_i32("SIGALRM");
+                        this.send_signal_to_process(sigalrm)
+                    }
+                ),
+            );
+            this.machine.signals.alarm = Some(timer);
+        }
+        Ok(Scalar::from_u32(remaining))
+    }
+
+    fn pause(&mut self, dest: &MPlaceTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        // The thread is woken up by `send_signal_to_thread`, and the handler runs before `pause`
+        // returns. Signals that are ignored never wake it up.
+        let dest = dest.clone();
+        this.block_thread(
+            BlockReason::Signal,
+            None,
+            callback!(
+                @capture<'tcx> { dest: MPlaceTy<'tcx> }
+                @unblock = |this| {
+                    let eintr = this.eval_libc("EINTR");
+                    this.set_last_error(eintr)?;
+                    this.write_scalar(Scalar::from_i32(-1), &dest)
+                }
+            ),
+        );
+        Ok(())
+    }
+
+    /// Implements `sigemptyset` and `sigfillset`.
+    fn sigset_init(&mut self, set: &OpTy<'tcx>, fill: bool) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let set = this.deref_pointer_as(set, this.libc_ty_layout("sigset_t"))?;
+        let mask = if fill {
+            (1..=this.max_signal()).fold(0, |acc, signum| acc | signal_bit(signum))
+        } else {
+            0
+        };
+        this.write_sigset(mask, &set)?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Implements `sigaddset`, `sigdelset`, and `sigismember`.
+    fn sigset_op(
+        &mut self,
+        link_name: Symbol,
+        set: &OpTy<'tcx>,
+        signum: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let set = this.deref_pointer_as(set, this.libc_ty_layout("sigset_t"))?;
+        let signum = this.read_scalar(signum)?.to_i32()?;
+        if !this.is_valid_signal(signum) {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+        let mask = this.read_sigset(&set)?;
+        match link_name.as_str() {
+            "sigaddset" => this.write_sigset(mask | signal_bit(signum), &set)?,
+            "sigdelset" => this.write_sigset(mask & !signal_bit(signum), &set)?,
+            "sigismember" => return Ok(Scalar::from_i32((mask & signal_bit(signum) != 0).into())),
+            name => bug!("`sigset_op` called on unexpected function {name}"),
+        }
+        Ok(Scalar::from_i32(0))
+    }
+
+    fn pthread_sigmask(
+        &mut self,
+        how: &OpTy<'tcx>,
+        set: &OpTy<'tcx>,
+        oldset: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let how = this.read_scalar(how)?.to_i32()?;
+        if !this.update_signal_mask(how, set, oldset)? {
+            return Ok(this.eval_libc("EINVAL"));
+        }
+        Ok(Scalar::from_i32(0))
+    }
+
+    fn sigprocmask(
+        &mut self,
+        how: &OpTy<'tcx>,
+        set: &OpTy<'tcx>,
+        oldset: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let how = this.read_scalar(how)?.to_i32()?;
+        if !this.update_signal_mask(how, set, oldset)? {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Called before every step of the active thread: delivers a pending signal if possible.
+    fn deliver_pending_signal(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        if !this.machine.signals.any_pending {
+            return Ok(());
+        }
+
+        let active_thread = this.active_thread();
+        let Some(thread) = this.machine.signals.threads.get(&active_thread) else { return Ok(()) };
+        let deliverable = thread.pending & !thread.blocked;
+        if deliverable == 0 {
+            return Ok(());
+        }
+        // We can only interrupt the thread at the beginning of a basic block, since that is where
+        // we can resume execution once the handler returns.
+        let Some(frame) = this.active_thread_stack().last() else { return Ok(()) };
+        let either::Either::Left(loc) = frame.loc else { return Ok(()) };
+        if loc.statement_index != 0 {
+            return Ok(());
+        }
+
+        let signum = i32::try_from(deliverable.trailing_zeros()).unwrap().strict_add(1);
+        this.machine.signals.thread(active_thread).pending &= !signal_bit(signum);
+        this.machine.signals.update_any_pending();
+
+        let action = this.machine.signals.action(signum);
+        match action.handler {
+            SignalHandler::Ignore => Ok(()),
+            SignalHandler::Default => this.signal_default_action(signum),
+            SignalHandler::Handler(handler) =>
+                this.invoke_signal_handler(signum, action, handler, loc.block),
+        }
+    }
+
+    /// Called when the stack frame of a signal handler is popped.
+    fn return_from_signal_handler(
+        &mut self,
+        frame: SignalHandlerFrame<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let active_thread = this.active_thread();
+        let thread = this.machine.signals.thread(active_thread);
+        thread.blocked = frame.prev_blocked;
+        thread.running_handlers = thread.running_handlers.strict_sub(1);
+        if let Some(siginfo) = frame.siginfo {
+            this.deallocate_ptr(siginfo.ptr(), None, MiriMemoryKind::Machine.into())?;
+        }
+        Ok(())
+    }
+
+    /// Reports UB if the active thread is running a signal handler and calls a function that is
+    /// not async-signal-safe.
+    fn check_async_signal_safety(&self, link_name: Symbol) -> InterpResult<'tcx> {
+        let this = self.eval_context_ref();
+
+        if !this.machine.signals.in_handler(this.active_thread()) {
+            return Ok(());
+        }
+        let is_unsafe = match link_name.as_str() {
+            // Unless the program sets its own global allocator, Rust allocations use `malloc`.
+            "__rust_alloc" | "__rust_alloc_zeroed" | "__rust_realloc" | "__rust_dealloc" =>
+                !matches!(this.tcx.allocator_kind(()), Some(AllocatorKind::Global)),
+            name => NOT_ASYNC_SIGNAL_SAFE.contains(&name),
+        };
+        if is_unsafe {
+            throw_ub_format!(
+                "calling `{link_name}` in a signal handler, but this function is not async-signal-safe"
+            );
+        }
+        Ok(())
+    }
+}
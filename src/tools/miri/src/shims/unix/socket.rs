@@ -133,6 +133,29 @@ fn write<'tcx>(
         writebuf.buf.extend(&bytes[..actual_write_size]);
         return Ok(Ok(actual_write_size));
     }
+
+    fn epoll_ready_events<'tcx>(&self, ecx: &MiriInterpCx<'tcx>) -> InterpResult<'tcx, u32> {
+        let mut ready = 0;
+        let readbuf = self.readbuf.borrow();
+        // A read does not block if there is data, or if it would return EOF.
+        if !readbuf.buf.is_empty() || !readbuf.buf_has_writer {
+            ready |= ecx.eval_libc_u32("EPOLLIN");
+        }
+        if !readbuf.buf_has_writer {
+            ready |= ecx.eval_libc_u32("EPOLLRDHUP");
+        }
+        match self.writebuf.upgrade() {
+            Some(writebuf) =>
+                if writebuf.borrow().buf.len() < MAX_SOCKETPAIR_BUFFER_CAPACITY {
+                    ready |= ecx.eval_libc_u32("EPOLLOUT");
+                },
+            None => {
+                // The peer is gone: writes fail with `EPIPE` right away.
+                ready |= ecx.eval_libc_u32("EPOLLOUT") | ecx.eval_libc_u32("EPOLLHUP");
+            }
+        }
+        Ok(ready)
+    }
 }
 
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
@@ -0,0 +1,269 @@
+//! Running the `#[test]` functions of a test crate one by one, for `-Zmiri-test-session`.
+//!
+//! Instead of interpreting the libtest harness, we look for the test descriptions generated by
+//! `#[test]` ourselves and run every test in a fresh `InterpCx`. This gives each test its own
+//! memory, threads and leak check, as if every test was run in a separate process. However, all
+//! tests share one compilation session: the crate is only type-checked once, and const-evaluation
+//! results as well as the initial values of statics are computed by (cached) queries, so every
+//! test starts from a copy of the already evaluated statics.
+
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_span::{sym, Symbol};
+
+use crate::coverage::CoverageState;
+use crate::eval::{call_main, init_ecx, run_ecx};
+use crate::*;
+
+/// The exit code of a process whose `main` panicked, and of a failed test run.
+const PANIC_EXIT_CODE: i64 = 101;
+
+/// A `#[test]` function, described by the constant that `#[test]` generates for it.
+struct Test {
+    /// The path of the test, relative to the crate root.
+    name: Symbol,
+    /// The `test::TestDescAndFn` constant describing the test.
+    def_id: DefId,
+}
+
+/// Which tests to run, parsed from the (libtest-style) program arguments.
+#[derive(Default)]
+struct TestFilter {
+    /// Only run tests whose name contains one of these strings (or is equal to one of them, with
+    /// `exact`). All tests are run if this is empty.
+    filters: Vec<String>,
+    exact: bool,
+    /// Whether to run ignored tests as well.
+    include_ignored: bool,
+    /// Whether to run *only* the ignored tests.
+    ignored_only: bool,
+}
+
+impl TestFilter {
+    fn parse(tcx: TyCtxt<'_>, args: &[String]) -> Self {
+        let mut filter = TestFilter::default();
+        for arg in args {
+            match arg.as_str() {
+                "--exact" => filter.exact = true,
+                "--include-ignored" => filter.include_ignored = true,
+                "--ignored" => filter.ignored_only = true,
+                // We never capture output, and always run one test at a time.
+                "--nocapture" | "--show-output" => {}
+                _ if arg.starts_with("--test-threads") => {}
+                _ if arg.starts_with('-') =>
+                    tcx.dcx().fatal(format!(
+                        "test harness argument `{arg}` is not supported with `-Zmiri-test-session`"
+                    )),
+                _ => filter.filters.push(arg.clone()),
+            }
+        }
+        filter
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.filters.is_empty()
+            || self.filters.iter().any(|filter| {
+                if self.exact { name == filter } else { name.contains(filter.as_str()) }
+            })
+    }
+}
+
+/// What a test should do for it to pass.
+#[derive(Clone, Copy, PartialEq)]
+enum Expectation {
+    Success,
+    /// `#[should_panic]`. The expected panic message, if any, is not checked.
+    Panic,
+}
+
+/// What we read from the `test::TestDesc` of a test.
+struct TestDesc {
+    /// Whether the test is `#[ignore]`d.
+    ignore: bool,
+    expectation: Expectation,
+}
+
+/// Returns all tests of the local crate, sorted by name like libtest does.
+fn collect_tests(tcx: TyCtxt<'_>) -> Vec<Test> {
+    let mut tests: Vec<Test> = tcx
+        .hir()
+        .body_owners()
+        .filter(|&def_id| tcx.def_kind(def_id) == DefKind::Const)
+        .filter_map(|def_id| {
+            let name = tcx.get_attr(def_id, sym::rustc_test_marker)?.value_str()?;
+            Some(Test { name, def_id: def_id.to_def_id() })
+        })
+        .collect();
+    tests.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+    tests
+}
+
+/// Reads the `TestDescAndFn` that `#[test]` generated for `test`, and returns its description and
+/// the test function with its return type.
+/// Returns `None` if `test` is not a `#[test]` function, but e.g. a `#[bench]` function.
+fn read_test<'tcx>(
+    ecx: &mut InterpCx<'tcx, MiriMachine<'tcx>>,
+    test: &Test,
+) -> InterpResult<'tcx, Option<(TestDesc, Scalar, Ty<'tcx>)>> {
+    let tcx = *ecx.tcx;
+    let test_desc_and_fn = ecx.eval_global(ty::Instance::mono(tcx, test.def_id))?;
+    let desc = ecx.project_field_named(&test_desc_and_fn, "desc")?;
+    let ignore = ecx.read_scalar(&ecx.project_field_named(&desc, "ignore")?)?.to_bool()?;
+    let should_panic = ecx.project_field_named(&desc, "should_panic")?;
+    let should_panic_variant = ecx.read_discriminant(&should_panic)?;
+    let should_panic_adt = should_panic.layout.ty.ty_adt_def().unwrap();
+    let expectation = if should_panic_adt.variant(should_panic_variant).name.as_str() == "No" {
+        Expectation::Success
+    } else {
+        Expectation::Panic
+    };
+
+    let testfn = ecx.project_field_named(&test_desc_and_fn, "testfn")?;
+    let testfn_variant = ecx.read_discriminant(&testfn)?;
+    let testfn_adt = testfn.layout.ty.ty_adt_def().unwrap();
+    if testfn_adt.variant(testfn_variant).name.as_str() != "StaticTestFn" {
+        return Ok(None);
+    }
+    let testfn = ecx.project_downcast(&testfn, testfn_variant)?;
+    let testfn = ecx.project_field(&testfn, 0)?;
+    let testfn_ret_ty = testfn.layout.ty.fn_sig(tcx).output().no_bound_vars().unwrap();
+    let testfn = ecx.read_scalar(&testfn)?;
+    Ok(Some((TestDesc { ignore, expectation }, testfn, testfn_ret_ty)))
+}
+
+/// Creates a fresh interpreter that is ready to run `test`, which must be a `#[test]` function.
+fn setup_test<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    test: &Test,
+    config: &MiriConfig,
+) -> InterpResult<'tcx, InterpCx<'tcx, MiriMachine<'tcx>>> {
+    let (mut ecx, entry_args) = init_ecx(tcx, config)?;
+    let (_, testfn, testfn_ret_ty) = read_test(&mut ecx, test)?.unwrap();
+    // The test function returns `Result<(), String>`, which the `start` lang item turns into an
+    // exit code for us.
+    call_main(&mut ecx, testfn, testfn_ret_ty, entry_args)?;
+    Ok(ecx)
+}
+
+/// Panics with the initialization error `err` of the interpreter for `test`.
+fn initialization_error(test: &Test, err: InterpErrorInfo<'_>) -> ! {
+    let (kind, backtrace) = err.into_parts();
+    backtrace.print_backtrace();
+    panic!("Miri initialization error for test `{}`: {kind:?}", test.name)
+}
+
+/// Runs all `#[test]` functions of the local crate, each in a fresh interpreter, and prints a
+/// libtest-style summary.
+/// Returns `Some(return_code)` if all tests ran to completion (whether they passed or not).
+/// Returns `None` if an evaluation error occurred in any of the tests.
+pub fn eval_tests<'tcx>(tcx: TyCtxt<'tcx>, config: MiriConfig) -> Option<i64> {
+    if !tcx.sess.is_test_crate() {
+        tcx.dcx().fatal("`-Zmiri-test-session` can only be used on crates built with `--test`");
+    }
+    // The first argument is the program name; the rest are for the test harness.
+    let filter = TestFilter::parse(tcx, &config.args[1..]);
+    let config = MiriConfig { args: config.args[..1].to_vec(), ..config };
+
+    let (tests, filtered): (Vec<_>, Vec<_>) =
+        collect_tests(tcx).into_iter().partition(|test| filter.matches(test.name.as_str()));
+    let mut filtered_out = filtered.len();
+
+    let mut coverage = config.coverage_out.clone().map(CoverageState::new);
+    let mut passed = 0;
+    let mut failed = Vec::new();
+    let mut ignored = 0;
+    let mut any_error = false;
+
+    println!();
+    println!("running {} test{}", tests.len(), if tests.len() == 1 { "" } else { "s" });
+    // Setting up an interpreter for a test is expensive, so we read the descriptions of all tests
+    // with a single one, and only set up a fresh one for each test that actually runs.
+    let mut desc_ecx = None;
+    for test in &tests {
+        if desc_ecx.is_none() {
+            match init_ecx(tcx, &config) {
+                Ok((ecx, _)) => desc_ecx = Some(ecx),
+                Err(err) => initialization_error(test, err),
+            }
+        }
+        let desc = match read_test(desc_ecx.as_mut().unwrap(), test) {
+            Ok(desc) => desc,
+            Err(err) => initialization_error(test, err),
+        };
+        let Some((TestDesc { ignore, expectation }, ..)) = desc else {
+            println!("test {} ... ignored, only `#[test]` functions are supported", test.name);
+            ignored += 1;
+            continue;
+        };
+        if filter.ignored_only && !ignore {
+            // With `--ignored`, we only run the tests that would otherwise be ignored.
+            filtered_out += 1;
+            continue;
+        }
+        if ignore && !filter.ignored_only && !filter.include_ignored {
+            println!("test {} ... ignored", test.name);
+            ignored += 1;
+            continue;
+        }
+
+        let mut ecx = match setup_test(tcx, test, &config) {
+            Ok(ecx) => ecx,
+            Err(err) => initialization_error(test, err),
+        };
+        let return_code = run_ecx(&mut ecx, config.ignore_leaks);
+        if let (Some(coverage), Some(test_coverage)) = (&mut coverage, ecx.machine.coverage.take())
+        {
+            coverage.merge(test_coverage);
+        }
+        let success = match (return_code, expectation) {
+            (None, _) => {
+                any_error = true;
+                false
+            }
+            (Some(0), Expectation::Success) => true,
+            (Some(PANIC_EXIT_CODE), Expectation::Panic) => true,
+            (Some(_), _) => false,
+        };
+        if success {
+            println!("test {} ... ok", test.name);
+            passed += 1;
+        } else {
+            if expectation == Expectation::Panic && return_code == Some(0) {
+                println!("note: test did not panic as expected");
+            }
+            println!("test {} ... FAILED", test.name);
+            failed.push(test.name);
+        }
+    }
+
+    if let Some(coverage) = &coverage {
+        if let Err(err) = coverage.write_report(tcx) {
+            tcx.dcx().warn(format!("failed to write the coverage report: {err}"));
+        }
+    }
+
+    if !failed.is_empty() {
+        println!();
+        println!("failures:");
+        for name in &failed {
+            println!("    {name}");
+        }
+    }
+    println!();
+    println!(
+        "test result: {}. {passed} passed; {} failed; {ignored} ignored; 0 measured; {} filtered out",
+        if failed.is_empty() { "ok" } else { "FAILED" },
+        failed.len(),
+        filtered_out,
+    );
+    println!();
+
+    if any_error {
+        None
+    } else if failed.is_empty() {
+        Some(0)
+    } else {
+        Some(PANIC_EXIT_CODE)
+    }
+}
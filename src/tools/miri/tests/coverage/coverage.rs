@@ -0,0 +1,27 @@
+// Run by `coverage_report` in `tests/ui.rs` with `-Zmiri-coverage` and
+// `-Zmiri-panic-on-unsupported`, which then checks the written report.
+
+use std::ffi::c_long;
+use std::panic;
+
+extern "C" {
+    fn miri_coverage_unknown_function();
+    fn syscall(num: c_long, ...) -> c_long;
+}
+
+fn called_thrice(x: u32) -> u32 {
+    if x % 2 == 0 { x / 2 } else { 3 * x + 1 }
+}
+
+#[allow(dead_code)]
+fn never_called() {}
+
+fn main() {
+    for x in 0..3 {
+        called_thrice(x);
+    }
+    for _ in 0..2 {
+        let _ = panic::catch_unwind(|| unsafe { miri_coverage_unknown_function() });
+    }
+    let _ = panic::catch_unwind(|| unsafe { syscall(0) });
+}
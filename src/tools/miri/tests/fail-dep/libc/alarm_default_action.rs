@@ -0,0 +1,9 @@
+//@ignore-target-windows: No libc signals on Windows
+
+fn main() {
+    unsafe {
+        libc::alarm(1);
+        // The default action of `SIGALRM` terminates the process.
+        libc::pause(); //~ERROR: terminated by SIGALRM
+    }
+}
@@ -0,0 +1,16 @@
+// Check that use-after-free errors show where the allocation was freed, and where the accessed part
+// of it was last written to.
+
+fn free(b: Box<[u8; 4]>) {
+    drop(b);
+}
+
+fn main() {
+    let mut b = Box::new([0u8; 4]);
+    let p = b.as_mut_ptr();
+    unsafe { *p.add(1) = 7 };
+    // This write is to a different part of the allocation, so it is not shown.
+    unsafe { *p.add(3) = 8 };
+    free(b);
+    let _val = unsafe { *p.add(1) }; //~ ERROR: has been freed
+}
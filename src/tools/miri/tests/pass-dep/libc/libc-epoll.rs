@@ -0,0 +1,155 @@
+//@only-target-linux
+// test_blocking_wait depends on a deterministic schedule.
+//@compile-flags: -Zmiri-preemption-rate=0
+
+use std::thread;
+
+fn main() {
+    test_eventfd_readiness();
+    test_socketpair_readiness();
+    test_oneshot();
+    test_edge_triggered();
+    test_ctl_errors();
+    test_timeout();
+    test_blocking_wait();
+}
+
+fn epoll_add(epfd: i32, fd: i32, events: u32, data: u64) {
+    let mut event = libc::epoll_event { events, u64: data };
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut event) };
+    assert_eq!(res, 0);
+}
+
+/// Waits for events and returns them as `(events, data)` pairs.
+fn epoll_wait(epfd: i32, timeout: i32) -> Vec<(u32, u64)> {
+    let mut events = [libc::epoll_event { events: 0, u64: 0 }; 8];
+    let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 8, timeout) };
+    assert!(res >= 0);
+    events[..res as usize].iter().map(|event| (event.events, event.u64)).collect()
+}
+
+fn eventfd_write(fd: i32, value: u64) {
+    let res = unsafe { libc::write(fd, value.to_ne_bytes().as_ptr().cast(), 8) };
+    assert_eq!(res, 8);
+}
+
+fn eventfd_read(fd: i32) -> u64 {
+    let mut buf = [0u8; 8];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), 8) };
+    assert_eq!(res, 8);
+    u64::from_ne_bytes(buf)
+}
+
+fn test_eventfd_readiness() {
+    let epfd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) };
+    epoll_add(epfd, fd, (libc::EPOLLIN | libc::EPOLLOUT) as u32, 42);
+
+    // The counter is 0, so the eventfd is only writable.
+    assert_eq!(epoll_wait(epfd, 0), [(libc::EPOLLOUT as u32, 42)]);
+    eventfd_write(fd, 1);
+    assert_eq!(epoll_wait(epfd, 0), [((libc::EPOLLIN | libc::EPOLLOUT) as u32, 42)]);
+    // Readiness is level-triggered.
+    assert_eq!(epoll_wait(epfd, 0), [((libc::EPOLLIN | libc::EPOLLOUT) as u32, 42)]);
+    assert_eq!(eventfd_read(fd), 1);
+    assert_eq!(epoll_wait(epfd, 0), [(libc::EPOLLOUT as u32, 42)]);
+
+    // Only the events we asked for are reported.
+    let mut event = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 7 };
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_MOD, fd, &mut event) }, 0);
+    assert_eq!(epoll_wait(epfd, 0), []);
+
+    // Deregistered file descriptors are not reported.
+    eventfd_write(fd, 1);
+    assert_eq!(epoll_wait(epfd, 0), [(libc::EPOLLIN as u32, 7)]);
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_DEL, fd, std::ptr::null_mut()) };
+    assert_eq!(res, 0);
+    assert_eq!(epoll_wait(epfd, 0), []);
+}
+
+fn test_socketpair_readiness() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let mut fds = [-1, -1];
+    let res = unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()) };
+    assert_eq!(res, 0);
+    epoll_add(epfd, fds[0], (libc::EPOLLIN | libc::EPOLLRDHUP) as u32, 0);
+    assert_eq!(epoll_wait(epfd, 0), []);
+
+    let data = "abc".as_bytes();
+    let res = unsafe { libc::write(fds[1], data.as_ptr().cast(), 3) };
+    assert_eq!(res, 3);
+    assert_eq!(epoll_wait(epfd, 0), [(libc::EPOLLIN as u32, 0)]);
+
+    // Closing the peer is reported as well.
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+    let hup = libc::EPOLLIN | libc::EPOLLRDHUP | libc::EPOLLHUP;
+    assert_eq!(epoll_wait(epfd, 0), [(hup as u32, 0)]);
+}
+
+fn test_oneshot() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = unsafe { libc::eventfd(1, libc::EFD_NONBLOCK) };
+    epoll_add(epfd, fd, (libc::EPOLLIN | libc::EPOLLONESHOT) as u32, 1);
+    assert_eq!(epoll_wait(epfd, 0), [(libc::EPOLLIN as u32, 1)]);
+    // The registration is disabled until it is re-armed.
+    assert_eq!(epoll_wait(epfd, 0), []);
+    let mut event =
+        libc::epoll_event { events: (libc::EPOLLIN | libc::EPOLLONESHOT) as u32, u64: 2 };
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_MOD, fd, &mut event) }, 0);
+    assert_eq!(epoll_wait(epfd, 0), [(libc::EPOLLIN as u32, 2)]);
+}
+
+fn test_edge_triggered() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) };
+    epoll_add(epfd, fd, (libc::EPOLLIN | libc::EPOLLOUT | libc::EPOLLET) as u32, 3);
+    assert_eq!(epoll_wait(epfd, 0), [(libc::EPOLLOUT as u32, 3)]);
+    // The eventfd stays writable, but that is not news.
+    assert_eq!(epoll_wait(epfd, 0), []);
+    // Becoming readable is reported once, along with everything else it is ready for.
+    eventfd_write(fd, 1);
+    assert_eq!(epoll_wait(epfd, 0), [((libc::EPOLLIN | libc::EPOLLOUT) as u32, 3)]);
+    assert_eq!(epoll_wait(epfd, 0), []);
+    // After draining it, becoming readable again is a new edge.
+    assert_eq!(eventfd_read(fd), 1);
+    assert_eq!(epoll_wait(epfd, 0), []);
+    eventfd_write(fd, 1);
+    assert_eq!(epoll_wait(epfd, 0), [((libc::EPOLLIN | libc::EPOLLOUT) as u32, 3)]);
+}
+
+fn test_ctl_errors() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = unsafe { libc::eventfd(0, 0) };
+    let mut event = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 0 };
+    unsafe {
+        assert_eq!(libc::epoll_ctl(epfd, libc::EPOLL_CTL_MOD, fd, &mut event), -1);
+        assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::ENOENT));
+        assert_eq!(libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut event), 0);
+        assert_eq!(libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut event), -1);
+        assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EEXIST));
+        assert_eq!(libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, 1000, &mut event), -1);
+        assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EBADF));
+    }
+}
+
+fn test_timeout() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = unsafe { libc::eventfd(0, 0) };
+    epoll_add(epfd, fd, libc::EPOLLIN as u32, 0);
+    let start = std::time::Instant::now();
+    assert_eq!(epoll_wait(epfd, 100), []);
+    assert!(start.elapsed().as_millis() >= 100);
+}
+
+fn test_blocking_wait() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) };
+    epoll_add(epfd, fd, libc::EPOLLIN as u32, 5);
+    let writer = thread::spawn(move || {
+        eventfd_write(fd, 3);
+    });
+    // This blocks until the other thread writes to the eventfd.
+    assert_eq!(epoll_wait(epfd, -1), [(libc::EPOLLIN as u32, 5)]);
+    assert_eq!(eventfd_read(fd), 3);
+    writer.join().unwrap();
+}
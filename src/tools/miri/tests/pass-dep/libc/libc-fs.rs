@@ -38,6 +38,8 @@ fn main() {
     test_isatty();
     test_read_and_uninit();
     test_nofollow_not_symlink();
+    #[cfg(target_os = "linux")]
+    test_epoll_regular_file();
 }
 
 fn test_file_open_unix_allow_two_args() {
@@ -432,3 +434,17 @@ fn test_nofollow_not_symlink() {
     let ret = unsafe { libc::open(cpath.as_ptr(), libc::O_NOFOLLOW | libc::O_CLOEXEC) };
     assert!(ret >= 0);
 }
+
+#[cfg(target_os = "linux")]
+fn test_epoll_regular_file() {
+    let path = utils::prepare_with_content("miri_test_libc_fs_epoll.txt", &[]);
+    let file = File::open(&path).unwrap();
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let mut event = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 0 };
+    // Regular files cannot be watched.
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, file.as_raw_fd(), &mut event) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EPERM));
+    drop(file);
+    remove_file(&path).unwrap();
+}
@@ -0,0 +1,213 @@
+//@ignore-target-windows: No libc signals on Windows
+// test_pthread_kill depends on a deterministic schedule.
+//@compile-flags: -Zmiri-preemption-rate=0
+
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+static LAST_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn handler(signum: libc::c_int) {
+    HANDLED.fetch_add(1, Ordering::Relaxed);
+    LAST_SIGNAL.store(signum, Ordering::Relaxed);
+}
+
+extern "C" fn siginfo_handler(
+    signum: libc::c_int,
+    info: *mut libc::siginfo_t,
+    _ctx: *mut libc::c_void,
+) {
+    assert_eq!(unsafe { (*info).si_signo }, signum);
+    HANDLED.fetch_add(1, Ordering::Relaxed);
+    LAST_SIGNAL.store(signum, Ordering::Relaxed);
+}
+
+fn reset() {
+    HANDLED.store(0, Ordering::Relaxed);
+    LAST_SIGNAL.store(0, Ordering::Relaxed);
+}
+
+fn install(signum: libc::c_int, handler: libc::sighandler_t, flags: libc::c_int) {
+    unsafe {
+        let mut act: libc::sigaction = MaybeUninit::zeroed().assume_init();
+        act.sa_sigaction = handler;
+        act.sa_flags = flags;
+        libc::sigemptyset(&mut act.sa_mask);
+        assert_eq!(libc::sigaction(signum, &act, ptr::null_mut()), 0);
+    }
+}
+
+fn main() {
+    test_sigset();
+    test_raise();
+    test_siginfo();
+    test_resethand();
+    test_ignore();
+    test_signal();
+    test_sigprocmask();
+    test_kill();
+    test_pthread_kill();
+    test_alarm();
+    test_invalid();
+}
+
+fn test_sigset() {
+    unsafe {
+        let mut set = MaybeUninit::<libc::sigset_t>::uninit();
+        assert_eq!(libc::sigemptyset(set.as_mut_ptr()), 0);
+        let mut set = set.assume_init();
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR1), 0);
+        assert_eq!(libc::sigaddset(&mut set, libc::SIGUSR1), 0);
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR1), 1);
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR2), 0);
+        assert_eq!(libc::sigdelset(&mut set, libc::SIGUSR1), 0);
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR1), 0);
+
+        assert_eq!(libc::sigfillset(&mut set), 0);
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR2), 1);
+        assert_eq!(libc::sigismember(&set, libc::SIGTERM), 1);
+    }
+}
+
+fn test_raise() {
+    reset();
+    install(libc::SIGUSR1, handler as libc::sighandler_t, 0);
+    // The handler runs before `raise` returns.
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+    assert_eq!(LAST_SIGNAL.load(Ordering::Relaxed), libc::SIGUSR1);
+    // The handler stays installed.
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 2);
+
+    // The previous disposition is reported.
+    unsafe {
+        let mut old: libc::sigaction = MaybeUninit::zeroed().assume_init();
+        assert_eq!(libc::sigaction(libc::SIGUSR1, ptr::null(), &mut old), 0);
+        assert_eq!(old.sa_sigaction, handler as libc::sighandler_t);
+    }
+}
+
+fn test_siginfo() {
+    reset();
+    install(libc::SIGUSR2, siginfo_handler as libc::sighandler_t, libc::SA_SIGINFO);
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR2) }, 0);
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+    assert_eq!(LAST_SIGNAL.load(Ordering::Relaxed), libc::SIGUSR2);
+}
+
+fn test_resethand() {
+    reset();
+    install(libc::SIGUSR1, handler as libc::sighandler_t, libc::SA_RESETHAND);
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+    unsafe {
+        let mut old: libc::sigaction = MaybeUninit::zeroed().assume_init();
+        assert_eq!(libc::sigaction(libc::SIGUSR1, ptr::null(), &mut old), 0);
+        assert_eq!(old.sa_sigaction, libc::SIG_DFL);
+    }
+}
+
+fn test_ignore() {
+    reset();
+    install(libc::SIGUSR1, libc::SIG_IGN, 0);
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 0);
+    // Signals that are ignored by default do not terminate the program.
+    assert_eq!(unsafe { libc::raise(libc::SIGCHLD) }, 0);
+}
+
+fn test_signal() {
+    reset();
+    let old = unsafe { libc::signal(libc::SIGUSR2, handler as libc::sighandler_t) };
+    assert_eq!(old, siginfo_handler as libc::sighandler_t);
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR2) }, 0);
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+    let old = unsafe { libc::signal(libc::SIGUSR2, libc::SIG_DFL) };
+    assert_eq!(old, handler as libc::sighandler_t);
+}
+
+fn test_sigprocmask() {
+    reset();
+    install(libc::SIGUSR1, handler as libc::sighandler_t, 0);
+    unsafe {
+        let mut set: libc::sigset_t = MaybeUninit::zeroed().assume_init();
+        libc::sigemptyset(&mut set);
+        libc::sigaddset(&mut set, libc::SIGUSR1);
+        assert_eq!(libc::sigprocmask(libc::SIG_BLOCK, &set, ptr::null_mut()), 0);
+
+        // A blocked signal stays pending...
+        assert_eq!(libc::raise(libc::SIGUSR1), 0);
+        assert_eq!(HANDLED.load(Ordering::Relaxed), 0);
+
+        let mut old: libc::sigset_t = MaybeUninit::zeroed().assume_init();
+        assert_eq!(libc::pthread_sigmask(libc::SIG_UNBLOCK, &set, &mut old), 0);
+        assert_eq!(libc::sigismember(&old, libc::SIGUSR1), 1);
+        // ...and is delivered once it gets unblocked.
+        assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+
+        assert_eq!(libc::pthread_sigmask(-1, &set, ptr::null_mut()), libc::EINVAL);
+    }
+}
+
+fn test_kill() {
+    reset();
+    install(libc::SIGUSR1, handler as libc::sighandler_t, 0);
+    unsafe {
+        assert_eq!(libc::kill(libc::getpid(), 0), 0);
+        assert_eq!(libc::kill(libc::getpid(), libc::SIGUSR1), 0);
+    }
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+}
+
+fn test_pthread_kill() {
+    reset();
+    install(libc::SIGUSR2, handler as libc::sighandler_t, 0);
+    let main_thread = unsafe { libc::pthread_self() };
+    thread::spawn(move || {
+        assert_eq!(unsafe { libc::pthread_kill(main_thread, libc::SIGUSR2) }, 0);
+    })
+    .join()
+    .unwrap();
+    // The signal is handled on the main thread.
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+    assert_eq!(LAST_SIGNAL.load(Ordering::Relaxed), libc::SIGUSR2);
+}
+
+fn test_alarm() {
+    reset();
+    install(libc::SIGALRM, handler as libc::sighandler_t, 0);
+    unsafe {
+        assert_eq!(libc::alarm(10), 0);
+        // Setting a new alarm returns the time left on the previous one, rounded up.
+        assert_eq!(libc::alarm(1), 10);
+        // `pause` returns once the handler of the signal ran.
+        assert_eq!(libc::pause(), -1);
+        assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINTR));
+        assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+        assert_eq!(LAST_SIGNAL.load(Ordering::Relaxed), libc::SIGALRM);
+        // The alarm fired, so there is nothing left to cancel.
+        assert_eq!(libc::alarm(0), 0);
+
+        // A cancelled alarm does not fire.
+        assert_eq!(libc::alarm(1), 0);
+        assert_eq!(libc::alarm(0), 1);
+    }
+    thread::sleep(Duration::from_secs(2));
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+}
+
+fn test_invalid() {
+    unsafe {
+        // `SIGKILL` cannot be caught.
+        let act: libc::sigaction = MaybeUninit::zeroed().assume_init();
+        assert_eq!(libc::sigaction(libc::SIGKILL, &act, ptr::null_mut()), -1);
+        assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+        assert_eq!(libc::raise(1000), -1);
+        assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+    }
+}
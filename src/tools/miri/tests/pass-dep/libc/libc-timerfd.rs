@@ -0,0 +1,156 @@
+//@only-target-linux
+
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::time::{Duration, Instant};
+
+fn main() {
+    test_nonblocking_read();
+    test_blocking_read();
+    test_rearm_while_blocked();
+    test_disarm_while_blocked();
+    test_interval();
+    test_gettime_and_disarm();
+    test_epoll();
+}
+
+fn itimerspec(value: Duration, interval: Duration) -> libc::itimerspec {
+    libc::itimerspec {
+        it_interval: libc::timespec {
+            tv_sec: interval.as_secs() as _,
+            tv_nsec: interval.subsec_nanos() as _,
+        },
+        it_value: libc::timespec {
+            tv_sec: value.as_secs() as _,
+            tv_nsec: value.subsec_nanos() as _,
+        },
+    }
+}
+
+/// Returns the number of expirations, or `None` if the timer did not expire.
+fn read_timer(fd: i32) -> Option<u64> {
+    let mut buf = [0u8; 8];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), 8) };
+    if res == -1 {
+        assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EAGAIN));
+        return None;
+    }
+    assert_eq!(res, 8);
+    Some(u64::from_ne_bytes(buf))
+}
+
+fn set_timer(fd: i32, value: Duration, interval: Duration) {
+    let spec = itimerspec(value, interval);
+    assert_eq!(unsafe { libc::timerfd_settime(fd, 0, &spec, ptr::null_mut()) }, 0);
+}
+
+fn test_nonblocking_read() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK) };
+    assert!(fd >= 0);
+    // A disarmed timer never expires.
+    assert_eq!(read_timer(fd), None);
+
+    set_timer(fd, Duration::from_millis(10), Duration::ZERO);
+    assert_eq!(read_timer(fd), None);
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(read_timer(fd), Some(1));
+    // A one-shot timer only expires once.
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(read_timer(fd), None);
+}
+
+fn test_blocking_read() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, 0) };
+    let start = Instant::now();
+    set_timer(fd, Duration::from_millis(100), Duration::from_millis(100));
+    // A blocking read waits for the first expiration...
+    assert_eq!(read_timer(fd), Some(1));
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    // ...and then for the next one.
+    assert_eq!(read_timer(fd), Some(1));
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+fn test_rearm_while_blocked() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, 0) };
+    let start = Instant::now();
+    set_timer(fd, Duration::from_millis(100), Duration::ZERO);
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        // The blocked read has not consumed anything yet.
+        let mut spec = MaybeUninit::<libc::itimerspec>::uninit();
+        assert_eq!(unsafe { libc::timerfd_gettime(fd, spec.as_mut_ptr()) }, 0);
+        let spec = unsafe { spec.assume_init() };
+        assert!(spec.it_value.tv_sec != 0 || spec.it_value.tv_nsec != 0);
+        // Move the expiration to 350ms after the start.
+        set_timer(fd, Duration::from_millis(300), Duration::ZERO);
+    });
+    // The read only returns once the timer expires with its new setting.
+    assert_eq!(read_timer(fd), Some(1));
+    assert!(start.elapsed() >= Duration::from_millis(350));
+    thread.join().unwrap();
+}
+
+fn test_disarm_while_blocked() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, 0) };
+    set_timer(fd, Duration::from_millis(100), Duration::ZERO);
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        set_timer(fd, Duration::ZERO, Duration::ZERO);
+    });
+    // Disarming the timer ends the blocked read without an expiration.
+    assert_eq!(read_timer(fd), None);
+    thread.join().unwrap();
+}
+
+fn test_interval() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK) };
+    set_timer(fd, Duration::from_millis(10), Duration::from_millis(10));
+    std::thread::sleep(Duration::from_millis(35));
+    // The timer expired after 10ms, 20ms and 30ms.
+    assert_eq!(read_timer(fd), Some(3));
+    assert_eq!(read_timer(fd), None);
+}
+
+fn test_gettime_and_disarm() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_BOOTTIME, libc::TFD_NONBLOCK) };
+    set_timer(fd, Duration::from_secs(10), Duration::from_secs(1));
+    let mut spec = MaybeUninit::<libc::itimerspec>::uninit();
+    assert_eq!(unsafe { libc::timerfd_gettime(fd, spec.as_mut_ptr()) }, 0);
+    let spec = unsafe { spec.assume_init() };
+    assert!(spec.it_value.tv_sec <= 10 && spec.it_value.tv_sec >= 9);
+    assert_eq!((spec.it_interval.tv_sec, spec.it_interval.tv_nsec), (1, 0));
+
+    // A zero value disarms the timer, and the old setting is reported.
+    let mut old = MaybeUninit::<libc::itimerspec>::uninit();
+    let disarm = itimerspec(Duration::ZERO, Duration::ZERO);
+    assert_eq!(unsafe { libc::timerfd_settime(fd, 0, &disarm, old.as_mut_ptr()) }, 0);
+    let old = unsafe { old.assume_init() };
+    assert_eq!(old.it_interval.tv_sec, 1);
+    let mut spec = MaybeUninit::<libc::itimerspec>::uninit();
+    assert_eq!(unsafe { libc::timerfd_gettime(fd, spec.as_mut_ptr()) }, 0);
+    let spec = unsafe { spec.assume_init() };
+    assert_eq!((spec.it_value.tv_sec, spec.it_value.tv_nsec), (0, 0));
+}
+
+fn test_epoll() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK) };
+    let mut event = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 1 };
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut event) }, 0);
+
+    let start = Instant::now();
+    set_timer(fd, Duration::from_millis(500), Duration::ZERO);
+    // Waiting forever wakes up when the timer expires.
+    let mut events = [libc::epoll_event { events: 0, u64: 0 }; 1];
+    let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 1, -1) };
+    assert_eq!(res, 1);
+    assert_eq!(events[0].u64, 1);
+    assert!(start.elapsed() >= Duration::from_millis(500));
+    assert_eq!(read_timer(fd), Some(1));
+
+    // A timeout that runs out before the timer expires.
+    set_timer(fd, Duration::from_secs(10), Duration::ZERO);
+    let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 1, 100) };
+    assert_eq!(res, 0);
+}
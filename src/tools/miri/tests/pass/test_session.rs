@@ -0,0 +1,40 @@
+//@compile-flags: --test -Zmiri-test-session
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// Every test starts with a fresh copy of the statics, so each of them sees `0`.
+#[test]
+fn counter_a() {
+    assert_eq!(COUNTER.fetch_add(1, Ordering::Relaxed), 0);
+}
+
+#[test]
+fn counter_b() {
+    assert_eq!(COUNTER.fetch_add(1, Ordering::Relaxed), 0);
+}
+
+#[test]
+#[should_panic]
+fn panics() {
+    panic!("expected");
+}
+
+#[test]
+fn returns_ok() -> Result<(), String> {
+    Ok(())
+}
+
+#[test]
+#[ignore]
+fn ignored() {
+    unreachable!();
+}
+
+mod nested {
+    #[test]
+    fn threads() {
+        std::thread::spawn(|| {}).join().unwrap();
+    }
+}
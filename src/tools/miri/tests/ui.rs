@@ -6,7 +6,7 @@
 
 use colored::*;
 use regex::bytes::Regex;
-use ui_test::color_eyre::eyre::{Context, Result};
+use ui_test::color_eyre::eyre::{ensure, eyre, Context, Result};
 use ui_test::{
     status_emitter, CommandBuilder, Config, Format, Match, Mode, OutputConflictHandling,
     RustfixMode,
@@ -255,6 +255,49 @@ fn ui(
         .with_context(|| format!("ui tests in {path} for {target} failed"))
 }
 
+/// Runs `tests/coverage/coverage.rs` with `-Zmiri-coverage` and checks the report it writes.
+fn coverage_report(target: &str, tmpdir: &Path) -> Result<()> {
+    let msg = format!("## Running coverage report test for {target}");
+    eprintln!("{}", msg.green().bold());
+
+    let out_dir = tmpdir.join("coverage");
+    let mut coverage_flag = OsString::from("-Zmiri-coverage=");
+    coverage_flag.push(&out_dir);
+    let status = Command::new(miri_path())
+        .arg(format!(
+            "--sysroot={}",
+            env::var("MIRI_SYSROOT").expect("MIRI_SYSROOT must be set to run the ui test suite")
+        ))
+        .args(["--edition=2021", "--target", target, "-Zmiri-panic-on-unsupported"])
+        .arg(coverage_flag)
+        .arg("tests/coverage/coverage.rs")
+        .status()?;
+    ensure!(status.success(), "tests/coverage/coverage.rs failed");
+
+    let report = |extension: &str| -> Result<String> {
+        for entry in std::fs::read_dir(&out_dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == extension) {
+                return Ok(std::fs::read_to_string(path)?);
+            }
+        }
+        Err(eyre!("no `.{extension}` file was written to {}", out_dir.display()))
+    };
+
+    let lcov = report("lcov")?;
+    ensure!(lcov.lines().any(|line| line == "SF:tests/coverage/coverage.rs"), "{lcov}");
+    for (function, calls) in [("main", 1), ("called_thrice", 3), ("never_called", 0)] {
+        let expected = format!("FNDA:{calls},{function}");
+        ensure!(lcov.lines().any(|line| line == expected), "`{expected}` is missing:\n{lcov}");
+    }
+
+    let unsupported = report("unsupported")?;
+    let expected = "2 can't call foreign function `miri_coverage_unknown_function` on OS `linux`\n\
+                    1 can't execute syscall with ID 0\n";
+    ensure!(unsupported == expected, "unexpected unsupported operations:\n{unsupported}");
+    Ok(())
+}
+
 fn get_target() -> String {
     env::var("MIRI_TEST_TARGET").ok().unwrap_or_else(get_host)
 }
@@ -301,6 +344,9 @@ fn main() -> Result<()> {
             tmpdir.path(),
         )?;
     }
+    if target.contains("linux") {
+        coverage_report(&target, tmpdir.path())?;
+    }
 
     Ok(())
 }
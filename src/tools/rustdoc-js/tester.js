@@ -430,6 +430,33 @@ function loadSearchJS(doc_folder, resource_suffix) {
             //console.log(this.descShards);
             this.descShards.get(crate)[shard].resolve(data.split("\n"));
         },
+        fullTextShards: new Map(),
+        loadFullTextShard: function(crate, kind, shard) {
+            const fname = `${crate}-${kind}-${shard}-`;
+            let fullTextShard = this.fullTextShards.get(fname);
+            if (fullTextShard === undefined) {
+                fullTextShard = {promise: null, resolve: null};
+                fullTextShard.promise = new Promise((resolve, reject) => {
+                    fullTextShard.resolve = resolve;
+                    fs.readFile(
+                        `${doc_folder}/search.fulltext/${crate}/${fname}${resource_suffix}.js`,
+                        (err, data) => {
+                            if (err) {
+                                reject(err);
+                            } else {
+                                eval(data.toString("utf8"));
+                            }
+                        },
+                    );
+                });
+                this.fullTextShards.set(fname, fullTextShard);
+            }
+            return fullTextShard.promise;
+        },
+        loadedFullTextShard: function(crate, kind, shard, data) {
+            this.fullTextShards.get(`${crate}-${kind}-${shard}-`)
+                .resolve(kind === "text" ? data.split("\n") : data);
+        },
     };
 
     const staticFiles = path.join(doc_folder, "static.files");
@@ -12,13 +12,14 @@
 use std::env;
 use std::fs::File;
 use std::io::{self, stdout, Read, Write};
+use std::ops;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use getopts::{Matches, Options};
 
 use crate::rustfmt::{
-    load_config, CliOptions, Color, Config, Edition, EmitMode, FileLines, FileName,
+    format_range, load_config, CliOptions, Color, Config, Edition, EmitMode, FileLines, FileName,
     FormatReportFormatterBuilder, Input, Session, Verbosity,
 };
 
@@ -90,6 +91,9 @@ pub enum OperationError {
     /// supported with standard input.
     #[error("Emit mode {0} not supported with standard output.")]
     StdinBadEmit(EmitMode),
+    /// Attempt to use `--range` with input files.
+    #[error("The `--range` option only works with standard input.")]
+    RangeWithFiles,
 }
 
 impl From<IoError> for OperationError {
@@ -116,7 +120,7 @@ fn make_opts() -> Options {
     );
     let is_nightly = is_nightly();
     let emit_opts = if is_nightly {
-        "[files|stdout|coverage|checkstyle|json]"
+        "[files|stdout|coverage|checkstyle|json|sarif|github]"
     } else {
         "[files|stdout]"
     };
@@ -150,6 +154,13 @@ fn make_opts() -> Options {
         "Prints the names of mismatched files that were formatted. Prints the names of \
          files that would be formatted when used with `--check` mode. ",
     );
+    opts.optopt(
+        "",
+        "range",
+        "Format only the items or statements around the given byte range of the \
+         standard input, and print the edit to apply to it as JSON.",
+        "START..END",
+    );
     opts.optmulti(
         "",
         "config",
@@ -181,6 +192,13 @@ fn make_opts() -> Options {
             "skip-children",
             "Don't reformat child modules (unstable).",
         );
+        opts.optopt(
+            "",
+            "cache-dir",
+            "Skip the files which were formatted in a previous run, and which did not \
+             change since, by recording them in a cache in DIR (unstable).",
+            "DIR",
+        );
     }
 
     opts.optflag("v", "verbose", "Print verbose output");
@@ -262,13 +280,26 @@ fn format_string(input: String, options: GetOptsOptions) -> Result<i32> {
     // try to read config from local directory
     let (mut config, _) = load_config(Some(Path::new(".")), Some(options.clone()))?;
 
+    if let Some(range) = options.range {
+        let edit = format_range(&input, range, &config).map_err(|e| format_err!("{e}"))?;
+        println!("{}", serde_json::to_string(&edit)?);
+        return Ok(0);
+    }
+
     if options.check {
-        config.set().emit_mode(EmitMode::Diff);
+        config
+            .set()
+            .emit_mode(options.emit_mode.unwrap_or(EmitMode::Diff));
     } else {
         match options.emit_mode {
             // Emit modes which work with standard input
             // None means default, which is Stdout.
-            None | Some(EmitMode::Stdout) | Some(EmitMode::Checkstyle) | Some(EmitMode::Json) => {}
+            None
+            | Some(EmitMode::Stdout)
+            | Some(EmitMode::Checkstyle)
+            | Some(EmitMode::Json)
+            | Some(EmitMode::Sarif)
+            | Some(EmitMode::GitHub) => {}
             Some(emit_mode) => {
                 return Err(OperationError::StdinBadEmit(emit_mode).into());
             }
@@ -316,6 +347,9 @@ fn format(
 
     let out = &mut stdout();
     let mut session = Session::new(config, Some(out));
+    if let Some(ref cache_dir) = options.cache_dir {
+        session.enable_cache(cache_dir);
+    }
 
     for file in files {
         if !file.exists() {
@@ -483,6 +517,10 @@ fn determine_operation(matches: &Matches) -> Result<Operation, OperationError> {
         })
         .collect();
 
+    if !files.is_empty() && matches.opt_present("range") {
+        return Err(OperationError::RangeWithFiles);
+    }
+
     // if no file argument is supplied, read from stdin
     if files.is_empty() {
         if minimal_config_path.is_some() {
@@ -502,6 +540,9 @@ fn determine_operation(matches: &Matches) -> Result<Operation, OperationError> {
 
 const STABLE_EMIT_MODES: [EmitMode; 3] = [EmitMode::Files, EmitMode::Stdout, EmitMode::Diff];
 
+/// Emit modes which report misformatted code, and so can be combined with `--check`.
+const CHECK_EMIT_MODES: [EmitMode; 2] = [EmitMode::Sarif, EmitMode::GitHub];
+
 /// Parsed command line options.
 #[derive(Clone, Debug, Default)]
 struct GetOptsOptions {
@@ -516,6 +557,8 @@ struct GetOptsOptions {
     edition: Option<Edition>,
     color: Option<Color>,
     file_lines: FileLines, // Default is all lines in all files.
+    cache_dir: Option<PathBuf>,
+    range: Option<ops::Range<usize>>,
     unstable_features: bool,
     error_on_unformatted: Option<bool>,
     print_misformatted_file_names: bool,
@@ -545,6 +588,7 @@ pub fn from_matches(matches: &Matches) -> Result<GetOptsOptions> {
                 if let Some(ref file_lines) = matches.opt_str("file-lines") {
                     options.file_lines = file_lines.parse()?;
                 }
+                options.cache_dir = matches.opt_str("cache-dir").map(PathBuf::from);
             } else {
                 let mut unstable_options = vec![];
                 if matches.opt_present("skip-children") {
@@ -556,6 +600,9 @@ pub fn from_matches(matches: &Matches) -> Result<GetOptsOptions> {
                 if matches.opt_present("file-lines") {
                     unstable_options.push("`--file-lines`");
                 }
+                if matches.opt_present("cache-dir") {
+                    unstable_options.push("`--cache-dir`");
+                }
                 if !unstable_options.is_empty() {
                     let s = if unstable_options.len() == 1 { "" } else { "s" };
                     return Err(format_err!(
@@ -594,11 +641,25 @@ pub fn from_matches(matches: &Matches) -> Result<GetOptsOptions> {
 
         options.check = matches.opt_present("check");
         if let Some(ref emit_str) = matches.opt_str("emit") {
-            if options.check {
-                return Err(format_err!("Invalid to use `--emit` and `--check`"));
+            let emit_mode = emit_mode_from_emit_str(emit_str)?;
+            if options.check && !CHECK_EMIT_MODES.contains(&emit_mode) {
+                return Err(format_err!(
+                    "Invalid to use `--emit` and `--check`, except with `--emit=sarif` or \
+                     `--emit=github`"
+                ));
             }
 
-            options.emit_mode = Some(emit_mode_from_emit_str(emit_str)?);
+            options.emit_mode = Some(emit_mode);
+        }
+
+        if let Some(ref range_str) = matches.opt_str("range") {
+            if options.check || options.emit_mode.is_some() {
+                return Err(format_err!(
+                    "Invalid to use `--range` and `--emit` or `--check`"
+                ));
+            }
+
+            options.range = Some(range_from_range_str(range_str)?);
         }
 
         if let Some(ref edition_str) = matches.opt_str("edition") {
@@ -668,7 +729,9 @@ fn apply_to(self, config: &mut Config) {
             config.set().edition(edition);
         }
         if self.check {
-            config.set().emit_mode(EmitMode::Diff);
+            config
+                .set()
+                .emit_mode(self.emit_mode.unwrap_or(EmitMode::Diff));
         } else if let Some(emit_mode) = self.emit_mode {
             config.set().emit_mode(emit_mode);
         }
@@ -702,6 +765,16 @@ fn edition_from_edition_str(edition_str: &str) -> Result<Edition> {
     }
 }
 
+fn range_from_range_str(range_str: &str) -> Result<ops::Range<usize>> {
+    match range_str.split_once("..") {
+        Some((start, end)) => match (start.parse(), end.parse()) {
+            (Ok(start), Ok(end)) => Ok(start..end),
+            _ => Err(format_err!("Invalid value for `--range`")),
+        },
+        None => Err(format_err!("Invalid value for `--range`")),
+    }
+}
+
 fn emit_mode_from_emit_str(emit_str: &str) -> Result<EmitMode> {
     match emit_str {
         "files" => Ok(EmitMode::Files),
@@ -709,6 +782,8 @@ fn emit_mode_from_emit_str(emit_str: &str) -> Result<EmitMode> {
         "coverage" => Ok(EmitMode::Coverage),
         "checkstyle" => Ok(EmitMode::Checkstyle),
         "json" => Ok(EmitMode::Json),
+        "sarif" => Ok(EmitMode::Sarif),
+        "github" => Ok(EmitMode::GitHub),
         _ => Err(format_err!("Invalid value for `--emit`")),
     }
 }
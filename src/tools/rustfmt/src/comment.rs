@@ -394,21 +394,29 @@ fn consume_same_line_comments(
 }
 
 /// Enum indicating if the code block contains rust based on attributes
+#[derive(Debug, PartialEq)]
 enum CodeBlockAttribute {
     Rust,
     NotRust,
 }
 
 impl CodeBlockAttribute {
-    /// Parse comma separated attributes list. Return rust only if all
-    /// attributes are valid rust attributes
+    /// Parse the attributes of a code block the way rustdoc does: they are separated by commas,
+    /// spaces or tabs, and custom classes are given in braces. Return rust only if all attributes
+    /// are valid rust attributes, which leaves out the code blocks that are `ignore`d or expected
+    /// to fail to compile.
     /// See <https://doc.rust-lang.org/rustdoc/print.html#attributes>
     fn new(attributes: &str) -> CodeBlockAttribute {
-        for attribute in attributes.split(',') {
-            match attribute.trim() {
-                "" | "rust" | "should_panic" | "no_run" | "edition2015" | "edition2018"
-                | "edition2021" => (),
-                "ignore" | "compile_fail" | "text" => return CodeBlockAttribute::NotRust,
+        let mut in_braces = false;
+        for attribute in attributes.split([',', ' ', '\t']) {
+            // Custom classes, e.g. `{.class}`, don't change how the code block is tested.
+            if in_braces || attribute.starts_with('{') {
+                in_braces = !attribute.ends_with('}');
+                continue;
+            }
+            match attribute {
+                "" | "rust" | "should_panic" | "no_run" | "test_harness" | "standalone_crate" => (),
+                _ if attribute.starts_with("edition") => (),
                 _ => return CodeBlockAttribute::NotRust,
             }
         }
@@ -416,6 +424,26 @@ fn new(attributes: &str) -> CodeBlockAttribute {
     }
 }
 
+/// Returns the fence that opens a code block on this line, e.g. ```` ``` ```` or `~~~~`, and the
+/// rest of the line.
+fn code_block_fence(line: &str) -> Option<(&str, &str)> {
+    let fence_char = line.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let rest = line.trim_start_matches(fence_char);
+    let fence = &line[..line.len() - rest.len()];
+    // The info string of a backtick fence can't contain backticks.
+    if fence.len() < 3 || (fence_char == '`' && rest.contains('`')) {
+        return None;
+    }
+    Some((fence, rest))
+}
+
+/// Checks whether this line closes the code block opened by `fence`: it has to be made of at
+/// least as many of the same fence characters, with nothing after them.
+fn closes_code_block(line: &str, fence: &str) -> bool {
+    let line = line.trim_end();
+    line.len() >= fence.len() && line.chars().all(|c| fence.starts_with(c))
+}
+
 /// Block that is formatted as an item.
 ///
 /// An item starts with either a star `*`, a dash `-`, a greater-than `>`, a plus '+', or a number
@@ -569,6 +597,8 @@ struct CommentRewrite<'a> {
     code_block_buffer: String,
     is_prev_line_multi_line: bool,
     code_block_attr: Option<CodeBlockAttribute>,
+    /// The fence that opened the code block we are in.
+    code_block_fence: String,
     item_block: Option<ItemizedBlock>,
     comment_line_separator: String,
     indent_str: String,
@@ -610,6 +640,7 @@ fn new(
             code_block_buffer: String::with_capacity(128),
             is_prev_line_multi_line: false,
             code_block_attr: None,
+            code_block_fence: String::new(),
             item_block: None,
             comment_line_separator: format!("{indent_str}{line_start}"),
             max_width,
@@ -750,7 +781,7 @@ fn handle_line(
                 )),
             };
         } else if self.code_block_attr.is_some() {
-            if line.starts_with("```") {
+            if closes_code_block(line, &self.code_block_fence) {
                 let code_block = match self.code_block_attr.as_ref().unwrap() {
                     CodeBlockAttribute::Rust
                         if self.fmt.config.format_code_in_doc_comments()
@@ -791,8 +822,9 @@ fn handle_line(
 
         self.code_block_attr = None;
         self.item_block = None;
-        if let Some(stripped) = line.strip_prefix("```") {
-            self.code_block_attr = Some(CodeBlockAttribute::new(stripped))
+        if let Some((fence, attributes)) = code_block_fence(line) {
+            self.code_block_attr = Some(CodeBlockAttribute::new(attributes));
+            self.code_block_fence = fence.to_owned();
         } else if self.fmt.config.wrap_comments() {
             if let Some(ib) = ItemizedBlock::new(line) {
                 self.item_block = Some(ib);
@@ -2136,4 +2168,49 @@ fn test_itemized_block_nonobvious_markers_are_rejected() {
             );
         }
     }
+
+    #[test]
+    fn test_code_block_attributes() {
+        let rust = [
+            "",
+            "rust",
+            "should_panic,edition2021",
+            "no_run test_harness",
+            "{.class} rust",
+        ];
+        for attributes in rust {
+            assert_eq!(
+                CodeBlockAttribute::new(attributes),
+                CodeBlockAttribute::Rust,
+                "{attributes}"
+            );
+        }
+        let not_rust = [
+            "text",
+            "ignore",
+            "ignore-wasm32",
+            "compile_fail,E0277",
+            "rust,toml",
+        ];
+        for attributes in not_rust {
+            assert_eq!(
+                CodeBlockAttribute::new(attributes),
+                CodeBlockAttribute::NotRust,
+                "{attributes}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_code_block_fences() {
+        assert_eq!(code_block_fence("```rust"), Some(("```", "rust")));
+        assert_eq!(code_block_fence("~~~~"), Some(("~~~~", "")));
+        assert_eq!(code_block_fence("``not a fence``"), None);
+        assert_eq!(code_block_fence("``"), None);
+        assert!(closes_code_block("````", "```"));
+        assert!(closes_code_block("~~~ ", "~~~"));
+        assert!(!closes_code_block("```", "````"));
+        assert!(!closes_code_block("```rust", "```"));
+        assert!(!closes_code_block("~~~", "```"));
+    }
 }
@@ -1,4 +1,5 @@
 use crate::config::file_lines::FileLines;
+use crate::config::import_groups::ImportGroups;
 use crate::config::macro_names::MacroSelectors;
 use crate::config::options::{IgnoreList, WidthHeuristics};
 
@@ -53,6 +54,12 @@ fn doc_hint() -> String {
     }
 }
 
+impl ConfigType for ImportGroups {
+    fn doc_hint() -> String {
+        String::from("[{ patterns = [<string>, ...], granularity = <granularity> }, ...]")
+    }
+}
+
 impl ConfigType for WidthHeuristics {
     fn doc_hint() -> String {
         String::new()
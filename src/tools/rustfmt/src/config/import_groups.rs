@@ -0,0 +1,226 @@
+//! This module contains types and functions to support custom import groups.
+
+use itertools::Itertools;
+use std::{fmt, str};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json as json;
+use thiserror::Error;
+
+use crate::config::options::ImportGranularity;
+
+/// Pattern segment matching any crate of the current Cargo workspace.
+pub(crate) const WORKSPACE_SEGMENT: &str = "$workspace";
+
+/// A pattern matched against the path of an import, e.g. `tokio::*` or `my_company_*`.
+///
+/// The pattern matches the imports whose path starts with its segments. A `*` in a
+/// segment matches any sequence of characters, a trailing `::*` is optional, and a
+/// `$workspace` segment matches any crate of the current Cargo workspace.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ImportPattern(String);
+
+impl ImportPattern {
+    fn segments(&self) -> impl Iterator<Item = &str> {
+        let pattern = match self.0.strip_suffix("::*") {
+            Some(pattern) => pattern,
+            None if self.0 == "*" => "",
+            None => &self.0,
+        };
+        pattern.split("::").filter(|segment| !segment.is_empty())
+    }
+
+    /// Returns the number of segments of the pattern which constrain the path. When an
+    /// import matches several patterns, the most specific one wins.
+    pub(crate) fn specificity(&self) -> usize {
+        self.segments().filter(|segment| *segment != "*").count()
+    }
+
+    /// Returns `true` if the pattern refers to the crates of the Cargo workspace.
+    pub(crate) fn uses_workspace(&self) -> bool {
+        self.segments().any(|segment| segment == WORKSPACE_SEGMENT)
+    }
+
+    /// Returns `true` if the import with the given path segments is matched.
+    pub(crate) fn matches(&self, path: &[&str], workspace_crates: &[String]) -> bool {
+        let mut path = path.iter();
+        self.segments().all(|segment| match path.next() {
+            Some(name) if segment == WORKSPACE_SEGMENT => {
+                workspace_crates.iter().any(|krate| krate == name)
+            }
+            Some(name) => glob_matches(segment, name),
+            None => false,
+        })
+    }
+}
+
+/// Matches `name` against `pattern`, in which `*` stands for any sequence of characters.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(name) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            name.char_indices()
+                .map(|(i, _)| i)
+                .chain(Some(name.len()))
+                .any(|i| glob_matches(rest, &name[i..]))
+        }
+    }
+}
+
+impl fmt::Display for ImportPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl str::FromStr for ImportPattern {
+    type Err = ImportGroupsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_valid = !s.is_empty()
+            && s.split("::").all(|segment| {
+                !segment.is_empty()
+                    && segment
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || matches!(c, '_' | '*' | '$'))
+            });
+        if is_valid {
+            Ok(ImportPattern(s.to_owned()))
+        } else {
+            Err(ImportGroupsError::InvalidPattern(s.to_owned()))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ImportPattern {
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(de)?;
+        str::FromStr::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for ImportPattern {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+/// A group of imports, separated from the other groups by a blank line.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImportGroup {
+    /// The imports that belong to the group.
+    pub patterns: Vec<ImportPattern>,
+    /// How to merge the imports of the group, instead of `imports_granularity`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub granularity: Option<ImportGranularity>,
+}
+
+impl fmt::Display for ImportGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ patterns = [{}]", self.patterns.iter().format(", "))?;
+        if let Some(granularity) = self.granularity {
+            write!(f, ", granularity = {granularity}")?;
+        }
+        write!(f, " }}")
+    }
+}
+
+/// The ordered list of import groups used by `group_imports = "Custom"`.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct ImportGroups(pub Vec<ImportGroup>);
+
+impl fmt::Display for ImportGroups {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]", self.0.iter().format(", "))
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ImportGroupsError {
+    #[error("{0}")]
+    Json(json::Error),
+    #[error("invalid import pattern: `{0}`")]
+    InvalidPattern(String),
+}
+
+// This impl is needed for `Config::override_value` to work for use in tests.
+impl str::FromStr for ImportGroups {
+    type Err = ImportGroupsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        json::from_str(s).map_err(ImportGroupsError::Json)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    fn pattern(s: &str) -> ImportPattern {
+        ImportPattern::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn import_pattern_matches() {
+        let workspace = ["my_app".to_owned(), "my_lib".to_owned()];
+        let matches = |p: &str, path: &[&str]| pattern(p).matches(path, &workspace);
+
+        assert!(matches("tokio", &["tokio"]));
+        assert!(matches("tokio", &["tokio", "net"]));
+        assert!(matches("tokio::*", &["tokio", "net"]));
+        assert!(matches("tokio::*", &["tokio"]));
+        assert!(!matches("tokio", &["tokio_util"]));
+        assert!(matches("tokio*", &["tokio_util", "codec"]));
+        assert!(matches("*", &["anything"]));
+        assert!(matches("std::*::Read", &["std", "io", "Read"]));
+        assert!(!matches("std::*::Read", &["std", "io"]));
+        assert!(matches("$workspace", &["my_lib", "Thing"]));
+        assert!(!matches("$workspace", &["serde"]));
+        assert!(matches("crate", &["crate", "config"]));
+    }
+
+    #[test]
+    fn import_pattern_specificity() {
+        assert_eq!(pattern("*").specificity(), 0);
+        assert_eq!(pattern("tokio::*").specificity(), 1);
+        assert_eq!(pattern("std::io").specificity(), 2);
+        assert!(pattern("$workspace::*").uses_workspace());
+    }
+
+    #[test]
+    fn import_groups_from_str() {
+        let groups = ImportGroups::from_str(
+            r#"[{"patterns": ["std", "core"]}, {"patterns": ["tokio"], "granularity": "Crate"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            groups,
+            ImportGroups(vec![
+                ImportGroup {
+                    patterns: vec![pattern("std"), pattern("core")],
+                    granularity: None,
+                },
+                ImportGroup {
+                    patterns: vec![pattern("tokio")],
+                    granularity: Some(ImportGranularity::Crate),
+                },
+            ])
+        );
+        assert_eq!(
+            format!("{groups}"),
+            "[{ patterns = [std, core] }, { patterns = [tokio], granularity = Crate }]"
+        );
+        assert!(ImportGroups::from_str(r#"[{"patterns": ["tokio::"]}]"#).is_err());
+    }
+}
@@ -10,6 +10,8 @@
 #[allow(unreachable_pub)]
 pub use crate::config::file_lines::{FileLines, FileName, Range};
 #[allow(unreachable_pub)]
+pub use crate::config::import_groups::ImportGroups;
+#[allow(unreachable_pub)]
 pub use crate::config::lists::*;
 #[allow(unreachable_pub)]
 pub use crate::config::macro_names::{MacroSelector, MacroSelectors};
@@ -23,6 +25,7 @@
 pub(crate) mod options;
 
 pub(crate) mod file_lines;
+pub(crate) mod import_groups;
 #[allow(unreachable_pub)]
 pub(crate) mod lists;
 pub(crate) mod macro_names;
@@ -95,6 +98,8 @@
         "Merge or split imports to the provided granularity";
     group_imports: GroupImportsTactic, GroupImportsTactic::Preserve, false,
         "Controls the strategy for how imports are grouped together";
+    import_groups: ImportGroups, ImportGroups::default(), false,
+        "Ordered groups of imports matched by path patterns, for group_imports = \"Custom\"";
     merge_imports: bool, false, false, "(deprecated: use imports_granularity instead)";
 
     // Ordering
@@ -653,6 +658,7 @@ fn test_dump_default_config() {
 imports_layout = "Mixed"
 imports_granularity = "Preserve"
 group_imports = "Preserve"
+import_groups = []
 reorder_imports = true
 reorder_modules = true
 reorder_impl_items = false
@@ -116,6 +116,8 @@ pub enum GroupImportsTactic {
     StdExternalCrate,
     /// Discard existing groups, and create a single group for everything
     One,
+    /// Discard existing groups, and create the groups listed in `import_groups`
+    Custom,
 }
 
 #[config_type]
@@ -168,6 +170,10 @@ pub enum EmitMode {
     Json,
     /// Output the changed lines (for internal value only)
     ModifiedLines,
+    /// Writes the misformatted hunks and their replacements as a SARIF log.
+    Sarif,
+    /// Writes the misformatted hunks as GitHub Actions warning annotations.
+    GitHub,
     /// Checks if a diff can be generated. If so, rustfmt outputs a diff and
     /// quits with exit code 1.
     /// This option is designed to be run in CI where a non-zero exit signifies
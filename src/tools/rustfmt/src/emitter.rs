@@ -2,10 +2,13 @@
 pub(crate) use self::diff::*;
 pub(crate) use self::files::*;
 pub(crate) use self::files_with_backup::*;
+pub(crate) use self::github::*;
 pub(crate) use self::json::*;
 pub(crate) use self::modified_lines::*;
+pub(crate) use self::sarif::*;
 pub(crate) use self::stdout::*;
 use crate::FileName;
+use std::env;
 use std::io::{self, Write};
 use std::path::Path;
 
@@ -13,8 +16,10 @@
 mod diff;
 mod files;
 mod files_with_backup;
+mod github;
 mod json;
 mod modified_lines;
+mod sarif;
 mod stdout;
 
 pub(crate) struct FormattedFile<'a> {
@@ -50,3 +55,18 @@ fn ensure_real_path(filename: &FileName) -> &Path {
         _ => panic!("cannot format `{filename}` and emit to files"),
     }
 }
+
+/// Returns the path of `filename` relative to the current directory if possible, with
+/// `/` as separator, which is how code review tools expect files to be named.
+fn relative_path(filename: &FileName) -> String {
+    match *filename {
+        FileName::Real(ref path) => {
+            let path = env::current_dir()
+                .ok()
+                .and_then(|cwd| path.strip_prefix(cwd).ok())
+                .unwrap_or(path);
+            path.to_string_lossy().replace('\\', "/")
+        }
+        FileName::Stdin => "stdin".to_owned(),
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+use crate::rustfmt_diff::{make_diff, Mismatch, ModifiedLines};
+
+/// Writes a GitHub Actions `warning` workflow command for each misformatted hunk, so that
+/// it is shown as an annotation on the changed lines of a pull request.
+#[derive(Debug, Default)]
+pub(crate) struct GitHubEmitter;
+
+impl Emitter for GitHubEmitter {
+    fn emit_formatted_file(
+        &mut self,
+        output: &mut dyn Write,
+        FormattedFile {
+            filename,
+            original_text,
+            formatted_text,
+        }: FormattedFile<'_>,
+    ) -> Result<EmitterResult, io::Error> {
+        const CONTEXT_SIZE: usize = 0;
+        let diff = make_diff(original_text, formatted_text, CONTEXT_SIZE);
+        let has_diff = !diff.is_empty();
+        output_github_annotations(output, filename, diff)?;
+        Ok(EmitterResult { has_diff })
+    }
+}
+
+fn output_github_annotations<T>(
+    mut writer: T,
+    filename: &FileName,
+    diff: Vec<Mismatch>,
+) -> Result<(), io::Error>
+where
+    T: Write,
+{
+    let file = escape_property(&relative_path(filename));
+    for chunk in ModifiedLines::from(diff).chunks {
+        let line = chunk.line_number_orig;
+        let end_line = line + chunk.lines_removed.saturating_sub(1);
+        let message = if chunk.lines.is_empty() {
+            "Should be removed".to_owned()
+        } else if chunk.lines_removed == 0 {
+            format!("Should be preceded by:\n{}", chunk.lines.join("\n"))
+        } else {
+            format!("Should be:\n{}", chunk.lines.join("\n"))
+        };
+        writeln!(
+            writer,
+            "::warning file={file},line={line},endLine={end_line},title=rustfmt::{}",
+            escape_data(&message)
+        )?;
+    }
+    Ok(())
+}
+
+/// Escapes the message of a workflow command.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a property value of a workflow command.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rustfmt_diff::DiffLine;
+    use std::path::PathBuf;
+
+    #[test]
+    fn emits_nothing_on_file_with_no_mismatches() {
+        let mut writer = Vec::new();
+        let _ = output_github_annotations(
+            &mut writer,
+            &FileName::Real(PathBuf::from("src/well_formatted.rs")),
+            vec![],
+        );
+        assert!(writer.is_empty());
+    }
+
+    #[test]
+    fn emits_one_annotation_per_hunk() {
+        let mut writer = Vec::new();
+        let mismatches = vec![
+            Mismatch {
+                line_number: 2,
+                line_number_orig: 2,
+                lines: vec![
+                    DiffLine::Resulting(String::from("fn foo( a:u8,")),
+                    DiffLine::Resulting(String::from("  b: u8) {}")),
+                    DiffLine::Expected(String::from("fn foo(a: u8, b: u8) {}")),
+                ],
+            },
+            Mismatch {
+                line_number: 7,
+                line_number_orig: 8,
+                lines: vec![DiffLine::Resulting(String::from(""))],
+            },
+        ];
+        let _ = output_github_annotations(
+            &mut writer,
+            &FileName::Real(PathBuf::from("src/lib,1.rs")),
+            mismatches,
+        );
+        assert_eq!(
+            String::from_utf8(writer).unwrap(),
+            "::warning file=src/lib%2C1.rs,line=2,endLine=3,title=rustfmt::\
+             Should be:%0Afn foo(a: u8, b: u8) {}\n\
+             ::warning file=src/lib%2C1.rs,line=8,endLine=8,title=rustfmt::Should be removed\n",
+        );
+    }
+}
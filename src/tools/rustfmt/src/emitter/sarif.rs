@@ -0,0 +1,293 @@
+use super::*;
+use crate::rustfmt_diff::{make_diff, Mismatch, ModifiedLines};
+use serde::Serialize;
+use serde_json::to_string as to_json_string;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+const RULE_ID: &str = "rustfmt";
+
+/// Writes a SARIF log with one result per misformatted hunk. Each result carries a fix
+/// that replaces the hunk with its formatted version.
+#[derive(Debug, Default)]
+pub(crate) struct SarifEmitter {
+    results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run<'a>; 1],
+}
+
+#[derive(Debug, Serialize)]
+struct Run<'a> {
+    tool: Tool,
+    results: &'a [SarifResult],
+}
+
+#[derive(Debug, Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: [Rule; 1],
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: &'static str,
+    short_description: Message,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: &'static str,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    fixes: Vec<Fix>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_column: Option<u32>,
+    end_line: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_column: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: ArtifactContent,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct ArtifactContent {
+    text: String,
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_footer(&self, output: &mut dyn Write) -> Result<(), io::Error> {
+        let log = SarifLog {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: [Run {
+                tool: Tool {
+                    driver: Driver {
+                        name: "rustfmt",
+                        version: option_env!("CARGO_PKG_VERSION").unwrap_or("unknown"),
+                        information_uri: "https://github.com/rust-lang/rustfmt",
+                        rules: [Rule {
+                            id: RULE_ID,
+                            short_description: Message {
+                                text: "Code is not formatted according to rustfmt".to_owned(),
+                            },
+                        }],
+                    },
+                },
+                results: &self.results,
+            }],
+        };
+        writeln!(output, "{}", &to_json_string(&log)?)
+    }
+
+    fn emit_formatted_file(
+        &mut self,
+        _output: &mut dyn Write,
+        FormattedFile {
+            filename,
+            original_text,
+            formatted_text,
+        }: FormattedFile<'_>,
+    ) -> Result<EmitterResult, io::Error> {
+        const CONTEXT_SIZE: usize = 0;
+        let diff = make_diff(original_text, formatted_text, CONTEXT_SIZE);
+        let has_diff = !diff.is_empty();
+        self.add_misformatted_file(filename, diff);
+        Ok(EmitterResult { has_diff })
+    }
+}
+
+impl SarifEmitter {
+    fn add_misformatted_file(&mut self, filename: &FileName, diff: Vec<Mismatch>) {
+        let artifact_location = ArtifactLocation {
+            uri: relative_path(filename),
+        };
+        for chunk in ModifiedLines::from(diff).chunks {
+            let start_line = chunk.line_number_orig;
+            let end_line = start_line + chunk.lines_removed.saturating_sub(1);
+            // The replaced lines are deleted along with their line breaks, so the
+            // inserted lines carry their own.
+            let text = chunk.lines.iter().map(|line| format!("{line}\n")).collect();
+            self.results.push(SarifResult {
+                rule_id: RULE_ID,
+                level: "warning",
+                message: Message {
+                    text: "Code is not formatted according to rustfmt".to_owned(),
+                },
+                locations: vec![Location {
+                    physical_location: PhysicalLocation {
+                        artifact_location: artifact_location.clone(),
+                        region: Region {
+                            start_line,
+                            start_column: None,
+                            end_line,
+                            end_column: None,
+                        },
+                    },
+                }],
+                fixes: vec![Fix {
+                    description: Message {
+                        text: "Format with rustfmt".to_owned(),
+                    },
+                    artifact_changes: vec![ArtifactChange {
+                        artifact_location: artifact_location.clone(),
+                        replacements: vec![Replacement {
+                            deleted_region: Region {
+                                start_line,
+                                start_column: Some(1),
+                                end_line: start_line + chunk.lines_removed,
+                                end_column: Some(1),
+                            },
+                            inserted_content: ArtifactContent { text },
+                        }],
+                    }],
+                }],
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rustfmt_diff::DiffLine;
+    use std::path::PathBuf;
+
+    #[test]
+    fn emits_empty_results_on_file_with_no_mismatches() {
+        let mut emitter = SarifEmitter::default();
+        emitter.add_misformatted_file(&FileName::Real(PathBuf::from("src/lib.rs")), vec![]);
+        let mut writer = Vec::new();
+        let _ = emitter.emit_footer(&mut writer);
+        let log = String::from_utf8(writer).unwrap();
+        assert!(log.starts_with(
+            r#"{"$schema":"https://json.schemastore.org/sarif-2.1.0.json","version":"2.1.0","#
+        ));
+        assert!(log.ends_with("\"results\":[]}]}\n"));
+    }
+
+    #[test]
+    fn emits_result_with_fix_per_hunk() {
+        let mut emitter = SarifEmitter::default();
+        let mismatch = Mismatch {
+            line_number: 79,
+            line_number_orig: 79,
+            lines: vec![
+                DiffLine::Resulting(String::from("fn Foo<T>() where T: Bar {")),
+                DiffLine::Expected(String::from("fn Foo<T>()")),
+                DiffLine::Expected(String::from("where")),
+                DiffLine::Expected(String::from("    T: Bar,")),
+                DiffLine::Expected(String::from("{")),
+            ],
+        };
+        emitter.add_misformatted_file(&FileName::Real(PathBuf::from("foo/bar.rs")), vec![mismatch]);
+
+        let artifact_location = ArtifactLocation {
+            uri: String::from("foo/bar.rs"),
+        };
+        assert_eq!(
+            emitter.results,
+            vec![SarifResult {
+                rule_id: RULE_ID,
+                level: "warning",
+                message: Message {
+                    text: String::from("Code is not formatted according to rustfmt"),
+                },
+                locations: vec![Location {
+                    physical_location: PhysicalLocation {
+                        artifact_location: artifact_location.clone(),
+                        region: Region {
+                            start_line: 79,
+                            start_column: None,
+                            end_line: 79,
+                            end_column: None,
+                        },
+                    },
+                }],
+                fixes: vec![Fix {
+                    description: Message {
+                        text: String::from("Format with rustfmt"),
+                    },
+                    artifact_changes: vec![ArtifactChange {
+                        artifact_location,
+                        replacements: vec![Replacement {
+                            deleted_region: Region {
+                                start_line: 79,
+                                start_column: Some(1),
+                                end_line: 80,
+                                end_column: Some(1),
+                            },
+                            inserted_content: ArtifactContent {
+                                text: String::from("fn Foo<T>()\nwhere\n    T: Bar,\n{\n"),
+                            },
+                        }],
+                    }],
+                }],
+            }]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 // High level formatting functions.
 
 use std::collections::HashMap;
+use std::fs;
 use std::io::{self, Write};
 use std::time::{Duration, Instant};
 
@@ -18,8 +19,10 @@
 use crate::visitor::FmtVisitor;
 use crate::{modules, source_file, ErrorKind, FormatReport, Input, Session};
 
+pub(crate) mod cache;
 mod generated;
 mod newline_style;
+pub(crate) mod range;
 
 // A map of the files of a crate, with their new content
 pub(crate) type SourceFile = Vec<FileRecord>;
@@ -44,6 +47,10 @@ pub(crate) fn format_input_inner(
                 };
             }
 
+            if let Some(ref mut cache) = self.cache {
+                cache.set_config(&self.config);
+            }
+
             let config = &self.config.clone();
             let format_result = format_project(input, config, self, is_macro_def);
 
@@ -154,6 +161,12 @@ fn format_project<T: FormatHandler>(
         if input_is_stdin && contains_skip(module.attrs()) {
             return echo_back_stdin(context.psess.snippet_provider(module.span).entire_snippet());
         }
+        if context.handler.is_known_formatted(&path) {
+            should_emit_verbose(input_is_stdin, config, || {
+                println!("Skipping {} (formatted according to the cache)", path)
+            });
+            continue;
+        }
         should_emit_verbose(input_is_stdin, config, || println!("Formatting {}", path));
         context.format_file(path, &module, is_macro_def)?;
     }
@@ -262,6 +275,9 @@ fn format_file(
 
 // Handle the results of formatting.
 trait FormatHandler {
+    // Whether the file is known to be formatted, and can be skipped.
+    fn is_known_formatted(&self, path: &FileName) -> bool;
+
     fn handle_formatted_file(
         &mut self,
         psess: &ParseSess,
@@ -272,6 +288,13 @@ fn handle_formatted_file(
 }
 
 impl<'b, T: Write + 'b> FormatHandler for Session<'b, T> {
+    fn is_known_formatted(&self, path: &FileName) -> bool {
+        match (&self.cache, path) {
+            (Some(cache), FileName::Real(path)) => cache.is_formatted(path),
+            _ => false,
+        }
+    }
+
     // Called for each formatted file.
     fn handle_formatted_file(
         &mut self,
@@ -280,6 +303,12 @@ fn handle_formatted_file(
         result: String,
         report: &mut FormatReport,
     ) -> Result<(), ErrorKind> {
+        // Read the file before it is overwritten, to record whether it was formatted.
+        let original_text = match (&self.cache, &path) {
+            (Some(cache), FileName::Real(path)) if cache.is_enabled() => fs::read(path).ok(),
+            _ => None,
+        };
+
         if let Some(ref mut out) = self.out {
             match source_file::write_file(
                 Some(psess),
@@ -299,6 +328,20 @@ fn handle_formatted_file(
             }
         }
 
+        if let (Some(cache), FileName::Real(real_path), Some(original_text)) =
+            (&mut self.cache, &path, original_text)
+        {
+            // Files with formatting errors are formatted again, to report the errors again.
+            let has_errors = report
+                .internal
+                .borrow()
+                .0
+                .get(&path)
+                .is_some_and(|errors| !errors.is_empty());
+            let is_formatted = !has_errors && original_text == result.as_bytes();
+            cache.set_formatted(real_path, &original_text, is_formatted);
+        }
+
         self.source_file.push((path, result));
         Ok(())
     }
@@ -0,0 +1,277 @@
+//! A persistent record of the files which are known to be formatted, so that they are not
+//! formatted again as long as neither their content, the configuration nor rustfmt change.
+
+use std::collections::HashMap;
+use std::fs;
+use std::hash::Hasher;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use rustc_data_structures::stable_hasher::{Hash64, StableHasher};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, EmitMode};
+
+/// The version of the cache format, which names the directory the cache file is stored in.
+/// It must be changed along with the format of the file or the way keys are computed, so
+/// that rustfmt never reads a cache written by an incompatible build.
+const CACHE_FORMAT_VERSION: &str = "v1";
+const CACHE_FILE_NAME: &str = "rustfmt-cache.json";
+
+/// Identifies the build of rustfmt, as the formatting of the same code may change
+/// between two builds.
+const RUSTFMT_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    "-",
+    include_str!(concat!(env!("OUT_DIR"), "/commit-info.txt"))
+);
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct CacheFile {
+    /// The key of each formatted file, see `FormatCache::key`.
+    files: HashMap<PathBuf, u64>,
+}
+
+/// The files known to be formatted, stored in a directory.
+#[derive(Debug)]
+pub(crate) struct FormatCache {
+    path: PathBuf,
+    files: HashMap<PathBuf, u64>,
+    /// The entries added (or removed, with `None`) since the cache was loaded.
+    changes: HashMap<PathBuf, Option<u64>>,
+    /// The hash of the configuration the files are being formatted with, or `None` if the
+    /// cache cannot be used with it.
+    config_hash: Option<u64>,
+}
+
+impl FormatCache {
+    /// Loads the cache stored in `dir`. A missing or unreadable cache is empty.
+    pub(crate) fn load(dir: &Path) -> FormatCache {
+        let path = dir.join(CACHE_FORMAT_VERSION).join(CACHE_FILE_NAME);
+        FormatCache {
+            files: read_cache_file(&path).files,
+            path,
+            changes: HashMap::new(),
+            config_hash: None,
+        }
+    }
+
+    /// Sets the configuration the next files are formatted with.
+    pub(crate) fn set_config(&mut self, config: &Config) {
+        self.config_hash = config_hash(config);
+    }
+
+    /// Returns `true` if the file at `path` is known to be formatted with the current
+    /// configuration.
+    pub(crate) fn is_formatted(&self, path: &Path) -> bool {
+        match (self.files.get(&cache_path(path)), self.key(path)) {
+            (Some(cached_key), Some(key)) => *cached_key == key,
+            _ => false,
+        }
+    }
+
+    /// Records whether the file at `path`, whose content is `text`, is formatted.
+    pub(crate) fn set_formatted(&mut self, path: &Path, text: &[u8], is_formatted: bool) {
+        let Some(config_hash) = self.config_hash else {
+            return;
+        };
+        let path = cache_path(path);
+        let key = is_formatted.then(|| key(config_hash, text));
+        if self.files.get(&path).copied() == key {
+            return;
+        }
+        match key {
+            Some(key) => self.files.insert(path.clone(), key),
+            None => self.files.remove(&path),
+        };
+        self.changes.insert(path, key);
+    }
+
+    /// Returns `true` if the cache is used with the current configuration.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.config_hash.is_some()
+    }
+
+    fn key(&self, path: &Path) -> Option<u64> {
+        let config_hash = self.config_hash?;
+        let text = fs::read(path).ok()?;
+        Some(key(config_hash, &text))
+    }
+
+    /// Writes the changes to the cache directory. They are merged into the cache as it is
+    /// now on disk, in case another rustfmt process updated it in the meantime.
+    pub(crate) fn save(&self) -> io::Result<()> {
+        if self.changes.is_empty() {
+            return Ok(());
+        }
+        let mut cache_file = read_cache_file(&self.path);
+        for (path, key) in &self.changes {
+            match key {
+                Some(key) => cache_file.files.insert(path.clone(), *key),
+                None => cache_file.files.remove(path),
+            };
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write to a temporary file first, so that a concurrent reader never sees a
+        // partially written cache.
+        let tmp_path = self.path.with_extension(format!("{}.tmp", process::id()));
+        fs::write(&tmp_path, serde_json::to_vec(&cache_file)?)?;
+        fs::rename(&tmp_path, &self.path)
+    }
+}
+
+fn read_cache_file(path: &Path) -> CacheFile {
+    fs::read(path)
+        .ok()
+        .and_then(|contents| serde_json::from_slice(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Paths are made absolute, so that the same file is found from any working directory.
+fn cache_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_owned())
+}
+
+// The keys are stored on disk, so they are computed with a hasher whose output doesn't depend
+// on the build of rustfmt or on the platform, unlike `DefaultHasher`.
+fn key(config_hash: u64, text: &[u8]) -> u64 {
+    let mut hasher = StableHasher::new();
+    hasher.write_u64(config_hash);
+    write_bytes(&mut hasher, text);
+    hasher.finish::<Hash64>().as_u64()
+}
+
+fn write_bytes(hasher: &mut StableHasher, bytes: &[u8]) {
+    hasher.write_u64(bytes.len() as u64);
+    hasher.write(bytes);
+}
+
+/// Hashes the options which affect the formatting of a file, along with the version of
+/// rustfmt. Returns `None` when formatted files cannot be skipped, because only some lines
+/// are formatted, or because the emit mode outputs something for every file.
+fn config_hash(config: &Config) -> Option<u64> {
+    let emits_every_file = matches!(
+        config.emit_mode(),
+        EmitMode::Stdout | EmitMode::Coverage | EmitMode::Checkstyle | EmitMode::ModifiedLines
+    );
+    if emits_every_file || !config.file_lines().is_all() {
+        return None;
+    }
+
+    let mut options = config.all_options();
+    // These options do not change the formatted code.
+    options.emit_mode = None;
+    options.make_backup = None;
+    options.color = None;
+    options.ignore = None;
+    options.print_misformatted_file_names = None;
+    let options = options.to_toml().ok()?;
+
+    let mut hasher = StableHasher::new();
+    write_bytes(&mut hasher, RUSTFMT_VERSION.as_bytes());
+    write_bytes(&mut hasher, options.as_bytes());
+    Some(hasher.finish::<Hash64>().as_u64())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("rustfmt-cache-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn formatted_file_is_invalidated_by_changes() {
+        let dir = temp_dir("invalidation");
+        let file = dir.join("lib.rs");
+        fs::write(&file, "fn main() {}\n").unwrap();
+
+        let mut config = Config::default();
+        let mut cache = FormatCache::load(&dir);
+        cache.set_config(&config);
+        assert!(!cache.is_formatted(&file));
+        cache.set_formatted(&file, b"fn main() {}\n", true);
+        assert!(cache.is_formatted(&file));
+
+        fs::write(&file, "fn main() { }\n").unwrap();
+        assert!(!cache.is_formatted(&file));
+        fs::write(&file, "fn main() {}\n").unwrap();
+        assert!(cache.is_formatted(&file));
+
+        config.set().max_width(80);
+        cache.set_config(&config);
+        assert!(!cache.is_formatted(&file));
+
+        // Options which only change how results are reported keep the cache.
+        let mut config = Config::default();
+        config.set().emit_mode(EmitMode::Diff);
+        cache.set_config(&config);
+        assert!(cache.is_formatted(&file));
+
+        config.set().emit_mode(EmitMode::Stdout);
+        cache.set_config(&config);
+        assert!(!cache.is_enabled());
+        assert!(!cache.is_formatted(&file));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_merges_with_the_cache_on_disk() {
+        let dir = temp_dir("save");
+        let (a, b) = (dir.join("a.rs"), dir.join("b.rs"));
+        fs::write(&a, "mod b;\n").unwrap();
+        fs::write(&b, "struct B;\n").unwrap();
+        let config = Config::default();
+
+        let mut first = FormatCache::load(&dir);
+        let mut second = FormatCache::load(&dir);
+        first.set_config(&config);
+        second.set_config(&config);
+        first.set_formatted(&a, b"mod b;\n", true);
+        second.set_formatted(&b, b"struct B;\n", true);
+        first.save().unwrap();
+        second.save().unwrap();
+
+        let mut cache = FormatCache::load(&dir);
+        cache.set_config(&config);
+        assert!(cache.is_formatted(&a));
+        assert!(cache.is_formatted(&b));
+
+        cache.set_formatted(&a, b"mod b;\n", false);
+        cache.save().unwrap();
+        let mut cache = FormatCache::load(&dir);
+        cache.set_config(&config);
+        assert!(!cache.is_formatted(&a));
+        assert!(cache.is_formatted(&b));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keys_are_stable() {
+        // The keys are stored on disk, so they must not change between builds. If they have
+        // to change, `CACHE_FORMAT_VERSION` must change too.
+        assert_eq!(key(1, b"fn main() {}\n"), 8031759977240332116);
+
+        let dir = temp_dir("layout");
+        let file = dir.join("lib.rs");
+        fs::write(&file, "fn main() {}\n").unwrap();
+        let mut cache = FormatCache::load(&dir);
+        cache.set_config(&Config::default());
+        cache.set_formatted(&file, b"fn main() {}\n", true);
+        cache.save().unwrap();
+        let cache_file = dir.join(CACHE_FORMAT_VERSION).join(CACHE_FILE_NAME);
+        assert!(cache_file.is_file());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
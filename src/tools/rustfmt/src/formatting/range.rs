@@ -0,0 +1,364 @@
+//! Formatting of a byte range of a single input, for editors that want to
+//! "format selection" or "format on type" without rewriting the whole file.
+
+use std::collections::HashMap;
+use std::{cmp, io, ops};
+
+use rustc_ast::ast;
+use rustc_ast::visit::{self, AssocCtxt, Visitor};
+use rustc_span::{BytePos, Pos, Span};
+use serde::Serialize;
+
+use crate::config::{Config, EmitMode, FileLines, FileName, Range, Verbosity};
+use crate::parse::parser::Parser;
+use crate::parse::session::ParseSess;
+use crate::rustfmt_diff::{make_diff, DiffLine};
+use crate::utils::{contains_skip, count_newlines};
+use crate::{ErrorKind, Input, Session};
+
+/// Replacement of the bytes `range` of the original text with `new_text`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TextEdit {
+    /// The byte range of the original text to replace.
+    pub range: ops::Range<usize>,
+    /// The text to put in its place.
+    pub new_text: String,
+}
+
+/// Formats the part of `text` selected by the byte range `range`.
+///
+/// The selection is widened to the smallest enclosing item or statement, or to the
+/// run of items or statements it overlaps in a module, block, `impl` or `trait`. Only
+/// that part is formatted; the rest of the text is left alone, even when it is not
+/// formatted itself. An empty range selects the innermost node around the cursor,
+/// which is what "format on type" wants.
+///
+/// Returns the smallest edit turning `text` into its formatted form, or `None` when
+/// the selection is already formatted.
+pub fn format_range(
+    text: &str,
+    range: ops::Range<usize>,
+    config: &Config,
+) -> Result<Option<TextEdit>, ErrorKind> {
+    if range.start > range.end
+        || range.end > text.len()
+        || !text.is_char_boundary(range.start)
+        || !text.is_char_boundary(range.end)
+    {
+        return Err(ErrorKind::InvalidRange(range.start, range.end));
+    }
+    if config.disable_all_formatting() {
+        return Ok(None);
+    }
+
+    let node = rustc_span::create_session_if_not_set_then(config.edition().into(), |_| {
+        enclosing_node(text, &range, config)
+    })?;
+    let Some(node) = node else {
+        return Ok(None);
+    };
+
+    let lo = line_of(text, node.start);
+    let hi = line_of(text, cmp::max(node.start, node.end.saturating_sub(1)));
+    let formatted = format_line_range(text, Range::new(lo, hi), config)?;
+    Ok(edit_within_lines(text, &formatted, lo, hi))
+}
+
+/// Finds the byte range of the node that `range` should be widened to. Returns `None`
+/// when the input must not be formatted at all.
+fn enclosing_node(
+    text: &str,
+    range: &ops::Range<usize>,
+    config: &Config,
+) -> Result<Option<ops::Range<usize>>, ErrorKind> {
+    let psess = ParseSess::new(config)?;
+    let krate = Parser::parse_crate(Input::Text(text.to_owned()), &psess)
+        .map_err(|_| ErrorKind::ParseError)?;
+    if contains_skip(&krate.attrs) {
+        return Ok(None);
+    }
+
+    let file = psess.span_to_file_contents(krate.spans.inner_span);
+    let mut finder = EnclosingNodeFinder {
+        base: file.start_pos,
+        range: range.clone(),
+        best: None,
+    };
+    finder.consider_list(krate.items.iter().map(|item| item.span));
+    visit::walk_crate(&mut finder, &krate);
+    Ok(Some(finder.best.unwrap_or_else(|| range.clone())))
+}
+
+/// Formats the lines `lines` of `text`, leaving the other lines as they are.
+fn format_line_range(text: &str, lines: Range, config: &Config) -> Result<String, ErrorKind> {
+    let mut config = config.clone();
+    config.set().emit_mode(EmitMode::Stdout);
+    config.set().verbose(Verbosity::Quiet);
+    let file_lines = FileLines::from_ranges(HashMap::from([(FileName::Stdin, vec![lines])]));
+    config.set().file_lines(file_lines);
+
+    let mut out = Vec::with_capacity(text.len());
+    {
+        let mut session = Session::new(config, Some(&mut out));
+        session.format(Input::Text(text.to_owned()))?;
+        if session.has_parsing_errors() {
+            return Err(ErrorKind::ParseError);
+        }
+    }
+    String::from_utf8(out).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e).into())
+}
+
+/// Collects the changes rustfmt made to the lines `lo..=hi` of `original` into a
+/// single edit. Changes outside of those lines, such as the removal of trailing
+/// blank lines at the end of the file, are dropped.
+fn edit_within_lines(original: &str, formatted: &str, lo: usize, hi: usize) -> Option<TextEdit> {
+    // The changed region as 1-based, end-exclusive line numbers: `(orig_lo, orig_hi)`
+    // in the original text and `(fmt_lo, fmt_hi)` in the formatted one.
+    let mut region: Option<(usize, usize, usize, usize)> = None;
+    for mismatch in make_diff(original, formatted, 0) {
+        let removed = mismatch
+            .lines
+            .iter()
+            .filter(|line| matches!(line, DiffLine::Resulting(_)))
+            .count();
+        let added = mismatch.lines.len() - removed;
+        let mut orig_lo = mismatch.line_number_orig as usize;
+        let mut orig_hi = orig_lo + removed;
+        let mut fmt_lo = mismatch.line_number as usize;
+        let mut fmt_hi = fmt_lo + added;
+        if removed == added {
+            // The lines were rewritten one for one, so the ones outside of the
+            // selected lines (e.g. the signature of the enclosing function) can be
+            // told apart and left out.
+            let first = cmp::max(orig_lo, lo);
+            let last = cmp::min(orig_hi, hi + 1);
+            if first >= last {
+                continue;
+            }
+            fmt_lo += first - orig_lo;
+            fmt_hi = fmt_lo + (last - first);
+            orig_lo = first;
+            orig_hi = last;
+        } else if removed == 0 && !(lo <= orig_lo && orig_lo <= hi + 1)
+            || removed > 0 && !(orig_lo <= hi && lo < orig_hi)
+        {
+            continue;
+        }
+
+        region = Some(match region {
+            Some((orig_lo, _, fmt_lo, _)) => (orig_lo, orig_hi, fmt_lo, fmt_hi),
+            None => (orig_lo, orig_hi, fmt_lo, fmt_hi),
+        });
+    }
+
+    let (orig_lo, orig_hi, fmt_lo, fmt_hi) = region?;
+    minimal_edit(
+        original,
+        formatted,
+        line_start(original, orig_lo)..line_start(original, orig_hi),
+        line_start(formatted, fmt_lo)..line_start(formatted, fmt_hi),
+    )
+}
+
+/// Shrinks the replacement of `original[old]` by `formatted[new]` to the bytes that
+/// actually differ.
+fn minimal_edit(
+    original: &str,
+    formatted: &str,
+    old: ops::Range<usize>,
+    new: ops::Range<usize>,
+) -> Option<TextEdit> {
+    let old_text = &original[old.clone()];
+    let new_text = &formatted[new];
+    if old_text == new_text {
+        return None;
+    }
+
+    let mut prefix = old_text
+        .bytes()
+        .zip(new_text.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old_text.is_char_boundary(prefix) || !new_text.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let mut suffix = old_text
+        .bytes()
+        .rev()
+        .zip(new_text.bytes().rev())
+        .take(cmp::min(old_text.len(), new_text.len()) - prefix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old_text.is_char_boundary(old_text.len() - suffix)
+        || !new_text.is_char_boundary(new_text.len() - suffix)
+    {
+        suffix -= 1;
+    }
+
+    Some(TextEdit {
+        range: old.start + prefix..old.end - suffix,
+        new_text: new_text[prefix..new_text.len() - suffix].to_owned(),
+    })
+}
+
+/// Returns the 1-based line number of the byte at `offset`.
+fn line_of(text: &str, offset: usize) -> usize {
+    count_newlines(&text[..offset]) + 1
+}
+
+/// Returns the byte offset at which the 1-based `line` starts, or the length of the
+/// text for lines past its end.
+fn line_start(text: &str, line: usize) -> usize {
+    if line <= 1 {
+        return 0;
+    }
+    text.match_indices('\n')
+        .nth(line - 2)
+        .map_or(text.len(), |(i, _)| i + 1)
+}
+
+/// Looks for the smallest item, statement, or run of list members that contains
+/// the selection.
+struct EnclosingNodeFinder {
+    base: BytePos,
+    range: ops::Range<usize>,
+    best: Option<ops::Range<usize>>,
+}
+
+impl EnclosingNodeFinder {
+    fn offsets(&self, span: Span) -> ops::Range<usize> {
+        (span.lo() - self.base).to_usize()..(span.hi() - self.base).to_usize()
+    }
+
+    fn consider(&mut self, node: ops::Range<usize>) {
+        let contains = node.start <= self.range.start && self.range.end <= node.end;
+        let is_smaller = self
+            .best
+            .as_ref()
+            .map_or(true, |best| node.len() < best.len());
+        if contains && is_smaller {
+            self.best = Some(node);
+        }
+    }
+
+    /// Considers the members of an item or statement list that overlap the
+    /// selection as a single node.
+    fn consider_list(&mut self, spans: impl Iterator<Item = Span>) {
+        let overlapping = spans
+            .map(|span| self.offsets(span))
+            .filter(|node| node.start <= self.range.end && self.range.start <= node.end)
+            .reduce(|a, b| cmp::min(a.start, b.start)..cmp::max(a.end, b.end));
+        if let Some(node) = overlapping {
+            self.consider(node);
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for EnclosingNodeFinder {
+    fn visit_item(&mut self, item: &'ast ast::Item) {
+        self.consider(self.offsets(item.span));
+        match item.kind {
+            ast::ItemKind::Mod(_, ast::ModKind::Loaded(ref items, ..)) => {
+                self.consider_list(items.iter().map(|item| item.span))
+            }
+            ast::ItemKind::Impl(ref iimpl) => {
+                self.consider_list(iimpl.items.iter().map(|item| item.span))
+            }
+            ast::ItemKind::Trait(ref trait_) => {
+                self.consider_list(trait_.items.iter().map(|item| item.span))
+            }
+            _ => {}
+        }
+        visit::walk_item(self, item);
+    }
+
+    fn visit_assoc_item(&mut self, item: &'ast ast::AssocItem, ctxt: AssocCtxt) {
+        self.consider(self.offsets(item.span));
+        visit::walk_assoc_item(self, item, ctxt);
+    }
+
+    fn visit_block(&mut self, block: &'ast ast::Block) {
+        self.consider_list(block.stmts.iter().map(|stmt| stmt.span));
+        visit::walk_block(self, block);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast ast::Stmt) {
+        self.consider(self.offsets(stmt.span));
+        visit::walk_stmt(self, stmt);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn format_selection(text: &str, selection: &str) -> String {
+        let start = text.find(selection).unwrap();
+        let range = start..start + selection.len();
+        match format_range(text, range, &Config::default()).unwrap() {
+            Some(edit) => {
+                let mut result = text.to_owned();
+                result.replace_range(edit.range, &edit.new_text);
+                result
+            }
+            None => text.to_owned(),
+        }
+    }
+
+    #[test]
+    fn formats_only_the_selected_statements() {
+        let text = "fn main() {\n    let a=1;\n    let b=2;\n}\nfn  other( ) {}\n";
+        assert_eq!(
+            format_selection(text, "let b=2;"),
+            "fn main() {\n    let a=1;\n    let b = 2;\n}\nfn  other( ) {}\n"
+        );
+        assert_eq!(
+            format_selection(text, "a=1;\n    let b"),
+            "fn main() {\n    let a = 1;\n    let b = 2;\n}\nfn  other( ) {}\n"
+        );
+    }
+
+    #[test]
+    fn widens_the_selection_to_the_enclosing_statement() {
+        let text = "fn main() {\n    foo(a,\n        b);\n    let c=3;\n}\n";
+        assert_eq!(
+            format_selection(text, "b)"),
+            "fn main() {\n    foo(a, b);\n    let c=3;\n}\n"
+        );
+    }
+
+    #[test]
+    fn leaves_the_enclosing_item_alone() {
+        let text = "fn  main( ) {\n    let a=1;\n}\n\n\n";
+        assert_eq!(
+            format_selection(text, "a=1"),
+            "fn  main( ) {\n    let a = 1;\n}\n\n\n"
+        );
+    }
+
+    #[test]
+    fn returns_minimal_edit() {
+        let text = "fn main() {\n    let a=1;\n}\n";
+        let edit = format_range(text, 20..20, &Config::default()).unwrap();
+        assert_eq!(
+            edit,
+            Some(TextEdit {
+                range: 21..22,
+                new_text: " = ".to_owned(),
+            })
+        );
+        let text = "fn main() {\n    let a = 1;\n}\n";
+        assert_eq!(
+            format_range(text, 20..20, &Config::default()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_ranges() {
+        let text = "fn main() {}\n";
+        assert!(format_range(text, 5..2, &Config::default()).is_err());
+        assert!(format_range(text, 0..100, &Config::default()).is_err());
+        assert!(format_range("// é\n", 4..5, &Config::default()).is_err());
+    }
+}
@@ -32,7 +32,7 @@
 use std::io::{self, Write};
 use std::mem;
 use std::panic;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 use rustc_ast::ast;
@@ -41,6 +41,7 @@
 
 use crate::comment::LineClasses;
 use crate::emitter::Emitter;
+use crate::formatting::cache::FormatCache;
 use crate::formatting::{FormatErrorMap, FormattingError, ReportedErrors, SourceFile};
 use crate::modules::ModuleResolutionError;
 use crate::parse::parser::DirectoryOwnership;
@@ -54,6 +55,8 @@
 
 pub use crate::format_report_formatter::{FormatReportFormatter, FormatReportFormatterBuilder};
 
+pub use crate::formatting::range::{format_range, TextEdit};
+
 pub use crate::rustfmt_diff::{ModifiedChunk, ModifiedLines};
 
 #[macro_use]
@@ -143,6 +146,10 @@ pub enum ErrorKind {
     /// Invalid glob pattern in `ignore` configuration option.
     #[error("Invalid glob pattern found in ignore list: {0}")]
     InvalidGlobPattern(ignore::Error),
+    /// The byte range given for range formatting is out of bounds, reversed, or
+    /// does not fall on character boundaries.
+    #[error("invalid byte range: {0}..{1}")]
+    InvalidRange(usize, usize),
 }
 
 impl ErrorKind {
@@ -444,6 +451,7 @@ pub struct Session<'b, T: Write> {
     pub(crate) errors: ReportedErrors,
     source_file: SourceFile,
     emitter: Box<dyn Emitter + 'b>,
+    cache: Option<FormatCache>,
 }
 
 impl<'b, T: Write + 'b> Session<'b, T> {
@@ -460,9 +468,20 @@ pub fn new(config: Config, mut out: Option<&'b mut T>) -> Session<'b, T> {
             emitter,
             errors: ReportedErrors::default(),
             source_file: SourceFile::new(),
+            cache: None,
         }
     }
 
+    /// Skips the files recorded as formatted in the cache stored in `dir`, and records the
+    /// formatted files in it. The cache is written when the session is dropped.
+    ///
+    /// A file is only skipped if neither its content, the configuration nor the version of
+    /// rustfmt changed since it was recorded. The cache is not used with the emit modes
+    /// which write something for every file, nor when `file_lines` is set.
+    pub fn enable_cache(&mut self, dir: &Path) {
+        self.cache = Some(FormatCache::load(dir));
+    }
+
     /// The main entry point for Rustfmt. Formats the given input according to the
     /// given config. `out` is only necessary if required by the configuration.
     pub fn format(&mut self, input: Input) -> Result<FormatReport, ErrorKind> {
@@ -533,6 +552,8 @@ pub(crate) fn create_emitter<'a>(config: &Config) -> Box<dyn Emitter + 'a> {
         EmitMode::ModifiedLines => Box::new(emitter::ModifiedLinesEmitter::default()),
         EmitMode::Checkstyle => Box::new(emitter::CheckstyleEmitter::default()),
         EmitMode::Diff => Box::new(emitter::DiffEmitter::new(config.clone())),
+        EmitMode::Sarif => Box::new(emitter::SarifEmitter::default()),
+        EmitMode::GitHub => Box::new(emitter::GitHubEmitter::default()),
     }
 }
 
@@ -541,6 +562,9 @@ fn drop(&mut self) {
         if let Some(ref mut out) = self.out {
             let _ = self.emitter.emit_footer(out);
         }
+        if let Some(ref cache) = self.cache {
+            let _ = cache.save();
+        }
     }
 }
 
@@ -25,7 +25,7 @@
     contains_comment, CharClasses, FindUncommented, FullCodeCharKind, LineClasses,
 };
 use crate::config::lists::*;
-use crate::config::Version;
+use crate::config::{Config, Version};
 use crate::expr::{rewrite_array, rewrite_assign_rhs, RhsAssignKind};
 use crate::lists::{itemize_list, write_list, ListFormatting};
 use crate::overflow;
@@ -458,6 +458,12 @@ pub(crate) fn rewrite_macro_def(
             None if context.macro_rewrite_failure.get() => {
                 Some(context.snippet(branch.body).trim().to_string())
             }
+            // Version Two keeps the arms that can't be formatted as they are, rather than the
+            // whole macro.
+            None if context.config.version() == Version::Two => {
+                let snippet = context.snippet(branch.span).trim();
+                Some(snippet.strip_suffix(';').unwrap_or(snippet).to_owned())
+            }
             None => None,
         },
         context.snippet_provider.span_after(span, "{"),
@@ -506,33 +512,71 @@ fn register_metavariable(
     map.insert(old_name, new_name);
 }
 
+// The markers that stand for the start and the end of a repetition such as `$($x),*` while the
+// body of a macro arm is formatted. The end marker also holds the separator and the repetition
+// operator, e.g. `/*zrepeat),**/`. Comments can appear anywhere, so the code in the repetition can
+// be parsed and formatted like any other code.
+const REPEAT_START: &str = "/*zrepeat(*/";
+const REPEAT_END: &str = "/*zrepeat)";
+const REPEAT_MARKER: &str = "zrepeat";
+
 // Replaces `$foo` with `zfoo`. We must check for name overlap to ensure we
 // aren't causing problems.
 // This should also work for escaped `$` variables, where we leave earlier `$`s.
-fn replace_names(input: &str) -> Option<(String, HashMap<String, String>)> {
+// With `repetitions`, also replaces the start and the end of repetitions with the markers
+// above, which `restore_repetitions` turns back into `$(` and `)` followed by the separator
+// and the operator.
+fn replace_names(input: &str, repetitions: bool) -> Option<(String, HashMap<String, String>)> {
     // Each substitution will require five or six extra bytes.
     let mut result = String::with_capacity(input.len() + 64);
     let mut substs = HashMap::new();
     let mut dollar_count = 0;
     let mut cur_name = String::new();
-
-    for (kind, c) in CharClasses::new(input.chars()) {
+    // The parenthesis depth of the repetitions we are in.
+    let mut open_repetitions = vec![];
+    let mut depth = 0;
+
+    let chars: Vec<_> = CharClasses::new(input.chars()).collect();
+    let mut i = 0;
+    while let Some(&(kind, c)) = chars.get(i) {
+        i += 1;
         if kind != FullCodeCharKind::Normal {
             result.push(c);
         } else if c == '$' {
             dollar_count += 1;
         } else if dollar_count == 0 {
+            if repetitions && c == '(' {
+                depth += 1;
+            } else if repetitions && c == ')' {
+                if open_repetitions.last() == Some(&depth) {
+                    open_repetitions.pop();
+                    let tail = repetition_tail(&chars, &mut i)?;
+                    result.push_str(REPEAT_END);
+                    result.push_str(&tail);
+                    result.push_str("*/");
+                    depth -= 1;
+                    continue;
+                }
+                depth -= 1;
+            }
             result.push(c);
         } else if !c.is_alphanumeric() && !cur_name.is_empty() {
             // Terminates a name following one or more dollars.
             register_metavariable(&mut substs, &mut result, &cur_name, dollar_count);
 
-            result.push(c);
             dollar_count = 0;
             cur_name.clear();
+            // Handle `c` like any character that doesn't follow a name.
+            i -= 1;
         } else if c == '(' && cur_name.is_empty() {
-            // FIXME: Support macro def with repeat.
-            return None;
+            if !repetitions || dollar_count != 1 {
+                // FIXME: Support macro def with repeat.
+                return None;
+            }
+            result.push_str(REPEAT_START);
+            dollar_count = 0;
+            depth += 1;
+            open_repetitions.push(depth);
         } else if c.is_alphanumeric() || c == '_' {
             cur_name.push(c);
         }
@@ -541,12 +585,150 @@ fn replace_names(input: &str) -> Option<(String, HashMap<String, String>)> {
     if !cur_name.is_empty() {
         register_metavariable(&mut substs, &mut result, &cur_name, dollar_count);
     }
+    if !open_repetitions.is_empty() {
+        return None;
+    }
 
     debug!("replace_names `{}` {:?}", result, substs);
 
     Some((result, substs))
 }
 
+// Reads the separator and the operator that follow the closing parenthesis of a repetition,
+// e.g. `,*` in `$($x),*`, and moves `i` after them.
+fn repetition_tail(chars: &[(FullCodeCharKind, char)], i: &mut usize) -> Option<String> {
+    let is_op = |c| matches!(c, '*' | '+' | '?');
+    let mut tail = String::new();
+    loop {
+        while chars.get(*i).map_or(false, |&(_, c)| c.is_whitespace()) {
+            *i += 1;
+        }
+        let &(kind, c) = chars.get(*i)?;
+        if kind != FullCodeCharKind::Normal {
+            return None;
+        }
+        let next_is_op = chars[*i + 1..]
+            .iter()
+            .find(|(_, c)| !c.is_whitespace())
+            .map_or(false, |&(_, c)| is_op(c));
+        if is_op(c) && (!next_is_op || !tail.is_empty()) {
+            *i += 1;
+            tail.push(c);
+            return Some(tail);
+        }
+        if !tail.is_empty() {
+            // The separator is a single token.
+            return None;
+        }
+        // Reads the separator, which can be any token but a delimiter.
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+        let start = *i;
+        while let Some(&(FullCodeCharKind::Normal, c)) = chars.get(*i) {
+            let same_token = if is_ident(chars[start].1) {
+                is_ident(c)
+            } else {
+                !is_ident(c) && !c.is_whitespace() && !"()[]{}$".contains(c)
+            };
+            if !same_token || (*i > start && is_op(c)) {
+                break;
+            }
+            tail.push(c);
+            *i += 1;
+        }
+        if tail.is_empty() || tail.contains('/') {
+            return None;
+        }
+    }
+}
+
+// Returns the code of each repetition of a body with the markers of `replace_names`, without
+// whitespace, in the order in which they start. This is used to check that formatting did not
+// move code into or out of a repetition.
+fn repetition_contents(body: &str) -> Option<Vec<String>> {
+    let mut contents: Vec<String> = vec![];
+    let mut open: Vec<usize> = vec![];
+    let mut rest = body;
+    loop {
+        let start = rest.find(REPEAT_START);
+        let end = rest.find(REPEAT_END);
+        let code_len = start.into_iter().chain(end).min().unwrap_or(rest.len());
+        let code: String = rest[..code_len].split_whitespace().collect();
+        for &index in &open {
+            contents[index].push_str(&code);
+        }
+        match (start, end) {
+            (Some(start), end) if end.map_or(true, |end| start < end) => {
+                for &index in &open {
+                    contents[index].push_str("$(");
+                }
+                open.push(contents.len());
+                contents.push(String::new());
+                rest = &rest[start + REPEAT_START.len()..];
+            }
+            (_, Some(end)) => {
+                let tail_start = end + REPEAT_END.len();
+                let tail_len = rest[tail_start..].find("*/")?;
+                let tail = &rest[tail_start..tail_start + tail_len];
+                open.pop()?;
+                for &index in &open {
+                    contents[index].push(')');
+                    contents[index].push_str(tail);
+                }
+                rest = &rest[tail_start + tail_len + 2..];
+            }
+            _ => break,
+        }
+    }
+    open.is_empty().then_some(contents)
+}
+
+// Replaces the markers of `replace_names` with the repetitions they stand for. The code of a
+// repetition whose markers are on lines of their own is indented one more level.
+fn restore_repetitions(body: &str, config: &Config) -> String {
+    let indent_str = Indent::from_width(config, config.tab_spaces()).to_string(config);
+    let mut result = String::with_capacity(body.len());
+    let mut block_repetitions = 0;
+    let mut need_indent = true;
+    for (kind, line) in LineClasses::new(body) {
+        let trimmed = line.trim();
+        if trimmed.starts_with(REPEAT_END)
+            && trimmed.ends_with("*/")
+            && trimmed.matches(REPEAT_MARKER).count() == 1
+        {
+            block_repetitions = usize::saturating_sub(block_repetitions, 1);
+        }
+        if need_indent && !is_empty_line(&line) {
+            for _ in 0..block_repetitions {
+                result.push_str(&indent_str);
+            }
+        }
+        need_indent = indent_next_line(kind, &line, config);
+        if trimmed == REPEAT_START {
+            block_repetitions += 1;
+        }
+
+        let mut line = line
+            .replace(&format!("{REPEAT_START} "), "$(")
+            .replace(REPEAT_START, "$(");
+        while let Some(end) = line.find(REPEAT_END) {
+            let tail_start = end + REPEAT_END.len();
+            let Some(tail_len) = line[tail_start..].find("*/") else {
+                break;
+            };
+            let tail = line[tail_start..tail_start + tail_len].to_owned();
+            let end = if line[..end].ends_with(' ') && !line[..end].trim().is_empty() {
+                end - 1
+            } else {
+                end
+            };
+            line.replace_range(end..tail_start + tail_len + 2, &format!("){tail}"));
+        }
+        result.push_str(&line);
+        result.push('\n');
+    }
+    result
+}
+
 #[derive(Debug, Clone)]
 enum MacroArgKind {
     /// e.g., `$x: expr`.
@@ -1273,7 +1455,12 @@ fn rewrite(
         // `$$`). We'll try and format like an AST node, but we'll substitute
         // variables for new names with the same length first.
 
-        let (body_str, substs) = replace_names(old_body)?;
+        // Version Two also formats the bodies with repetitions, e.g. `$($x),*`.
+        let repetitions = context.config.version() == Version::Two;
+        if repetitions && old_body.contains(REPEAT_MARKER) {
+            return None;
+        }
+        let (body_str, substs) = replace_names(old_body, repetitions)?;
 
         let mut config = context.config.clone();
         config.set().show_parse_errors(false);
@@ -1304,6 +1491,12 @@ fn rewrite(
         if !filtered_str_fits(&new_body_snippet.snippet, config.max_width(), shape) {
             return None;
         }
+        if repetitions
+            && repetition_contents(&new_body_snippet.snippet) != repetition_contents(&body_str)
+        {
+            debug!("rewrite_macro_def: formatting changed the code of a repetition");
+            return None;
+        }
 
         // Indent the body since it is in a block.
         let indent_str = body_indent.to_string(&config);
@@ -1323,6 +1516,10 @@ fn rewrite(
             )
             .0;
 
+        if repetitions {
+            new_body = restore_repetitions(&new_body, &config);
+        }
+
         // Undo our replacement of macro variables.
         // FIXME: this could be *much* more efficient.
         for (old, new) in &substs {
@@ -1454,3 +1651,41 @@ fn rewrite_macro_with_items(
     result.push_str(trailing_semicolon);
     Some(result)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replace_names_with_repetitions() {
+        let (body, substs) = replace_names("vec![$($x),+ $(,)?]", true).unwrap();
+        assert_eq!(
+            body,
+            "vec![/*zrepeat(*/zx/*zrepeat),+*/ /*zrepeat(*/,/*zrepeat)?*/]"
+        );
+        assert_eq!(substs["$x"], "zx");
+        assert_eq!(
+            repetition_contents(&body),
+            Some(vec!["zx".to_owned(), ",".to_owned()])
+        );
+
+        let (body, _) = replace_names("$($k => $v);*", true).unwrap();
+        assert_eq!(body, "/*zrepeat(*/zk => zv/*zrepeat);**/");
+
+        // Repetitions are only replaced when asked to, and escaped ones are never replaced.
+        assert_eq!(replace_names("$($x),*", false), None);
+        assert_eq!(replace_names("$$($x)*", true), None);
+        // A repetition needs an operator.
+        assert_eq!(replace_names("$($x)", true), None);
+    }
+
+    #[test]
+    fn restore_repetitions_indents_blocks() {
+        let body = "/*zrepeat(*/\nimpl Foo for zt {}\n/*zrepeat)**/\n\
+                    foo(/*zrepeat(*/ zx /*zrepeat),**/);\n";
+        assert_eq!(
+            restore_repetitions(body, &Config::default()),
+            "$(\n    impl Foo for zt {}\n)*\nfoo($(zx),*);\n"
+        );
+    }
+}
@@ -6,12 +6,17 @@
 
 // FIXME(#2455): Reorder trait items.
 
-use std::cmp::Ordering;
+use std::cell::RefCell;
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 use rustc_ast::{ast, attr};
 use rustc_span::{symbol::sym, Span};
 
-use crate::config::{Config, GroupImportsTactic};
+use crate::config::{Config, FileName, GroupImportsTactic};
 use crate::imports::{normalize_use_trees_with_granularity, UseSegmentKind, UseTree};
 use crate::items::{is_mod_decl, rewrite_extern_crate, rewrite_mod};
 use crate::lists::{itemize_list, write_list, ListFormatting, ListItem};
@@ -107,16 +112,21 @@ fn rewrite_reorderable_or_regroupable_items(
             for (item, list_item) in normalized_items.iter_mut().zip(list_items) {
                 item.list_item = Some(list_item.clone());
             }
-            normalized_items = normalize_use_trees_with_granularity(
-                normalized_items,
-                context.config.imports_granularity(),
-            );
+            let granularity = context.config.imports_granularity();
 
             let mut regrouped_items = match context.config.group_imports() {
                 GroupImportsTactic::Preserve | GroupImportsTactic::One => {
-                    vec![normalized_items]
+                    vec![normalize_use_trees_with_granularity(
+                        normalized_items,
+                        granularity,
+                    )]
+                }
+                GroupImportsTactic::StdExternalCrate => group_imports(
+                    normalize_use_trees_with_granularity(normalized_items, granularity),
+                ),
+                GroupImportsTactic::Custom => {
+                    group_imports_by_patterns(context, normalized_items, span)
                 }
-                GroupImportsTactic::StdExternalCrate => group_imports(normalized_items),
             };
 
             if context.config.reorder_imports() {
@@ -198,6 +208,135 @@ fn group_imports(uts: Vec<UseTree>) -> Vec<Vec<UseTree>> {
     vec![std_imports, external_imports, local_imports]
 }
 
+/// Divides imports into the groups of the `import_groups` option, in their order. An
+/// import goes to the group with its most specific matching pattern, or to an extra last
+/// group if none matches. Each group is then merged or split to its own granularity.
+fn group_imports_by_patterns(
+    context: &RewriteContext<'_>,
+    uts: Vec<UseTree>,
+    span: Span,
+) -> Vec<Vec<UseTree>> {
+    let import_groups = context.config.import_groups().0;
+    let uses_workspace = import_groups
+        .iter()
+        .flat_map(|group| &group.patterns)
+        .any(|pattern| pattern.uses_workspace());
+    let workspace_crates = match context.psess.span_to_filename(span) {
+        FileName::Real(ref path) if uses_workspace => {
+            workspace_crates(path.parent().unwrap_or(Path::new(".")))
+        }
+        FileName::Stdin if uses_workspace => match env::current_dir() {
+            Ok(dir) => workspace_crates(&dir),
+            Err(_) => Rc::from(Vec::new()),
+        },
+        _ => Rc::from(Vec::new()),
+    };
+
+    let mut groups = vec![Vec::new(); import_groups.len() + 1];
+    for ut in uts {
+        let path: Vec<&str> = ut
+            .path
+            .iter()
+            .map_while(|segment| match segment.kind {
+                UseSegmentKind::Ident(ref name, _) => Some(name.as_str()),
+                UseSegmentKind::Slf(_) => Some("self"),
+                UseSegmentKind::Super(_) => Some("super"),
+                UseSegmentKind::Crate(_) => Some("crate"),
+                UseSegmentKind::Glob | UseSegmentKind::List(_) => None,
+            })
+            .collect();
+        let index = import_groups
+            .iter()
+            .enumerate()
+            .flat_map(|(i, group)| group.patterns.iter().map(move |pattern| (i, pattern)))
+            .filter(|(_, pattern)| pattern.matches(&path, &workspace_crates))
+            // The first group wins among equally specific patterns.
+            .min_by_key(|(i, pattern)| (Reverse(pattern.specificity()), *i))
+            .map_or(import_groups.len(), |(i, _)| i);
+        groups[index].push(ut);
+    }
+
+    let default_granularity = context.config.imports_granularity();
+    groups
+        .into_iter()
+        .enumerate()
+        .map(|(i, uts)| {
+            let granularity = import_groups
+                .get(i)
+                .and_then(|group| group.granularity)
+                .unwrap_or(default_granularity);
+            normalize_use_trees_with_granularity(uts, granularity)
+        })
+        .collect()
+}
+
+/// The crates of the Cargo workspaces seen so far, see `workspace_crates`.
+#[derive(Default)]
+struct WorkspaceCache {
+    /// The crates of each workspace, by the directory of its root manifest.
+    crates: HashMap<PathBuf, Rc<[String]>>,
+    /// The workspace root of the directory of each package manifest we've seen.
+    roots: HashMap<PathBuf, PathBuf>,
+}
+
+thread_local! {
+    static WORKSPACE_CACHE: RefCell<WorkspaceCache> = RefCell::new(WorkspaceCache::default());
+}
+
+/// Returns the names of the library crates of the Cargo workspace containing `dir`, as they are
+/// written in paths. Running `cargo metadata` is slow, so it is only run once per workspace.
+fn workspace_crates(dir: &Path) -> Rc<[String]> {
+    let Some(package_dir) = dir.ancestors().find(|dir| dir.join("Cargo.toml").is_file()) else {
+        return Rc::from(Vec::new());
+    };
+    WORKSPACE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if let Some(root) = cache.roots.get(package_dir) {
+            return Rc::clone(&cache.crates[root]);
+        }
+        let mut cmd = cargo_metadata::MetadataCommand::new();
+        cmd.no_deps()
+            .current_dir(package_dir)
+            .other_options(vec![String::from("--offline")]);
+        let Ok(metadata) = cmd.exec() else {
+            let crates: Rc<[String]> = Rc::from(Vec::new());
+            cache
+                .roots
+                .insert(package_dir.to_path_buf(), package_dir.to_path_buf());
+            cache
+                .crates
+                .insert(package_dir.to_path_buf(), Rc::clone(&crates));
+            return crates;
+        };
+        let root = metadata.workspace_root.clone().into_std_path_buf();
+        let packages = metadata.workspace_packages();
+        // Only library crates can be imported, and their name may differ from the package name.
+        let crates: Rc<[String]> = packages
+            .iter()
+            .flat_map(|package| &package.targets)
+            .filter(|target| {
+                target.is_lib()
+                    || target
+                        .kind
+                        .iter()
+                        .any(|kind| matches!(kind.as_str(), "rlib" | "dylib" | "proc-macro"))
+            })
+            .map(|target| target.name.replace('-', "_"))
+            .collect();
+        for package in &packages {
+            if let Some(dir) = package.manifest_path.parent() {
+                cache
+                    .roots
+                    .insert(dir.as_std_path().to_path_buf(), root.clone());
+            }
+        }
+        // `dir` may belong to the workspace without being a member, e.g. a virtual manifest.
+        cache.roots.insert(package_dir.to_path_buf(), root.clone());
+        cache.crates.insert(root, Rc::clone(&crates));
+        crates
+    })
+}
+
 /// A simplified version of `ast::ItemKind`.
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 enum ReorderableItemKind {
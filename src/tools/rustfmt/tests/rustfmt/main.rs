@@ -1,7 +1,7 @@
 //! Integration tests for rustfmt.
 
 use std::env;
-use std::fs::remove_file;
+use std::fs::{self, remove_file};
 use std::path::Path;
 use std::process::Command;
 
@@ -207,3 +207,97 @@ fn rustfmt_emits_error_when_control_brace_style_is_always_next_line() {
     let (_stdout, stderr) = rustfmt(&args);
     assert!(!stderr.contains("error[internal]: left behind trailing whitespace"))
 }
+
+#[test]
+fn range_only_works_with_stdin() {
+    let args = ["--range", "0..10", "src/lib.rs"];
+    let (_stdout, stderr) = rustfmt(&args);
+    assert!(stderr.contains("The `--range` option only works with standard input."));
+}
+
+#[test]
+fn cache_dir_skips_formatted_files() {
+    let dir = env::temp_dir().join(format!("rustfmt-cache-dir-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("lib.rs");
+    fs::write(&file, "fn main() {}\n").unwrap();
+    let args = [
+        "--unstable-features",
+        "--cache-dir",
+        dir.to_str().unwrap(),
+        "--check",
+        "--verbose",
+        file.to_str().unwrap(),
+    ];
+
+    let (stdout, _stderr) = rustfmt(&args);
+    assert!(stdout.contains("Formatting"), "{stdout}");
+    let (stdout, _stderr) = rustfmt(&args);
+    assert!(
+        stdout.contains("formatted according to the cache"),
+        "{stdout}"
+    );
+
+    // A changed file is formatted again.
+    fs::write(&file, "fn main() { }\n").unwrap();
+    let (stdout, _stderr) = rustfmt(&args);
+    assert!(stdout.contains("Formatting"), "{stdout}");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn custom_import_groups_match_workspace_crates() {
+    let dir = env::temp_dir().join(format!("rustfmt-workspace-{}", std::process::id()));
+    let files = [
+        ("Cargo.toml", "[workspace]\nmembers = [\"app\", \"util\"]\n"),
+        (
+            "app/Cargo.toml",
+            "[package]\nname = \"my-app\"\nversion = \"0.1.0\"\n",
+        ),
+        ("app/src/main.rs", ""),
+        (
+            "util/Cargo.toml",
+            "[package]\nname = \"util-pkg\"\nversion = \"0.1.0\"\n\n[lib]\nname = \"util_lib\"\n",
+        ),
+        ("util/src/lib.rs", ""),
+        (
+            "rustfmt.toml",
+            "group_imports = \"Custom\"\n\
+             import_groups = [{ patterns = [\"std\"] }, { patterns = [\"$workspace\"] }]\n",
+        ),
+    ];
+    for (path, contents) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    let main = dir.join("app/src/main.rs");
+    fs::write(
+        &main,
+        "use util_lib::Helper;\nuse serde::Serialize;\nuse util_pkg::Thing;\nuse my_app::X;\n\
+         use std::fmt;\n",
+    )
+    .unwrap();
+    let config = dir.join("rustfmt.toml");
+    let args = [
+        "--config-path",
+        config.to_str().unwrap(),
+        "--emit",
+        "stdout",
+        main.to_str().unwrap(),
+    ];
+
+    // Crates are matched by the name of their library target, and packages without one aren't
+    // crates that can be imported.
+    let (stdout, stderr) = rustfmt(&args);
+    assert!(
+        stdout.contains(
+            "use std::fmt;\n\nuse util_lib::Helper;\n\n\
+             use my_app::X;\nuse serde::Serialize;\nuse util_pkg::Thing;\n"
+        ),
+        "{stdout}{stderr}"
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
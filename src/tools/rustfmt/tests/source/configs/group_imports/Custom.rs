@@ -0,0 +1,18 @@
+// rustfmt-group_imports: Custom
+// rustfmt-import_groups: [{"patterns": ["std", "core", "alloc"]}, {"patterns": ["*"]}, {"patterns": ["tokio::*"], "granularity": "Crate"}, {"patterns": ["crate", "self", "super"]}]
+use chrono::Utc;
+use super::update::convert_publish_payload;
+use tokio::sync::mpsc;
+
+use juniper::{FieldError, FieldResult};
+use uuid::Uuid;
+use alloc::alloc::Layout;
+
+use std::sync::Arc;
+use tokio::net::TcpStream;
+
+use broker::database::PooledConnection;
+
+use super::schema::{Context, Payload};
+use core::f32;
+use crate::models::Event;
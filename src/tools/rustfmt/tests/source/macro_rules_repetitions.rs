@@ -0,0 +1,28 @@
+// rustfmt-version: Two
+
+macro_rules! point {
+    // Formatting would add a trailing comma inside the repetition, so this arm is kept as is.
+    ($($name:ident: $value:expr),*) => {
+        Point { $( $name : $value ),* }
+    };
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl Foo for $t { fn foo(&self)->u8{ 1 } }
+        )+
+    };
+}
+
+macro_rules! list {
+    ($($x:expr),*) => {
+        vec![ $( $x ),* ]
+    };
+    ($f:ident; $($x:expr),+) => {
+        $f( $( $x + 1 ),+ )
+    };
+}
+
+// The first arm can't be formatted, the second one is.
+macro_rules! mixed {
+    { $x:expr } => { $x   +   1 };
+    ($x:expr) => { foo( $x,1 ) };
+}
@@ -0,0 +1,32 @@
+// rustfmt-format_code_in_doc_comments: true
+
+/// Formatted, the attributes are separated by spaces:
+///
+/// ```rust no_run edition2024
+/// let x = foo(1, 2);
+/// # fn foo(a:u8,b:u8) {}
+/// ```
+///
+/// Formatted, with a longer fence that contains a fence:
+///
+/// ````{.example} should_panic
+/// let s = "```";
+/// assert!(s.is_empty());
+/// ````
+///
+/// Formatted, with a tilde fence:
+///
+/// ~~~
+/// let v = vec![1];
+/// ~~~
+///
+/// Not formatted:
+///
+/// ```ignore-wasm32
+/// let   x = 1;
+/// ```
+///
+/// ```compile_fail,E0308
+/// let   x: u8 = "";
+/// ```
+fn foo() {}
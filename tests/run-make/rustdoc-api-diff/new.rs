@@ -0,0 +1,9 @@
+pub fn kept(x: u32) -> u32 {
+    x
+}
+
+pub fn changed(x: u64) -> u64 {
+    x
+}
+
+pub fn added() {}
@@ -0,0 +1,57 @@
+// Checks that `--api-diff` compares the JSON documentation of two versions of a crate, and writes
+// the report to the output directory without printing anything.
+
+use run_make_support::rfs::read_to_string;
+use run_make_support::{assert_contains, assert_not_contains, rustdoc};
+
+fn main() {
+    for (version, input) in [("1.0.0", "old.rs"), ("2.0.0", "new.rs")] {
+        rustdoc()
+            .input(input)
+            .crate_name("foo")
+            .arg("-Zunstable-options")
+            .arg("--crate-version")
+            .arg(version)
+            .output_format("json")
+            .out_dir(version)
+            .run();
+    }
+
+    rustdoc()
+        .input("2.0.0/foo.json")
+        .arg("-Zunstable-options")
+        .arg("--api-diff")
+        .arg("1.0.0/foo.json")
+        .out_dir("diff")
+        .run()
+        .assert_stdout_equals("");
+
+    let markdown = read_to_string("diff/api-diff.md");
+    assert_contains(&markdown, "# API changes in `foo` from 1.0.0 to 2.0.0");
+    assert_contains(&markdown, "Required version bump: **major**");
+    assert_contains(&markdown, "## Breaking changes (major)");
+    assert_contains(&markdown, "- **changed** function `foo::changed`: signature changed");
+    assert_contains(
+        &markdown,
+        "- **removed** function `foo::removed`: removed from the public API",
+    );
+    assert_contains(&markdown, "## Compatible changes (minor)");
+    assert_contains(&markdown, "- **added** function `foo::added`: added to the public API");
+    assert_not_contains(&markdown, "foo::kept");
+
+    let html = read_to_string("diff/api-diff.html");
+    assert_contains(&html, "foo::added");
+    assert_not_contains(&html, "foo::kept");
+
+    // Options that only affect documenting a crate are rejected rather than ignored.
+    rustdoc()
+        .input("2.0.0/foo.json")
+        .arg("-Zunstable-options")
+        .arg("--api-diff")
+        .arg("1.0.0/foo.json")
+        .arg("--cfg")
+        .arg("feature=\"std\"")
+        .out_dir("diff")
+        .run_fail()
+        .assert_stderr_contains("`--api-diff` cannot be combined with `--cfg`");
+}
@@ -0,0 +1,63 @@
+//@ has foo/fn.open.html
+//@ count - '//*[@class="stab cfg-variants"]//option' 2
+//@ has - '//*[@class="stab cfg-variants"]//*[@data-cfg-variant="unix"]/pre' 'fn open(fd: i32)'
+//@ has - '//*[@class="stab cfg-variants"]//*[@data-cfg-variant="windows"]/pre' \
+//    'fn open(handle: isize)'
+#[cfg(unix_like)]
+pub fn open(fd: i32) {}
+#[cfg(windows_like)]
+pub fn open(handle: isize) {}
+
+//@ has foo/fn.pipe.html
+//@ has - '//*[@class="stab cfg-variants"]//*[@data-cfg-variant="windows"]' \
+//    'Not available with windows.'
+#[cfg(unix_like)]
+pub fn pipe() {}
+
+//@ has foo/fn.read.html
+//@ has - '//*[@class="stab cfg-variants"]//*[@data-cfg-variant="unix"]//p' 'Reads with read(2).'
+//@ has - '//*[@class="stab cfg-variants"]//*[@data-cfg-variant="windows"]//p' \
+//    'Reads with ReadFile.'
+#[cfg_attr(unix_like, doc = "Reads with read(2).")]
+#[cfg_attr(windows_like, doc = "Reads with ReadFile.")]
+pub fn read() {}
+
+//@ has foo/index.html
+//@ has - '//*[@id="cfg-variant-items"]' 'Items in Other Configurations'
+//@ has - '//*[@id="cfg-variant.spawn"]/*[@class="stab portability"]' 'windows'
+//@ has - '//*[@id="cfg-variant.spawn"]/..//*[@data-cfg-variant="windows"]/pre' 'fn spawn()'
+//@ has - '//*[@id="cfg-variant.spawn"]/..//*[@data-cfg-variant="windows"]//p' \
+//    'Spawns a process.'
+//@ has - '//*[@id="cfg-variant.spawn"]/..//*[@data-cfg-variant="unix"]' \
+//    'Not available with unix.'
+//@ !has - '//*[@id="cfg-variant.pipe"]'
+//@ !has foo/fn.spawn.html
+/// Spawns a process.
+#[cfg(windows_like)]
+pub fn spawn() {}
+
+//@ has foo/fn.close.html
+//@ !has - '//*[@class="stab cfg-variants"]'
+pub fn close() {}
+
+//@ has foo/struct.File.html
+//@ has - '//*[@id="method.raw"]/../..//*[@class="stab cfg-variants"]'
+//@ has - '//*[@id="cfg-variant.handle"]/..//*[@data-cfg-variant="windows"]/pre' \
+//    'fn handle('
+//@ !has - '//*[@id="cfg-variant.raw"]'
+pub struct File;
+
+impl File {
+    #[cfg(unix_like)]
+    pub fn raw(&self) -> i32 {
+        0
+    }
+    #[cfg(windows_like)]
+    pub fn raw(&self) -> isize {
+        0
+    }
+    #[cfg(windows_like)]
+    pub fn handle(&self) -> isize {
+        0
+    }
+}
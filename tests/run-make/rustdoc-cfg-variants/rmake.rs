@@ -0,0 +1,32 @@
+// Checks that `--cfg-variant` shows the signature and docs of the items that differ between the
+// configurations given as JSON documentation, with a selector to switch between them, and lists
+// the items that only exist in the other configurations.
+
+use run_make_support::{htmldocck, rustdoc};
+
+fn main() {
+    for (label, cfg) in [("unix", "unix_like"), ("windows", "windows_like")] {
+        rustdoc()
+            .input("foo.rs")
+            .arg("-Zunstable-options")
+            .output_format("json")
+            .arg("--cfg")
+            .arg(cfg)
+            .out_dir(label)
+            .run();
+    }
+
+    let out_dir = "doc";
+    rustdoc()
+        .input("foo.rs")
+        .arg("-Zunstable-options")
+        .arg("--cfg")
+        .arg("unix_like")
+        .arg("--cfg-variant")
+        .arg("unix=unix/foo.json")
+        .arg("--cfg-variant")
+        .arg("windows=windows/foo.json")
+        .out_dir(out_dir)
+        .run();
+    htmldocck().arg(out_dir).arg("foo.rs").run();
+}
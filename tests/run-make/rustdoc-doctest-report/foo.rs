@@ -0,0 +1,25 @@
+//! ```
+//! assert_eq!(foo::add(1, 2), 3);
+//! ```
+
+/// ```
+/// assert_eq!(foo::add(1, 1), 3);
+/// ```
+pub fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+/// ```ignore
+/// foo::untested();
+/// ```
+pub fn untested() {}
+
+/// ```no_run
+/// foo::compiled();
+/// ```
+pub fn compiled() {}
+
+pub fn undocumented() {}
+
+#[allow(rustdoc::missing_doc_code_examples)]
+pub fn allowed() {}
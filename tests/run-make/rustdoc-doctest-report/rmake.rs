@@ -0,0 +1,32 @@
+// Checks the report written by `--doctest-report`: the outcome of every doctest, and the public
+// items without a runnable example.
+
+use run_make_support::rfs::read_to_string;
+use run_make_support::{assert_contains, assert_not_contains, rustc, rustdoc};
+
+fn main() {
+    rustc().input("foo.rs").crate_type("rlib").run();
+    // One of the doctests fails, but the report is written anyway.
+    rustdoc()
+        .input("foo.rs")
+        .arg("-Zunstable-options")
+        .arg("--test")
+        .arg("--doctest-report")
+        .arg("report")
+        .extern_("foo", "libfoo.rlib")
+        .run_fail();
+
+    let json = read_to_string("report/doctests.json");
+    assert_contains(&json, r#""item": "foo","#);
+    assert_contains(&json, r#""outcome": "passed""#);
+    assert_contains(&json, r#""outcome": "failed""#);
+    assert_contains(&json, r#""outcome": "ignored""#);
+    assert_contains(&json, r#""outcome": "compiled""#);
+    // `untested`, `compiled` and `undocumented` have no runnable example, and `allowed` isn't
+    // expected to have one.
+    assert_contains(&json, r#""items_without_runnable_example": 3"#);
+    assert_not_contains(&json, r#""item": "foo::allowed""#);
+
+    let html = read_to_string("report/doctests.html");
+    assert_contains(&html, "<code>foo::undocumented</code>");
+}
@@ -0,0 +1,32 @@
+//! The crate docs.
+
+pub mod inner {
+    //! Things that are used by [`Wrapper`](crate::Wrapper).
+
+    /// A wrapper around a number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use foo::Wrapper;
+    /// let wrapper = Wrapper { value: 1 };
+    /// ```
+    pub struct Wrapper {
+        /// The number, see [`double`](crate::double).
+        pub value: u32,
+    }
+
+    impl Wrapper {
+        /// Creates a wrapper.
+        pub fn new(value: u32) -> Self {
+            Wrapper { value }
+        }
+    }
+}
+
+pub use inner::Wrapper;
+
+/// Doubles a number. Not to be confused with `-x`.
+pub fn double(x: u32) -> u32 {
+    x * 2
+}
@@ -0,0 +1,43 @@
+// Checks the pages written by `--output-format markdown` and `--output-format man`: one page per
+// module, re-exported items shown with the rest of the module, and intra-doc links rewritten into
+// relative links.
+
+use run_make_support::rfs::read_to_string;
+use run_make_support::{assert_contains, assert_not_contains, rustdoc};
+
+fn main() {
+    rustdoc()
+        .input("foo.rs")
+        .arg("-Zunstable-options")
+        .output_format("markdown")
+        .out_dir("md")
+        .run();
+
+    let root = read_to_string("md/foo/index.md");
+    assert_contains(&root, "# Crate `foo`");
+    assert_contains(&root, "### [`inner`](inner/index.md)");
+    // The re-export is inlined, with its fields and methods.
+    assert_contains(&root, "<a id=\"struct.Wrapper\"></a>");
+    assert_contains(&root, "pub struct Wrapper {\n    pub value: u32,\n}");
+    assert_contains(&root, "<a id=\"struct.Wrapper.method.new\"></a>");
+    assert_contains(&root, "pub fn new(value: u32) -> Self");
+    assert_contains(&root, "see [`double`](index.md#fn.double)");
+    // Headings are moved below the heading of the item, and hidden lines are removed.
+    assert_contains(&root, "#### Examples");
+    assert_not_contains(&root, "use foo::Wrapper;");
+
+    // Links to items that are shown on another page point to that page.
+    let inner = read_to_string("md/foo/inner/index.md");
+    assert_contains(&inner, "# Module `foo::inner`");
+    assert_contains(&inner, "[`Wrapper`](../index.md#struct.Wrapper)");
+
+    rustdoc().input("foo.rs").arg("-Zunstable-options").output_format("man").out_dir("man").run();
+
+    let root = read_to_string("man/man3/foo.3");
+    assert_contains(&root, ".TH \"foo\" 3");
+    assert_contains(&root, ".SH STRUCTS\n.SS Wrapper\n");
+    assert_contains(&root, "Not to be confused with \\fB\\-x\\fP.");
+    assert_contains(&root, ".SH SEE ALSO\n\\fBfoo::inner\\fP(3)");
+    let inner = read_to_string("man/man3/foo::inner.3");
+    assert_contains(&inner, "foo::inner \\- Things that are used by `Wrapper`.");
+}
@@ -0,0 +1,10 @@
+fn main() {
+    // this is a
+
+    //  ..
+
+    // BIG
+
+    // item
+    foobar::parse("2");
+}
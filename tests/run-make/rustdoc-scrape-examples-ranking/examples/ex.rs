@@ -0,0 +1,4 @@
+fn main() {
+    foobar::parse("1");
+    foobar::parse_all(&["1"]);
+}
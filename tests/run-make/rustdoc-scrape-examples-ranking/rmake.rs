@@ -0,0 +1,54 @@
+// Checks that examples are scraped from integration tests, that they are ranked by where they
+// come from, by size and by the shape of their arguments, and that `#[doc(example_exclude)]`
+// opts items out on both sides.
+
+use std::path::Path;
+
+use run_make_support::{htmldocck, rustc, rustdoc};
+
+fn main() {
+    let out_dir = Path::new("rustdoc");
+    let crate_name = "foobar";
+    rustc().input("src/lib.rs").crate_name(crate_name).crate_type("lib").emit("metadata").run();
+
+    let deps = [
+        ("examples/ex.rs", "example"),
+        ("examples/big.rs", "example"),
+        ("tests/it.rs", "test"),
+        ("tests/edge.rs", "test"),
+    ];
+    let mut calls = Vec::new();
+    for (dep, kind) in deps {
+        let dep_stem = Path::new(dep).file_stem().unwrap().to_str().unwrap();
+        let out_calls = out_dir.join(format!("{dep_stem}.calls"));
+        rustdoc()
+            .input(dep)
+            .crate_name(dep_stem)
+            .crate_type("bin")
+            .output(out_dir)
+            .extern_(crate_name, format!("lib{crate_name}.rmeta"))
+            .arg("-Zunstable-options")
+            .arg("--scrape-examples-output-path")
+            .arg(&out_calls)
+            .arg("--scrape-examples-target-crate")
+            .arg(crate_name)
+            .arg("--scrape-examples-kind")
+            .arg(kind)
+            .run();
+        calls.push(out_calls);
+    }
+
+    let mut rustdoc = rustdoc();
+    rustdoc
+        .input("src/lib.rs")
+        .output(out_dir)
+        .crate_name(crate_name)
+        .crate_type("lib")
+        .arg("-Zunstable-options");
+    for calls in calls {
+        rustdoc.arg("--with-examples").arg(calls);
+    }
+    rustdoc.run();
+
+    htmldocck().arg(out_dir).arg("src/lib.rs").run();
+}
@@ -0,0 +1,17 @@
+// ignore-tidy-linelength
+
+#![feature(doc_example_exclude)]
+
+// The smallest example from `examples/` is shown first. The test passes a different kind of
+// argument, so it comes before the bigger example, which calls `parse` like the first one does.
+// @has foobar/fn.parse.html '//*[@class="docblock scraped-example-list"]' 'examples/ex.rs'
+// @has - '//*[@class="more-scraped-examples"]/div[2]/div[@class="scraped-example-title"]' 'tests/it.rs'
+// @has - '//*[@class="more-scraped-examples"]/div[3]/div[@class="scraped-example-title"]' 'examples/big.rs'
+// @!has - '//*[@class="docblock scraped-example-list"]' 'tests/edge.rs'
+
+pub fn parse(_s: &str) {}
+
+// @has foobar/fn.parse_all.html
+// @!has - '//*[@class="docblock scraped-example-list"]' ''
+#[doc(example_exclude)]
+pub fn parse_all(_s: &[&str]) {}
@@ -0,0 +1,7 @@
+#![feature(doc_example_exclude)]
+
+#[test]
+#[doc(example_exclude)]
+fn empty() {
+    foobar::parse("");
+}
@@ -0,0 +1,5 @@
+#[test]
+fn from_string() {
+    let s = String::from("3");
+    foobar::parse(&s);
+}
@@ -0,0 +1,25 @@
+//@ has foo/fn.old.html
+//@ !has - '//span[@class="since"]'
+pub fn old() {}
+
+//@ has foo/fn.newer.html
+//@ has - '//span[@class="since"]' '1.1.0'
+//@ has - '//span[@class="since"]/@title' 'Available since version 1.1.0'
+#[cfg(since_1_1)]
+pub fn newer() {}
+
+//@ has foo/fn.newest.html
+//@ has - '//span[@class="since"]' '1.2.0'
+#[cfg(since_1_2)]
+pub fn newest() {}
+
+//@ has foo/struct.Old.html
+//@ !has - '//*[@id="method.old"]//span[@class="since"]'
+//@ has - '//*[@id="method.newer"]//span[@class="since"]' '1.1.0'
+pub struct Old;
+
+impl Old {
+    pub fn old(&self) {}
+    #[cfg(since_1_1)]
+    pub fn newer(&self) {}
+}
@@ -0,0 +1,33 @@
+// Checks that `--version-history` shows the version since which each item has been part of the
+// public API, given the JSON documentation of the earlier versions.
+
+use run_make_support::{htmldocck, rustdoc};
+
+fn main() {
+    for (version, cfgs) in [("1.0.0", &[][..]), ("1.1.0", &["since_1_1"][..])] {
+        let mut cmd = rustdoc();
+        cmd.input("foo.rs").arg("-Zunstable-options").output_format("json").out_dir(version);
+        for cfg in cfgs {
+            cmd.arg("--cfg").arg(cfg);
+        }
+        cmd.run();
+    }
+
+    let out_dir = "doc";
+    rustdoc()
+        .input("foo.rs")
+        .arg("-Zunstable-options")
+        .arg("--crate-version")
+        .arg("1.2.0")
+        .arg("--cfg")
+        .arg("since_1_1")
+        .arg("--cfg")
+        .arg("since_1_2")
+        .arg("--version-history")
+        .arg("1.0.0=1.0.0/foo.json")
+        .arg("--version-history")
+        .arg("1.1.0=1.1.0/foo.json")
+        .out_dir(out_dir)
+        .run();
+    htmldocck().arg(out_dir).arg("foo.rs").run();
+}
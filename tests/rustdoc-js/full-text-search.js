@@ -0,0 +1,33 @@
+// exact-check
+
+const EXPECTED = [
+    {
+        'query': 'retry backoff',
+        'in_docs': [
+            {
+                'path': 'full_text_search',
+                'name': 'send',
+                'desc': '…requests are tried again, with an exponential <b>backoff</b> between ' +
+                    'every <b>retry</b>.',
+            },
+        ],
+    },
+    {
+        'query': 'retry',
+        'in_docs': [
+            { 'path': 'full_text_search', 'name': 'send' },
+            {
+                'path': 'full_text_search',
+                'name': 'sleep',
+                'desc': '…number of milliseconds. Used between every <b>retry</b> of `send`.',
+            },
+        ],
+    },
+    {
+        'query': 'backoff',
+        'in_docs': [
+            { 'path': 'full_text_search', 'name': 'Backoff' },
+            { 'path': 'full_text_search', 'name': 'send' },
+        ],
+    },
+];
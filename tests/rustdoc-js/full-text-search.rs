@@ -0,0 +1,17 @@
+//@ compile-flags: -Zunstable-options --full-text-search
+
+/// Sends a request.
+///
+/// Failed requests are tried again, with an exponential backoff between every retry.
+pub fn send() {}
+
+/// Waits for the given number of milliseconds.
+///
+/// Used between every retry of `send`.
+pub fn sleep(ms: u64) {}
+
+/// The backoff strategies.
+pub enum Backoff {
+    Constant,
+    Exponential,
+}
@@ -0,0 +1,38 @@
+//@ compile-flags: --json-body-references
+
+//@ set Point = "$.index[*][?(@.name=='Point')].id"
+pub struct Point {
+    pub x: i32,
+}
+
+//@ set new = "$.index[*][?(@.name=='new')].id"
+impl Point {
+    pub fn new(x: i32) -> Self {
+        Point { x }
+    }
+}
+
+//@ set ORIGIN = "$.index[*][?(@.name=='ORIGIN')].id"
+pub const ORIGIN: i32 = 0;
+
+//@ set helper = "$.index[*][?(@.name=='helper')].id"
+pub fn helper(_: Point) {}
+
+// Private items aren't documented, so they aren't listed.
+fn private() {}
+
+// Closures are part of the body of the function that contains them.
+//@ count "$.index[*][?(@.name=='caller')].inner.function.references[*]" 4
+//@ is "$.index[*][?(@.name=='caller')].inner.function.references[0]" $new
+//@ is "$.index[*][?(@.name=='caller')].inner.function.references[1]" $Point
+//@ is "$.index[*][?(@.name=='caller')].inner.function.references[2]" $ORIGIN
+//@ is "$.index[*][?(@.name=='caller')].inner.function.references[3]" $helper
+pub fn caller() {
+    let p = Point::new(ORIGIN);
+    private();
+    let _f = move || helper(p);
+}
+
+//@ count "$.index[*][?(@.name=='new')].inner.function.references[*]" 1
+//@ is "$.index[*][?(@.name=='new')].inner.function.references[0]" $Point
+//@ is "$.index[*][?(@.name=='helper')].inner.function.references" []
@@ -0,0 +1,37 @@
+//@ compile-flags: --show-type-layout -Z unstable-options
+// ignore-tidy-linelength
+
+//@ is "$.index[*][?(@.name=='Pair')].inner.struct.layout.size" 8
+//@ is "$.index[*][?(@.name=='Pair')].inner.struct.layout.align" 4
+//@ is "$.index[*][?(@.name=='Pair')].inner.struct.layout.niche_values" 0
+//@ is "$.index[*][?(@.name=='Pair')].inner.struct.layout.variants" []
+#[repr(C)]
+pub struct Pair {
+    pub a: u8,
+    pub b: u32,
+}
+
+//@ is "$.index[*][?(@.name=='Flag')].inner.struct.layout.size" 1
+//@ is "$.index[*][?(@.name=='Flag')].inner.struct.layout.niche_values" 254
+pub struct Flag(pub bool);
+
+//@ is "$.index[*][?(@.name=='Tagged')].inner.enum.layout.size" 2
+//@ is "$.index[*][?(@.name=='Tagged')].inner.enum.layout.align" 1
+//@ is "$.index[*][?(@.name=='Tagged')].inner.enum.layout.variants" '[{"name": "A", "size": 1, "is_uninhabited": false}, {"name": "B", "size": 0, "is_uninhabited": false}]'
+#[repr(u8)]
+pub enum Tagged {
+    A(u8),
+    B,
+}
+
+//@ is "$.index[*][?(@.name=='Never')].inner.enum.layout.is_uninhabited" true
+pub enum Never {}
+
+//@ is "$.index[*][?(@.name=='Bits')].inner.union.layout.size" 4
+pub union Bits {
+    pub int: u32,
+    pub float: f32,
+}
+
+//@ is "$.index[*][?(@.name=='Generic')].inner.struct.layout" null
+pub struct Generic<T>(pub T);
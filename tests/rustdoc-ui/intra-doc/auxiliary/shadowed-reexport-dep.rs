@@ -0,0 +1,6 @@
+pub mod inner {
+    pub struct Config;
+
+    /// Makes a [Config].
+    pub fn make() {}
+}
@@ -0,0 +1,69 @@
+// Links in the docs of an item are resolved where the docs are written, but the item is shown
+// where it is re-exported, in a module where the same name can refer to another item.
+
+//@ aux-build: shadowed-reexport-dep.rs
+
+#![deny(rustdoc::shadowed_intra_doc_links)]
+//~^ NOTE lint level is defined
+
+mod inner {
+    pub struct Config;
+
+    impl Config {
+        pub fn new() -> Self {
+            Config
+        }
+    }
+
+    pub struct Helper;
+
+    /// Builds a [Config].
+    //~^ ERROR `Config` resolves to a different item where this item is re-exported
+    //~| NOTE `Config` is `crate::inner::Config` here
+    //~| HELP use its path
+    pub fn build() {}
+
+    /// Calls [struct@Config] and [Config::new].
+    //~^ ERROR `Config` resolves to a different item where this item is re-exported
+    //~| NOTE `Config` is `crate::inner::Config` here
+    //~| HELP use its path
+    //~| ERROR `Config::new` resolves to a different item where this item is re-exported
+    //~| NOTE `Config` is `crate::inner::Config` here
+    //~| HELP use its path
+    pub fn configure() {}
+
+    /// Uses a [Helper] and a [crate::Config], which are the same items everywhere.
+    pub fn help() {}
+}
+
+pub use inner::build;
+//~^ NOTE `Config` refers to `crate::Config` where this item is re-exported
+pub use inner::configure;
+//~^ NOTE `Config` refers to `crate::Config` where this item is re-exported
+//~| NOTE `Config` refers to `crate::Config` where this item is re-exported
+pub use inner::{help, Helper};
+
+// The docs come from another crate, so the error points at the re-export.
+extern crate shadowed_reexport_dep;
+pub use shadowed_reexport_dep::inner::make;
+//~^ ERROR `Config` resolves to a different item where this item is re-exported
+//~| NOTE `Config` refers to `crate::Config` where this item is re-exported
+//~| NOTE in the docs of the re-exported item, this is `shadowed_reexport_dep::inner::Config`
+//~| HELP use its path
+
+/// Not the config of the `inner` module.
+pub struct Config;
+
+mod glob {
+    pub struct Config;
+
+    /// Resets the [Config].
+    //~^ ERROR `Config` resolves to a different item where this item is re-exported
+    //~| NOTE `Config` is `crate::glob::Config` here
+    //~| HELP use its path
+    pub fn reset() {}
+}
+
+// Items re-exported by a glob are checked too.
+pub use glob::*;
+//~^ NOTE `Config` refers to `crate::Config` where this item is re-exported
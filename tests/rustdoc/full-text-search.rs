@@ -0,0 +1,19 @@
+//@ compile-flags: -Zunstable-options --full-text-search
+
+// The search index lists the first word of every shard of the full-text index, which is written
+// next to the descriptions.
+//@ hasraw 'search-index.js' '"x":["again"]'
+//@ hasraw 'search.fulltext/foo/foo-terms-0-.js' '"retry":'
+//@ hasraw 'search.fulltext/foo/foo-text-0-.js' 'with an exponential backoff between every retry.'
+//@ !hasraw 'search.fulltext/foo/foo-text-0-.js' 'loop'
+
+#![crate_name = "foo"]
+
+/// Sends a request.
+///
+/// Failed requests are tried again, with an exponential backoff between every retry.
+///
+/// ```
+/// loop { foo::send() }
+/// ```
+pub fn send() {}
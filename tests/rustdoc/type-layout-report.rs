@@ -0,0 +1,29 @@
+//@ compile-flags: --show-type-layout -Z unstable-options
+// ignore-tidy-linelength
+
+#![crate_name = "foo"]
+
+//@ has foo/layout-report.html '//h1' 'Layout of all types'
+//@ has - '//table[@class="layout-report"]//a[@href="struct.Pair.html"]' 'foo::Pair'
+//@ has - '//table[@class="layout-report"]//td[@data-sort="8"]' '8 bytes'
+//@ matchesraw foo/layout-report.tsv '\nfoo::Pair\t8\t4\t0\t\n'
+#[repr(C)]
+pub struct Pair {
+    pub a: u8,
+    pub b: u32,
+}
+
+// Private types are listed too, without a link since they aren't documented.
+//@ has foo/layout-report.html '//table[@class="layout-report"]//td/code' 'foo::private::Hidden'
+//@ matchesraw foo/layout-report.tsv '\nfoo::private::Hidden\t2\t1\t254\tA=1,B=0\n'
+mod private {
+    #[repr(u8)]
+    pub enum Hidden {
+        A(u8),
+        B,
+    }
+}
+
+// Generic types have no layout.
+//@ !hasraw foo/layout-report.tsv 'foo::Generic'
+pub struct Generic<T>(pub T);
@@ -0,0 +1,10 @@
+//@ compile-flags: -Zunstable-options --versioned-docs --crate-version=2.0.0
+
+// Every version is documented into its own directory. Next to these directories are the list of
+// all documented versions, and a page redirecting to the newest one.
+//@ has '2.0.0/versioned_docs/index.html' '//*[@class="version"]' '2.0.0'
+//@ has '2.0.0/versioned_docs/struct.Foo.html'
+//@ hasraw 'versions.js' '"versioned_docs":["2.0.0"]'
+//@ hasraw 'versioned_docs/index.html' '../2.0.0/versioned_docs/index.html'
+
+pub struct Foo;
@@ -0,0 +1,12 @@
+//@ run-rustfix
+//@ rustfix-only-machine-applicable
+//@ check-fixed-idempotent
+//@ check-pass
+
+// Checks that `check-fixed-idempotent` accepts code that gets no further machine-applicable
+// suggestions once fixed.
+
+fn main() {
+    let _a = (1 + 1);
+    //~^ WARNING unnecessary parentheses around assigned value
+}
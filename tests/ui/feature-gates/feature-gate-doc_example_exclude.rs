@@ -0,0 +1,4 @@
+#[doc(example_exclude)] //~ ERROR: `#[doc(example_exclude)]` is experimental
+fn helper() {}
+
+fn main() {}